@@ -1,8 +1,17 @@
+use std::cell::RefCell;
+
 use serde::Serialize;
+use silkprint::RenderSession;
 use silkprint::error::SilkprintError;
 use silkprint::fonts::{add_external_font, clear_external_fonts};
 use wasm_bindgen::prelude::*;
 
+thread_local! {
+    /// Render session reused across calls so registered fonts are parsed
+    /// once. Dropped whenever the font set changes.
+    static SESSION: RefCell<Option<RenderSession>> = const { RefCell::new(None) };
+}
+
 #[derive(Debug, Serialize)]
 struct WasmThemeInfo<'a> {
     name: &'a str,
@@ -20,6 +29,7 @@ struct WasmThemeInfo<'a> {
 #[wasm_bindgen]
 pub fn register_font(data: &[u8]) {
     add_external_font(data.to_vec());
+    SESSION.with_borrow_mut(|session| *session = None);
 }
 
 /// Clear all previously registered fonts.
@@ -28,6 +38,19 @@ pub fn register_font(data: &[u8]) {
 #[wasm_bindgen]
 pub fn reset_fonts() {
     clear_external_fonts();
+    SESSION.with_borrow_mut(|session| *session = None);
+}
+
+/// The shared render session, created on first use.
+fn session() -> Result<RenderSession, SilkprintError> {
+    SESSION.with_borrow_mut(|slot| {
+        if let Some(session) = slot {
+            return Ok(session.clone());
+        }
+        let session = RenderSession::new(&[])?;
+        *slot = Some(session.clone());
+        Ok(session)
+    })
 }
 
 /// Format a SilkprintError with full diagnostics for display in the browser.
@@ -85,8 +108,9 @@ pub fn render_pdf(markdown: &str, theme_name: &str) -> Result<Vec<u8>, JsError>
         ..Default::default()
     };
 
-    let (pdf_bytes, _warnings) =
-        silkprint::render(markdown, None, &options).map_err(|e| JsError::new(&format_error(&e)))?;
+    let (pdf_bytes, _warnings) = session()
+        .and_then(|session| session.render(markdown, None, &options))
+        .map_err(|e| JsError::new(&format_error(&e)))?;
 
     Ok(pdf_bytes)
 }
@@ -110,8 +134,9 @@ pub fn render_pdf_with_options(
         ..Default::default()
     };
 
-    let (pdf_bytes, _warnings) =
-        silkprint::render(markdown, None, &options).map_err(|e| JsError::new(&format_error(&e)))?;
+    let (pdf_bytes, _warnings) = session()
+        .and_then(|session| session.render(markdown, None, &options))
+        .map_err(|e| JsError::new(&format_error(&e)))?;
 
     Ok(pdf_bytes)
 }
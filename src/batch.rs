@@ -0,0 +1,356 @@
+//! Batch rendering: glob expansion, output tree mirroring, and a worker pool.
+//!
+//! `silkprint pdf 'docs/**/*.md' --out-dir build/` renders every match to
+//! `build/` with the directory layout below `docs/` preserved. Globs are
+//! expanded here rather than by the shell so quoted patterns work the same
+//! everywhere, including `**` on shells without `globstar`.
+
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::error::SilkprintError;
+
+/// One file to render and where its PDF goes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchJob {
+    pub input: PathBuf,
+    pub output: PathBuf,
+}
+
+/// Whether `pattern` should be expanded as a glob: it contains `*`, `?`,
+/// or `[` and does not name an existing file as written.
+pub fn is_glob(pattern: &str) -> bool {
+    has_wildcards(pattern) && !Path::new(pattern).exists()
+}
+
+fn has_wildcards(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '['])
+}
+
+/// Expand `patterns` into jobs writing under `out_dir`.
+///
+/// A glob's output mirrors the tree below its literal prefix (`docs/` for
+/// `docs/**/*.md`); a plain file path lands directly in `out_dir`. Inputs
+/// matched by more than one pattern are rendered once; distinct inputs that
+/// would write the same PDF are an error.
+pub fn plan(patterns: &[String], out_dir: &Path) -> Result<Vec<BatchJob>, SilkprintError> {
+    let mut jobs: Vec<BatchJob> = Vec::new();
+
+    for pattern in patterns {
+        let (base, matches) = if is_glob(pattern) {
+            let (base, rest) = split_literal_prefix(pattern);
+            let mut matches = Vec::new();
+            expand(&base, &rest, &mut matches);
+            matches.sort();
+            (base, matches)
+        } else {
+            let path = PathBuf::from(pattern);
+            let base = path.parent().map(Path::to_path_buf).unwrap_or_default();
+            let matches = if path.is_file() {
+                vec![path]
+            } else {
+                Vec::new()
+            };
+            (base, matches)
+        };
+
+        if matches.is_empty() {
+            return Err(SilkprintError::NoMatchingInputs {
+                pattern: pattern.clone(),
+            });
+        }
+
+        for input in matches {
+            if jobs.iter().any(|job| job.input == input) {
+                continue;
+            }
+            let relative = input.strip_prefix(&base).unwrap_or(&input);
+            let output = out_dir.join(relative).with_extension("pdf");
+            if let Some(other) = jobs.iter().find(|job| job.output == output) {
+                return Err(SilkprintError::OutputClash {
+                    output: output.display().to_string(),
+                    first: other.input.display().to_string(),
+                    second: input.display().to_string(),
+                });
+            }
+            jobs.push(BatchJob { input, output });
+        }
+    }
+
+    Ok(jobs)
+}
+
+/// Run `f` over `jobs` on up to `threads` worker threads.
+///
+/// Results come back in job order. Workers pull the next job as they finish,
+/// so a few slow documents don't hold up the rest. A panic in `f` is
+/// re-raised on the calling thread rather than dropping that worker's results.
+pub fn run_parallel<T, F>(jobs: &[BatchJob], threads: usize, f: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(&BatchJob) -> T + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let workers = threads.clamp(1, jobs.len().max(1));
+
+    let mut results: Vec<(usize, T)> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(job) = jobs.get(index) else {
+                            break;
+                        };
+                        done.push((index, f(job)));
+                    }
+                    done
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|payload| std::panic::resume_unwind(payload))
+            })
+            .collect()
+    });
+
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Split a pattern into the leading components without metacharacters (the
+/// directory to walk from) and the remaining component patterns.
+fn split_literal_prefix(pattern: &str) -> (PathBuf, Vec<String>) {
+    let mut base = PathBuf::new();
+    let mut rest = Vec::new();
+
+    for component in Path::new(pattern).components() {
+        let text = component.as_os_str().to_string_lossy();
+        if rest.is_empty() && !has_wildcards(&text) {
+            base.push(component);
+        } else if !matches!(component, Component::CurDir) {
+            rest.push(text.into_owned());
+        }
+    }
+
+    (base, rest)
+}
+
+/// Collect files under `dir` whose relative path matches `rest`.
+fn expand(dir: &Path, rest: &[String], out: &mut Vec<PathBuf>) {
+    let Some((first, tail)) = rest.split_first() else {
+        if dir.is_file() {
+            out.push(dir.to_path_buf());
+        }
+        return;
+    };
+
+    let read_from = if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    };
+    let Ok(entries) = std::fs::read_dir(read_from) else {
+        return;
+    };
+    let mut entries: Vec<_> = entries.flatten().collect();
+    entries.sort_by_key(std::fs::DirEntry::file_name);
+
+    if first == "**" {
+        // Zero directories…
+        expand(dir, tail, out);
+        // …or descend one more and keep `**` active. `file_type` doesn't
+        // follow symlinks, so a link back up the tree can't recurse forever.
+        for entry in &entries {
+            let name = entry.file_name();
+            let is_dir = entry.file_type().is_ok_and(|kind| kind.is_dir());
+            if is_dir && !name.to_string_lossy().starts_with('.') {
+                expand(&dir.join(&name), rest, out);
+            }
+        }
+        return;
+    }
+
+    for entry in &entries {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if !matches_component(first, &name) {
+            continue;
+        }
+        let path = dir.join(name.as_ref());
+        if tail.is_empty() {
+            if path.is_file() {
+                out.push(path);
+            }
+        } else if path.is_dir() {
+            expand(&path, tail, out);
+        }
+    }
+}
+
+/// Match one path component against `*`, `?`, and `[...]` wildcards.
+///
+/// As in shells, a leading `.` must be matched literally, so `*.md` skips
+/// hidden files.
+fn matches_component(pattern: &str, name: &str) -> bool {
+    if name.starts_with('.') && !pattern.starts_with('.') {
+        return false;
+    }
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    wildcard_match(&pattern, &name)
+}
+
+fn wildcard_match(pattern: &[char], name: &[char]) -> bool {
+    let Some((&first, rest)) = pattern.split_first() else {
+        return name.is_empty();
+    };
+    match first {
+        '*' => (0..=name.len()).any(|skip| wildcard_match(rest, &name[skip..])),
+        '?' => !name.is_empty() && wildcard_match(rest, &name[1..]),
+        '[' => {
+            let Some((&c, name_rest)) = name.split_first() else {
+                return false;
+            };
+            match match_class(rest, c) {
+                Some((true, after)) => wildcard_match(after, name_rest),
+                Some((false, _)) => false,
+                // No closing `]`: treat `[` literally.
+                None => c == '[' && wildcard_match(rest, name_rest),
+            }
+        }
+        literal => name.first() == Some(&literal) && wildcard_match(rest, &name[1..]),
+    }
+}
+
+/// Match `c` against a `[...]` class whose body starts at `class`. Returns
+/// whether it matched and the pattern after the closing `]`.
+fn match_class(class: &[char], c: char) -> Option<(bool, &[char])> {
+    let (negated, body) = match class.split_first() {
+        Some((&('!' | '^'), body)) => (true, body),
+        _ => (false, class),
+    };
+    // A `]` right after the opening bracket is a literal member.
+    let close = body
+        .iter()
+        .skip(1)
+        .position(|&ch| ch == ']')
+        .map(|i| i + 1)?;
+    let members = &body[..close];
+
+    let mut matched = false;
+    let mut i = 0;
+    while i < members.len() {
+        if i + 2 < members.len() && members[i + 1] == '-' {
+            matched |= (members[i]..=members[i + 2]).contains(&c);
+            i += 3;
+        } else {
+            matched |= members[i] == c;
+            i += 1;
+        }
+    }
+    Some((matched != negated, &body[close + 1..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wildcards_match_components() {
+        assert!(matches_component("*.md", "guide.md"));
+        assert!(!matches_component("*.md", ".hidden.md"));
+        assert!(matches_component("ch??.md", "ch01.md"));
+        assert!(matches_component("[a-c]*.md", "beta.md"));
+        assert!(!matches_component("[!a-c]*.md", "beta.md"));
+        assert!(!matches_component("*.md", "notes.txt"));
+    }
+
+    #[test]
+    fn plan_mirrors_tree_below_glob_prefix() {
+        let dir = tempfile::tempdir().expect("should create tempdir");
+        let docs = dir.path().join("docs");
+        std::fs::create_dir_all(docs.join("ops/db")).expect("should create dirs");
+        std::fs::write(docs.join("index.md"), "# Index").expect("should write");
+        std::fs::write(docs.join("ops/db/restore.md"), "# Restore").expect("should write");
+        std::fs::write(docs.join("ops/notes.txt"), "skip").expect("should write");
+
+        let pattern = format!("{}/**/*.md", docs.display());
+        let out = dir.path().join("build");
+        let jobs = plan(&[pattern.clone(), pattern], &out).expect("should plan");
+
+        assert_eq!(
+            jobs,
+            vec![
+                BatchJob {
+                    input: docs.join("index.md"),
+                    output: out.join("index.pdf"),
+                },
+                BatchJob {
+                    input: docs.join("ops/db/restore.md"),
+                    output: out.join("ops/db/restore.pdf"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn plan_rejects_patterns_without_matches() {
+        let dir = tempfile::tempdir().expect("should create tempdir");
+        let pattern = format!("{}/*.md", dir.path().display());
+        assert!(matches!(
+            plan(&[pattern], dir.path()),
+            Err(SilkprintError::NoMatchingInputs { .. })
+        ));
+    }
+
+    #[test]
+    fn plan_rejects_clashing_outputs() {
+        let dir = tempfile::tempdir().expect("should create tempdir");
+        for sub in ["a", "b"] {
+            std::fs::create_dir_all(dir.path().join(sub)).expect("should create dirs");
+            std::fs::write(dir.path().join(sub).join("readme.md"), "# Readme")
+                .expect("should write");
+        }
+        let patterns: Vec<String> = ["a", "b"]
+            .iter()
+            .map(|sub| dir.path().join(sub).join("readme.md").display().to_string())
+            .collect();
+        assert!(matches!(
+            plan(&patterns, dir.path()),
+            Err(SilkprintError::OutputClash { .. })
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn globstar_does_not_follow_symlink_loops() {
+        let dir = tempfile::tempdir().expect("should create tempdir");
+        let docs = dir.path().join("docs");
+        std::fs::create_dir_all(docs.join("sub")).expect("should create dirs");
+        std::fs::write(docs.join("sub/page.md"), "# Page").expect("should write");
+        std::os::unix::fs::symlink(&docs, docs.join("sub/loop")).expect("should symlink");
+
+        let pattern = format!("{}/**/*.md", docs.display());
+        let jobs = plan(&[pattern], &dir.path().join("build")).expect("should plan");
+        assert_eq!(jobs.len(), 1);
+    }
+
+    #[test]
+    fn run_parallel_keeps_job_order() {
+        let jobs: Vec<_> = (0..20)
+            .map(|i| BatchJob {
+                input: PathBuf::from(format!("{i}.md")),
+                output: PathBuf::from(format!("{i}.pdf")),
+            })
+            .collect();
+        let results = run_parallel(&jobs, 4, |job| job.input.clone());
+        let inputs: Vec<_> = jobs.into_iter().map(|job| job.input).collect();
+        assert_eq!(results, inputs);
+    }
+}
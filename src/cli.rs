@@ -48,6 +48,44 @@ pub struct Cli {
     #[arg(short, long, global = true, default_value = "a4", value_name = "SIZE")]
     pub paper: String,
 
+    /// PDF standard to conform to: a-2b, a-3b (archival), ua-1 (accessible).
+    #[arg(long, global = true, value_name = "STANDARD")]
+    pub pdf_standard: Option<String>,
+
+    /// Repair theme colors that miss a WCAG level (`--fix-contrast=aaa`) by
+    /// adjusting their lightness, and report the new values [default: aa].
+    #[arg(
+        long,
+        global = true,
+        value_name = "LEVEL",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "aa"
+    )]
+    pub fix_contrast: Option<String>,
+
+    /// Render with a light-paper variant of the theme: `--print-safe` keeps
+    /// its hues, `--print-safe=greyscale` uses none.
+    #[arg(
+        long,
+        global = true,
+        value_name = "MODE",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "color"
+    )]
+    pub print_safe: Option<String>,
+
+    /// Emit ```typst code blocks as raw Typst instead of listings, and apply
+    /// front matter `template:` files. Only for documents you trust.
+    #[arg(long, global = true)]
+    pub allow_raw_typst: bool,
+
+    /// Watermark every page with TEXT, or with an image file (.png, .jpg,
+    /// .svg, …) relative to the document.
+    #[arg(long, global = true, value_name = "TEXT|IMAGE")]
+    pub watermark: Option<String>,
+
     /// List all available themes and exit.
     #[arg(long)]
     pub list_themes: bool,
@@ -64,7 +102,7 @@ pub struct Cli {
     #[arg(long, global = true)]
     pub dump_typst: bool,
 
-    /// Emit HTML instead of PDF. Implies export output.
+    /// Emit a standalone, themed HTML document instead of PDF. Implies export output.
     #[arg(long, global = true)]
     pub dump_html: bool,
 
@@ -72,6 +110,20 @@ pub struct Cli {
     #[arg(long, global = true)]
     pub validate_links: bool,
 
+    /// Render every input into DIR, mirroring the directory tree below each
+    /// glob. Implies PDF output.
+    #[arg(long, global = true, value_name = "DIR")]
+    pub out_dir: Option<PathBuf>,
+
+    /// Number of files to render in parallel [default: number of CPUs].
+    #[arg(short, long, global = true, value_name = "N")]
+    pub jobs: Option<usize>,
+
+    /// Re-render the PDF whenever the input, theme, or referenced files change.
+    /// Implies PDF output.
+    #[arg(long, global = true)]
+    pub watch: bool,
+
     /// Open the PDF in system viewer after rendering. Implies PDF output.
     #[arg(long, global = true)]
     pub open: bool,
@@ -117,6 +169,24 @@ pub struct Cli {
     #[arg(long, global = true, value_name = "COLS")]
     pub width: Option<u16>,
 
+    /// Diagnostics format: human, or json for one JSON object per line on stdout.
+    #[arg(
+        long,
+        global = true,
+        default_value = "human",
+        value_name = "FMT",
+        value_parser = ["human", "json"]
+    )]
+    pub message_format: String,
+
+    /// Fail with a non-zero exit code if any warning is reported.
+    #[arg(long, global = true)]
+    pub deny_warnings: bool,
+
+    /// Fail if a specific warning is reported (e.g. image-not-found). Repeatable.
+    #[arg(long, global = true, value_name = "NAME")]
+    pub deny: Vec<String>,
+
     /// Color output: auto, always, never.
     #[arg(long, global = true, default_value = "auto", value_name = "WHEN")]
     pub color: String,
@@ -137,15 +207,35 @@ pub struct Cli {
 
 /// Explicit mode subcommands. The bare form (no subcommand) auto-routes:
 /// terminal reader in a TTY, one-shot ANSI when piped, PDF when `-o`/`--check`/
-/// `--dump-typst`/`--open` is present.
+/// `--dump-typst`/`--open`/`--watch` is present.
 #[derive(Debug, clap::Subcommand)]
 pub enum Command {
     /// Render the Markdown file to a PDF.
+    ///
+    /// Several files or a quoted glob (`'docs/**/*.md'`) render in parallel,
+    /// into `--out-dir` when given.
     Pdf {
-        /// Path to the Markdown file to render.
+        /// Markdown files or glob patterns to render.
+        #[arg(value_name = "INPUT")]
+        inputs: Vec<PathBuf>,
+    },
+
+    /// Render a book — a directory or a SUMMARY.md / `chapters:` manifest —
+    /// into a single PDF.
+    ///
+    /// Chapters share one outline and start on a new page; links between
+    /// chapter files become internal PDF links.
+    Book {
+        /// Book directory, SUMMARY.md, or Markdown manifest with `chapters:`.
         input: Option<PathBuf>,
     },
 
+    /// Check, scaffold, import, export, and preview theme files.
+    Theme {
+        #[command(subcommand)]
+        action: ThemeCommand,
+    },
+
     /// Read a Markdown file in the terminal with full styling.
     ///
     /// Launches a scrollable TUI in an interactive terminal and emits styled
@@ -157,6 +247,73 @@ pub enum Command {
     },
 }
 
+/// `silkprint theme` actions for theme authors.
+#[derive(Debug, clap::Subcommand)]
+pub enum ThemeCommand {
+    /// Validate a theme: TOML syntax, inheritance, unknown keys, unresolved
+    /// color references, and WCAG contrast.
+    Check {
+        /// Theme file, or the name of an installed theme.
+        #[arg(value_name = "THEME")]
+        file: String,
+    },
+
+    /// Scaffold a theme that overrides an existing one.
+    ///
+    /// Writes `.silkprint/themes/<NAME>.toml`, where `--theme <NAME>` finds
+    /// it, unless `-o` names another path.
+    New {
+        /// Name of the new theme.
+        name: String,
+
+        /// Theme to inherit from.
+        #[arg(long, default_value = "silk-light", value_name = "THEME")]
+        extends: String,
+    },
+
+    /// Convert a VS Code (.json) or TextMate (.tmTheme) color theme into a
+    /// SilkPrint theme.
+    ///
+    /// Writes `.silkprint/themes/<NAME>.toml` unless `-o` names another path.
+    /// Colors that miss a WCAG contrast minimum are adjusted and reported.
+    Import {
+        /// Editor theme file.
+        file: PathBuf,
+
+        /// Theme file name [default: derived from the editor theme's name].
+        #[arg(long, value_name = "NAME")]
+        name: Option<String>,
+    },
+
+    /// Export a resolved theme for other renderers: CSS custom properties,
+    /// a flat JSON token dump, a `TextMate` theme, or an opaline theme.
+    ///
+    /// Writes to stdout unless `-o` names a file. `--print-safe` and
+    /// `--fix-contrast` apply to the exported theme.
+    Export {
+        /// Theme file, or the name of an installed theme.
+        #[arg(value_name = "THEME")]
+        file: String,
+
+        /// Output format.
+        #[arg(
+            long,
+            default_value = "css",
+            value_name = "FORMAT",
+            value_parser = ["css", "json", "tmtheme", "opaline"]
+        )]
+        format: String,
+    },
+
+    /// Render a specimen document that exercises every theme section to PDF
+    /// [default output: <THEME>-preview.pdf].
+    Preview {
+        /// Theme file, or the name of an installed theme.
+        #[arg(value_name = "THEME")]
+        file: String,
+    },
+}
+
 impl Cli {
     /// Validate flag combinations, returning errors for conflicts.
     pub fn validate(&self) -> Result<(), crate::error::SilkprintError> {
@@ -185,6 +342,40 @@ impl Cli {
                 details: "cannot combine --dump-html and --dump-typst".to_string(),
             });
         }
+        if self.watch && (self.check || self.dump_typst || self.dump_html) {
+            return Err(crate::error::SilkprintError::ConflictingOptions {
+                details: "--watch requires PDF output".to_string(),
+            });
+        }
+        if self.watch && self.output.as_deref() == Some("-") {
+            return Err(crate::error::SilkprintError::ConflictingOptions {
+                details: "--watch incompatible with stdout output".to_string(),
+            });
+        }
+        // Several inputs, a glob or --out-dir make this a batch render.
+        let is_batch = self.batch_inputs().is_some();
+        if is_batch && (self.output.is_some() || self.dump_typst || self.dump_html || self.open) {
+            return Err(crate::error::SilkprintError::ConflictingOptions {
+                details: "batch rendering writes one PDF per input (incompatible with -o, --dump-typst, --dump-html, --open)".to_string(),
+            });
+        }
+        if self.watch && is_batch {
+            return Err(crate::error::SilkprintError::ConflictingOptions {
+                details: "--watch applies to a single document, not a batch".to_string(),
+            });
+        }
+        if self.json_messages() && self.writes_stdout() {
+            return Err(crate::error::SilkprintError::ConflictingOptions {
+                details: "--message-format json writes to stdout; use -o <PATH> for the output"
+                    .to_string(),
+            });
+        }
+        crate::warnings::WarningPolicy::new(self.deny_warnings, &self.deny)?;
+        if self.jobs == Some(0) {
+            return Err(crate::error::SilkprintError::ConflictingOptions {
+                details: "--jobs must be at least 1".to_string(),
+            });
+        }
         if self.toc && self.no_toc {
             return Err(crate::error::SilkprintError::ConflictingOptions {
                 details: "cannot combine --toc and --no-toc".to_string(),
@@ -198,6 +389,24 @@ impl Cli {
         Ok(())
     }
 
+    /// Whether diagnostics are emitted as JSON lines (`--message-format json`).
+    pub fn json_messages(&self) -> bool {
+        self.message_format == "json"
+    }
+
+    /// Which warnings fail the build, from `--deny-warnings` / `--deny`.
+    pub fn warning_policy(&self) -> crate::warnings::WarningPolicy {
+        // Names are checked in `validate`.
+        crate::warnings::WarningPolicy::new(self.deny_warnings, &self.deny).unwrap_or_default()
+    }
+
+    /// Whether the primary output goes to stdout (`-o -`, or a Typst/HTML
+    /// dump without `-o`).
+    fn writes_stdout(&self) -> bool {
+        let to_stdout = self.output.as_deref().is_none_or(|path| path == "-");
+        self.output.as_deref() == Some("-") || ((self.dump_typst || self.dump_html) && to_stdout)
+    }
+
     /// Resolve the TOC override from `--toc` / `--no-toc` flags.
     pub fn toc_override(&self) -> Option<bool> {
         if self.toc {
@@ -222,10 +431,11 @@ impl Cli {
     /// otherwise the top-level positional.
     pub fn effective_input(&self) -> Option<PathBuf> {
         let from_command = match &self.command {
-            Some(Command::Pdf { input }) => input.clone(),
+            Some(Command::Pdf { inputs }) => inputs.first().cloned(),
+            Some(Command::Book { input }) => input.clone(),
+            Some(Command::Theme { .. }) | None => None,
             #[cfg(feature = "terminal")]
             Some(Command::Read { input }) => input.clone(),
-            None => None,
         };
         from_command.or_else(|| self.input.clone())
     }
@@ -233,7 +443,33 @@ impl Cli {
     /// Whether a PDF-output signal is present. In the bare form (no subcommand)
     /// this forces PDF rendering instead of the terminal reader.
     pub fn pdf_signaled(&self) -> bool {
-        self.output.is_some() || self.check || self.dump_typst || self.dump_html || self.open
+        self.output.is_some()
+            || self.check
+            || self.dump_typst
+            || self.dump_html
+            || self.open
+            || self.watch
+            || self.out_dir.is_some()
+    }
+
+    /// The input patterns when this is a batch render: several `pdf` inputs,
+    /// a glob, or an explicit `--out-dir`. `None` for a single document.
+    pub fn batch_inputs(&self) -> Option<Vec<String>> {
+        let inputs: Vec<String> = match &self.command {
+            Some(Command::Pdf { inputs }) if !inputs.is_empty() => inputs
+                .iter()
+                .map(|input| input.to_string_lossy().into_owned())
+                .collect(),
+            _ => self
+                .input
+                .iter()
+                .map(|input| input.to_string_lossy().into_owned())
+                .collect(),
+        };
+        let is_batch = self.out_dir.is_some()
+            || inputs.len() > 1
+            || inputs.iter().any(|input| crate::batch::is_glob(input));
+        (is_batch && !inputs.is_empty()).then_some(inputs)
     }
 
     /// Determine the output path for PDF mode.
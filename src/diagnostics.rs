@@ -0,0 +1,282 @@
+//! Machine-readable diagnostics for `--message-format json`.
+//!
+//! Each warning or error becomes one JSON object per line:
+//!
+//! ```json
+//! {"severity":"warning","code":"silkprint::image::not_found","name":"image-not-found","message":"image 'a.png' not found, skipping","file":"doc.md"}
+//! ```
+//!
+//! `code` is stable across releases; `message` is for humans and may change.
+
+use std::fmt;
+use std::path::Path;
+
+use miette::{LabeledSpan, NamedSource, SourceSpan};
+use serde::Serialize;
+
+use crate::error::SilkprintError;
+use crate::warnings::{SilkprintWarning, WarningPolicy};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A location in a source file. `line` and `column` are 1-based; `offset`
+/// and `length` are in bytes, and omitted when the source text isn't known.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Span {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub length: Option<usize>,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// One diagnostic record.
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: String,
+    /// Short `--deny` name; warnings only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub help: Option<String>,
+    /// Compiler output and similar detail lines.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub span: Option<Span>,
+}
+
+impl Diagnostic {
+    /// A warning record. Warnings denied by `policy` are reported as errors.
+    ///
+    /// The warning's own location file takes precedence over `file`;
+    /// `source` is that file's text, used to resolve byte offsets.
+    pub fn from_warning(
+        warning: &SilkprintWarning,
+        file: Option<&Path>,
+        source: Option<&str>,
+        policy: &WarningPolicy,
+    ) -> Self {
+        let location = warning.location();
+        let severity = if policy.is_denied(warning) {
+            Severity::Error
+        } else {
+            Severity::Warning
+        };
+        Self {
+            severity,
+            code: warning.code().to_string(),
+            name: Some(warning.name().to_string()),
+            message: warning.to_string(),
+            help: None,
+            notes: Vec::new(),
+            file: location
+                .and_then(|location| location.file.as_deref())
+                .or(file)
+                .map(|path| path.display().to_string()),
+            span: location.map(|location| {
+                let bytes = source.and_then(|text| location.byte_span(text));
+                Span {
+                    offset: bytes.map(|(offset, _)| offset),
+                    length: bytes.map(|(_, length)| length),
+                    line: location.line,
+                    column: location.column,
+                }
+            }),
+        }
+    }
+
+    /// An error record from any miette diagnostic, with the first labelled
+    /// span resolved to a line and column when source code is attached.
+    pub fn from_error(error: &(dyn miette::Diagnostic + 'static), file: Option<&Path>) -> Self {
+        let code = error
+            .code()
+            .as_ref()
+            .map_or_else(|| "silkprint::error".to_string(), ToString::to_string);
+        let mut notes = Vec::new();
+        if let Some(SilkprintError::TypstCompilation { diagnostics }) =
+            (error as &dyn std::error::Error).downcast_ref::<SilkprintError>()
+        {
+            notes.clone_from(diagnostics);
+        }
+        let mut source = error.source();
+        while let Some(cause) = source {
+            notes.push(cause.to_string());
+            source = cause.source();
+        }
+
+        Self {
+            severity: Severity::Error,
+            code,
+            name: None,
+            message: error.to_string(),
+            help: error.help().as_ref().map(ToString::to_string),
+            notes,
+            file: file.map(|path| path.display().to_string()),
+            span: first_span(error),
+        }
+    }
+
+    /// Serialize as a single JSON line (no trailing newline).
+    pub fn to_json_line(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+/// Resolve the first label of `error` against its source code.
+fn first_span(error: &dyn miette::Diagnostic) -> Option<Span> {
+    let label = error.labels()?.next()?;
+    let contents = error.source_code()?.read_span(label.inner(), 0, 0).ok()?;
+    Some(Span {
+        offset: Some(label.offset()),
+        length: Some(label.len()),
+        line: contents.line() + 1,
+        column: label.offset().saturating_sub(contents.span().offset()) + 1,
+    })
+}
+
+/// A located warning with its source text, for miette's graphical report
+/// handler to draw as a labelled snippet.
+///
+/// Control characters in the message and source are blanked (keeping byte
+/// offsets intact) so untrusted Markdown can't inject terminal escapes.
+#[derive(Debug)]
+pub struct WarningReport {
+    message: String,
+    code: &'static str,
+    src: NamedSource<String>,
+    span: SourceSpan,
+}
+
+impl WarningReport {
+    /// `None` if the warning has no location or it doesn't fit `source`.
+    pub fn new(warning: &SilkprintWarning, source: &str) -> Option<Self> {
+        let location = warning.location()?;
+        let (offset, length) = location.byte_span(source)?;
+        let name = location
+            .file
+            .as_ref()
+            .map_or_else(|| "<input>".to_string(), |path| path.display().to_string());
+        Some(Self {
+            message: blank_control(&warning.to_string()),
+            code: warning.code(),
+            src: NamedSource::new(name, blank_control(source)),
+            span: (offset, length).into(),
+        })
+    }
+}
+
+impl fmt::Display for WarningReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for WarningReport {}
+
+impl miette::Diagnostic for WarningReport {
+    fn code(&self) -> Option<Box<dyn fmt::Display + '_>> {
+        Some(Box::new(self.code))
+    }
+
+    fn severity(&self) -> Option<miette::Severity> {
+        Some(miette::Severity::Warning)
+    }
+
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        Some(&self.src)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        Some(Box::new(std::iter::once(LabeledSpan::underline(self.span))))
+    }
+}
+
+/// Replace control characters other than newline and tab with spaces of the
+/// same byte length.
+fn blank_control(text: &str) -> String {
+    text.chars()
+        .map(|c| {
+            if c.is_control() && !matches!(c, '\n' | '\r' | '\t') {
+                " ".repeat(c.len_utf8())
+            } else {
+                c.to_string()
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::warnings::SourceLocation;
+
+    #[test]
+    fn denied_warnings_become_errors() {
+        let warning = SilkprintWarning::ImageNotFound {
+            path: "a.png".to_string(),
+            location: None,
+        };
+        let policy = WarningPolicy::new(false, &["image-not-found".to_string()])
+            .expect("should accept a known name");
+        let record = Diagnostic::from_warning(&warning, Some(Path::new("doc.md")), None, &policy);
+
+        assert_eq!(record.severity, Severity::Error);
+        let json = record.to_json_line();
+        assert!(
+            json.contains(r#""code":"silkprint::image::not_found""#),
+            "{json}"
+        );
+        assert!(json.contains(r#""file":"doc.md""#), "{json}");
+        assert!(!json.contains("span"), "{json}");
+    }
+
+    #[test]
+    fn located_warnings_resolve_offsets() {
+        let source = "# Doc\n\nSee ![x](gone.png).\n";
+        let warning = SilkprintWarning::ImageNotFound {
+            path: "gone.png".to_string(),
+            location: Some(SourceLocation {
+                file: Some("doc.md".into()),
+                line: 3,
+                column: 5,
+                end_line: 3,
+                end_column: 18,
+            }),
+        };
+        let record =
+            Diagnostic::from_warning(&warning, None, Some(source), &WarningPolicy::default());
+        let span = record.span.expect("should carry a span");
+        assert_eq!((span.line, span.column), (3, 5));
+        assert_eq!(
+            (span.offset, span.length),
+            (Some(11), Some("![x](gone.png)".len()))
+        );
+        assert_eq!(record.file.as_deref(), Some("doc.md"));
+
+        let report = WarningReport::new(&warning, source).expect("should fit the source");
+        let rendered = format!("{:?}", miette::Report::new(report));
+        assert!(rendered.contains("gone.png"), "{rendered}");
+    }
+
+    #[test]
+    fn front_matter_errors_carry_spans() {
+        let err = crate::render::frontmatter::extract("---\ntitle: [unclosed\n---\n\nBody")
+            .expect_err("should reject malformed YAML");
+        let record = Diagnostic::from_error(&err, None);
+
+        assert_eq!(record.code, "silkprint::frontmatter");
+        let span = record.span.expect("front matter errors should have a span");
+        assert!(span.line >= 1);
+    }
+}
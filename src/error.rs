@@ -8,7 +8,10 @@ use std::io;
 #[derive(Debug, thiserror::Error, miette::Diagnostic)]
 pub enum SilkprintError {
     #[error("Failed to read input file: {path}")]
-    #[diagnostic(help("Check that the file exists and is readable"))]
+    #[diagnostic(
+        code(silkprint::io::input),
+        help("Check that the file exists and is readable")
+    )]
     InputRead {
         path: String,
         #[source]
@@ -41,6 +44,26 @@ pub enum SilkprintError {
         message: String,
     },
 
+    #[error("Invalid theme name: {name}")]
+    #[diagnostic(
+        code(silkprint::theme::name),
+        help(
+            "Theme names are file stems: letters, digits, '-', '_' and '.', not starting with '.' or '_'"
+        )
+    )]
+    InvalidThemeName { name: String },
+
+    #[error("Theme '{name}' has {count} problem(s)")]
+    #[diagnostic(code(silkprint::theme::check), help("Fix the problems above"))]
+    ThemeCheckFailed { name: String, count: usize },
+
+    #[error("Cannot import editor theme {path}: {message}")]
+    #[diagnostic(
+        code(silkprint::theme::import),
+        help("Import a VS Code color theme (.json) or a TextMate theme (.tmTheme)")
+    )]
+    ThemeImport { path: String, message: String },
+
     #[error("Theme inheritance cycle detected: {chain}")]
     #[diagnostic(code(silkprint::theme::cycle))]
     ThemeCycle { chain: String },
@@ -50,10 +73,39 @@ pub enum SilkprintError {
     ThemeInheritanceDepth { chain: String },
 
     #[error("Invalid paper size: {size}")]
-    #[diagnostic(help("Valid sizes: a4, letter, a5, legal"))]
+    #[diagnostic(
+        code(silkprint::cli::paper),
+        help("Valid sizes: a4, letter, a5, legal")
+    )]
     InvalidPaperSize { size: String },
 
-    #[error("Conflicting CLI options")]
+    #[error("Invalid PDF standard: {standard}")]
+    #[diagnostic(
+        code(silkprint::cli::pdf_standard),
+        help("Valid standards: a-2b, a-3b, ua-1")
+    )]
+    InvalidPdfStandard { standard: String },
+
+    #[error("Invalid contrast level: {level}")]
+    #[diagnostic(code(silkprint::theme::contrast_level), help("Valid levels: AA, AAA"))]
+    InvalidContrastLevel { level: String },
+
+    #[error("Invalid print-safe mode: {mode}")]
+    #[diagnostic(
+        code(silkprint::theme::print_safe),
+        help("Valid modes: color, greyscale")
+    )]
+    InvalidPrintSafe { mode: String },
+
+    #[error("Document does not meet {standard}: {count} violation(s)")]
+    #[diagnostic(code(silkprint::pdf::standard), help("{details}"))]
+    PdfStandardUnmet {
+        standard: String,
+        count: usize,
+        details: String,
+    },
+
+    #[error("Conflicting CLI options: {details}")]
     #[diagnostic(code(silkprint::cli::conflict))]
     ConflictingOptions { details: String },
 
@@ -68,15 +120,65 @@ pub enum SilkprintError {
     )]
     TypstCompilation { diagnostics: Vec<String> },
 
-    #[error("Rendering failed")]
+    #[error("Rendering failed: {details}")]
     #[diagnostic(code(silkprint::render), help("{hint}"))]
     RenderFailed { details: String, hint: String },
 
+    #[error("Watermark image '{path}' is outside the document root '{root}'")]
+    #[diagnostic(
+        code(silkprint::watermark::outside_root),
+        help("Typst only loads files under the document's directory; move the image there")
+    )]
+    WatermarkOutsideRoot { path: String, root: String },
+
     #[error("Failed to fetch remote document: {url}")]
     #[diagnostic(code(silkprint::remote), help("{message}"))]
     RemoteFetch { url: String, message: String },
 
+    #[error("No chapters found for book: {path}")]
+    #[diagnostic(
+        code(silkprint::book::empty),
+        help(
+            "Add a SUMMARY.md, a front-matter `chapters:` list, or Markdown files to the directory"
+        )
+    )]
+    BookEmpty { path: String },
+
+    #[error("No files match '{pattern}'")]
+    #[diagnostic(
+        code(silkprint::batch::no_match),
+        help("Quote glob patterns so silkprint expands them, e.g. 'docs/**/*.md'")
+    )]
+    NoMatchingInputs { pattern: String },
+
+    #[error("'{first}' and '{second}' would both render to '{output}'")]
+    #[diagnostic(
+        code(silkprint::batch::output_clash),
+        help("Render them with separate --out-dir runs, or use a glob so the tree is mirrored")
+    )]
+    OutputClash {
+        output: String,
+        first: String,
+        second: String,
+    },
+
+    #[error("{failed} of {total} files failed to render")]
+    #[diagnostic(code(silkprint::batch::failed))]
+    BatchFailed { failed: usize, total: usize },
+
+    #[error("Unknown warning name '{name}'")]
+    #[diagnostic(code(silkprint::cli::unknown_warning), help("Known warnings: {names}"))]
+    UnknownWarning { name: String, names: String },
+
+    #[error("{count} denied warning(s)")]
+    #[diagnostic(
+        code(silkprint::warning::denied),
+        help("Fix the warnings above, or relax --deny-warnings / --deny")
+    )]
+    DeniedWarnings { count: usize },
+
     #[error("Failed to write output: {path}")]
+    #[diagnostic(code(silkprint::io::output))]
     OutputWrite {
         path: String,
         #[source]
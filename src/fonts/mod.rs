@@ -1,3 +1,11 @@
+use std::path::{Path, PathBuf};
+
+use typst::foundations::Bytes;
+use typst::text::Font;
+
+use crate::theme::tokens::FontTokens;
+use crate::warnings::{SilkprintWarning, WarningCollector};
+
 // ── Native: embed all fonts at compile time ─────────────────────────
 #[cfg(not(target_arch = "wasm32"))]
 mod native {
@@ -62,6 +70,82 @@ mod wasm {
     }
 }
 
+// ── Theme fonts ─────────────────────────────────────────────────────
+
+/// Whether `path` has a font file extension Typst can load.
+pub(crate) fn is_font_file(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| {
+        let e = ext.to_ascii_lowercase();
+        e == "ttf" || e == "otf" || e == "ttc" || e == "otc"
+    })
+}
+
+/// Load the font files a theme ships via `heading_source`, `body_source`
+/// and `mono_source`. Each may name a file or a directory of fonts.
+///
+/// Missing and unparseable files are reported as [`FontNotAvailable`]
+/// warnings; the theme then falls back to its font family chain.
+///
+/// [`FontNotAvailable`]: SilkprintWarning::FontNotAvailable
+pub fn load_theme_fonts(tokens: &FontTokens, warnings: &mut WarningCollector) -> Vec<Bytes> {
+    let mut seen: Vec<PathBuf> = Vec::new();
+    let mut fonts = Vec::new();
+
+    for (source, family) in [
+        (&tokens.heading_source, &tokens.heading),
+        (&tokens.body_source, &tokens.body),
+        (&tokens.mono_source, &tokens.mono),
+    ] {
+        if source.is_empty() {
+            continue;
+        }
+        let path = PathBuf::from(source);
+        let files = if path.is_dir() {
+            let mut files: Vec<PathBuf> = std::fs::read_dir(&path)
+                .into_iter()
+                .flatten()
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|file| is_font_file(file))
+                .collect();
+            files.sort();
+            files
+        } else {
+            vec![path.clone()]
+        };
+
+        let unavailable = |name: &Path| SilkprintWarning::FontNotAvailable {
+            name: name.display().to_string(),
+            fallback: family.clone(),
+        };
+        if files.is_empty() {
+            warnings.push(unavailable(&path));
+        }
+        for file in files {
+            if seen.contains(&file) {
+                continue;
+            }
+            let data = match std::fs::read(&file) {
+                Ok(data) => Bytes::new(data),
+                Err(err) => {
+                    tracing::debug!(path = %file.display(), %err, "theme font unreadable");
+                    warnings.push(unavailable(&file));
+                    continue;
+                }
+            };
+            if Font::iter(data.clone()).next().is_none() {
+                warnings.push(unavailable(&file));
+                continue;
+            }
+            tracing::debug!(path = %file.display(), "loaded theme font");
+            fonts.push(data);
+            seen.push(file);
+        }
+    }
+
+    fonts
+}
+
 // ── Re-exports ──────────────────────────────────────────────────────
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -85,3 +169,55 @@ pub fn add_external_font(_data: Vec<u8>) {}
 /// No-op on native — fonts are embedded at compile time.
 #[cfg(not(target_arch = "wasm32"))]
 pub fn clear_external_fonts() {}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use crate::ThemeSource;
+    use crate::theme::load_theme_from;
+    use crate::theme::registry::ThemeRegistry;
+
+    use super::*;
+
+    #[test]
+    fn theme_fonts_resolve_from_the_defining_theme() {
+        let dir = tempdir().expect("should create temp dir");
+        let parent_dir = dir.path().join("brand");
+        std::fs::create_dir_all(parent_dir.join("fonts")).expect("should create font dir");
+        std::fs::copy(
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("fonts/core/NewCMMath-Book.otf"),
+            parent_dir.join("fonts/Math.otf"),
+        )
+        .expect("should copy font");
+        std::fs::write(parent_dir.join("fonts/notes.txt"), "not a font").expect("should write");
+        std::fs::write(parent_dir.join("fonts/Broken.ttf"), "not a font").expect("should write");
+        std::fs::write(
+            parent_dir.join("brand.toml"),
+            "[meta]\nname = \"Brand\"\nextends = \"silk-light\"\n\n\
+             [fonts]\nbody_source = \"fonts\"\nmono_source = \"missing.ttf\"\n",
+        )
+        .expect("should write theme");
+        let child = dir.path().join("report.toml");
+        std::fs::write(&child, "[meta]\nname = \"Report\"\nextends = \"brand\"\n")
+            .expect("should write theme");
+
+        let registry = ThemeRegistry::with_dirs(None, Some(parent_dir.clone()));
+        let mut warnings = WarningCollector::new();
+        let theme = load_theme_from(&ThemeSource::Custom(child), &registry, &mut warnings)
+            .expect("should resolve the chain");
+
+        assert_eq!(theme.fonts.len(), 1, "only the parseable font should load");
+        let unavailable: Vec<String> = warnings
+            .warnings()
+            .iter()
+            .filter_map(|w| match w {
+                SilkprintWarning::FontNotAvailable { name, .. } => Some(name.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(unavailable.len(), 2, "{unavailable:?}");
+        assert!(unavailable[0].ends_with("Broken.ttf"));
+        assert!(unavailable[1].ends_with("missing.ttf"));
+    }
+}
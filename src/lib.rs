@@ -1,5 +1,7 @@
+pub mod batch;
 #[cfg(feature = "cli")]
 pub mod cli;
+pub mod diagnostics;
 pub mod error;
 pub mod fonts;
 pub mod render;
@@ -14,7 +16,7 @@ use warnings::WarningCollector;
 /// How to find the theme to apply.
 #[derive(Debug, Clone)]
 pub enum ThemeSource {
-    /// A built-in theme by name.
+    /// A theme by name: built-in, or from a user or project theme directory.
     BuiltIn(String),
     /// A custom theme file path.
     Custom(PathBuf),
@@ -55,6 +57,40 @@ impl PaperSize {
     }
 }
 
+/// A PDF standard the output must conform to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PdfStandard {
+    /// PDF/A-2b: long-term archiving, visual fidelity.
+    A2b,
+    /// PDF/A-3b: PDF/A-2b that also permits embedded files.
+    A3b,
+    /// PDF/UA-1: tagged, accessible PDF. Requires a document title and alt
+    /// text for every image.
+    Ua1,
+}
+
+impl PdfStandard {
+    pub fn from_str_case_insensitive(s: &str) -> Result<Self, SilkprintError> {
+        match s.to_lowercase().as_str() {
+            "a-2b" => Ok(Self::A2b),
+            "a-3b" => Ok(Self::A3b),
+            "ua-1" => Ok(Self::Ua1),
+            _ => Err(SilkprintError::InvalidPdfStandard {
+                standard: s.to_string(),
+            }),
+        }
+    }
+
+    /// Display name, as used in the standards themselves (`PDF/A-2b`).
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::A2b => "PDF/A-2b",
+            Self::A3b => "PDF/A-3b",
+            Self::Ua1 => "PDF/UA-1",
+        }
+    }
+}
+
 /// Options for the render pipeline.
 #[derive(Debug, Clone)]
 pub struct RenderOptions {
@@ -66,6 +102,24 @@ pub struct RenderOptions {
     pub font_dirs: Vec<PathBuf>,
     pub toc: Option<bool>,
     pub title_page: Option<bool>,
+    /// Standard the PDF must conform to, if any.
+    pub pdf_standard: Option<PdfStandard>,
+    /// Unix time to stamp instead of the system clock, for reproducible
+    /// output. Falls back to `SOURCE_DATE_EPOCH` when `None`.
+    pub fixed_time: Option<i64>,
+    /// WCAG level to repair theme colors to, overriding the theme's
+    /// `[meta] enforce_contrast`.
+    pub fix_contrast: Option<theme::contrast::ContrastLevel>,
+    /// Render with a print-safe variant of the theme: white paper, in color
+    /// or greyscale.
+    pub print_safe: Option<theme::print::PrintSafe>,
+    /// Emit ```` ```typst ```` blocks verbatim instead of as code listings.
+    /// Only for documents you trust.
+    pub allow_raw_typst: bool,
+    /// Watermark text, or an image when it has an image extension: an
+    /// absolute path under the document's directory, or a path relative to
+    /// it. Replaces the text or image of front matter `watermark:`.
+    pub watermark: Option<String>,
 }
 
 impl Default for RenderOptions {
@@ -77,35 +131,213 @@ impl Default for RenderOptions {
             font_dirs: Vec::new(),
             toc: None,
             title_page: None,
+            pdf_standard: None,
+            fixed_time: None,
+            fix_contrast: None,
+            print_safe: None,
+            allow_raw_typst: false,
+            watermark: None,
+        }
+    }
+}
+
+impl RenderOptions {
+    /// The load-time theme changes these options ask for.
+    pub fn theme_adjustments(&self) -> theme::ThemeAdjustments {
+        theme::ThemeAdjustments {
+            fix_contrast: self.fix_contrast,
+            print_safe: self.print_safe,
         }
     }
 }
 
 /// Render markdown to PDF bytes.
+///
+/// Loads fonts for this one call; hold a [`RenderSession`] to render several
+/// documents.
 pub fn render(
     input: &str,
     input_path: Option<&Path>,
     options: &RenderOptions,
 ) -> Result<(Vec<u8>, Vec<warnings::SilkprintWarning>), SilkprintError> {
+    RenderSession::new(&options.font_dirs)?.render(input, input_path, options)
+}
+
+/// Render a book — a directory or `SUMMARY.md`/`chapters:` manifest — to PDF bytes.
+///
+/// Chapters are compiled into one document with a single outline; links
+/// between chapter files become internal PDF links.
+pub fn render_book(
+    path: &Path,
+    options: &RenderOptions,
+) -> Result<(Vec<u8>, Vec<warnings::SilkprintWarning>), SilkprintError> {
+    RenderSession::new(&options.font_dirs)?.render_book(path, options)
+}
+
+/// A reusable renderer that keeps fonts and Typst's caches between documents.
+///
+/// [`render`] and [`render_book`] build a session per call. Batch jobs,
+/// watchers, and servers should hold one instead: fonts are parsed once, and
+/// Typst's memoized layout is shared by everything the session renders.
+/// Cloning is cheap and clones share the same fonts.
+#[derive(Clone)]
+pub struct RenderSession {
+    engine: render::typst::Engine,
+}
+
+impl RenderSession {
+    /// Load the bundled fonts plus any fonts in `font_dirs`.
+    ///
+    /// A session's fonts are fixed: `font_dirs` in the [`RenderOptions`]
+    /// passed to later calls is ignored.
+    pub fn new(font_dirs: &[PathBuf]) -> Result<Self, SilkprintError> {
+        Ok(Self {
+            engine: render::typst::Engine::new(font_dirs)?,
+        })
+    }
+
+    /// Render markdown to PDF bytes. See [`render`].
+    pub fn render(
+        &self,
+        input: &str,
+        input_path: Option<&Path>,
+        options: &RenderOptions,
+    ) -> Result<(Vec<u8>, Vec<warnings::SilkprintWarning>), SilkprintError> {
+        let mut warnings = WarningCollector::new();
+
+        // Extract front matter first — it may override the theme
+        let (front_matter, source) = split_front_matter(input, input_path, &mut warnings)?;
+        let effective_theme_source = resolve_effective_theme(options, front_matter.as_ref());
+        let resolved_theme = load_render_theme(&effective_theme_source, options, &mut warnings)?;
+
+        let pdf_bytes = render::render_pipeline(
+            &self.engine,
+            &source.text,
+            front_matter.as_ref(),
+            input_path,
+            options,
+            &resolved_theme,
+            &mut warnings,
+        )?;
+        Ok((pdf_bytes, warnings.into_warnings()))
+    }
+
+    /// Render a book to PDF bytes. See [`render_book`].
+    pub fn render_book(
+        &self,
+        path: &Path,
+        options: &RenderOptions,
+    ) -> Result<(Vec<u8>, Vec<warnings::SilkprintWarning>), SilkprintError> {
+        let mut warnings = WarningCollector::new();
+
+        let book = render::book::Book::load(path)?;
+        if let Some(fm) = &book.front_matter {
+            render::frontmatter::warn_unknown_fields(fm, &mut warnings);
+        }
+        let effective_theme_source = resolve_effective_theme(options, book.front_matter.as_ref());
+        let resolved_theme = load_render_theme(&effective_theme_source, options, &mut warnings)?;
+
+        let pdf_bytes = render::render_book_pipeline(
+            &self.engine,
+            &book,
+            options,
+            &resolved_theme,
+            &mut warnings,
+        )?;
+        Ok((pdf_bytes, warnings.into_warnings()))
+    }
+
+    /// A renderer for one changing document that shares this session's fonts.
+    pub fn incremental(&self) -> IncrementalRenderer {
+        IncrementalRenderer {
+            compiler: render::typst::Compiler::new(self.engine.clone()),
+        }
+    }
+}
+
+/// A PDF rebuilt by [`IncrementalRenderer`], with the files it was built from.
+#[derive(Debug)]
+pub struct Rebuild {
+    pub pdf: Vec<u8>,
+    pub warnings: Vec<warnings::SilkprintWarning>,
+    /// Local files besides the input that the output depends on: included
+    /// files, a custom theme, images, bibliographies. Files that are referenced but missing
+    /// are included, so a watcher can pick them up once they appear.
+    pub dependencies: Vec<PathBuf>,
+}
+
+/// Re-renders one document to PDF as it changes, for `--watch`.
+///
+/// Created by [`RenderSession::incremental`]. The Typst world is kept between
+/// revisions, so rebuilds reuse Typst's memoized parsing and layout.
+pub struct IncrementalRenderer {
+    compiler: render::typst::Compiler,
+}
+
+impl IncrementalRenderer {
+    /// Render the current revision of `input`. Same pipeline as [`render`].
+    pub fn render(
+        &mut self,
+        input: &str,
+        input_path: Option<&Path>,
+        options: &RenderOptions,
+    ) -> Result<Rebuild, SilkprintError> {
+        let mut warnings = WarningCollector::new();
+
+        let (front_matter, source) = split_front_matter(input, input_path, &mut warnings)?;
+        let effective_theme_source = resolve_effective_theme(options, front_matter.as_ref());
+        let resolved_theme = load_render_theme(&effective_theme_source, options, &mut warnings)?;
+
+        let document = render::prepare_document(
+            &source.text,
+            front_matter.as_ref(),
+            input_path,
+            render::image::ImageMode::Compile,
+            options,
+            &resolved_theme,
+            &mut warnings,
+        )?;
+        render::conformance::enforce(options.pdf_standard, &warnings)?;
+        let pdf = self.compiler.compile_to_pdf(
+            &document.typst_source,
+            &resolved_theme,
+            &document.root_dir,
+            &document.mermaid_svgs,
+            &document.remote_assets,
+            render::typst::PdfSettings::from_options(options),
+        )?;
+
+        let mut dependencies = source.dependencies;
+        dependencies.extend(document.dependencies);
+        dependencies.extend(theme::theme_files(
+            &effective_theme_source,
+            &theme::registry::ThemeRegistry::discover(),
+        ));
+        Ok(Rebuild {
+            pdf,
+            warnings: warnings.into_warnings(),
+            dependencies,
+        })
+    }
+}
+
+/// Render a book to Typst source (intermediate representation).
+pub fn render_book_to_typst(
+    path: &Path,
+    options: &RenderOptions,
+) -> Result<(String, Vec<warnings::SilkprintWarning>), SilkprintError> {
     let mut warnings = WarningCollector::new();
 
-    // Extract front matter first — it may override the theme
-    let (front_matter, body) = render::frontmatter::extract(input)?;
-    if let Some(fm) = &front_matter {
+    let book = render::book::Book::load(path)?;
+    if let Some(fm) = &book.front_matter {
         render::frontmatter::warn_unknown_fields(fm, &mut warnings);
     }
-    let effective_theme_source = resolve_effective_theme(options, front_matter.as_ref());
-    let resolved_theme = theme::load_theme(&effective_theme_source, &mut warnings)?;
+    let effective_theme_source = resolve_effective_theme(options, book.front_matter.as_ref());
+    let resolved_theme = load_render_theme(&effective_theme_source, options, &mut warnings)?;
 
-    let pdf_bytes = render::render_pipeline(
-        &body,
-        front_matter.as_ref(),
-        input_path,
-        options,
-        &resolved_theme,
-        &mut warnings,
-    )?;
-    Ok((pdf_bytes, warnings.into_warnings()))
+    let typst_source =
+        render::render_book_to_typst_source(&book, options, &resolved_theme, &mut warnings)?;
+    Ok((typst_source, warnings.into_warnings()))
 }
 
 /// Render markdown to a styled terminal string (one-shot ANSI).
@@ -135,15 +367,16 @@ pub fn render_to_terminal_with_origin(
 ) -> Result<(String, Vec<warnings::SilkprintWarning>), SilkprintError> {
     let mut warnings = WarningCollector::new();
 
-    let (front_matter, body) = render::frontmatter::extract(input)?;
-    if let Some(fm) = &front_matter {
-        render::frontmatter::warn_unknown_fields(fm, &mut warnings);
-    }
+    let local_path = match origin {
+        Some(render::origin::DocumentOrigin::Local(path)) => Some(path.as_path()),
+        _ => None,
+    };
+    let (front_matter, source) = split_front_matter(input, local_path, &mut warnings)?;
     let effective_theme_source = resolve_effective_theme(options, front_matter.as_ref());
-    let resolved_theme = theme::load_theme(&effective_theme_source, &mut warnings)?;
+    let resolved_theme = load_render_theme(&effective_theme_source, options, &mut warnings)?;
 
     let output = render::terminal::render_to_string_with_origin(
-        &body,
+        &source.text,
         &resolved_theme,
         terminal_options,
         &mut warnings,
@@ -173,7 +406,7 @@ pub fn resolve_terminal_theme(
         render::frontmatter::warn_unknown_fields(fm, &mut warnings);
     }
     let source = resolve_effective_theme(options, front_matter.as_ref());
-    let resolved = theme::load_theme(&source, &mut warnings)?;
+    let resolved = load_render_theme(&source, options, &mut warnings)?;
     let name = match &source {
         ThemeSource::BuiltIn(n) => n.clone(),
         _ => resolved.tokens.meta.name.clone(),
@@ -204,15 +437,12 @@ pub fn render_to_typst_with_path(
 ) -> Result<(String, Vec<warnings::SilkprintWarning>), SilkprintError> {
     let mut warnings = WarningCollector::new();
 
-    let (front_matter, body) = render::frontmatter::extract(input)?;
-    if let Some(fm) = &front_matter {
-        render::frontmatter::warn_unknown_fields(fm, &mut warnings);
-    }
+    let (front_matter, source) = split_front_matter(input, input_path, &mut warnings)?;
     let effective_theme_source = resolve_effective_theme(options, front_matter.as_ref());
-    let resolved_theme = theme::load_theme(&effective_theme_source, &mut warnings)?;
+    let resolved_theme = load_render_theme(&effective_theme_source, options, &mut warnings)?;
 
     let typst_source = render::render_to_typst_source(
-        &body,
+        &source.text,
         front_matter.as_ref(),
         input_path,
         options,
@@ -222,19 +452,88 @@ pub fn render_to_typst_with_path(
     Ok((typst_source, warnings.into_warnings()))
 }
 
+/// Render markdown to a standalone, themed HTML document.
+pub fn render_to_html(
+    input: &str,
+    options: &RenderOptions,
+) -> Result<(String, Vec<warnings::SilkprintWarning>), SilkprintError> {
+    render_to_html_with_options(input, None, options, false)
+}
+
+/// Render markdown to a standalone, themed HTML document with an optional
+/// input path for asset resolution and link validation.
 pub fn render_to_html_with_path(
     input: &str,
     input_path: Option<&Path>,
     validate_links: bool,
+) -> Result<(String, Vec<warnings::SilkprintWarning>), SilkprintError> {
+    render_to_html_with_options(input, input_path, &RenderOptions::default(), validate_links)
+}
+
+/// [`render_to_html_with_path`], with the theme and its adjustments taken
+/// from `options`.
+pub fn render_to_html_with_options(
+    input: &str,
+    input_path: Option<&Path>,
+    options: &RenderOptions,
+    validate_links: bool,
 ) -> Result<(String, Vec<warnings::SilkprintWarning>), SilkprintError> {
     let mut warnings = WarningCollector::new();
 
+    let (front_matter, source) = split_front_matter(input, input_path, &mut warnings)?;
+    let effective_theme_source = resolve_effective_theme(options, front_matter.as_ref());
+    let resolved_theme = load_render_theme(&effective_theme_source, options, &mut warnings)?;
+
+    let html = render::render_to_html_source(
+        &source.text,
+        front_matter.as_ref(),
+        input_path,
+        &resolved_theme,
+        validate_links,
+        &mut warnings,
+    )?;
+    Ok((html, warnings.into_warnings()))
+}
+
+/// Load `source` for a render, with the adjustments `options` asks for.
+fn load_render_theme(
+    source: &ThemeSource,
+    options: &RenderOptions,
+    warnings: &mut WarningCollector,
+) -> Result<theme::ResolvedTheme, SilkprintError> {
+    theme::load_theme_with(
+        source,
+        &theme::registry::ThemeRegistry::discover(),
+        options.theme_adjustments(),
+        warnings,
+    )
+}
+
+/// Split off front matter, warning about unknown fields, resolve includes,
+/// and point located warnings at `input_path` (or the included file) in file
+/// (not body) coordinates.
+fn split_front_matter(
+    input: &str,
+    input_path: Option<&Path>,
+    warnings: &mut WarningCollector,
+) -> Result<
+    (
+        Option<render::frontmatter::FrontMatter>,
+        render::include::Expanded,
+    ),
+    SilkprintError,
+> {
     let (front_matter, body) = render::frontmatter::extract(input)?;
     if let Some(fm) = &front_matter {
-        render::frontmatter::warn_unknown_fields(fm, &mut warnings);
+        render::frontmatter::warn_unknown_fields(fm, warnings);
     }
-    let html = render::render_to_html_source(&body, input_path, validate_links, &mut warnings)?;
-    Ok((html, warnings.into_warnings()))
+    warnings.set_source(
+        input_path,
+        render::frontmatter::body_line_offset(input, &body),
+    );
+    let expanded = render::include::expand(&body, input_path, warnings);
+    warnings.set_line_origins(expanded.origins.clone());
+    Ok((front_matter, expanded))
 }
 
 /// Determine the effective theme source, respecting precedence:
@@ -263,7 +562,9 @@ fn resolve_effective_theme(
 
 #[cfg(test)]
 mod tests {
-    use super::render_to_html_with_path;
+    use super::{
+        RenderOptions, RenderSession, render_to_html_with_options, render_to_html_with_path,
+    };
 
     #[test]
     fn renders_markdown_to_html() {
@@ -271,7 +572,60 @@ mod tests {
             render_to_html_with_path("# Title\n\nBody", None, false).expect("html");
 
         assert!(warnings.is_empty());
-        assert!(html.contains("<h1>Title</h1>"));
+        assert!(html.contains("<h1 id=\"title\">Title</h1>"));
         assert!(html.contains("<p>Body</p>"));
     }
+
+    #[test]
+    fn html_export_is_standalone_and_themed() {
+        let input = "---\ntitle: Themed\n---\n\n# Title\n";
+        let (html, _warnings) =
+            render_to_html_with_options(input, None, &RenderOptions::default(), false)
+                .expect("html");
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>Themed</title>"));
+        assert!(html.contains("--sp-heading-color:"));
+        assert!(html.contains("@font-face"));
+    }
+
+    #[test]
+    fn incremental_renderer_rebuilds_and_reports_dependencies() {
+        let dir = tempfile::tempdir().expect("should create tempdir");
+        let input_path = dir.path().join("doc.md");
+        let options = RenderOptions::default();
+        let session = RenderSession::new(&options.font_dirs).expect("should load fonts");
+        let mut renderer = session.incremental();
+
+        let first = renderer
+            .render(
+                "# One\n\n![Diagram](missing.png)\n",
+                Some(&input_path),
+                &options,
+            )
+            .expect("should render first revision");
+        assert!(first.pdf.starts_with(b"%PDF"));
+        assert_eq!(first.dependencies, vec![dir.path().join("missing.png")]);
+
+        let second = renderer
+            .render("# Two\n", Some(&input_path), &options)
+            .expect("should render second revision");
+        assert!(second.pdf.starts_with(b"%PDF"));
+        assert!(second.dependencies.is_empty());
+        assert!(second.warnings.is_empty());
+    }
+
+    #[test]
+    fn render_session_renders_several_documents() {
+        let options = RenderOptions::default();
+        let session = RenderSession::new(&options.font_dirs).expect("should load fonts");
+
+        for input in ["# First\n\nBody", "# Second\n\n| a |\n|---|\n| 1 |\n"] {
+            let (pdf, warnings) = session
+                .render(input, None, &options)
+                .expect("should render with a shared session");
+            assert!(pdf.starts_with(b"%PDF"));
+            assert!(warnings.is_empty());
+        }
+    }
 }
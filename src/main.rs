@@ -1,18 +1,24 @@
 #![allow(clippy::print_stdout, clippy::print_stderr)]
 
+use std::collections::{HashMap, HashSet};
 use std::io::{self, IsTerminal, Write};
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::time::Instant;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
 
 use clap::Parser;
 use indicatif::{ProgressBar, ProgressStyle};
 use owo_colors::OwoColorize;
 use tracing::debug;
 
-use silkprint::cli::Cli;
+use silkprint::cli::{Cli, ThemeCommand};
+use silkprint::diagnostics::Diagnostic;
+use silkprint::theme::contrast::ContrastLevel;
+use silkprint::theme::print::PrintSafe;
+use silkprint::theme::registry::{ThemeLocation, ThemeOrigin, ThemeRegistry};
 use silkprint::warnings::SilkprintWarning;
-use silkprint::{PaperSize, RenderOptions, ThemeSource};
+use silkprint::{PaperSize, PdfStandard, RenderOptions, ThemeSource};
 
 // ── Color control ──────────────────────────────────────────────
 
@@ -141,11 +147,12 @@ const SEPARATOR: &str = "\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500
 
 // ── Mode handlers ──────────────────────────────────────────────
 
-/// Handle `--list-themes`: display all built-in themes and exit.
+/// Handle `--list-themes`: display built-in and custom themes and exit.
 fn handle_list_themes() {
-    let mut themes = silkprint::theme::builtin::list_themes();
+    let registry = ThemeRegistry::discover();
+    let mut themes = registry.list();
     // Print-safe first, then the rest — alphabetical within each group
-    themes.sort_by(|a, b| b.print_safe.cmp(&a.print_safe).then(a.name.cmp(b.name)));
+    themes.sort_by(|a, b| b.print_safe.cmp(&a.print_safe).then(a.name.cmp(&b.name)));
 
     // Column widths (plain text, before colorization)
     let name_w = 22;
@@ -185,7 +192,7 @@ fn handle_list_themes() {
             println!("  {thin_sep}");
         }
         prev_print_safe = t.print_safe;
-        let swatch = theme_swatch(t.name);
+        let swatch = theme_swatch(&registry, &t.name);
         let is_default = t.name == "silkcircuit-dawn";
 
         // Pad the plain name first, then colorize
@@ -210,10 +217,15 @@ fn handle_list_themes() {
             " ".to_string()
         };
 
-        println!(
-            "  {swatch}  {name}  {variant}  {badge}  {}",
-            dim(t.description)
-        );
+        let description = match t.origin {
+            ThemeOrigin::BuiltIn => dim(&t.description),
+            origin => format!(
+                "{} {}",
+                purple(&format!("[{}]", origin.label())),
+                dim(&t.description)
+            ),
+        };
+        println!("  {swatch}  {name}  {variant}  {badge}  {description}");
     }
 
     println!("  {wide_sep}");
@@ -229,8 +241,8 @@ fn handle_list_themes() {
 ///
 /// Extracts background, heading, text, and link colors from the theme TOML
 /// and renders each as a truecolor `█` block.
-fn theme_swatch(name: &str) -> String {
-    let [bg, heading, text, link] = extract_swatch_colors(name);
+fn theme_swatch(registry: &ThemeRegistry, name: &str) -> String {
+    let [bg, heading, text, link] = extract_swatch_colors(registry, name);
 
     if !color_enabled() {
         return "\u{2588}\u{2588}\u{2588}\u{2588}".to_string();
@@ -250,10 +262,13 @@ fn theme_swatch(name: &str) -> String {
     )
 }
 
-/// Extract 4 key swatch colors from a theme's embedded TOML.
+/// Extract 4 key swatch colors from a theme's TOML.
+///
+/// Custom themes may inherit their colors, so they are fully resolved;
+/// built-ins are read straight from the embedded TOML.
 ///
 /// Returns `[background, heading_color, text_color, link_color]` as hex strings.
-fn extract_swatch_colors(name: &str) -> [String; 4] {
+fn extract_swatch_colors(registry: &ThemeRegistry, name: &str) -> [String; 4] {
     let fallback = || {
         [
             "#888888".to_string(),
@@ -263,8 +278,24 @@ fn extract_swatch_colors(name: &str) -> [String; 4] {
         ]
     };
 
-    let Some(toml_str) = silkprint::theme::builtin::get_builtin_theme(name) else {
-        return fallback();
+    let toml_str = match registry.lookup(name) {
+        Some(ThemeLocation::BuiltIn(toml_str)) => toml_str,
+        Some(ThemeLocation::File { .. }) => {
+            let source = ThemeSource::BuiltIn(name.to_string());
+            let mut warnings = silkprint::warnings::WarningCollector::new();
+            let Ok(theme) = silkprint::theme::load_theme_from(&source, registry, &mut warnings)
+            else {
+                return fallback();
+            };
+            let tokens = theme.tokens;
+            return [
+                tokens.page.background,
+                tokens.headings.color,
+                tokens.text.color,
+                tokens.links.color,
+            ];
+        }
+        None => return fallback(),
     };
 
     let Ok(table) = toml_str.parse::<toml::Table>() else {
@@ -346,7 +377,7 @@ fn handle_check(
     }
     let elapsed = start.elapsed();
 
-    display_warnings(&warnings);
+    report_warnings(cli, Some(input_path), &warnings);
 
     eprintln!(
         "  {} {} validated in {:.0?}",
@@ -355,25 +386,27 @@ fn handle_check(
         elapsed,
     );
 
-    Ok(())
+    deny_warnings(cli, &warnings)
 }
 
 /// Handle `--dump-typst`: emit Typst markup to stdout or file.
 fn handle_dump_typst(
+    cli: &Cli,
     input_path: &std::path::Path,
-    output: Option<&str>,
     options: &RenderOptions,
-    quiet: bool,
 ) -> miette::Result<()> {
     let input = read_document_input(input_path)?.body;
 
     let (typst_source, warnings) =
         silkprint::render_to_typst_with_path(&input, Some(input_path), options)?;
 
-    if !quiet {
-        display_warnings(&warnings);
-    }
+    report_warnings(cli, Some(input_path), &warnings);
+    write_typst_output(&typst_source, cli.output.as_deref(), cli.quiet)?;
+    deny_warnings(cli, &warnings)
+}
 
+/// Write generated Typst source to a file, or stdout for `None` / `-`.
+fn write_typst_output(typst_source: &str, output: Option<&str>, quiet: bool) -> miette::Result<()> {
     match output {
         Some(path) if path != "-" => {
             std::fs::write(path, &typst_source).map_err(|e| {
@@ -404,14 +437,20 @@ fn handle_dump_typst(
     Ok(())
 }
 
-fn handle_dump_html(cli: &Cli, input_path: &std::path::Path) -> miette::Result<()> {
+fn handle_dump_html(
+    cli: &Cli,
+    input_path: &std::path::Path,
+    options: &RenderOptions,
+) -> miette::Result<()> {
     let input = read_document_input(input_path)?.body;
-    let (html, warnings) =
-        silkprint::render_to_html_with_path(&input, Some(input_path), cli.validate_links)?;
+    let (html, warnings) = silkprint::render_to_html_with_options(
+        &input,
+        Some(input_path),
+        options,
+        cli.validate_links,
+    )?;
 
-    if !cli.quiet {
-        display_warnings(&warnings);
-    }
+    report_warnings(cli, Some(input_path), &warnings);
 
     match cli.output.as_deref() {
         Some(path) if path != "-" => {
@@ -435,7 +474,7 @@ fn handle_dump_html(cli: &Cli, input_path: &std::path::Path) -> miette::Result<(
         }
     }
 
-    Ok(())
+    deny_warnings(cli, &warnings)
 }
 
 /// Create a spinner with `SilkCircuit` styling.
@@ -523,8 +562,21 @@ fn handle_render(cli: &Cli, input_path: &Path, options: &RenderOptions) -> miett
     if cli.validate_links {
         append_link_warnings(&input, Some(input_path), &mut warnings);
     }
+    finish_render(cli, input_path, &pdf_bytes, &warnings, start)
+}
+
+/// Write rendered PDF bytes, report warnings and a summary, and open the
+/// result if requested. `input_path` names the default output file.
+fn finish_render(
+    cli: &Cli,
+    input_path: &Path,
+    pdf_bytes: &[u8],
+    warnings: &[SilkprintWarning],
+    start: Instant,
+) -> miette::Result<()> {
+    let verbose = cli.verbose > 0;
     let output_path = cli.resolve_output_path(input_path);
-    let page_count = estimate_page_count(&pdf_bytes);
+    let page_count = estimate_page_count(pdf_bytes);
 
     if verbose {
         let bar = "\u{2588}".repeat(page_count.min(20));
@@ -546,7 +598,7 @@ fn handle_render(cli: &Cli, input_path: &Path, options: &RenderOptions) -> miett
             );
         }
         debug!("writing PDF to: {}", path.display());
-        std::fs::write(path, &pdf_bytes).map_err(|e| {
+        std::fs::write(path, pdf_bytes).map_err(|e| {
             silkprint::error::SilkprintError::OutputWrite {
                 path: path.display().to_string(),
                 source: e,
@@ -555,7 +607,7 @@ fn handle_render(cli: &Cli, input_path: &Path, options: &RenderOptions) -> miett
     } else {
         // stdout mode
         debug!("writing PDF to stdout");
-        io::stdout().write_all(&pdf_bytes).map_err(|e| {
+        io::stdout().write_all(pdf_bytes).map_err(|e| {
             silkprint::error::SilkprintError::OutputWrite {
                 path: "<stdout>".to_string(),
                 source: e,
@@ -565,9 +617,7 @@ fn handle_render(cli: &Cli, input_path: &Path, options: &RenderOptions) -> miett
 
     let elapsed = start.elapsed();
 
-    if !cli.quiet {
-        display_warnings(&warnings);
-    }
+    report_warnings(cli, Some(input_path), warnings);
 
     // Summary output
     if verbose {
@@ -592,6 +642,8 @@ fn handle_render(cli: &Cli, input_path: &Path, options: &RenderOptions) -> miett
         );
     }
 
+    deny_warnings(cli, warnings)?;
+
     // Open in system viewer if requested
     if cli.open
         && let Some(ref path) = output_path
@@ -606,6 +658,362 @@ fn handle_render(cli: &Cli, input_path: &Path, options: &RenderOptions) -> miett
     Ok(())
 }
 
+// ── Watch mode ─────────────────────────────────────────────────
+
+/// Quiet period after the last file event before rebuilding, so a burst of
+/// saves (editor temp file + rename, formatter passes) triggers one render.
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// Handle `--watch`: render once, then re-render whenever the input or a file
+/// it depends on changes. Errors are reported and watching continues.
+fn handle_watch(cli: &Cli, input_path: &Path, options: &RenderOptions) -> miette::Result<()> {
+    let Some(output_path) = cli.resolve_output_path(input_path) else {
+        return Err(silkprint::error::SilkprintError::ConflictingOptions {
+            details: "--watch incompatible with stdout output".to_string(),
+        }
+        .into());
+    };
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        if let Ok(event) = res {
+            let _ = tx.send(event);
+        }
+    })
+    .map_err(|e| silkprint::error::SilkprintError::RenderFailed {
+        details: format!("failed to start file watcher: {e}"),
+        hint: "Check the platform's file watch limits (e.g. fs.inotify.max_user_watches)"
+            .to_string(),
+    })?;
+
+    let mut renderer = silkprint::RenderSession::new(&options.font_dirs)?.incremental();
+    let mut watched_files = HashSet::from([watch_key(input_path)]);
+    let mut watched_dirs = HashSet::new();
+    let mut first_build = true;
+    let mut announced = false;
+
+    loop {
+        let start = Instant::now();
+        match rebuild_watched(cli, input_path, &output_path, options, &mut renderer) {
+            Ok((pages, warnings, dependencies)) => {
+                watched_files = std::iter::once(input_path)
+                    .chain(dependencies.iter().map(PathBuf::as_path))
+                    .map(watch_key)
+                    .collect();
+                report_warnings(cli, Some(input_path), &warnings);
+                if !cli.quiet {
+                    report_rebuild(&output_path, pages, warnings.len(), start, first_build);
+                }
+                if first_build && cli.open {
+                    open::that(&output_path).map_err(|e| {
+                        silkprint::error::SilkprintError::RenderFailed {
+                            details: format!("failed to open PDF viewer: {e}"),
+                            hint: "Check that a PDF viewer is installed and associated with .pdf files".to_string(),
+                        }
+                    })?;
+                }
+                first_build = false;
+            }
+            // Keep the previous dependency set: a broken theme or a half-saved
+            // file should not stop us from noticing the fix.
+            Err(err) => report_error(cli, Some(input_path), &err),
+        }
+
+        for file in &watched_files {
+            let Some(dir) = file.parent() else { continue };
+            if !watched_dirs.contains(dir)
+                && watcher
+                    .watch(dir, notify::RecursiveMode::NonRecursive)
+                    .is_ok()
+            {
+                watched_dirs.insert(dir.to_path_buf());
+            }
+        }
+        if watched_dirs.is_empty() {
+            return Err(silkprint::error::SilkprintError::RenderFailed {
+                details: format!("failed to watch {}", input_path.display()),
+                hint: "Check the platform's file watch limits (e.g. fs.inotify.max_user_watches)"
+                    .to_string(),
+            }
+            .into());
+        }
+        if !announced && !cli.quiet {
+            eprintln!("  {}", dim("Watching for changes (Ctrl+C to stop)"));
+        }
+        announced = true;
+
+        wait_for_change(&rx, &watched_files);
+    }
+}
+
+/// Render the input once and write the PDF, returning the page count,
+/// warnings, and the files the output was built from.
+fn rebuild_watched(
+    cli: &Cli,
+    input_path: &Path,
+    output_path: &Path,
+    options: &RenderOptions,
+    renderer: &mut silkprint::IncrementalRenderer,
+) -> miette::Result<(usize, Vec<SilkprintWarning>, Vec<PathBuf>)> {
+    let input = read_document_input(input_path)?.body;
+    let rebuild = renderer.render(&input, Some(input_path), options)?;
+    let mut warnings = rebuild.warnings;
+    if cli.validate_links {
+        append_link_warnings(&input, Some(input_path), &mut warnings);
+    }
+    std::fs::write(output_path, &rebuild.pdf).map_err(|e| {
+        silkprint::error::SilkprintError::OutputWrite {
+            path: output_path.display().to_string(),
+            source: e,
+        }
+    })?;
+    Ok((
+        estimate_page_count(&rebuild.pdf),
+        warnings,
+        rebuild.dependencies,
+    ))
+}
+
+/// One line per build: `✓ doc.pdf (3 pages, 412ms)` first, then
+/// `↻ doc.pdf rebuilt in 38ms · 3 pages · 1 warning`.
+fn report_rebuild(
+    output_path: &Path,
+    pages: usize,
+    warning_count: usize,
+    start: Instant,
+    first_build: bool,
+) {
+    let elapsed = start.elapsed();
+    let display_path = output_path.display().to_string();
+    if first_build {
+        eprintln!(
+            "  {} {} ({} pages, {:.0?})",
+            green("\u{2713}"),
+            cyan(&display_path),
+            pages,
+            elapsed,
+        );
+        return;
+    }
+    let warnings = match warning_count {
+        0 => String::new(),
+        1 => format!(" \u{b7} {}", yellow("1 warning")),
+        n => format!(" \u{b7} {}", yellow(&format!("{n} warnings"))),
+    };
+    eprintln!(
+        "  {} {} rebuilt in {:.0?} \u{b7} {} pages{}",
+        purple("\u{21bb}"),
+        cyan(&display_path),
+        elapsed,
+        pages,
+        warnings,
+    );
+}
+
+/// Block until a watched file changes, then until events go quiet.
+fn wait_for_change(rx: &mpsc::Receiver<notify::Event>, watched_files: &HashSet<PathBuf>) {
+    let is_relevant = |event: &notify::Event| {
+        matches!(
+            event.kind,
+            notify::EventKind::Create(_)
+                | notify::EventKind::Modify(_)
+                | notify::EventKind::Remove(_)
+        ) && event
+            .paths
+            .iter()
+            .any(|path| watched_files.contains(&watch_key(path)))
+    };
+
+    loop {
+        let Ok(event) = rx.recv() else { return };
+        if is_relevant(&event) {
+            break;
+        }
+    }
+    while rx.recv_timeout(WATCH_DEBOUNCE).is_ok() {}
+}
+
+/// Normalize a path for comparison with watcher events, which report
+/// absolute paths with symlinked directories resolved.
+fn watch_key(path: &Path) -> PathBuf {
+    let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let (Some(dir), Some(name)) = (absolute.parent(), absolute.file_name()) else {
+        return absolute;
+    };
+    dir.canonicalize()
+        .map_or_else(|_| absolute.clone(), |dir| dir.join(name))
+}
+
+// ── Batch mode ─────────────────────────────────────────────────
+
+/// The result of rendering one file in a batch.
+struct BatchOutcome {
+    result: miette::Result<(usize, Vec<SilkprintWarning>)>,
+    elapsed: Duration,
+}
+
+/// Handle batch rendering: expand globs, render every file on a worker pool
+/// sharing one font set, and print a per-file summary table. Failures —
+/// including denied warnings — don't stop the batch, but make the exit code
+/// non-zero.
+fn run_batch(cli: &Cli, patterns: &[String]) -> miette::Result<()> {
+    let options = build_render_options(cli)?;
+    let out_dir = cli.out_dir.clone().unwrap_or_else(|| PathBuf::from("."));
+    let jobs = silkprint::batch::plan(patterns, &out_dir)?;
+    let threads = cli.jobs.unwrap_or_else(|| {
+        std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get)
+    });
+
+    let start = Instant::now();
+    let spinner = (!cli.quiet && cli.verbose == 0 && io::stderr().is_terminal())
+        .then(|| make_spinner(&format!("Rendering {} files", jobs.len())));
+    let session = silkprint::RenderSession::new(&options.font_dirs)?;
+    let outcomes = silkprint::batch::run_parallel(&jobs, threads, |job| {
+        let job_start = Instant::now();
+        let result =
+            render_batch_job(cli, &session, &options, job).and_then(|(pages, warnings)| {
+                if cli.json_messages() {
+                    report_warnings(cli, Some(&job.input), &warnings);
+                }
+                deny_warnings(cli, &warnings)?;
+                Ok((pages, warnings))
+            });
+        if let Err(err) = &result
+            && cli.json_messages()
+        {
+            report_error(cli, Some(&job.input), err);
+        }
+        BatchOutcome {
+            result,
+            elapsed: job_start.elapsed(),
+        }
+    });
+    if let Some(ref sp) = spinner {
+        sp.finish_and_clear();
+    }
+
+    let failed = outcomes.iter().filter(|o| o.result.is_err()).count();
+    print_batch_table(cli, &jobs, &outcomes);
+    if !cli.quiet {
+        let verb = if cli.check { "validated" } else { "rendered" };
+        let mut summary = format!("  {} {} {verb}", green("\u{2713}"), jobs.len() - failed);
+        if failed > 0 {
+            summary.push_str(&format!(" \u{b7} {}", coral(&format!("{failed} failed"))));
+        }
+        eprintln!("{summary} \u{b7} {:.1?}", start.elapsed());
+    }
+
+    if failed > 0 {
+        return Err(silkprint::error::SilkprintError::BatchFailed {
+            failed,
+            total: jobs.len(),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Render one batch file and write its PDF (unless `--check`), returning the
+/// page count and warnings.
+fn render_batch_job(
+    cli: &Cli,
+    session: &silkprint::RenderSession,
+    options: &RenderOptions,
+    job: &silkprint::batch::BatchJob,
+) -> miette::Result<(usize, Vec<SilkprintWarning>)> {
+    let input = read_document_input(&job.input)?.body;
+    let (pdf_bytes, mut warnings) = session.render(&input, Some(&job.input), options)?;
+    if cli.validate_links {
+        append_link_warnings(&input, Some(&job.input), &mut warnings);
+    }
+    if !cli.check {
+        if let Some(dir) = job.output.parent()
+            && !dir.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(dir).map_err(|e| {
+                silkprint::error::SilkprintError::OutputWrite {
+                    path: dir.display().to_string(),
+                    source: e,
+                }
+            })?;
+        }
+        std::fs::write(&job.output, &pdf_bytes).map_err(|e| {
+            silkprint::error::SilkprintError::OutputWrite {
+                path: job.output.display().to_string(),
+                source: e,
+            }
+        })?;
+    }
+    Ok((estimate_page_count(&pdf_bytes), warnings))
+}
+
+/// Print one row per file — status, input, pages, warnings, duration — with
+/// the error under each failed row. `--quiet` keeps only the failures.
+fn print_batch_table(cli: &Cli, jobs: &[silkprint::batch::BatchJob], outcomes: &[BatchOutcome]) {
+    let names: Vec<String> = jobs.iter().map(|j| j.input.display().to_string()).collect();
+    let name_w = names
+        .iter()
+        .map(|n| n.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max(4);
+
+    if !cli.quiet {
+        eprintln!(
+            "    {}",
+            dim(&format!(
+                "{:<name_w$}  {:>5}  {:>8}  {:>8}",
+                "file", "pages", "warnings", "time"
+            ))
+        );
+    }
+
+    for (name, outcome) in names.iter().zip(outcomes) {
+        let time = format!("{:.0?}", outcome.elapsed);
+        match &outcome.result {
+            Ok((pages, warnings)) => {
+                if cli.quiet {
+                    continue;
+                }
+                let warning_count = if warnings.is_empty() {
+                    format!("{:>8}", 0)
+                } else {
+                    yellow(&format!("{:>8}", warnings.len()))
+                };
+                eprintln!(
+                    "  {} {:<name_w$}  {:>5}  {}  {:>8}",
+                    green("\u{2713}"),
+                    name,
+                    pages,
+                    warning_count,
+                    time,
+                );
+                if cli.verbose > 0 {
+                    for w in warnings {
+                        eprintln!(
+                            "      {} {}",
+                            yellow("\u{26a0}"),
+                            strip_control(&w.to_string())
+                        );
+                    }
+                }
+            }
+            Err(err) => {
+                eprintln!(
+                    "  {} {:<name_w$}  {:>5}  {:>8}  {:>8}",
+                    coral("\u{2717}"),
+                    name,
+                    "\u{2013}",
+                    "\u{2013}",
+                    time,
+                );
+                let reason: Vec<String> = err.chain().map(ToString::to_string).collect();
+                eprintln!("      {}", coral(&strip_control(&reason.join(": "))));
+            }
+        }
+    }
+}
+
 // ── Helpers ────────────────────────────────────────────────────
 
 struct InputDocument {
@@ -676,6 +1084,8 @@ fn append_link_warnings(
     let arena = comrak::Arena::new();
     let root = silkprint::render::markdown::parse(&arena, input);
     let mut collector = silkprint::warnings::WarningCollector::new();
+    // The whole file is parsed here (front matter included), so no offset.
+    collector.set_source(input_path, 0);
     silkprint::render::linkcheck::validate_links(root, input_path, &mut collector);
     warnings.extend(collector.into_warnings());
 }
@@ -685,12 +1095,84 @@ fn append_link_warnings(
 /// Warning text can echo attacker-controlled markdown (a fence language,
 /// footnote name, or HTML tag from an untrusted file), so control characters
 /// are stripped before printing to neutralize terminal escape injection.
+///
+/// Warnings that point into a readable source file are drawn by miette with
+/// a labelled snippet of the offending line.
 fn display_warnings(warnings: &[SilkprintWarning]) {
+    let mut sources = SourceCache::default();
     for w in warnings {
-        eprintln!("  {} {}", yellow("\u{26a0}"), strip_control(&w.to_string()));
+        if let Some(report) = sources
+            .get(w)
+            .and_then(|text| silkprint::diagnostics::WarningReport::new(w, text))
+        {
+            eprintln!("{:?}", miette::Report::new(report));
+            continue;
+        }
+        let mut line = format!("  {} {}", yellow("\u{26a0}"), strip_control(&w.to_string()));
+        if let Some(location) = w.location() {
+            let file = location
+                .file
+                .as_ref()
+                .map_or_else(String::new, |path| format!("{}:", path.display()));
+            line.push_str(&dim(&strip_control(&format!(
+                " ({file}{}:{})",
+                location.line, location.column
+            ))));
+        }
+        eprintln!("{line}");
     }
 }
 
+/// Source text of the files warnings point into, read once per file.
+#[derive(Default)]
+struct SourceCache {
+    files: HashMap<PathBuf, Option<String>>,
+}
+
+impl SourceCache {
+    fn get(&mut self, warning: &SilkprintWarning) -> Option<&str> {
+        let path = warning.location()?.file.as_ref()?;
+        self.files
+            .entry(path.clone())
+            .or_insert_with(|| std::fs::read_to_string(path).ok())
+            .as_deref()
+    }
+}
+
+/// Report warnings for `file`: JSON lines on stdout with
+/// `--message-format json`, otherwise styled text unless `--quiet`.
+fn report_warnings(cli: &Cli, file: Option<&Path>, warnings: &[SilkprintWarning]) {
+    if cli.json_messages() {
+        let policy = cli.warning_policy();
+        let mut sources = SourceCache::default();
+        for w in warnings {
+            let record = Diagnostic::from_warning(w, file, sources.get(w), &policy);
+            println!("{}", record.to_json_line());
+        }
+    } else if !cli.quiet {
+        display_warnings(warnings);
+    }
+}
+
+/// Report an error without exiting (watch and batch mode keep going).
+fn report_error(cli: &Cli, file: Option<&Path>, err: &miette::Report) {
+    if cli.json_messages() {
+        println!("{}", Diagnostic::from_error(&**err, file).to_json_line());
+    } else {
+        eprintln!("{err:?}");
+    }
+}
+
+/// Fail if any of `warnings` is denied by `--deny-warnings` / `--deny`.
+fn deny_warnings(cli: &Cli, warnings: &[SilkprintWarning]) -> miette::Result<()> {
+    let policy = cli.warning_policy();
+    let count = warnings.iter().filter(|w| policy.is_denied(w)).count();
+    if count > 0 {
+        return Err(silkprint::error::SilkprintError::DeniedWarnings { count }.into());
+    }
+    Ok(())
+}
+
 /// Remove terminal control characters (keeping tab) from untrusted text.
 fn strip_control(s: &str) -> String {
     s.chars()
@@ -740,6 +1222,17 @@ fn theme_flag_explicit() -> bool {
 /// Build `RenderOptions` from the parsed CLI arguments.
 fn build_render_options(cli: &Cli) -> miette::Result<RenderOptions> {
     let paper = PaperSize::from_str_case_insensitive(&cli.paper)?;
+    let pdf_standard = cli
+        .pdf_standard
+        .as_deref()
+        .map(PdfStandard::from_str_case_insensitive)
+        .transpose()?;
+    let fix_contrast = fix_contrast_level(cli)?;
+    let print_safe = cli
+        .print_safe
+        .as_deref()
+        .map(PrintSafe::from_str_case_insensitive)
+        .transpose()?;
     let theme = resolve_theme_source(&cli.theme);
     let font_dirs = cli.font_dir.iter().cloned().collect();
 
@@ -750,9 +1243,35 @@ fn build_render_options(cli: &Cli) -> miette::Result<RenderOptions> {
         font_dirs,
         toc: cli.toc_override(),
         title_page: cli.title_page_override(),
+        pdf_standard,
+        fixed_time: None,
+        fix_contrast,
+        print_safe,
+        allow_raw_typst: cli.allow_raw_typst,
+        watermark: cli.watermark.as_deref().map(watermark_flag),
     })
 }
 
+/// `--watermark`, with an image path made absolute against the working
+/// directory; rendering rebases it onto the document's directory.
+fn watermark_flag(flag: &str) -> String {
+    if !silkprint::render::preamble::is_image_watermark(flag) {
+        return flag.to_string();
+    }
+    std::fs::canonicalize(flag)
+        .or_else(|_| std::path::absolute(flag))
+        .map_or_else(|_| flag.to_string(), |path| path.display().to_string())
+}
+
+/// The WCAG level `--fix-contrast` asks for, if given.
+fn fix_contrast_level(cli: &Cli) -> miette::Result<Option<ContrastLevel>> {
+    Ok(cli
+        .fix_contrast
+        .as_deref()
+        .map(ContrastLevel::from_str_case_insensitive)
+        .transpose()?)
+}
+
 #[cfg(feature = "terminal")]
 fn long_flag_explicit(name: &str) -> bool {
     let assignment = format!("{name}=");
@@ -1058,14 +1577,303 @@ fn run_pdf(cli: &Cli, input_path: &Path) -> miette::Result<()> {
         return handle_check(cli, input_path, &options);
     }
     if cli.dump_typst {
-        return handle_dump_typst(input_path, cli.output.as_deref(), &options, cli.quiet);
+        return handle_dump_typst(cli, input_path, &options);
     }
     if cli.dump_html {
-        return handle_dump_html(cli, input_path);
+        return handle_dump_html(cli, input_path, &options);
+    }
+    if cli.watch {
+        return handle_watch(cli, input_path, &options);
     }
     handle_render(cli, input_path, &options)
 }
 
+/// Handle `book` mode: render a directory or manifest of chapters to one PDF,
+/// sharing the `--check` / `--dump-typst` sub-modes with `pdf`.
+fn run_book(cli: &Cli, input_path: &Path) -> miette::Result<()> {
+    if cli.dump_html {
+        return Err(silkprint::error::SilkprintError::ConflictingOptions {
+            details: "--dump-html does not apply to books".to_string(),
+        }
+        .into());
+    }
+    if cli.watch || cli.out_dir.is_some() {
+        return Err(silkprint::error::SilkprintError::ConflictingOptions {
+            details: "--watch and --out-dir do not apply to books".to_string(),
+        }
+        .into());
+    }
+    let options = build_render_options(cli)?;
+    if cli.dump_typst {
+        let (typst_source, warnings) = silkprint::render_book_to_typst(input_path, &options)?;
+        report_warnings(cli, Some(input_path), &warnings);
+        write_typst_output(&typst_source, cli.output.as_deref(), cli.quiet)?;
+        return deny_warnings(cli, &warnings);
+    }
+
+    let start = Instant::now();
+    let spinner = (!cli.quiet && cli.verbose == 0 && io::stderr().is_terminal())
+        .then(|| make_spinner(&format!("Rendering book {}", input_path.display())));
+    let render_result = silkprint::render_book(input_path, &options);
+    if let Some(ref sp) = spinner {
+        sp.finish_and_clear();
+    }
+    let (pdf_bytes, warnings) = render_result?;
+
+    if cli.check {
+        report_warnings(cli, Some(input_path), &warnings);
+        eprintln!(
+            "  {} {} validated in {:.0?}",
+            green("\u{2713}"),
+            input_path.display(),
+            start.elapsed(),
+        );
+        return deny_warnings(cli, &warnings);
+    }
+    finish_render(
+        cli,
+        &book_output_stem(input_path),
+        &pdf_bytes,
+        &warnings,
+        start,
+    )
+}
+
+/// The path a book's default output file is named after: the book directory
+/// for a directory or `SUMMARY.md`, otherwise the manifest file itself.
+fn book_output_stem(input_path: &Path) -> PathBuf {
+    let path = std::path::absolute(input_path).unwrap_or_else(|_| input_path.to_path_buf());
+    let is_summary = path
+        .file_name()
+        .is_some_and(|name| name.eq_ignore_ascii_case("SUMMARY.md"));
+    if is_summary {
+        let dir = path.parent().unwrap_or(&path);
+        // mdBook keeps SUMMARY.md under src/; name the output after the book.
+        if dir.file_name().is_some_and(|name| name == "src") {
+            return dir.parent().unwrap_or(dir).to_path_buf();
+        }
+        return dir.to_path_buf();
+    }
+    path
+}
+
+// ── Theme authoring ────────────────────────────────────────────
+
+/// Handle `theme check` / `theme new` / `theme preview`.
+fn run_theme(cli: &Cli, action: &ThemeCommand) -> miette::Result<()> {
+    match action {
+        ThemeCommand::Check { file } => handle_theme_check(cli, file),
+        ThemeCommand::New { name, extends } => handle_theme_new(cli, name, extends),
+        ThemeCommand::Import { file, name } => handle_theme_import(cli, file, name.as_deref()),
+        ThemeCommand::Export { file, format } => handle_theme_export(cli, file, format),
+        ThemeCommand::Preview { file } => handle_theme_preview(cli, file),
+    }
+}
+
+/// Handle `theme check`: report every problem with a theme, then fail if
+/// any were errors (or denied warnings).
+fn handle_theme_check(cli: &Cli, theme_arg: &str) -> miette::Result<()> {
+    let start = Instant::now();
+    let source = resolve_theme_source(theme_arg);
+    let file = match &source {
+        ThemeSource::Custom(path) => Some(path.as_path()),
+        _ => None,
+    };
+
+    let check = silkprint::theme::authoring::check_theme(
+        &source,
+        &ThemeRegistry::discover(),
+        fix_contrast_level(cli)?,
+    )?;
+    let count = check.errors.len();
+    for error in check.errors {
+        report_error(cli, file, &miette::Report::new(error));
+    }
+    report_warnings(cli, file, &check.warnings);
+    if count > 0 {
+        return Err(silkprint::error::SilkprintError::ThemeCheckFailed {
+            name: theme_arg.to_string(),
+            count,
+        }
+        .into());
+    }
+
+    if !cli.quiet {
+        eprintln!(
+            "  {} {} checked in {:.0?}",
+            green("\u{2713}"),
+            theme_arg,
+            start.elapsed(),
+        );
+    }
+    deny_warnings(cli, &check.warnings)
+}
+
+/// Handle `theme new`: write a theme that extends `extends` into the
+/// project theme directory (or `-o`), refusing to overwrite.
+fn handle_theme_new(cli: &Cli, name: &str, extends: &str) -> miette::Result<()> {
+    let toml_source =
+        silkprint::theme::authoring::scaffold(name, extends, &ThemeRegistry::discover())?;
+    let Some(path) = write_new_theme(cli, name, &toml_source)? else {
+        return Ok(());
+    };
+
+    if !cli.quiet {
+        eprintln!(
+            "  {} {} created, extending {}",
+            green("\u{2713}"),
+            cyan(&path.display().to_string()),
+            coral(extends),
+        );
+    }
+    Ok(())
+}
+
+/// Handle `theme import`: convert an editor theme and write it like
+/// `theme new`, reporting every color changed for contrast.
+fn handle_theme_import(cli: &Cli, file: &Path, name: Option<&str>) -> miette::Result<()> {
+    let imported = silkprint::theme::import::import_theme_file(file)?;
+    let stem = silkprint::theme::import::file_stem(name, &imported)?;
+    report_warnings(cli, Some(file), &imported.warnings);
+    let written = write_new_theme(cli, &stem, &imported.toml)?;
+
+    if !cli.quiet {
+        for adjustment in &imported.adjustments {
+            eprintln!(
+                "  {} {}: {} {} {} {}",
+                yellow("\u{25b2}"),
+                adjustment.element,
+                dim(&adjustment.from),
+                dim("\u{2192}"),
+                adjustment.to,
+                dim(&format!(
+                    "(was {:.1}:1, needs {:.1}:1)",
+                    adjustment.ratio, adjustment.minimum
+                )),
+            );
+        }
+        if let Some(path) = written {
+            eprintln!(
+                "  {} {} imported from {}",
+                green("\u{2713}"),
+                cyan(&path.display().to_string()),
+                coral(&file.display().to_string()),
+            );
+        }
+    }
+    deny_warnings(cli, &imported.warnings)
+}
+
+/// Write a new theme file named `name` into the project theme directory (or
+/// `-o`), refusing to overwrite. Returns the path, or `None` for stdout.
+fn write_new_theme(cli: &Cli, name: &str, toml_source: &str) -> miette::Result<Option<PathBuf>> {
+    let path = match cli.output.as_deref() {
+        Some("-") => {
+            io::stdout()
+                .write_all(toml_source.as_bytes())
+                .map_err(|e| silkprint::error::SilkprintError::OutputWrite {
+                    path: "<stdout>".to_string(),
+                    source: e,
+                })?;
+            return Ok(None);
+        }
+        Some(path) => PathBuf::from(path),
+        None => {
+            Path::new(silkprint::theme::registry::PROJECT_THEME_DIR).join(format!("{name}.toml"))
+        }
+    };
+    let write_error = |source| silkprint::error::SilkprintError::OutputWrite {
+        path: path.display().to_string(),
+        source,
+    };
+    if path.exists() {
+        return Err(write_error(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            "file already exists",
+        ))
+        .into());
+    }
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir).map_err(write_error)?;
+    }
+    std::fs::write(&path, toml_source).map_err(write_error)?;
+
+    Ok(Some(path))
+}
+
+/// Handle `theme export`: resolve the theme and write it in `format` to
+/// `-o` or stdout.
+fn handle_theme_export(cli: &Cli, theme_arg: &str, format: &str) -> miette::Result<()> {
+    let options = build_render_options(cli)?;
+    let mut warnings = silkprint::warnings::WarningCollector::new();
+    let theme = silkprint::theme::load_theme_with(
+        &resolve_theme_source(theme_arg),
+        &ThemeRegistry::discover(),
+        options.theme_adjustments(),
+        &mut warnings,
+    )?;
+    let warnings = warnings.into_warnings();
+    report_warnings(cli, None, &warnings);
+
+    let exported = match format {
+        "json" => silkprint::theme::export::to_json(&theme),
+        "tmtheme" => theme.tmtheme_xml.clone(),
+        "opaline" => silkprint::theme::export::to_opaline(&theme),
+        _ => silkprint::theme::export::to_css(&theme),
+    };
+    match cli.output.as_deref() {
+        Some(path) if path != "-" => {
+            std::fs::write(path, &exported).map_err(|e| {
+                silkprint::error::SilkprintError::OutputWrite {
+                    path: path.to_string(),
+                    source: e,
+                }
+            })?;
+            if !cli.quiet {
+                eprintln!(
+                    "  {} {} exported as {format} to {}",
+                    green("\u{2713}"),
+                    theme_arg,
+                    cyan(path)
+                );
+            }
+        }
+        _ => {
+            io::stdout().write_all(exported.as_bytes()).map_err(|e| {
+                silkprint::error::SilkprintError::OutputWrite {
+                    path: "<stdout>".to_string(),
+                    source: e,
+                }
+            })?;
+        }
+    }
+    deny_warnings(cli, &warnings)
+}
+
+/// Handle `theme preview`: render the built-in specimen with the theme.
+fn handle_theme_preview(cli: &Cli, theme_arg: &str) -> miette::Result<()> {
+    let start = Instant::now();
+    let mut options = build_render_options(cli)?;
+    options.theme = resolve_theme_source(theme_arg);
+    options.theme_explicit = true;
+
+    let spinner = (!cli.quiet && cli.verbose == 0 && io::stderr().is_terminal())
+        .then(|| make_spinner(&format!("Rendering specimen with {theme_arg}")));
+    let render_result = silkprint::render(silkprint::theme::authoring::SPECIMEN, None, &options);
+    if let Some(ref sp) = spinner {
+        sp.finish_and_clear();
+    }
+    let (pdf_bytes, warnings) = render_result?;
+
+    // Named after the theme: `nord` and `themes/nord.toml` both preview
+    // to `nord-preview.pdf`.
+    let stem = Path::new(theme_arg)
+        .file_stem()
+        .map_or_else(|| theme_arg.into(), |stem| stem.to_string_lossy());
+    let preview_path = PathBuf::from(format!("{stem}-preview.pdf"));
+    finish_render(cli, &preview_path, &pdf_bytes, &warnings, start)
+}
+
 // ── Entrypoint ─────────────────────────────────────────────────
 
 fn main() -> miette::Result<()> {
@@ -1076,6 +1884,23 @@ fn main() -> miette::Result<()> {
     setup_miette();
     setup_tracing(cli.verbose, cli.quiet);
 
+    let result = run(&cli);
+    if cli.json_messages()
+        && let Err(err) = &result
+    {
+        // A batch has no single input file to blame.
+        let file = cli
+            .batch_inputs()
+            .is_none()
+            .then(|| cli.effective_input())
+            .flatten();
+        report_error(&cli, file.as_deref(), err);
+        std::process::exit(1);
+    }
+    result
+}
+
+fn run(cli: &Cli) -> miette::Result<()> {
     // Validate flag conflicts
     cli.validate()?;
 
@@ -1090,17 +1915,25 @@ fn main() -> miette::Result<()> {
     // Explicit subcommand pins the mode; input may live on the subcommand.
     match &cli.command {
         Some(silkprint::cli::Command::Pdf { .. }) => {
+            if let Some(patterns) = cli.batch_inputs() {
+                return run_batch(cli, &patterns);
+            }
+            let input = require_input(cli.effective_input())?;
+            return run_pdf(cli, &input);
+        }
+        Some(silkprint::cli::Command::Book { .. }) => {
             let input = require_input(cli.effective_input())?;
-            return run_pdf(&cli, &input);
+            return run_book(cli, &input);
         }
+        Some(silkprint::cli::Command::Theme { action }) => return run_theme(cli, action),
         #[cfg(feature = "terminal")]
         Some(silkprint::cli::Command::Read { .. }) => {
             let effective_input = cli.effective_input();
             if let Some((raw, remote)) = parse_remote_read_input(effective_input.as_ref())? {
-                return handle_read_remote(&cli, &raw, &remote);
+                return handle_read_remote(cli, &raw, &remote);
             }
             let input = require_input(effective_input)?;
-            return handle_read(&cli, &input);
+            return handle_read(cli, &input);
         }
         None => {}
     }
@@ -1110,13 +1943,16 @@ fn main() -> miette::Result<()> {
     #[cfg(feature = "terminal")]
     if !cli.pdf_signaled() {
         if let Some((raw, remote)) = parse_remote_read_input(cli.input.as_ref())? {
-            return handle_read_remote(&cli, &raw, &remote);
+            return handle_read_remote(cli, &raw, &remote);
         }
         let input = require_input(cli.input.clone())?;
-        return handle_read(&cli, &input);
+        return handle_read(cli, &input);
+    }
+    if let Some(patterns) = cli.batch_inputs() {
+        return run_batch(cli, &patterns);
     }
     let input = require_input(cli.input.clone())?;
-    run_pdf(&cli, &input)
+    run_pdf(cli, &input)
 }
 
 #[cfg(all(test, feature = "terminal"))]
@@ -0,0 +1,558 @@
+//! Citations and bibliographies: `bibliography:` front matter and `[@key]`.
+//!
+//! BibTeX (`.bib`) and Hayagriva (`.yml`/`.yaml`) files are handed to Typst's
+//! bibliography engine. Pandoc-style citations are lowered to `#cite` calls
+//! before emission:
+//!
+//! ```text
+//! [@doe99]             → #cite(label("doe99"))
+//! [see @doe99, p. 4]   → see #cite(label("doe99"), supplement: [p. 4])
+//! [@doe99; @roe02]     → #cite(label("doe99"))#cite(label("roe02"))
+//! [-@doe99]            → #cite(label("doe99"), form: "year")
+//! @doe99 argues        → #cite(label("doe99"), form: "prose") argues
+//! ```
+//!
+//! Keys are checked against the bibliography files up front, so a typo is a
+//! warning and literal text rather than a Typst compile error.
+
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use comrak::Arena;
+use comrak::nodes::{AstNode, NodeValue};
+
+use crate::warnings::{SilkprintWarning, SourceLocation, WarningCollector};
+
+use super::escape::{escape_typst_content, escape_typst_string};
+use super::frontmatter::FrontMatter;
+
+/// Citation style: a Typst built-in name (`"apa"`, `"ieee"`) or a CSL file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Style {
+    Named(String),
+    File(PathBuf),
+}
+
+/// The bibliography files and citation style declared in front matter.
+#[derive(Debug)]
+pub struct Bibliography {
+    files: Vec<PathBuf>,
+    style: Option<Style>,
+    keys: HashSet<String>,
+}
+
+impl Bibliography {
+    /// Load the `bibliography:` files named in front matter, relative to `base_dir`.
+    ///
+    /// Returns `None` when no bibliography is declared or none of the files
+    /// could be read; missing files are reported as warnings.
+    pub fn load(
+        front_matter: Option<&FrontMatter>,
+        base_dir: &Path,
+        warnings: &mut WarningCollector,
+    ) -> Option<Self> {
+        let front_matter = front_matter?;
+        let sources = front_matter.bibliography.as_ref()?;
+
+        let mut files = Vec::new();
+        let mut keys = HashSet::new();
+        for source in sources {
+            let path = base_dir.join(source);
+            match std::fs::read_to_string(&path) {
+                Ok(text) => {
+                    keys.extend(parse_keys(&path, &text));
+                    files.push(path);
+                }
+                Err(_) => warnings.push(SilkprintWarning::BibliographyNotFound {
+                    path: source.clone(),
+                }),
+            }
+        }
+        if files.is_empty() {
+            return None;
+        }
+
+        let style = front_matter.csl.as_ref().map(|csl| {
+            if Path::new(csl)
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("csl"))
+            {
+                Style::File(base_dir.join(csl))
+            } else {
+                Style::Named(csl.clone())
+            }
+        });
+
+        Some(Self { files, style, keys })
+    }
+
+    /// Bibliography and `.csl` style files named in front matter, whether or
+    /// not they exist yet.
+    pub fn declared_files(front_matter: Option<&FrontMatter>, base_dir: &Path) -> Vec<PathBuf> {
+        let Some(front_matter) = front_matter else {
+            return Vec::new();
+        };
+        front_matter
+            .bibliography
+            .iter()
+            .flatten()
+            .chain(front_matter.csl.iter().filter(|csl| {
+                Path::new(csl)
+                    .extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("csl"))
+            }))
+            .map(|file| base_dir.join(file))
+            .collect()
+    }
+
+    /// Whether `key` is defined in one of the bibliography files.
+    pub fn contains(&self, key: &str) -> bool {
+        self.keys.contains(key)
+    }
+
+    /// Rewrite citation syntax in `root` into raw `#cite` nodes.
+    ///
+    /// Returns `true` if anything was cited, i.e. whether the document needs
+    /// a references section.
+    pub fn lower_citations<'a>(
+        &self,
+        arena: &'a Arena<'a>,
+        root: &'a AstNode<'a>,
+        warnings: &mut WarningCollector,
+    ) -> bool {
+        let texts: Vec<_> = root
+            .descendants()
+            .filter(|node| matches!(node.data.borrow().value, NodeValue::Text(_)))
+            .filter(|node| !inside_link(node))
+            .collect();
+
+        let mut cited = false;
+        for node in texts {
+            let pieces = {
+                let data = node.data.borrow();
+                let NodeValue::Text(text) = &data.value else {
+                    continue;
+                };
+                let location = SourceLocation::from_sourcepos(data.sourcepos);
+                self.split(text, location.as_ref(), warnings)
+            };
+            if !pieces.iter().any(|piece| matches!(piece, Piece::Cite(_))) {
+                continue;
+            }
+            cited = true;
+            for piece in pieces {
+                let value = match piece {
+                    Piece::Text(text) => NodeValue::Text(text.into()),
+                    Piece::Cite(raw) => NodeValue::Raw(raw),
+                };
+                node.insert_before(arena.alloc(value.into()));
+            }
+            node.detach();
+        }
+        cited
+    }
+
+    /// The `#bibliography(...)` call for a Typst world rooted at `root_dir`.
+    pub fn to_typst(&self, root_dir: &Path) -> String {
+        let paths: Vec<String> = self
+            .files
+            .iter()
+            .map(|path| format!("\"{}\"", escape_typst_string(&typst_path(path, root_dir))))
+            .collect();
+        let sources = match paths.as_slice() {
+            [single] => single.clone(),
+            many => format!("({},)", many.join(", ")),
+        };
+
+        let mut out = format!("\n#bibliography({sources}");
+        match &self.style {
+            Some(Style::Named(name)) => {
+                let _ = write!(out, ", style: \"{}\"", escape_typst_string(name));
+            }
+            Some(Style::File(path)) => {
+                let _ = write!(
+                    out,
+                    ", style: \"{}\"",
+                    escape_typst_string(&typst_path(path, root_dir))
+                );
+            }
+            None => {}
+        }
+        out.push_str(")\n");
+        out
+    }
+
+    /// Split a text run into plain text and lowered `#cite` calls.
+    /// Unknown keys are reported at `location`, the text node's.
+    fn split(
+        &self,
+        text: &str,
+        location: Option<&SourceLocation>,
+        warnings: &mut WarningCollector,
+    ) -> Vec<Piece> {
+        let mut pieces = Vec::new();
+        let mut plain = String::new();
+        let mut rest = text;
+
+        while let Some(start) = rest.find(['[', '@']) {
+            plain.push_str(&rest[..start]);
+            let tail = &rest[start..];
+            let preceded_by_word = plain.chars().next_back().is_some_and(char::is_alphanumeric);
+
+            let lowered = if tail.starts_with('[') {
+                let group = tail
+                    .find(']')
+                    .and_then(|close| Some((parse_group(&tail[1..close])?, close + 1)));
+                // A group with an unknown key stays literal, brackets and all.
+                group.map(|(items, consumed)| {
+                    let lowered = self
+                        .cite_group(&items, location, warnings)
+                        .unwrap_or_else(|| vec![Piece::Text(tail[..consumed].to_string())]);
+                    (lowered, consumed)
+                })
+            } else if preceded_by_word {
+                None
+            } else {
+                let key = citation_key(&tail[1..]);
+                self.contains(key).then(|| {
+                    let cite = CiteItem {
+                        prefix: "",
+                        key,
+                        locator: "",
+                        suppress_author: false,
+                    };
+                    let call = format!("{};", cite.to_typst("prose"));
+                    (vec![Piece::Cite(call)], 1 + key.len())
+                })
+            };
+
+            match lowered {
+                Some((group, consumed)) => {
+                    for piece in group {
+                        match piece {
+                            Piece::Text(text) => plain.push_str(&text),
+                            Piece::Cite(_) => {
+                                if !plain.is_empty() {
+                                    pieces.push(Piece::Text(std::mem::take(&mut plain)));
+                                }
+                                pieces.push(piece);
+                            }
+                        }
+                    }
+                    rest = &tail[consumed..];
+                }
+                None => {
+                    // `[` and `@` are single bytes.
+                    plain.push_str(&tail[..1]);
+                    rest = &tail[1..];
+                }
+            }
+        }
+
+        plain.push_str(rest);
+        if !plain.is_empty() {
+            pieces.push(Piece::Text(plain));
+        }
+        pieces
+    }
+
+    /// Lower a bracketed group, or leave it as text if any key is unknown.
+    fn cite_group(
+        &self,
+        items: &[CiteItem<'_>],
+        location: Option<&SourceLocation>,
+        warnings: &mut WarningCollector,
+    ) -> Option<Vec<Piece>> {
+        let unknown: Vec<_> = items
+            .iter()
+            .filter(|item| !self.contains(item.key))
+            .collect();
+        if !unknown.is_empty() {
+            for item in unknown {
+                warnings.push(SilkprintWarning::UnknownCitation {
+                    key: item.key.to_string(),
+                    location: location.cloned(),
+                });
+            }
+            return None;
+        }
+
+        let mut pieces = Vec::new();
+        let mut calls = String::new();
+        for item in items {
+            if !item.prefix.is_empty() {
+                if !calls.is_empty() {
+                    pieces.push(Piece::Cite(format!("{};", std::mem::take(&mut calls))));
+                }
+                pieces.push(Piece::Text(format!("{} ", item.prefix)));
+            }
+            let form = if item.suppress_author {
+                "year"
+            } else {
+                "normal"
+            };
+            calls.push_str(&item.to_typst(form));
+        }
+        // `;` ends the call so following `(`/`.` text can't extend it.
+        pieces.push(Piece::Cite(format!("{calls};")));
+        Some(pieces)
+    }
+}
+
+/// A run of text after citation lowering.
+#[derive(Debug, PartialEq, Eq)]
+enum Piece {
+    Text(String),
+    Cite(String),
+}
+
+/// One `prefix @key, locator` entry of a bracketed citation group.
+#[derive(Debug, PartialEq, Eq)]
+struct CiteItem<'s> {
+    prefix: &'s str,
+    key: &'s str,
+    locator: &'s str,
+    suppress_author: bool,
+}
+
+impl CiteItem<'_> {
+    fn to_typst(&self, form: &str) -> String {
+        let mut call = format!("#cite(label(\"{}\")", escape_typst_string(self.key));
+        if !self.locator.is_empty() {
+            let _ = write!(
+                call,
+                ", supplement: [{}]",
+                escape_typst_content(self.locator)
+            );
+        }
+        if form != "normal" {
+            let _ = write!(call, ", form: \"{form}\"");
+        }
+        call.push(')');
+        call
+    }
+}
+
+/// Parse the inside of `[see @a, p. 4; @b]`. `None` if it isn't a citation.
+fn parse_group(inner: &str) -> Option<Vec<CiteItem<'_>>> {
+    inner
+        .split(';')
+        .map(|item| {
+            let at = item.find('@')?;
+            let (prefix, suppress_author) = match item[..at].trim_end().strip_suffix('-') {
+                Some(prefix) => (prefix, true),
+                None => (&item[..at], false),
+            };
+            let key = citation_key(&item[at + 1..]);
+            if key.is_empty() {
+                return None;
+            }
+            let locator = item[at + 1 + key.len()..].trim();
+            let locator = locator.strip_prefix(',').unwrap_or(locator).trim();
+            Some(CiteItem {
+                prefix: prefix.trim(),
+                key,
+                locator,
+                suppress_author,
+            })
+        })
+        .collect()
+}
+
+/// The citation key at the start of `s`, without trailing punctuation.
+fn citation_key(s: &str) -> &str {
+    let len = s
+        .char_indices()
+        .find(|&(_, c)| !(c.is_alphanumeric() || matches!(c, '_' | '-' | ':' | '.' | '/' | '+')))
+        .map_or(s.len(), |(i, _)| i);
+    s[..len].trim_end_matches(['.', ':', '-', '/', '+'])
+}
+
+fn inside_link(node: &AstNode<'_>) -> bool {
+    node.ancestors().skip(1).any(|ancestor| {
+        matches!(
+            ancestor.data.borrow().value,
+            NodeValue::Link(_) | NodeValue::Image(_) | NodeValue::WikiLink(_)
+        )
+    })
+}
+
+/// Entry keys from a BibTeX or Hayagriva file, chosen by extension.
+fn parse_keys(path: &Path, text: &str) -> Vec<String> {
+    let is_yaml = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("yml") || ext.eq_ignore_ascii_case("yaml"));
+    if is_yaml {
+        hayagriva_keys(text)
+    } else {
+        bibtex_keys(text)
+    }
+}
+
+/// Hayagriva files are a top-level mapping of key → entry.
+fn hayagriva_keys(text: &str) -> Vec<String> {
+    serde_yaml_ng::from_str::<serde_yaml_ng::Mapping>(text)
+        .map(|mapping| {
+            mapping
+                .keys()
+                .filter_map(|key| key.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// `@article{key, ...}` → `key`, skipping `@string`/`@comment`/`@preamble`.
+fn bibtex_keys(text: &str) -> Vec<String> {
+    let mut keys = Vec::new();
+    for (at, _) in text.match_indices('@') {
+        let rest = &text[at + 1..];
+        let Some(open) = rest.find(['{', '(']) else {
+            continue;
+        };
+        let kind = rest[..open].trim();
+        if kind.is_empty()
+            || !kind.chars().all(|c| c.is_ascii_alphabetic())
+            || ["comment", "string", "preamble"]
+                .iter()
+                .any(|skip| kind.eq_ignore_ascii_case(skip))
+        {
+            continue;
+        }
+        let body = &rest[open + 1..];
+        let Some(end) = body.find(',') else {
+            continue;
+        };
+        let key = body[..end].trim();
+        if !key.is_empty() && !key.contains(char::is_whitespace) {
+            keys.push(key.to_string());
+        }
+    }
+    keys
+}
+
+/// Root-relative path for the Typst world, like book-mode image rebasing.
+fn typst_path(path: &Path, root_dir: &Path) -> String {
+    match path.strip_prefix(root_dir) {
+        Ok(relative) => {
+            let parts: Vec<_> = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect();
+            format!("/{}", parts.join("/"))
+        }
+        Err(_) => path.to_string_lossy().into_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::render::markdown;
+
+    const BIBTEX: &str = "@string{acm = \"ACM\"}\n\
+        @article{doe99,\n  author = {Doe, Jane},\n  note = {mail jane@example.com},\n}\n\
+        @book{roe02, title = {Things}}\n";
+
+    fn bibliography(dir: &Path) -> Bibliography {
+        std::fs::write(dir.join("refs.bib"), BIBTEX).expect("should write bib");
+        let front_matter = FrontMatter {
+            bibliography: Some(vec!["refs.bib".to_string()]),
+            csl: Some("apa".to_string()),
+            ..Default::default()
+        };
+        let mut warnings = WarningCollector::new();
+        Bibliography::load(Some(&front_matter), dir, &mut warnings).expect("should load")
+    }
+
+    #[test]
+    fn reads_bibtex_and_hayagriva_keys() {
+        assert_eq!(bibtex_keys(BIBTEX), vec!["doe99", "roe02"]);
+        assert_eq!(
+            hayagriva_keys("doe99:\n  type: article\nroe02:\n  type: book\n"),
+            vec!["doe99", "roe02"]
+        );
+    }
+
+    #[test]
+    fn parses_prefix_locator_and_suppressed_author() {
+        let items = parse_group("see @doe99, p. 4; -@roe02").expect("citation group");
+        assert_eq!(
+            items,
+            vec![
+                CiteItem {
+                    prefix: "see",
+                    key: "doe99",
+                    locator: "p. 4",
+                    suppress_author: false,
+                },
+                CiteItem {
+                    prefix: "",
+                    key: "roe02",
+                    locator: "",
+                    suppress_author: true,
+                },
+            ]
+        );
+        assert_eq!(parse_group("just brackets"), None);
+    }
+
+    #[test]
+    fn lowers_citations_and_warns_on_unknown_keys() {
+        let dir = tempdir().expect("should create temp dir");
+        let bib = bibliography(dir.path());
+        let arena = Arena::new();
+        let root = markdown::parse(
+            &arena,
+            "As @doe99 shows [see @roe02, p. 4]. Also [@nobody] and @someone.\n",
+        );
+        let mut warnings = WarningCollector::new();
+
+        assert!(bib.lower_citations(&arena, root, &mut warnings));
+
+        let mut out = String::new();
+        for node in root.descendants() {
+            match &node.data.borrow().value {
+                NodeValue::Text(text) => out.push_str(text),
+                NodeValue::Raw(raw) => out.push_str(raw),
+                _ => {}
+            }
+        }
+        assert!(out.contains("As #cite(label(\"doe99\"), form: \"prose\"); shows"));
+        assert!(out.contains("see #cite(label(\"roe02\"), supplement: [p. 4]);."));
+        assert!(out.contains("[@nobody]"));
+        assert!(out.contains("@someone."));
+        assert!(matches!(
+            warnings.warnings(),
+            [SilkprintWarning::UnknownCitation { key, location: Some(location) }]
+                if key == "nobody" && location.line == 1
+        ));
+    }
+
+    #[test]
+    fn emits_root_relative_bibliography_call() {
+        let dir = tempdir().expect("should create temp dir");
+        let bib = bibliography(dir.path());
+        assert_eq!(
+            bib.to_typst(dir.path()),
+            "\n#bibliography(\"/refs.bib\", style: \"apa\")\n"
+        );
+    }
+
+    #[test]
+    fn missing_file_warns_and_disables_bibliography() {
+        let dir = tempdir().expect("should create temp dir");
+        let front_matter = FrontMatter {
+            bibliography: Some(vec!["missing.bib".to_string()]),
+            ..Default::default()
+        };
+        let mut warnings = WarningCollector::new();
+        assert!(Bibliography::load(Some(&front_matter), dir.path(), &mut warnings).is_none());
+        assert!(matches!(
+            warnings.warnings(),
+            [SilkprintWarning::BibliographyNotFound { .. }]
+        ));
+    }
+}
@@ -0,0 +1,659 @@
+//! Book mode — several Markdown chapters rendered into one document.
+//!
+//! Chapter order comes from an mdBook-style `SUMMARY.md` or a front-matter
+//! `chapters:` list. Each chapter is parsed on its own so images resolve
+//! relative to the chapter's directory, then links between chapters are
+//! rewritten into internal anchors and the chapters are concatenated behind a
+//! single preamble (and therefore a single `#outline`).
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::{Component, Path, PathBuf};
+
+use comrak::nodes::{AstNode, NodeValue};
+
+use crate::RenderOptions;
+use crate::error::SilkprintError;
+use crate::theme::ResolvedTheme;
+use crate::warnings::WarningCollector;
+
+use super::bibliography::Bibliography;
+use super::conformance;
+use super::crossref;
+use super::escape::escape_typst_content;
+use super::frontmatter::{self, FrontMatter};
+use super::image::{ImageMode, PreparedImages};
+use super::include;
+use super::layout;
+use super::markdown;
+use super::preamble;
+
+/// URL prefix marking a link that was rewritten to an internal anchor.
+///
+/// The Typst emitter turns `silkprint-anchor:<label>` into `#link(label(..))`.
+pub const INTERNAL_LINK_PREFIX: &str = "silkprint-anchor:";
+
+/// Manifest file names probed when a directory is given.
+const SUMMARY_CANDIDATES: &[&str] = &["SUMMARY.md", "src/SUMMARY.md"];
+
+/// A multi-file document: book metadata plus ordered chapters.
+#[derive(Debug, Clone)]
+pub struct Book {
+    /// Directory every chapter (and its assets) lives under. Typst resolves
+    /// files relative to this root.
+    pub root: PathBuf,
+    /// Front matter of the manifest, used for the title page and outline.
+    pub front_matter: Option<FrontMatter>,
+    /// Manifest body rendered before the first chapter (front-matter
+    /// manifests only — a `SUMMARY.md` body is the chapter list itself).
+    pub intro: String,
+    /// The manifest the intro comes from and how many lines precede it, so
+    /// warnings in the intro point at the right place.
+    pub intro_origin: Option<(PathBuf, usize)>,
+    pub chapters: Vec<Chapter>,
+}
+
+/// A single chapter file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chapter {
+    pub path: PathBuf,
+    /// Title from the manifest link text, when there is one. Used as the
+    /// chapter heading when the file has no level-1 heading of its own.
+    pub title: Option<String>,
+}
+
+impl Book {
+    /// Load a book from a directory or a manifest file.
+    ///
+    /// A directory uses its `SUMMARY.md` (or `src/SUMMARY.md`), falling back
+    /// to every Markdown file in alphabetical order. A file with a
+    /// front-matter `chapters:` list uses that list; any other file is read
+    /// as a `SUMMARY.md`.
+    pub fn load(path: &Path) -> Result<Self, SilkprintError> {
+        let path = absolute(path);
+        let book = if path.is_dir() {
+            match SUMMARY_CANDIDATES
+                .iter()
+                .map(|name| path.join(name))
+                .find(|candidate| candidate.is_file())
+            {
+                Some(summary) => Self::from_manifest(&summary)?,
+                None => Self::from_directory(&path)?,
+            }
+        } else {
+            Self::from_manifest(&path)?
+        };
+
+        if book.chapters.is_empty() {
+            return Err(SilkprintError::BookEmpty {
+                path: path.display().to_string(),
+            });
+        }
+        Ok(book)
+    }
+
+    fn from_manifest(path: &Path) -> Result<Self, SilkprintError> {
+        let input = read_file(path)?;
+        let (front_matter, body) = frontmatter::extract(&input)?;
+        let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
+
+        let intro_origin = Some((
+            path.to_path_buf(),
+            frontmatter::body_line_offset(&input, &body),
+        ));
+        let (chapters, intro) = match front_matter.as_ref().and_then(|fm| fm.chapters.as_ref()) {
+            Some(list) => {
+                let chapters = list
+                    .iter()
+                    .map(|entry| Chapter {
+                        path: normalize(&base_dir.join(entry)),
+                        title: None,
+                    })
+                    .collect();
+                (chapters, body)
+            }
+            None => (parse_summary(&body, base_dir), String::new()),
+        };
+
+        Ok(Self {
+            root: book_root(base_dir, &chapters),
+            front_matter,
+            intro,
+            intro_origin,
+            chapters,
+        })
+    }
+
+    fn from_directory(dir: &Path) -> Result<Self, SilkprintError> {
+        let entries = std::fs::read_dir(dir).map_err(|source| SilkprintError::InputRead {
+            path: dir.display().to_string(),
+            source,
+        })?;
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file() && is_markdown_path(path))
+            .collect();
+        // README / index lead, as they do in mdBook; everything else is alphabetical.
+        paths.sort_by_key(|path| {
+            let stem = path
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_ascii_lowercase())
+                .unwrap_or_default();
+            (!matches!(stem.as_str(), "readme" | "index"), path.clone())
+        });
+
+        Ok(Self {
+            root: dir.to_path_buf(),
+            front_matter: None,
+            intro: String::new(),
+            intro_origin: None,
+            chapters: paths
+                .into_iter()
+                .map(|path| Chapter { path, title: None })
+                .collect(),
+        })
+    }
+}
+
+/// Extract the chapter list from a `SUMMARY.md` body.
+///
+/// Every link to a local Markdown file becomes a chapter, in document order.
+/// Draft chapters (`[Title]()`), remote links, and duplicates are skipped.
+pub fn parse_summary(body: &str, base_dir: &Path) -> Vec<Chapter> {
+    let arena = comrak::Arena::new();
+    let root = markdown::parse(&arena, body);
+    let mut chapters: Vec<Chapter> = Vec::new();
+
+    for node in root.descendants() {
+        let url = match &node.data.borrow().value {
+            NodeValue::Link(link) => link.url.clone(),
+            _ => continue,
+        };
+        let file = url.split_once('#').map_or(url.as_str(), |(file, _)| file);
+        if file.is_empty() || file.contains("://") || !is_markdown_path(Path::new(file)) {
+            continue;
+        }
+        let path = normalize(&base_dir.join(file));
+        if chapters.iter().any(|c| c.path == path) {
+            continue;
+        }
+        let mut title = String::new();
+        markdown::collect_text(node, &mut title);
+        let title = title.trim();
+        chapters.push(Chapter {
+            path,
+            title: (!title.is_empty()).then(|| title.to_string()),
+        });
+    }
+
+    chapters
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// Assembly
+// ═══════════════════════════════════════════════════════════════════
+
+/// Typst content for a whole book, ready to follow the preamble.
+#[derive(Debug, Default)]
+pub struct AssembledBook {
+    pub content: String,
+    /// Mermaid sources across all chapters, indexed like the emitted vpaths.
+    pub mermaid_sources: Vec<String>,
+    /// Downloaded remote images across all chapters, keyed by virtual path.
+    pub remote_assets: HashMap<String, Vec<u8>>,
+}
+
+/// Parse, cross-link, and emit every chapter of `book` as one Typst body.
+///
+/// Each chapter starts on a new page with a `<chapter-N>` anchor, and its
+/// headings get `<chapter-N-slug>` labels so `other.md#slug` links resolve.
+/// A chapter without a level-1 heading is headed by its manifest title.
+pub fn assemble(
+    book: &Book,
+    theme: &ResolvedTheme,
+    mode: ImageMode,
+    options: &RenderOptions,
+    warnings: &mut WarningCollector,
+) -> Result<AssembledBook, SilkprintError> {
+    let arena = comrak::Arena::new();
+
+    let mut roots = Vec::with_capacity(book.chapters.len());
+    let mut line_offsets = Vec::with_capacity(book.chapters.len());
+    let mut line_origins = Vec::with_capacity(book.chapters.len());
+    for chapter in &book.chapters {
+        let input = read_file(&chapter.path)?;
+        let (front_matter, body) = frontmatter::extract(&input)?;
+        if let Some(fm) = &front_matter {
+            frontmatter::warn_unknown_fields(fm, warnings);
+        }
+        let line_offset = frontmatter::body_line_offset(&input, &body);
+        warnings.set_source(Some(&chapter.path), line_offset);
+        let expanded = include::expand(&body, Some(&chapter.path), warnings);
+        roots.push(markdown::parse(&arena, &expanded.text));
+        line_offsets.push(line_offset);
+        line_origins.push(expanded.origins);
+    }
+
+    let index = AnchorIndex {
+        chapters: book
+            .chapters
+            .iter()
+            .enumerate()
+            .map(|(idx, chapter)| (chapter.path.clone(), idx))
+            .collect(),
+        anchors: roots
+            .iter()
+            .enumerate()
+            .map(|(idx, &root)| label_headings(&arena, root, idx))
+            .collect(),
+    };
+
+    // One references section for the whole book, relative to the book root.
+    let bibliography = Bibliography::load(book.front_matter.as_ref(), &book.root, warnings);
+    let mut cited = false;
+    let page_layout = layout::Layout::resolve(theme, book.front_matter.as_ref());
+    let numbering = preamble::heading_numbering(&theme.tokens, book.front_matter.as_ref());
+
+    let mut prepared = Vec::with_capacity(roots.len());
+    for (idx, (&root, chapter)) in roots.iter().zip(&book.chapters).enumerate() {
+        let chapter_dir = chapter.path.parent().unwrap_or(&book.root);
+        warnings.set_source(Some(&chapter.path), line_offsets[idx]);
+        warnings.set_line_origins(line_origins[idx].clone());
+        rewrite_links(root, idx, chapter_dir, &index);
+        let mut images = PreparedImages::prepare(root, mode, Some(chapter_dir), warnings);
+        images.rebase(chapter_dir, &book.root, &format!("c{idx}_"));
+        markdown::check_content(root, warnings);
+        conformance::check_content(root, options.pdf_standard, warnings);
+        if let Some(bibliography) = &bibliography {
+            cited |= bibliography.lower_citations(&arena, root, warnings);
+        }
+        super::lower_typst_blocks(&arena, root, options, &page_layout);
+        prepared.push(images);
+    }
+
+    let mut assembled = AssembledBook::default();
+
+    if !book.intro.trim().is_empty() {
+        // A separate arena: the intro's images only need to outlive its own AST.
+        let intro_arena = comrak::Arena::new();
+        match &book.intro_origin {
+            Some((manifest, offset)) => warnings.set_source(Some(manifest), *offset),
+            None => warnings.set_source(None, 0),
+        }
+        let manifest = book.intro_origin.as_ref().map(|(path, _)| path.as_path());
+        let expanded = include::expand(&book.intro, manifest, warnings);
+        warnings.set_line_origins(expanded.origins);
+        let intro = markdown::parse(&intro_arena, &expanded.text);
+        let images = PreparedImages::prepare(intro, mode, Some(&book.root), warnings);
+        markdown::check_content(intro, warnings);
+        conformance::check_content(intro, options.pdf_standard, warnings);
+        if let Some(bibliography) = &bibliography {
+            cited |= bibliography.lower_citations(&intro_arena, intro, warnings);
+        }
+        super::lower_typst_blocks(&intro_arena, intro, options, &page_layout);
+        let (content, mermaid) =
+            markdown::emit_typst_with_numbering(intro, theme, numbering, &images, warnings);
+        assembled.content.push_str(&content);
+        assembled.mermaid_sources.extend(mermaid);
+        assembled
+            .remote_assets
+            .extend(images.remote_assets().clone());
+    }
+
+    for (idx, (&root, images)) in roots.iter().zip(&prepared).enumerate() {
+        warnings.set_source(Some(&book.chapters[idx].path), line_offsets[idx]);
+        warnings.set_line_origins(line_origins[idx].clone());
+        let (content, mermaid) = markdown::emit_typst_with_mermaid_offset(
+            root,
+            theme,
+            numbering,
+            images,
+            assembled.mermaid_sources.len(),
+            warnings,
+        );
+        let _ = writeln!(
+            assembled.content,
+            "\n#pagebreak(weak: true)\n#metadata(none) <{}>",
+            chapter_label(idx)
+        );
+        if let Some(title) = &book.chapters[idx].title
+            && !has_top_level_heading(root)
+        {
+            let _ = writeln!(assembled.content, "\n= {}", escape_typst_content(title));
+        }
+        assembled.content.push_str(&content);
+        assembled.mermaid_sources.extend(mermaid);
+        assembled
+            .remote_assets
+            .extend(images.remote_assets().clone());
+    }
+
+    warnings.set_source(None, 0);
+    conformance::check_document(book.front_matter.as_ref(), options.pdf_standard, warnings);
+
+    if let Some(bibliography) = bibliography.filter(|_| cited) {
+        assembled
+            .content
+            .push_str(&bibliography.to_typst(&book.root));
+    }
+
+    Ok(assembled)
+}
+
+/// Where every chapter and heading anchor lives.
+struct AnchorIndex {
+    chapters: HashMap<PathBuf, usize>,
+    /// Per chapter: heading slug → Typst label.
+    anchors: Vec<HashMap<String, String>>,
+}
+
+impl AnchorIndex {
+    /// Resolve a link target written in chapter `current` to a Typst label.
+    ///
+    /// Returns `None` for anything that isn't another chapter (or an anchor
+    /// in this one), leaving the link untouched.
+    fn resolve(&self, target: &str, current: usize, chapter_dir: &Path) -> Option<String> {
+        let (file, anchor) = target
+            .split_once('#')
+            .map_or((target, None), |(file, anchor)| (file, Some(anchor)));
+
+        let chapter = if file.is_empty() {
+            current
+        } else {
+            if file.contains(':') || !is_markdown_path(Path::new(file)) {
+                return None;
+            }
+            *self.chapters.get(&normalize(&chapter_dir.join(file)))?
+        };
+
+        match anchor.filter(|a| !a.is_empty()) {
+            Some(anchor) => match self.anchors[chapter].get(&markdown::heading_slug(anchor)) {
+                Some(label) => Some(label.clone()),
+                None if file.is_empty() => None,
+                None => Some(chapter_label(chapter)),
+            },
+            None if file.is_empty() => None,
+            None => Some(chapter_label(chapter)),
+        }
+    }
+}
+
+/// Label every heading in a chapter, returning the slug → label map.
+///
+/// Labels are appended as raw `<label>` nodes so the emitter writes
+/// `= Title <chapter-N-slug>`. A heading with its own `{#sec:id}` keeps
+/// that label instead, written by the emitter.
+fn label_headings<'a>(
+    arena: &'a comrak::Arena<'a>,
+    root: &'a AstNode<'a>,
+    chapter: usize,
+) -> HashMap<String, String> {
+    let mut anchors = HashMap::new();
+    let mut seen: HashMap<String, usize> = HashMap::new();
+
+    let headings: Vec<_> = root
+        .descendants()
+        .filter(|node| matches!(node.data.borrow().value, NodeValue::Heading(_)))
+        .collect();
+
+    for heading in headings {
+        let mut text = String::new();
+        markdown::collect_text(heading, &mut text);
+        let (title, attrs) = crossref::split_heading_attributes(&text);
+        let slug = markdown::unique_heading_slug(title, &mut seen);
+
+        if let Some(id) = attrs.and_then(|attrs| attrs.id) {
+            anchors.insert(slug, id);
+            continue;
+        }
+        let label = format!("{}-{slug}", chapter_label(chapter));
+        heading.append(arena.alloc(NodeValue::Raw(format!(" <{label}>")).into()));
+        anchors.insert(slug, label);
+    }
+
+    anchors
+}
+
+/// Point links and wikilinks at other chapters to their internal anchors.
+fn rewrite_links<'a>(
+    root: &'a AstNode<'a>,
+    chapter: usize,
+    chapter_dir: &Path,
+    index: &AnchorIndex,
+) {
+    for node in root.descendants() {
+        let mut data = node.data.borrow_mut();
+        match &mut data.value {
+            NodeValue::Link(link) => {
+                if let Some(label) = index.resolve(&link.url, chapter, chapter_dir) {
+                    link.url = format!("{INTERNAL_LINK_PREFIX}{label}");
+                }
+            }
+            NodeValue::WikiLink(link) => {
+                let target = markdown::wikilink_target(&link.url);
+                if let Some(label) = index.resolve(&target, chapter, chapter_dir) {
+                    link.url = format!("{INTERNAL_LINK_PREFIX}{label}");
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn has_top_level_heading<'a>(root: &'a AstNode<'a>) -> bool {
+    root.descendants()
+        .any(|node| matches!(node.data.borrow().value, NodeValue::Heading(h) if h.level == 1))
+}
+
+fn chapter_label(chapter: usize) -> String {
+    format!("chapter-{}", chapter + 1)
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// Paths
+// ═══════════════════════════════════════════════════════════════════
+
+fn read_file(path: &Path) -> Result<String, SilkprintError> {
+    std::fs::read_to_string(path).map_err(|source| SilkprintError::InputRead {
+        path: path.display().to_string(),
+        source,
+    })
+}
+
+fn is_markdown_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("md") || ext.eq_ignore_ascii_case("markdown"))
+}
+
+fn absolute(path: &Path) -> PathBuf {
+    normalize(&std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf()))
+}
+
+/// Lexically resolve `.` and `..` components without touching the filesystem.
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !out.pop() {
+                    out.push("..");
+                }
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// The deepest directory containing the manifest and every chapter, so
+/// Typst can reach chapters that live beside (not below) the manifest.
+fn book_root(base_dir: &Path, chapters: &[Chapter]) -> PathBuf {
+    let mut root = base_dir.to_path_buf();
+    for chapter in chapters {
+        let dir = chapter.path.parent().unwrap_or(&chapter.path);
+        while !dir.starts_with(&root) {
+            if !root.pop() {
+                break;
+            }
+        }
+    }
+    root
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+
+    fn test_theme() -> ResolvedTheme {
+        ResolvedTheme {
+            tokens: crate::theme::tokens::ThemeTokens::default(),
+            tmtheme_xml: String::new(),
+            fonts: Vec::new(),
+        }
+    }
+
+    fn write(path: &Path, content: &str) {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).expect("should create dirs");
+        }
+        std::fs::write(path, content).expect("should write file");
+    }
+
+    #[test]
+    fn summary_lists_chapters_in_order_and_skips_drafts() {
+        let summary = "# Summary\n\n[Intro](README.md)\n\n- [Setup](guide/setup.md)\n  \
+                       - [Deep](guide/deep.md#part)\n- [Draft]()\n- [Site](https://example.com/x.md)\n";
+        let chapters = parse_summary(summary, Path::new("/book"));
+
+        let paths: Vec<_> = chapters.iter().map(|c| c.path.clone()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("/book/README.md"),
+                PathBuf::from("/book/guide/setup.md"),
+                PathBuf::from("/book/guide/deep.md"),
+            ]
+        );
+        assert_eq!(chapters[1].title.as_deref(), Some("Setup"));
+    }
+
+    #[test]
+    fn front_matter_chapters_define_order() {
+        let dir = tempdir().expect("should create temp dir");
+        write(&dir.path().join("b.md"), "# B\n");
+        write(&dir.path().join("a.md"), "# A\n");
+        write(
+            &dir.path().join("book.md"),
+            "---\ntitle: Manual\nchapters:\n  - b.md\n  - a.md\n---\n\nPreface.\n",
+        );
+
+        let book = Book::load(&dir.path().join("book.md")).expect("should load book");
+
+        let names: Vec<_> = book
+            .chapters
+            .iter()
+            .filter_map(|c| c.path.file_name())
+            .collect();
+        assert_eq!(names, vec!["b.md", "a.md"]);
+        assert!(book.intro.contains("Preface."));
+        assert_eq!(
+            book.front_matter.and_then(|fm| fm.title).as_deref(),
+            Some("Manual")
+        );
+    }
+
+    #[test]
+    fn directory_without_summary_is_an_error_when_empty() {
+        let dir = tempdir().expect("should create temp dir");
+        let err = Book::load(dir.path()).expect_err("empty book should fail");
+        assert!(matches!(err, SilkprintError::BookEmpty { .. }));
+    }
+
+    #[test]
+    fn assemble_links_chapters_and_rebases_images() {
+        let dir = tempdir().expect("should create temp dir");
+        write(
+            &dir.path().join("SUMMARY.md"),
+            "- [One](one.md)\n- [Two](guide/two.md)\n",
+        );
+        write(
+            &dir.path().join("one.md"),
+            "# One\n\nSee [setup](guide/two.md#getting-started) and [[guide/two]].\n",
+        );
+        write(
+            &dir.path().join("guide/two.md"),
+            "# Two\n\n## Getting Started\n\n![chart](img/chart.svg)\n\nBack to [one](../one.md).\n",
+        );
+        write(
+            &dir.path().join("guide/img/chart.svg"),
+            "<svg xmlns=\"http://www.w3.org/2000/svg\"/>",
+        );
+
+        let book = Book::load(dir.path()).expect("should load book");
+        let mut warnings = WarningCollector::new();
+        let assembled = assemble(
+            &book,
+            &test_theme(),
+            ImageMode::TypstOnly,
+            &RenderOptions::default(),
+            &mut warnings,
+        )
+        .expect("should assemble");
+        let content = &assembled.content;
+
+        assert!(content.contains("#metadata(none) <chapter-1>"));
+        assert!(content.contains("#pagebreak(weak: true)\n#metadata(none) <chapter-2>"));
+        assert!(content.contains("= Getting Started <chapter-2-getting-started>"));
+        assert!(content.contains("#link(label(\"chapter-2-getting-started\"))[setup]"));
+        assert!(content.contains("#link(label(\"chapter-2\"))["));
+        assert!(content.contains("#link(label(\"chapter-1\"))[one]"));
+        assert!(content.contains("\"/guide/img/chart.svg\""));
+        assert!(warnings.is_empty(), "{:?}", warnings.warnings());
+    }
+
+    #[test]
+    fn assemble_heads_untitled_chapters_with_summary_title() {
+        let dir = tempdir().expect("should create temp dir");
+        write(
+            &dir.path().join("SUMMARY.md"),
+            "- [Getting *Started*](one.md)\n- [Ignored](two.md)\n",
+        );
+        write(&dir.path().join("one.md"), "Just prose.\n");
+        write(&dir.path().join("two.md"), "# Own Title\n");
+
+        let book = Book::load(dir.path()).expect("should load book");
+        let mut warnings = WarningCollector::new();
+        let assembled = assemble(
+            &book,
+            &test_theme(),
+            ImageMode::TypstOnly,
+            &RenderOptions::default(),
+            &mut warnings,
+        )
+        .expect("should assemble");
+        let content = &assembled.content;
+
+        assert!(
+            content.contains("<chapter-1>\n\n= Getting Started\n"),
+            "got: {content}"
+        );
+        assert!(!content.contains("Ignored"), "got: {content}");
+    }
+
+    #[test]
+    fn book_root_covers_sibling_chapters() {
+        let chapters = vec![Chapter {
+            path: PathBuf::from("/docs/shared/intro.md"),
+            title: None,
+        }];
+        assert_eq!(
+            book_root(Path::new("/docs/book"), &chapters),
+            PathBuf::from("/docs")
+        );
+    }
+}
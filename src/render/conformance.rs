@@ -0,0 +1,191 @@
+//! Conformance checks for `--pdf-standard`.
+//!
+//! Typst enforces most of each standard itself (font embedding, color
+//! spaces, tagging structure), but reports gaps in the *content* as compile
+//! errors with no position in the Markdown. The requirements an author has to
+//! fix by hand are checked here first and reported as located warnings; for
+//! standards where they are mandatory, [`enforce`] turns them into an error
+//! before Typst runs.
+
+use comrak::nodes::{AstNode, NodeValue};
+use scraper::{Html, Selector};
+
+use crate::PdfStandard;
+use crate::error::SilkprintError;
+use crate::warnings::{SilkprintWarning, SourceLocation, WarningCollector};
+
+use super::frontmatter::FrontMatter;
+use super::markdown::collect_text;
+
+/// Check document-level requirements: PDF/UA needs a title.
+pub fn check_document(
+    front_matter: Option<&FrontMatter>,
+    standard: Option<PdfStandard>,
+    warnings: &mut WarningCollector,
+) {
+    if standard != Some(PdfStandard::Ua1) {
+        return;
+    }
+    let has_title = front_matter
+        .and_then(|fm| fm.title.as_deref())
+        .is_some_and(|title| !title.trim().is_empty());
+    if !has_title {
+        warnings.push(violation(
+            PdfStandard::Ua1,
+            "document has no title; add `title:` to the front matter".to_string(),
+            None,
+        ));
+    }
+}
+
+/// Check content requirements: PDF/UA needs alt text on every image.
+pub fn check_content<'a>(
+    root: &'a AstNode<'a>,
+    standard: Option<PdfStandard>,
+    warnings: &mut WarningCollector,
+) {
+    if standard != Some(PdfStandard::Ua1) {
+        return;
+    }
+    for node in root.descendants() {
+        let data = node.data.borrow();
+        let location = || SourceLocation::from_sourcepos(data.sourcepos);
+        match &data.value {
+            NodeValue::Image(link) => {
+                let mut alt = String::new();
+                collect_text(node, &mut alt);
+                if alt.trim().is_empty() {
+                    warnings.push(missing_alt(&link.url, location()));
+                }
+            }
+            NodeValue::HtmlBlock(block) => {
+                for src in html_images_without_alt(&block.literal) {
+                    warnings.push(missing_alt(&src, location()));
+                }
+            }
+            NodeValue::HtmlInline(html) if html.contains("<img") => {
+                for src in html_images_without_alt(html) {
+                    warnings.push(missing_alt(&src, location()));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Fail if `standard` makes any reported violation mandatory to fix.
+///
+/// PDF/UA-1 violations would otherwise surface as an unexplained Typst
+/// export error; the PDF/A levels checked here have no content requirements.
+pub fn enforce(
+    standard: Option<PdfStandard>,
+    warnings: &WarningCollector,
+) -> Result<(), SilkprintError> {
+    let Some(standard @ PdfStandard::Ua1) = standard else {
+        return Ok(());
+    };
+    let violations: Vec<String> = warnings
+        .warnings()
+        .iter()
+        .filter(|w| matches!(w, SilkprintWarning::PdfStandardViolation { .. }))
+        .map(|w| match w.location() {
+            Some(location) => {
+                let file = location
+                    .file
+                    .as_ref()
+                    .map_or_else(String::new, |path| format!("{}:", path.display()));
+                format!("{file}{}:{}: {w}", location.line, location.column)
+            }
+            None => w.to_string(),
+        })
+        .collect();
+    if violations.is_empty() {
+        return Ok(());
+    }
+    Err(SilkprintError::PdfStandardUnmet {
+        standard: standard.name().to_string(),
+        count: violations.len(),
+        details: violations.join("\n"),
+    })
+}
+
+fn missing_alt(src: &str, location: Option<SourceLocation>) -> SilkprintWarning {
+    violation(
+        PdfStandard::Ua1,
+        format!("image '{src}' has no alt text"),
+        location,
+    )
+}
+
+fn violation(
+    standard: PdfStandard,
+    message: String,
+    location: Option<SourceLocation>,
+) -> SilkprintWarning {
+    SilkprintWarning::PdfStandardViolation {
+        standard: standard.name().to_string(),
+        message,
+        location,
+    }
+}
+
+/// `src` of every `<img>` in `html` with a missing or blank `alt`.
+fn html_images_without_alt(html: &str) -> Vec<String> {
+    let document = Html::parse_fragment(html);
+    let selector = Selector::parse("img").expect("valid img selector");
+    document
+        .select(&selector)
+        .filter(|img| {
+            img.value()
+                .attr("alt")
+                .is_none_or(|alt| alt.trim().is_empty())
+        })
+        .map(|img| img.value().attr("src").unwrap_or_default().to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::markdown;
+
+    #[test]
+    fn ua1_requires_title_and_alt_text() {
+        let arena = comrak::Arena::new();
+        let root = markdown::parse(
+            &arena,
+            "![](a.png) ![Chart](b.png)\n\n<img src=\"c.png\">\n",
+        );
+        let mut warnings = WarningCollector::new();
+
+        check_document(None, Some(PdfStandard::Ua1), &mut warnings);
+        check_content(root, Some(PdfStandard::Ua1), &mut warnings);
+
+        let messages: Vec<String> = warnings
+            .warnings()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(messages.len(), 3, "{messages:?}");
+        assert!(messages[0].contains("no title"));
+        assert!(messages[1].contains("'a.png'"));
+        assert!(messages[2].contains("'c.png'"));
+        assert!(matches!(
+            enforce(Some(PdfStandard::Ua1), &warnings),
+            Err(SilkprintError::PdfStandardUnmet { count: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn archival_standards_have_no_content_requirements() {
+        let arena = comrak::Arena::new();
+        let root = markdown::parse(&arena, "![](a.png)\n");
+        let mut warnings = WarningCollector::new();
+
+        check_document(None, Some(PdfStandard::A2b), &mut warnings);
+        check_content(root, Some(PdfStandard::A2b), &mut warnings);
+
+        assert!(warnings.is_empty());
+        assert!(enforce(Some(PdfStandard::A2b), &warnings).is_ok());
+    }
+}
@@ -0,0 +1,453 @@
+//! Cross-references: `{#fig:id}` attribute labels and `@fig:id` references.
+//!
+//! Labels attach to headings, standalone images, tables, code blocks, and
+//! display math using Pandoc-style attribute blocks:
+//!
+//! ````text
+//! ## Method {#sec:method}
+//!
+//! ![System overview](arch.svg){#fig:arch}
+//!
+//! Table: Results by quarter {#tbl:results}    (paragraph after a table)
+//!
+//! ```rust {#lst:main caption="Entry point"}
+//!
+//! $$ E = mc^2 $$ {#eq:energy}
+//! ````
+//!
+//! Headings also take `{-}` or `{.unnumbered}` to skip numbering, and
+//! `{.appendix}` — or a level-1 `# Appendix` — to letter the headings after
+//! it. Standalone images and tables take `{.wide}` to span every column of
+//! a multi-column page.
+//!
+//! [`collect`] strips the attribute syntax from the AST before emission and
+//! records each label against its node, so the emitter can wrap the node in a
+//! numbered `#figure(...)` / `#math.equation(...)` and resolve `@fig:arch`.
+
+use std::collections::{HashMap, HashSet};
+
+use comrak::nodes::{AstNode, NodeValue};
+
+use crate::warnings::{SilkprintWarning, SourceLocation, WarningCollector};
+
+/// Reference prefixes recognized in running text (`@fig:arch`).
+pub const REFERENCE_PREFIXES: &[&str] = &["sec", "fig", "tbl", "lst", "eq"];
+
+/// Heading titles that switch to appendix numbering on their own.
+const APPENDIX_TITLES: &[&str] = &["appendix", "appendices"];
+
+/// A parsed `{#id .class key="value"}` attribute block.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Attributes {
+    pub id: Option<String>,
+    pub caption: Option<String>,
+    /// `{-}` or `{.unnumbered}`: leave a heading out of the numbering.
+    pub unnumbered: bool,
+    /// `{.appendix}`: letter the headings that follow.
+    pub appendix: bool,
+    /// `{.wide}`: span all of the page's columns.
+    pub wide: bool,
+}
+
+/// Labels found in a document, keyed by the node they attach to.
+#[derive(Debug, Default)]
+pub struct CrossRefs {
+    attributes: HashMap<usize, Attributes>,
+    labels: HashSet<String>,
+}
+
+impl CrossRefs {
+    /// The label and caption attached to `node`, if any.
+    pub fn get(&self, node: &AstNode<'_>) -> Option<&Attributes> {
+        self.attributes.get(&node_key(node))
+    }
+
+    /// Whether `label` names a labeled element in this document.
+    pub fn contains(&self, label: &str) -> bool {
+        self.labels.contains(label)
+    }
+
+    fn insert(&mut self, node: &AstNode<'_>, attrs: Attributes, warnings: &mut WarningCollector) {
+        if let Some(id) = &attrs.id
+            && !self.labels.insert(id.clone())
+        {
+            warnings.push(SilkprintWarning::DuplicateLabel {
+                label: id.clone(),
+                location: SourceLocation::from_sourcepos(node.data.borrow().sourcepos),
+            });
+            return;
+        }
+        self.attributes.insert(node_key(node), attrs);
+    }
+}
+
+/// Node identity for the side table — stable for the lifetime of the arena.
+fn node_key(node: &AstNode<'_>) -> usize {
+    std::ptr::from_ref(node).addr()
+}
+
+/// Find attribute blocks, strip them from the AST, and record their labels.
+pub fn collect<'a>(root: &'a AstNode<'a>, warnings: &mut WarningCollector) -> CrossRefs {
+    let mut refs = CrossRefs::default();
+    let nodes: Vec<_> = root.descendants().collect();
+
+    for node in nodes {
+        let kind = match &node.data.borrow().value {
+            NodeValue::Heading(_) => Target::Heading,
+            NodeValue::Image(_) | NodeValue::Math(_) => Target::Trailing,
+            NodeValue::Table(_) => Target::CaptionParagraph,
+            NodeValue::CodeBlock(_) => Target::InfoString,
+            _ => continue,
+        };
+        let attrs = match kind {
+            Target::Heading => take_heading_attributes(node),
+            Target::Trailing => take_trailing_attributes(node),
+            Target::CaptionParagraph => take_table_caption(node),
+            Target::InfoString => take_info_attributes(node),
+        };
+        if let Some(attrs) = attrs {
+            refs.insert(node, attrs, warnings);
+        }
+    }
+
+    refs
+}
+
+enum Target {
+    Heading,
+    Trailing,
+    CaptionParagraph,
+    InfoString,
+}
+
+/// `## Title {#sec:x .unnumbered}` — the block ends the heading's last text.
+/// A level-1 `# Appendix` counts as `{.appendix}` without one.
+fn take_heading_attributes<'a>(heading: &'a AstNode<'a>) -> Option<Attributes> {
+    // Book mode appends a raw `<label>` after the title; look past it.
+    let mut last = heading.last_child();
+    while let Some(node) = last
+        && matches!(node.data.borrow().value, NodeValue::Raw(_))
+    {
+        last = node.previous_sibling();
+    }
+
+    let mut attrs = None;
+    if let Some(node) = last
+        && let NodeValue::Text(text) = &mut node.data.borrow_mut().value
+    {
+        let (title, parsed) = split_heading_attributes(text);
+        if parsed.is_some() {
+            *text = title.to_string().into();
+            attrs = parsed;
+        }
+    }
+
+    let mut title = String::new();
+    super::markdown::collect_text(heading, &mut title);
+    let level = match &heading.data.borrow().value {
+        NodeValue::Heading(h) => h.level,
+        _ => 0,
+    };
+    if level == 1
+        && APPENDIX_TITLES
+            .iter()
+            .any(|name| title.trim().eq_ignore_ascii_case(name))
+    {
+        attrs.get_or_insert_with(Attributes::default).appendix = true;
+    }
+    attrs
+}
+
+/// Split a heading's text into its title and a trailing attribute block.
+pub fn split_heading_attributes(text: &str) -> (&str, Option<Attributes>) {
+    let trimmed = text.trim_end();
+    if trimmed.ends_with('}')
+        && let Some(brace) = trimmed.rfind('{')
+        && let Some(attrs) = parse_attributes(&trimmed[brace..])
+    {
+        return (trimmed[..brace].trim_end(), Some(attrs));
+    }
+    (text, None)
+}
+
+/// `![..](..){#fig:x}` / `$$..$$ {#eq:x}` — the block must be the only other
+/// thing in its paragraph, so it is emitted standalone.
+fn take_trailing_attributes<'a>(node: &'a AstNode<'a>) -> Option<Attributes> {
+    if let NodeValue::Math(math) = &node.data.borrow().value
+        && !math.display_math
+    {
+        return None;
+    }
+    let next = node.next_sibling()?;
+    if node.previous_sibling().is_some() || next.next_sibling().is_some() {
+        return None;
+    }
+    let attrs = match &next.data.borrow().value {
+        NodeValue::Text(text) => parse_attributes(text.trim())?,
+        _ => return None,
+    };
+    next.detach();
+    Some(attrs)
+}
+
+/// A paragraph right after a table: `Table: caption {#tbl:x}`, `: caption
+/// {#tbl:x}`, or a bare `{#tbl:x}`.
+fn take_table_caption<'a>(table: &'a AstNode<'a>) -> Option<Attributes> {
+    let (paragraph, attrs) = table_caption(table)?;
+    paragraph.detach();
+    Some(attrs)
+}
+
+/// The caption paragraph after a table and its attributes, left in place.
+pub fn table_caption<'a>(table: &'a AstNode<'a>) -> Option<(&'a AstNode<'a>, Attributes)> {
+    let paragraph = table.next_sibling()?;
+    if !matches!(paragraph.data.borrow().value, NodeValue::Paragraph) {
+        return None;
+    }
+    let mut text = String::new();
+    for child in paragraph.children() {
+        match &child.data.borrow().value {
+            NodeValue::Text(t) => text.push_str(t),
+            NodeValue::SoftBreak => text.push(' '),
+            _ => return None,
+        }
+    }
+    let text = text.trim();
+    let brace = text.rfind('{')?;
+    let mut attrs = parse_attributes(&text[brace..])?;
+    let caption = text[..brace].trim();
+    let caption = caption
+        .strip_prefix("Table:")
+        .or_else(|| caption.strip_prefix(':'))
+        .unwrap_or(caption)
+        .trim();
+    if attrs.caption.is_none() && !caption.is_empty() {
+        attrs.caption = Some(caption.to_string());
+    }
+    Some((paragraph, attrs))
+}
+
+/// ```` ```rust {#lst:main caption="Entry"} ```` — attributes in the info string.
+fn take_info_attributes(node: &AstNode<'_>) -> Option<Attributes> {
+    let mut data = node.data.borrow_mut();
+    let NodeValue::CodeBlock(block) = &mut data.value else {
+        return None;
+    };
+    let start = block.info.find('{')?;
+    let attrs = parse_attributes(block.info[start..].trim_end())?;
+    block.info = block.info[..start].trim_end().to_string();
+    Some(attrs)
+}
+
+/// Parse `{#id .class key="value" key=value}`. Returns `None` unless the
+/// whole string is a single attribute block with an `#id`, `-`,
+/// `.unnumbered`, `.appendix` or `.wide`. Other classes are ignored.
+pub fn parse_attributes(s: &str) -> Option<Attributes> {
+    let inner = s.strip_prefix('{')?.strip_suffix('}')?;
+    let mut attrs = Attributes::default();
+    let mut rest = inner.trim();
+
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('#') {
+            let end = after.find(char::is_whitespace).unwrap_or(after.len());
+            let id = &after[..end];
+            if id.is_empty() || !id.chars().all(is_label_char) {
+                return None;
+            }
+            attrs.id = Some(id.to_string());
+            rest = after[end..].trim_start();
+        } else if let Some(after) = rest.strip_prefix('.') {
+            let end = after.find(char::is_whitespace).unwrap_or(after.len());
+            match &after[..end] {
+                "unnumbered" => attrs.unnumbered = true,
+                "appendix" => attrs.appendix = true,
+                "wide" => attrs.wide = true,
+                _ => {}
+            }
+            rest = after[end..].trim_start();
+        } else if let Some(after) = rest
+            .strip_prefix('-')
+            .filter(|after| after.is_empty() || after.starts_with(char::is_whitespace))
+        {
+            attrs.unnumbered = true;
+            rest = after.trim_start();
+        } else {
+            let (key, after) = rest.split_once('=')?;
+            let (value, remaining) = if let Some(quoted) = after.strip_prefix('"') {
+                let close = quoted.find('"')?;
+                (&quoted[..close], &quoted[close + 1..])
+            } else {
+                let end = after.find(char::is_whitespace).unwrap_or(after.len());
+                (&after[..end], &after[end..])
+            };
+            if key.trim() == "caption" {
+                attrs.caption = Some(value.to_string());
+            }
+            rest = remaining.trim_start();
+        }
+    }
+
+    (attrs.id.is_some() || attrs.unnumbered || attrs.appendix || attrs.wide).then_some(attrs)
+}
+
+/// Characters allowed in a label: letters, digits, `_`, `-`, `:`, `.`.
+fn is_label_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | ':' | '.')
+}
+
+/// A run of text split around `@prefix:id` references.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Segment<'s> {
+    Text(&'s str),
+    Reference(&'s str),
+}
+
+/// Split text into plain runs and `@fig:id`-style references.
+///
+/// Only the [`REFERENCE_PREFIXES`] count, so e-mail addresses and social
+/// handles pass through untouched. Trailing sentence punctuation is not part
+/// of the label.
+pub fn split_references(text: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    let mut last = 0;
+    let mut search = 0;
+
+    while let Some(offset) = text[search..].find('@') {
+        let at = search + offset;
+        search = at + 1;
+        // `user@fig:x` is an address, not a reference.
+        if text[..at]
+            .chars()
+            .next_back()
+            .is_some_and(char::is_alphanumeric)
+        {
+            continue;
+        }
+        let body = &text[at + 1..];
+        let len = body
+            .char_indices()
+            .find(|&(_, c)| !is_label_char(c))
+            .map_or(body.len(), |(i, _)| i);
+        let label = body[..len].trim_end_matches(['.', ':', '-']);
+        let Some((prefix, name)) = label.split_once(':') else {
+            continue;
+        };
+        if name.is_empty() || !REFERENCE_PREFIXES.contains(&prefix) {
+            continue;
+        }
+        if at > last {
+            segments.push(Segment::Text(&text[last..at]));
+        }
+        segments.push(Segment::Reference(label));
+        last = at + 1 + label.len();
+        search = last;
+    }
+
+    if last < text.len() {
+        segments.push(Segment::Text(&text[last..]));
+    }
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::markdown;
+
+    #[test]
+    fn parses_id_and_quoted_caption() {
+        assert_eq!(
+            parse_attributes("{#lst:main caption=\"Entry point\"}"),
+            Some(Attributes {
+                id: Some("lst:main".to_string()),
+                caption: Some("Entry point".to_string()),
+                ..Attributes::default()
+            })
+        );
+        assert_eq!(parse_attributes("{.class}"), None);
+        assert!(parse_attributes("{.wide}").is_some_and(|attrs| attrs.wide));
+        assert_eq!(parse_attributes("not an attribute"), None);
+    }
+
+    #[test]
+    fn splits_references_and_ignores_addresses() {
+        let segments = split_references("See @fig:arch. Mail me@fig:x or @someone.");
+        assert_eq!(
+            segments,
+            vec![
+                Segment::Text("See "),
+                Segment::Reference("fig:arch"),
+                Segment::Text(". Mail me@fig:x or @someone."),
+            ]
+        );
+    }
+
+    #[test]
+    fn collect_strips_attribute_syntax() {
+        let arena = comrak::Arena::new();
+        let root = markdown::parse(
+            &arena,
+            "![Arch](arch.svg){#fig:arch}\n\n| a |\n|---|\n| 1 |\n\nTable: Results {#tbl:res}\n\n```rust {#lst:main}\nfn main() {}\n```\n",
+        );
+        let mut warnings = WarningCollector::new();
+        let refs = collect(root, &mut warnings);
+
+        assert!(refs.contains("fig:arch"));
+        assert!(refs.contains("tbl:res"));
+        assert!(refs.contains("lst:main"));
+        let mut text = String::new();
+        markdown::collect_text(root, &mut text);
+        assert!(
+            !text.contains("{#"),
+            "attribute text should be stripped: {text}"
+        );
+        let table = root
+            .descendants()
+            .find(|n| matches!(n.data.borrow().value, NodeValue::Table(_)))
+            .expect("table node");
+        assert_eq!(
+            refs.get(table).and_then(|a| a.caption.as_deref()),
+            Some("Results")
+        );
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn heading_attributes_and_appendix_switch() {
+        let arena = comrak::Arena::new();
+        let root = markdown::parse(
+            &arena,
+            "# Intro {#sec:intro}\n\n## Notes {-}\n\n# Appendix\n\n# Data {.unnumbered #sec:data}\n",
+        );
+        let mut warnings = WarningCollector::new();
+        let refs = collect(root, &mut warnings);
+
+        let headings: Vec<_> = root.children().collect();
+        let attrs = |index: usize| refs.get(headings[index]).cloned().unwrap_or_default();
+        assert_eq!(attrs(0).id.as_deref(), Some("sec:intro"));
+        assert!(attrs(1).unnumbered);
+        assert!(attrs(2).appendix);
+        assert!(attrs(3).unnumbered);
+        assert!(refs.contains("sec:data"));
+
+        let mut text = String::new();
+        markdown::collect_text(root, &mut text);
+        assert!(
+            !text.contains('{'),
+            "attribute text should be stripped: {text}"
+        );
+    }
+
+    #[test]
+    fn duplicate_labels_warn() {
+        let arena = comrak::Arena::new();
+        let root = markdown::parse(&arena, "![a](a.png){#fig:x}\n\n![b](b.png){#fig:x}\n");
+        let mut warnings = WarningCollector::new();
+        collect(root, &mut warnings);
+        assert!(matches!(
+            warnings.warnings(),
+            [SilkprintWarning::DuplicateLabel { location: Some(location), .. }]
+                if location.line == 3
+        ));
+    }
+}
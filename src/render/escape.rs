@@ -21,6 +21,81 @@ pub(crate) fn escape_typst_string(s: &str) -> String {
     s.replace('\\', "\\\\").replace('"', "\\\"")
 }
 
+/// Escape text for HTML content and double-quoted attribute values.
+pub(crate) fn escape_html(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + s.len() / 8);
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Escape text for a double-quoted CSS string such as a `font-family` name.
+///
+/// `<` is escaped too so a value can't close the surrounding `<style>`.
+pub(crate) fn escape_css_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    for c in s.chars() {
+        match c {
+            '"' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' => out.push_str("\\a "),
+            '<' => out.push_str("\\3c "),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Standard (padded) base64, used for OSC 52 clipboard writes and `data:` URIs.
+pub(crate) fn base64_encode(bytes: &[u8]) -> String {
+    const TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b0 = chunk[0];
+        let b1 = *chunk.get(1).unwrap_or(&0);
+        let b2 = *chunk.get(2).unwrap_or(&0);
+        out.push(char::from(TABLE[usize::from(b0 >> 2)]));
+        out.push(char::from(
+            TABLE[usize::from(((b0 & 0b0000_0011) << 4) | (b1 >> 4))],
+        ));
+        if chunk.len() > 1 {
+            out.push(char::from(
+                TABLE[usize::from(((b1 & 0b0000_1111) << 2) | (b2 >> 6))],
+            ));
+        } else {
+            out.push('=');
+        }
+        if chunk.len() > 2 {
+            out.push(char::from(TABLE[usize::from(b2 & 0b0011_1111)]));
+        } else {
+            out.push('=');
+        }
+    }
+    out
+}
+
+/// Whether `s` is a plain Typst length (`72pt`, `1.5em`, `50%`) that can be
+/// spliced into Typst code as is.
+pub(crate) fn is_typst_length(s: &str) -> bool {
+    let Some(number) = ["pt", "mm", "cm", "in", "em", "%"]
+        .iter()
+        .find_map(|unit| s.strip_suffix(unit))
+    else {
+        return false;
+    };
+    number.bytes().all(|b| b.is_ascii_digit() || b == b'.') && number.parse::<f64>().is_ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -49,4 +124,37 @@ mod tests {
         assert!(result.contains("\\\\"));
         assert!(result.contains("\\\""));
     }
+
+    #[test]
+    fn escape_html_markup_and_quotes() {
+        let result = escape_html(r#"<a href="x">Tom & 'Jerry'</a>"#);
+        assert_eq!(
+            result,
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; &#39;Jerry&#39;&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn typst_lengths_are_plain_numbers_with_units() {
+        for length in ["72pt", "1.5em", "8mm", "50%", "0.5in"] {
+            assert!(is_typst_length(length), "{length}");
+        }
+        for value in [
+            "",
+            "pt",
+            "72",
+            "-1pt",
+            "1e3pt",
+            "72pt) + x",
+            "72pt, fill: red",
+        ] {
+            assert!(!is_typst_length(value), "{value}");
+        }
+    }
+
+    #[test]
+    fn escape_css_string_quotes_and_backslashes() {
+        let result = escape_css_string(r#"Evil" } body { x: "\</style>"#);
+        assert_eq!(result, r#"Evil\" } body { x: \"\\\3c /style>"#);
+    }
 }
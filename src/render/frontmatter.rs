@@ -4,6 +4,7 @@ use serde::Deserialize;
 
 use crate::PaperSize;
 use crate::error::SilkprintError;
+use crate::render::escape::is_typst_length;
 use crate::warnings::{SilkprintWarning, WarningCollector};
 
 /// Parsed YAML front matter from a Markdown document.
@@ -23,9 +24,39 @@ pub struct FrontMatter {
     pub toc: Option<bool>,
     #[serde(rename = "toc-depth")]
     pub toc_depth: Option<u8>,
+    /// Heading numbering pattern (`"1.1"`), `true` for `"1.1"`, or `false`.
+    #[serde(deserialize_with = "numbering_pattern")]
     pub numbering: Option<String>,
     #[serde(rename = "font-size")]
     pub font_size: Option<String>,
+    /// Page columns, over the theme's `[page] columns`.
+    pub columns: Option<u8>,
+    /// Space between columns, over the theme's `[page] column_gap`.
+    #[serde(rename = "column-gap", deserialize_with = "typst_length")]
+    pub column_gap: Option<String>,
+    /// Ordered chapter files for book mode, relative to the manifest.
+    pub chapters: Option<Vec<String>>,
+    /// BibTeX or Hayagriva files, relative to the document. A single path or a list.
+    #[serde(deserialize_with = "string_or_list")]
+    pub bibliography: Option<Vec<String>>,
+    /// Citation style: a Typst built-in style name or a `.csl` file.
+    #[serde(alias = "citation-style")]
+    pub csl: Option<String>,
+    /// Typst template (`.typ`) applied after the theme's rules, relative to
+    /// the document. Overrides the theme's template.
+    pub template: Option<String>,
+    /// Running header slots over the theme's `[header]`, or `false`.
+    #[serde(deserialize_with = "bool_or_running")]
+    pub header: Option<RunningOverride>,
+    /// Running footer slots over the theme's `[footer]`, or `false`.
+    #[serde(deserialize_with = "bool_or_running")]
+    pub footer: Option<RunningOverride>,
+    /// Text or image behind (or over) every page. A plain string is text.
+    #[serde(deserialize_with = "text_or_watermark")]
+    pub watermark: Option<Watermark>,
+    /// Marking like `CONFIDENTIAL`, bannered on every page and written to
+    /// the PDF's keywords.
+    pub classification: Option<String>,
 
     /// Unknown fields from the front matter YAML.
     ///
@@ -64,6 +95,155 @@ impl<'de> Deserialize<'de> for FlexibleDate {
     }
 }
 
+/// Accept either `key: value` or `key: [a, b]` for list-valued fields.
+fn string_or_list<'de, D>(deserializer: D) -> Result<Option<Vec<String>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    Ok(match Option::<OneOrMany>::deserialize(deserializer)? {
+        Some(OneOrMany::One(value)) => Some(vec![value]),
+        Some(OneOrMany::Many(values)) => Some(values),
+        None => None,
+    })
+}
+
+/// Accept `numbering: "1.a"` or `numbering: true` / `false`.
+fn numbering_pattern<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum PatternOrBool {
+        Bool(bool),
+        Pattern(String),
+    }
+
+    Ok(match Option::<PatternOrBool>::deserialize(deserializer)? {
+        Some(PatternOrBool::Bool(true)) => Some("1.1".to_string()),
+        Some(PatternOrBool::Bool(false)) => Some("none".to_string()),
+        Some(PatternOrBool::Pattern(pattern)) => Some(pattern),
+        None => None,
+    })
+}
+
+/// Front matter override for a running header or footer. Unset fields
+/// keep the theme's value.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct RunningOverride {
+    pub enabled: Option<bool>,
+    pub left: Option<String>,
+    pub center: Option<String>,
+    pub right: Option<String>,
+    #[serde(rename = "title-page")]
+    pub title_page: Option<bool>,
+    #[serde(rename = "skip-chapter-pages")]
+    pub skip_chapter_pages: Option<bool>,
+}
+
+/// Accept either `header: false` or `header: { left: ..., right: ... }`.
+/// A map turns the header on unless it says `enabled: false`.
+fn bool_or_running<'de, D>(deserializer: D) -> Result<Option<RunningOverride>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum BoolOrSlots {
+        Bool(bool),
+        Slots(RunningOverride),
+    }
+
+    Ok(match Option::<BoolOrSlots>::deserialize(deserializer)? {
+        Some(BoolOrSlots::Bool(enabled)) => Some(RunningOverride {
+            enabled: Some(enabled),
+            ..RunningOverride::default()
+        }),
+        Some(BoolOrSlots::Slots(slots)) => Some(RunningOverride {
+            enabled: slots.enabled.or(Some(true)),
+            ..slots
+        }),
+        None => None,
+    })
+}
+
+/// A watermark: `text` or `image` (relative to the document), with
+/// settings that fall back to the theme's `[watermark]`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Watermark {
+    pub text: Option<String>,
+    pub image: Option<String>,
+    /// Degrees; positive turns clockwise.
+    pub rotation: Option<f64>,
+    /// 0.0 (invisible) to 1.0 (solid).
+    pub opacity: Option<f64>,
+    /// A hex color or a theme `[colors]` key.
+    pub color: Option<String>,
+    /// Font size for text, width for images, as a Typst length.
+    pub size: Option<String>,
+    /// `background` (default) or `foreground`, over the body. Text only:
+    /// an image is faded by veiling it in the page color, which would also
+    /// veil the body.
+    pub layer: Option<String>,
+}
+
+/// Accept only a Typst length like `6mm`, which is written into Typst as is.
+fn typst_length<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = Option::<String>::deserialize(deserializer)?;
+    if let Some(length) = value.as_deref().filter(|s| !is_typst_length(s)) {
+        return Err(serde::de::Error::custom(format!(
+            "'{length}' is not a length like 6mm or 4%"
+        )));
+    }
+    Ok(value)
+}
+
+/// Accept either `watermark: DRAFT` or `watermark: { text: DRAFT, ... }`.
+fn text_or_watermark<'de, D>(deserializer: D) -> Result<Option<Watermark>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum TextOrSettings {
+        Text(String),
+        Settings(Watermark),
+    }
+
+    Ok(match Option::<TextOrSettings>::deserialize(deserializer)? {
+        Some(TextOrSettings::Text(text)) => Some(Watermark {
+            text: Some(text),
+            ..Watermark::default()
+        }),
+        Some(TextOrSettings::Settings(watermark)) => {
+            if let Some(size) = watermark.size.as_deref().filter(|s| !is_typst_length(s)) {
+                return Err(serde::de::Error::custom(format!(
+                    "watermark size '{size}' is not a length like 72pt or 50%"
+                )));
+            }
+            if watermark.image.is_some() && watermark.layer.as_deref() == Some("foreground") {
+                return Err(serde::de::Error::custom(
+                    "image watermarks can't use layer: foreground",
+                ));
+            }
+            Some(watermark)
+        }
+        None => None,
+    })
+}
+
 /// Extract front matter from input, returning (`front_matter`, `body_without_front_matter`).
 ///
 /// Front matter is delimited by `---` lines at the start of the document.
@@ -95,6 +275,15 @@ pub fn extract(input: &str) -> Result<(Option<FrontMatter>, String), SilkprintEr
     parse_yaml_section(input, rest, end_pos)
 }
 
+/// Number of lines before `body` in `input`, where `body` is the text
+/// returned by [`extract`]. Lets body-relative line numbers (from comrak)
+/// be reported against the original file.
+pub fn body_line_offset(input: &str, body: &str) -> usize {
+    input
+        .strip_suffix(body)
+        .map_or(0, |prefix| prefix.matches('\n').count())
+}
+
 /// Parse the YAML section between the two `---` delimiters and split the body.
 fn parse_yaml_section(
     original_input: &str,
@@ -292,6 +481,95 @@ mod tests {
         assert_eq!(body, "# Heading\n");
     }
 
+    #[test]
+    fn bibliography_accepts_single_path_or_list() {
+        let input = "---\nbibliography: refs.bib\ncitation-style: apa\n---\n";
+        let fm = extract(input)
+            .expect("should parse")
+            .0
+            .expect("front matter");
+        assert_eq!(fm.bibliography, Some(vec!["refs.bib".to_string()]));
+        assert_eq!(fm.csl.as_deref(), Some("apa"));
+
+        let input = "---\nbibliography: [a.bib, b.yml]\n---\n";
+        let fm = extract(input)
+            .expect("should parse")
+            .0
+            .expect("front matter");
+        assert_eq!(
+            fm.bibliography,
+            Some(vec!["a.bib".to_string(), "b.yml".to_string()])
+        );
+        assert!(fm.extras.is_empty());
+    }
+
+    #[test]
+    fn header_accepts_slots_or_false() {
+        let input = "---\nheader:\n  left: \"{title}\"\n  right: \"{page}\"\nfooter: false\n---\n";
+        let fm = extract(input)
+            .expect("should parse")
+            .0
+            .expect("front matter");
+        let header = fm.header.expect("header override");
+        assert_eq!(header.enabled, Some(true));
+        assert_eq!(header.left.as_deref(), Some("{title}"));
+        assert_eq!(header.center, None);
+        let footer = fm.footer.expect("footer override");
+        assert_eq!(footer.enabled, Some(false));
+        assert!(fm.extras.is_empty());
+    }
+
+    #[test]
+    fn watermark_accepts_text_or_settings() {
+        let input = "---\nwatermark: DRAFT\nclassification: CONFIDENTIAL\n---\n";
+        let fm = extract(input)
+            .expect("should parse")
+            .0
+            .expect("front matter");
+        let watermark = fm.watermark.expect("watermark");
+        assert_eq!(watermark.text.as_deref(), Some("DRAFT"));
+        assert_eq!(fm.classification.as_deref(), Some("CONFIDENTIAL"));
+
+        let input = "---\nwatermark:\n  image: seal.png\n  opacity: 0.2\n  rotation: 0\n---\n";
+        let fm = extract(input)
+            .expect("should parse")
+            .0
+            .expect("front matter");
+        let watermark = fm.watermark.expect("watermark");
+        assert_eq!(watermark.image.as_deref(), Some("seal.png"));
+        assert_eq!(watermark.opacity, Some(0.2));
+        assert_eq!(watermark.rotation, Some(0.0));
+        assert!(fm.extras.is_empty());
+
+        let input = "---\nwatermark:\n  text: DRAFT\n  size: \"1pt, fill: red\"\n---\n";
+        assert!(extract(input).is_err());
+
+        let input = "---\nwatermark:\n  image: seal.png\n  layer: foreground\n---\n";
+        assert!(extract(input).is_err());
+    }
+
+    #[test]
+    fn column_gap_must_be_a_length() {
+        let fm = extract("---\ncolumn-gap: 6mm\n---\n")
+            .expect("should parse")
+            .0
+            .expect("front matter");
+        assert_eq!(fm.column_gap.as_deref(), Some("6mm"));
+        assert!(extract("---\ncolumn-gap: \"6mm)[#panic()]\"\n---\n").is_err());
+    }
+
+    #[test]
+    fn numbering_accepts_pattern_or_bool() {
+        for (value, expected) in [("\"I.a\"", "I.a"), ("true", "1.1"), ("false", "none")] {
+            let input = format!("---\nnumbering: {value}\n---\n");
+            let fm = extract(&input)
+                .expect("should parse")
+                .0
+                .expect("front matter");
+            assert_eq!(fm.numbering.as_deref(), Some(expected));
+        }
+    }
+
     #[test]
     fn extract_windows_line_endings() {
         let input = "---\r\ntitle: Windows\r\n---\r\nBody text\r\n";
@@ -343,6 +621,14 @@ mod tests {
         assert!(!merged.toc);
     }
 
+    #[test]
+    fn body_line_offset_counts_front_matter_lines() {
+        let input = "---\ntitle: Doc\n---\n\n# Body\n";
+        let (_, body) = extract(input).expect("should parse");
+        assert_eq!(body_line_offset(input, &body), 3);
+        assert_eq!(body_line_offset("# Body\n", "# Body\n"), 0);
+    }
+
     #[test]
     fn merge_frontmatter_over_theme() {
         let fm = FrontMatter {
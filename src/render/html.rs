@@ -211,8 +211,11 @@ fn emit_element(
 
         // ─── Unknown tags ────────────────────────────────────────
         _ => {
+            // The caller knows where the HTML sits in the Markdown; see
+            // `WarningCollector::extend_located`.
             warnings.push(SilkprintWarning::UnsupportedHtmlTag {
                 tag: tag.to_string(),
+                location: None,
             });
             emit_children(node, out, images, warnings, ctx);
         }
@@ -329,15 +332,16 @@ fn emit_image(
             .unwrap_or_default();
         let escaped_src = escape_typst_string(typst_path);
         let escaped_label = escape_typst_content(label);
+        let alt_arg = super::markdown::alt_argument(alt);
 
         if matches!(ctx, Context::Inline | Context::TableCell) {
-            let _ = write!(out, "#box(image(\"{escaped_src}\"{width_arg}))");
+            let _ = write!(out, "#box(image(\"{escaped_src}\"{width_arg}{alt_arg}))");
         } else if escaped_label.is_empty() {
-            let _ = write!(out, "#figure(image(\"{escaped_src}\"{width_arg}))");
+            let _ = write!(out, "#figure(image(\"{escaped_src}\"{width_arg}{alt_arg}))");
         } else {
             let _ = write!(
                 out,
-                "#figure(image(\"{escaped_src}\"{width_arg}), caption: [{escaped_label}])"
+                "#figure(image(\"{escaped_src}\"{width_arg}{alt_arg}), caption: [{escaped_label}])"
             );
         }
     } else {
@@ -798,7 +802,7 @@ mod tests {
         assert!(!w.is_empty());
         let warning = &w.warnings()[0];
         assert!(
-            matches!(warning, SilkprintWarning::UnsupportedHtmlTag { tag } if tag == "marquee"),
+            matches!(warning, SilkprintWarning::UnsupportedHtmlTag { tag, .. } if tag == "marquee"),
             "got: {warning:?}"
         );
     }
@@ -0,0 +1,1284 @@
+//! Standalone HTML export driven by the resolved theme.
+//!
+//! Walks the same comrak AST the Typst emitter consumes and produces a single
+//! self-contained HTML file: theme tokens become CSS custom properties, the
+//! theme's bundled fonts are inlined as `@font-face` data URIs, code blocks
+//! are coloured from the `[syntax]` palette, mermaid diagrams are embedded as
+//! inline SVG, and images become `data:` URIs. The browser view matches the
+//! PDF without any external assets.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::Path;
+
+use comrak::nodes::{AstNode, NodeValue, TableAlignment};
+use typst::foundations::Bytes;
+use typst::text::{Font, FontStyle};
+
+use crate::theme::ResolvedTheme;
+use crate::theme::syntax::resolve_syntax_tokens;
+use crate::theme::tokens::ThemeTokens;
+use crate::warnings::{SilkprintWarning, SourceLocation, WarningCollector};
+
+use super::crossref::CrossRefs;
+use super::escape::{base64_encode, escape_css_string, escape_html};
+use super::frontmatter::FrontMatter;
+use super::image::{PreparedImage, PreparedImages, is_remote_image, resolve_image_path};
+use super::markdown::{
+    ExtractedNode, collect_text, extract_node, is_standalone_image, unique_heading_slug,
+    wikilink_target,
+};
+
+/// Marker left in the body where a rendered mermaid SVG is spliced in.
+const MERMAID_PLACEHOLDER: &str = "<!--silkprint:mermaid:";
+
+/// Render a parsed document to a complete, self-contained HTML page.
+///
+/// `images` must have been prepared with [`ImageMode::Compile`] so remote
+/// images are already downloaded; local images are read from `root_dir`.
+///
+/// [`ImageMode::Compile`]: super::image::ImageMode::Compile
+pub fn emit_document<'a>(
+    root: &'a AstNode<'a>,
+    theme: &ResolvedTheme,
+    front_matter: Option<&FrontMatter>,
+    images: &PreparedImages,
+    root_dir: Option<&Path>,
+    warnings: &mut WarningCollector,
+) -> String {
+    let body = emit_body(root, theme, images, root_dir, warnings);
+    let t = &theme.tokens;
+
+    let lang = front_matter
+        .and_then(|fm| fm.lang.as_deref())
+        .unwrap_or("en");
+    let title = front_matter
+        .and_then(|fm| fm.title.as_deref())
+        .unwrap_or("Document");
+
+    let mut out = String::with_capacity(body.len() + 16384);
+    out.push_str("<!DOCTYPE html>\n");
+    let _ = writeln!(out, "<html lang=\"{}\">", escape_html(lang));
+    out.push_str("<head>\n");
+    out.push_str("<meta charset=\"utf-8\">\n");
+    out.push_str("<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n");
+    out.push_str("<meta name=\"generator\" content=\"silkprint\">\n");
+    if let Some(author) = front_matter.and_then(|fm| fm.author.as_deref()) {
+        let _ = writeln!(
+            out,
+            "<meta name=\"author\" content=\"{}\">",
+            escape_html(author)
+        );
+    }
+    let _ = writeln!(out, "<title>{}</title>", escape_html(title));
+    out.push_str("<style>\n");
+    out.push_str(&font_faces(t));
+    out.push_str(&stylesheet(t));
+    out.push_str("</style>\n");
+    out.push_str("</head>\n");
+    out.push_str("<body>\n");
+    out.push_str("<main class=\"silkprint\">\n");
+    if let Some(fm) = front_matter {
+        emit_title_block(&mut out, fm);
+    }
+    out.push_str(&body);
+    out.push_str("</main>\n");
+    out.push_str("</body>\n");
+    out.push_str("</html>\n");
+    out
+}
+
+/// Render only the document body (no `<html>` shell, styles, or fonts).
+pub fn emit_body<'a>(
+    root: &'a AstNode<'a>,
+    theme: &ResolvedTheme,
+    images: &PreparedImages,
+    root_dir: Option<&Path>,
+    warnings: &mut WarningCollector,
+) -> String {
+    // Strip `{#sec:id}` attribute blocks; heading ids become element ids.
+    let crossrefs = super::crossref::collect(root, warnings);
+    let footnote_defs = collect_footnote_nodes(root);
+
+    let mut ctx = HtmlContext {
+        out: String::with_capacity(8192),
+        crossrefs,
+        heading_slugs: HashMap::new(),
+        images,
+        root_dir,
+        warnings,
+        footnote_defs: footnote_defs.keys().cloned().collect(),
+        footnote_order: Vec::new(),
+        table_alignments: Vec::new(),
+        table_cell_index: 0,
+        in_table_header: false,
+        unwrap_paragraphs: false,
+        mermaid_sources: Vec::new(),
+    };
+
+    emit_node(root, &mut ctx);
+    emit_footnotes(&footnote_defs, &mut ctx);
+
+    let HtmlContext {
+        mut out,
+        mermaid_sources,
+        warnings,
+        ..
+    } = ctx;
+
+    if !mermaid_sources.is_empty() {
+        tracing::info!(count = mermaid_sources.len(), "rendering mermaid diagrams");
+        let svgs = super::mermaid::render_all(&mermaid_sources, theme, warnings);
+        for idx in 0..mermaid_sources.len() {
+            let vpath = format!("{}{idx}.svg", super::mermaid::MERMAID_VPATH_PREFIX);
+            let svg = svgs
+                .get(&vpath)
+                .map(|bytes| String::from_utf8_lossy(bytes).into_owned())
+                .unwrap_or_default();
+            out = out.replace(&format!("{MERMAID_PLACEHOLDER}{idx}-->"), &svg);
+        }
+    }
+
+    out
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// Emitter context & recursive walker
+// ═══════════════════════════════════════════════════════════════════
+
+/// Mutable state carried through the recursive tree walk.
+struct HtmlContext<'w> {
+    out: String,
+    crossrefs: CrossRefs,
+    /// Slugs handed out so far, for `intro`, `intro-1`, ... de-duplication.
+    heading_slugs: HashMap<String, usize>,
+    images: &'w PreparedImages,
+    root_dir: Option<&'w Path>,
+    warnings: &'w mut WarningCollector,
+    /// Names of every footnote definition in the document.
+    footnote_defs: Vec<String>,
+    /// Footnote names in order of first reference — this is their number.
+    footnote_order: Vec<String>,
+    table_alignments: Vec<TableAlignment>,
+    table_cell_index: usize,
+    in_table_header: bool,
+    /// Render paragraphs without `<p>` (description terms, footnote bodies).
+    unwrap_paragraphs: bool,
+    mermaid_sources: Vec<String>,
+}
+
+impl HtmlContext<'_> {
+    fn push(&mut self, s: &str) {
+        self.out.push_str(s);
+    }
+
+    fn push_text(&mut self, s: &str) {
+        self.out.push_str(&escape_html(s));
+    }
+}
+
+/// Emit a single AST node and all its children.
+#[allow(clippy::too_many_lines)]
+fn emit_node<'a>(node: &'a AstNode<'a>, ctx: &mut HtmlContext<'_>) {
+    match extract_node(node) {
+        // ─── Front matter (already extracted) / footnote defs (emitted last) ──
+        ExtractedNode::FrontMatter | ExtractedNode::FootnoteDefinition => {}
+
+        // ─── Transparent containers ──────────────────────────────
+        ExtractedNode::Document
+        | ExtractedNode::Escaped
+        | ExtractedNode::EscapedTag
+        | ExtractedNode::DescriptionItem => emit_children(node, ctx),
+
+        // ─── Paragraph ───────────────────────────────────────────
+        ExtractedNode::Paragraph => {
+            if ctx.unwrap_paragraphs || in_tight_list(node) {
+                emit_children(node, ctx);
+            } else {
+                ctx.push("<p>");
+                emit_children(node, ctx);
+                ctx.push("</p>\n");
+            }
+        }
+
+        // ─── Heading ─────────────────────────────────────────────
+        // Explicit `{#sec:id}` labels win; other headings get the same
+        // GitHub-style slug book mode uses for its Typst labels.
+        ExtractedNode::Heading { level } => {
+            let id = match ctx.crossrefs.get(node).and_then(|attrs| attrs.id.clone()) {
+                Some(id) => id,
+                None => {
+                    let mut text = String::new();
+                    collect_text(node, &mut text);
+                    unique_heading_slug(&text, &mut ctx.heading_slugs)
+                }
+            };
+            let _ = write!(ctx.out, "<h{level} id=\"{}\">", escape_html(&id));
+            emit_children(node, ctx);
+            let _ = writeln!(ctx.out, "</h{level}>");
+        }
+
+        ExtractedNode::ThematicBreak => ctx.push("<hr>\n"),
+
+        // ─── Inline text ─────────────────────────────────────────
+        ExtractedNode::Text(text) => ctx.push_text(&text),
+        ExtractedNode::SoftBreak => ctx.push("\n"),
+        ExtractedNode::LineBreak => ctx.push("<br>\n"),
+        ExtractedNode::ShortCode { emoji } => ctx.push_text(&emoji),
+        ExtractedNode::Raw(raw) | ExtractedNode::HtmlInline(raw) => ctx.push(&raw),
+
+        // ─── Inline formatting ───────────────────────────────────
+        ExtractedNode::Strong => wrap(node, ctx, "<strong>", "</strong>"),
+        ExtractedNode::Emph => wrap(node, ctx, "<em>", "</em>"),
+        ExtractedNode::Strikethrough => wrap(node, ctx, "<del>", "</del>"),
+        ExtractedNode::SpoileredText => wrap(node, ctx, "<span class=\"spoiler\">", "</span>"),
+        ExtractedNode::Underline => wrap(node, ctx, "<u>", "</u>"),
+        ExtractedNode::Superscript => wrap(node, ctx, "<sup>", "</sup>"),
+        ExtractedNode::Subscript | ExtractedNode::Subtext => wrap(node, ctx, "<sub>", "</sub>"),
+        ExtractedNode::Highlight => wrap(node, ctx, "<mark>", "</mark>"),
+
+        ExtractedNode::Code { literal, .. } => {
+            let _ = write!(ctx.out, "<code>{}</code>", escape_html(&literal));
+        }
+
+        // ─── Links ───────────────────────────────────────────────
+        ExtractedNode::Link { url } => {
+            let _ = write!(ctx.out, "<a href=\"{}\">", escape_html(&url));
+            emit_children(node, ctx);
+            ctx.push("</a>");
+        }
+        ExtractedNode::WikiLink { url } => {
+            let target = wikilink_target(&url);
+            let _ = write!(ctx.out, "<a href=\"{}\">", escape_html(&target));
+            emit_children(node, ctx);
+            ctx.push("</a>");
+        }
+
+        // ─── Image ───────────────────────────────────────────────
+        ExtractedNode::Image { url, title } => emit_image(node, ctx, &url, &title),
+
+        // ─── Block quote ─────────────────────────────────────────
+        ExtractedNode::BlockQuote | ExtractedNode::MultilineBlockQuote => {
+            ctx.push("<blockquote>\n");
+            emit_children(node, ctx);
+            ctx.push("</blockquote>\n");
+        }
+
+        // ─── Lists ───────────────────────────────────────────────
+        ExtractedNode::List {
+            is_ordered, start, ..
+        } => {
+            if is_ordered {
+                if start > 1 {
+                    let _ = writeln!(ctx.out, "<ol start=\"{start}\">");
+                } else {
+                    ctx.push("<ol>\n");
+                }
+                emit_children(node, ctx);
+                ctx.push("</ol>\n");
+            } else {
+                ctx.push("<ul>\n");
+                emit_children(node, ctx);
+                ctx.push("</ul>\n");
+            }
+        }
+        ExtractedNode::Item { .. } => {
+            ctx.push("<li>");
+            emit_children(node, ctx);
+            ctx.push("</li>\n");
+        }
+        ExtractedNode::TaskItem { checked } => {
+            ctx.push("<li class=\"task\"><input type=\"checkbox\" disabled");
+            if checked {
+                ctx.push(" checked");
+            }
+            ctx.push("> ");
+            emit_children(node, ctx);
+            ctx.push("</li>\n");
+        }
+
+        // ─── Description list ────────────────────────────────────
+        ExtractedNode::DescriptionList => {
+            ctx.push("<dl>\n");
+            emit_children(node, ctx);
+            ctx.push("</dl>\n");
+        }
+        ExtractedNode::DescriptionTerm => {
+            ctx.push("<dt>");
+            emit_tight_children(node, ctx);
+            ctx.push("</dt>\n");
+        }
+        ExtractedNode::DescriptionDetails => {
+            ctx.push("<dd>");
+            emit_tight_children(node, ctx);
+            ctx.push("</dd>\n");
+        }
+
+        // ─── Code block ──────────────────────────────────────────
+        ExtractedNode::CodeBlock { info, literal } => {
+            let lang = info.split([' ', ',', '\t']).next().unwrap_or("");
+
+            if lang == "math" {
+                let _ = writeln!(
+                    ctx.out,
+                    "<div class=\"math display\">{}</div>",
+                    escape_html(literal.trim())
+                );
+            } else if lang == "csv"
+                && let Some(rows) = super::csv::parse_rows(&literal)
+            {
+                emit_csv_table(ctx, &rows);
+            } else if lang == "mermaid" {
+                let idx = ctx.mermaid_sources.len();
+                ctx.mermaid_sources.push(literal);
+                let _ = writeln!(
+                    ctx.out,
+                    "<figure class=\"mermaid\">{MERMAID_PLACEHOLDER}{idx}--></figure>"
+                );
+            } else {
+                emit_code_block(ctx, lang, &literal);
+            }
+        }
+
+        // ─── Raw HTML passes through unchanged ───────────────────
+        ExtractedNode::HtmlBlock { literal } => ctx.push(&literal),
+
+        // ─── Table ───────────────────────────────────────────────
+        ExtractedNode::Table { alignments, .. } => {
+            ctx.table_alignments = alignments;
+            ctx.push("<table>\n");
+            let mut body_open = false;
+            for row in node.children() {
+                let is_header = matches!(
+                    extract_node(row),
+                    ExtractedNode::TableRow { is_header: true }
+                );
+                if is_header {
+                    ctx.push("<thead>\n");
+                } else if !body_open {
+                    ctx.push("<tbody>\n");
+                    body_open = true;
+                }
+                emit_node(row, ctx);
+                if is_header {
+                    ctx.push("</thead>\n");
+                }
+            }
+            if body_open {
+                ctx.push("</tbody>\n");
+            }
+            ctx.push("</table>\n");
+            ctx.table_alignments.clear();
+        }
+        ExtractedNode::TableRow { is_header } => {
+            ctx.in_table_header = is_header;
+            ctx.table_cell_index = 0;
+            ctx.push("<tr>");
+            emit_children(node, ctx);
+            ctx.push("</tr>\n");
+        }
+        ExtractedNode::TableCell => {
+            let tag = if ctx.in_table_header { "th" } else { "td" };
+            let align = match ctx.table_alignments.get(ctx.table_cell_index) {
+                Some(TableAlignment::Left) => Some("left"),
+                Some(TableAlignment::Center) => Some("center"),
+                Some(TableAlignment::Right) => Some("right"),
+                Some(TableAlignment::None) | None => None,
+            };
+            if let Some(align) = align {
+                let _ = write!(ctx.out, "<{tag} style=\"text-align: {align}\">");
+            } else {
+                let _ = write!(ctx.out, "<{tag}>");
+            }
+            emit_children(node, ctx);
+            let _ = write!(ctx.out, "</{tag}>");
+            ctx.table_cell_index += 1;
+        }
+
+        // ─── Footnote reference ──────────────────────────────────
+        ExtractedNode::FootnoteReference { name } => {
+            if ctx.footnote_defs.contains(&name) {
+                let number = match ctx.footnote_order.iter().position(|n| n == &name) {
+                    Some(pos) => pos + 1,
+                    None => {
+                        ctx.footnote_order.push(name.clone());
+                        ctx.footnote_order.len()
+                    }
+                };
+                let id = escape_html(&name);
+                let _ = write!(
+                    ctx.out,
+                    "<sup class=\"footnote-ref\"><a href=\"#fn-{id}\" id=\"fnref-{id}\">{number}</a></sup>"
+                );
+            } else {
+                let _ = write!(ctx.out, "<sup>{}</sup>", escape_html(&name));
+                ctx.warnings.push(SilkprintWarning::FootnoteNotFound {
+                    name,
+                    location: SourceLocation::from_sourcepos(node.data.borrow().sourcepos),
+                });
+            }
+        }
+
+        // ─── Math (Typst syntax — shown as source) ───────────────
+        ExtractedNode::Math { literal, display } => {
+            let escaped = escape_html(literal.trim());
+            if display {
+                let _ = write!(ctx.out, "<div class=\"math display\">{escaped}</div>");
+            } else {
+                let _ = write!(ctx.out, "<span class=\"math\">{escaped}</span>");
+            }
+        }
+
+        // ─── Alert (GitHub-style) ────────────────────────────────
+        ExtractedNode::Alert { title, icon, color } => {
+            let kind = color.trim_end_matches("_color");
+            let _ = writeln!(ctx.out, "<div class=\"alert alert-{kind}\">");
+            let _ = writeln!(
+                ctx.out,
+                "<p class=\"alert-title\">{icon} {}</p>",
+                escape_html(&title)
+            );
+            emit_children(node, ctx);
+            ctx.push("</div>\n");
+        }
+    }
+}
+
+fn emit_children<'a>(node: &'a AstNode<'a>, ctx: &mut HtmlContext<'_>) {
+    for child in node.children() {
+        emit_node(child, ctx);
+    }
+}
+
+/// Emit children with paragraphs unwrapped (description terms and details).
+fn emit_tight_children<'a>(node: &'a AstNode<'a>, ctx: &mut HtmlContext<'_>) {
+    let prev = ctx.unwrap_paragraphs;
+    ctx.unwrap_paragraphs = true;
+    emit_children(node, ctx);
+    ctx.unwrap_paragraphs = prev;
+}
+
+/// Whether a paragraph sits directly in an item of a tight list.
+fn in_tight_list(paragraph: &AstNode<'_>) -> bool {
+    let Some(item) = paragraph.parent() else {
+        return false;
+    };
+    if !matches!(
+        extract_node(item),
+        ExtractedNode::Item { .. } | ExtractedNode::TaskItem { .. }
+    ) {
+        return false;
+    }
+    item.parent()
+        .is_some_and(|list| matches!(extract_node(list), ExtractedNode::List { tight: true, .. }))
+}
+
+fn wrap<'a>(node: &'a AstNode<'a>, ctx: &mut HtmlContext<'_>, open: &str, close: &str) {
+    ctx.push(open);
+    emit_children(node, ctx);
+    ctx.push(close);
+}
+
+fn emit_image<'a>(node: &'a AstNode<'a>, ctx: &mut HtmlContext<'_>, url: &str, title: &str) {
+    let mut alt_text = String::new();
+    collect_text(node, &mut alt_text);
+
+    let label = if !title.is_empty() {
+        title.to_string()
+    } else if !alt_text.is_empty() {
+        alt_text.clone()
+    } else {
+        url.to_string()
+    };
+    let standalone = is_standalone_image(node);
+
+    let Some(src) = image_data_uri(url, ctx.images, ctx.root_dir) else {
+        let class = if standalone {
+            "image-missing block"
+        } else {
+            "image-missing"
+        };
+        let _ = write!(
+            ctx.out,
+            "<span class=\"{class}\">[image: {}]</span>",
+            escape_html(&label)
+        );
+        return;
+    };
+
+    let alt = escape_html(if alt_text.is_empty() {
+        &label
+    } else {
+        &alt_text
+    });
+    if standalone {
+        let _ = write!(
+            ctx.out,
+            "<figure><img src=\"{src}\" alt=\"{alt}\"><figcaption>{}</figcaption></figure>",
+            escape_html(&label)
+        );
+    } else {
+        let _ = write!(
+            ctx.out,
+            "<img class=\"inline\" src=\"{src}\" alt=\"{alt}\">"
+        );
+    }
+}
+
+fn emit_code_block(ctx: &mut HtmlContext<'_>, lang: &str, literal: &str) {
+    let code = literal.strip_suffix('\n').unwrap_or(literal);
+    if lang.is_empty() {
+        ctx.push("<pre class=\"code\"><code>");
+    } else {
+        let _ = write!(
+            ctx.out,
+            "<pre class=\"code\" data-lang=\"{lang}\"><code class=\"language-{lang}\">",
+            lang = escape_html(lang)
+        );
+    }
+    highlight_code(&mut ctx.out, code, (!lang.is_empty()).then_some(lang));
+    ctx.push("</code></pre>\n");
+}
+
+/// Colour a code block with `tok-*` classes styled from the theme's `[syntax]`.
+///
+/// Tokenization reuses the terminal reader's syntect classifier, so it is only
+/// available with the `terminal` feature; without it code is emitted plain.
+#[cfg(feature = "terminal")]
+fn highlight_code(out: &mut String, code: &str, lang: Option<&str>) {
+    use super::terminal::highlight::highlight_block;
+    use super::terminal::model::{Role, SyntaxRole};
+
+    for (index, line) in highlight_block(code, lang).iter().enumerate() {
+        if index > 0 {
+            out.push('\n');
+        }
+        for span in line {
+            let text = escape_html(&span.text);
+            match span.role {
+                Role::Syntax(role) if role != SyntaxRole::Text => {
+                    let _ = write!(
+                        out,
+                        "<span class=\"tok-{}\">{text}</span>",
+                        syntax_class(role)
+                    );
+                }
+                _ => out.push_str(&text),
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "terminal"))]
+fn highlight_code(out: &mut String, code: &str, _lang: Option<&str>) {
+    out.push_str(&escape_html(code));
+}
+
+/// Class suffix for a syntax role — the same names as `TOKEN_SCOPE_MAP`.
+#[cfg(feature = "terminal")]
+fn syntax_class(role: super::terminal::model::SyntaxRole) -> &'static str {
+    use super::terminal::model::SyntaxRole;
+    match role {
+        SyntaxRole::Text => "text",
+        SyntaxRole::Keyword => "keyword",
+        SyntaxRole::String => "string",
+        SyntaxRole::Number => "number",
+        SyntaxRole::Function => "function",
+        SyntaxRole::Type => "type",
+        SyntaxRole::Comment => "comment",
+        SyntaxRole::Constant => "constant",
+        SyntaxRole::Boolean => "boolean",
+        SyntaxRole::Operator => "operator",
+        SyntaxRole::Property => "property",
+        SyntaxRole::Tag => "tag",
+        SyntaxRole::Attribute => "attribute",
+        SyntaxRole::Variable => "variable",
+        SyntaxRole::Builtin => "builtin",
+        SyntaxRole::Punctuation => "punctuation",
+        SyntaxRole::Escape => "escape",
+    }
+}
+
+fn emit_csv_table(ctx: &mut HtmlContext<'_>, rows: &[Vec<String>]) {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    if columns == 0 {
+        return;
+    }
+
+    ctx.push("<table>\n");
+    for (index, row) in rows.iter().enumerate() {
+        let tag = if index == 0 { "th" } else { "td" };
+        if index == 0 {
+            ctx.push("<thead>\n");
+        }
+        ctx.push("<tr>");
+        for idx in 0..columns {
+            let cell = row.get(idx).map_or("", String::as_str);
+            let _ = write!(ctx.out, "<{tag}>{}</{tag}>", escape_html(cell));
+        }
+        ctx.push("</tr>\n");
+        if index == 0 {
+            ctx.push("</thead>\n<tbody>\n");
+        }
+    }
+    ctx.push("</tbody>\n</table>\n");
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// Footnotes
+// ═══════════════════════════════════════════════════════════════════
+
+fn collect_footnote_nodes<'a>(root: &'a AstNode<'a>) -> HashMap<String, &'a AstNode<'a>> {
+    root.descendants()
+        .filter_map(|node| match &node.data.borrow().value {
+            NodeValue::FootnoteDefinition(def) => Some((def.name.clone(), node)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Emit the footnote section, numbered in order of first reference.
+///
+/// Definitions may themselves reference further footnotes, so the order list
+/// can grow while it is being walked.
+fn emit_footnotes<'a>(defs: &HashMap<String, &'a AstNode<'a>>, ctx: &mut HtmlContext<'_>) {
+    if ctx.footnote_order.is_empty() {
+        return;
+    }
+
+    ctx.push("<section class=\"footnotes\">\n<ol>\n");
+    let mut index = 0;
+    while let Some(name) = ctx.footnote_order.get(index).cloned() {
+        index += 1;
+        let Some(def) = defs.get(&name) else { continue };
+        let id = escape_html(&name);
+        let _ = write!(ctx.out, "<li id=\"fn-{id}\">");
+        emit_tight_children(def, ctx);
+        let _ = writeln!(
+            ctx.out,
+            " <a href=\"#fnref-{id}\" class=\"footnote-backref\">\u{21A9}</a></li>"
+        );
+    }
+    ctx.push("</ol>\n</section>\n");
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// Title block
+// ═══════════════════════════════════════════════════════════════════
+
+fn emit_title_block(out: &mut String, fm: &FrontMatter) {
+    if fm.title.is_none() && fm.subtitle.is_none() && fm.author.is_none() {
+        return;
+    }
+
+    out.push_str("<header class=\"title-block\">\n");
+    if let Some(title) = &fm.title {
+        let _ = writeln!(out, "<p class=\"title\">{}</p>", escape_html(title));
+    }
+    if let Some(subtitle) = &fm.subtitle {
+        let _ = writeln!(out, "<p class=\"subtitle\">{}</p>", escape_html(subtitle));
+    }
+    if let Some(author) = &fm.author {
+        let _ = writeln!(out, "<p class=\"author\">{}</p>", escape_html(author));
+    }
+    if let Some(date) = &fm.date {
+        let _ = writeln!(out, "<p class=\"date\">{}</p>", escape_html(&date.0));
+    }
+    out.push_str("</header>\n");
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// Assets — images and fonts as data URIs
+// ═══════════════════════════════════════════════════════════════════
+
+/// Inline an image as a `data:` URI, or `None` when it cannot be loaded.
+fn image_data_uri(src: &str, images: &PreparedImages, root_dir: Option<&Path>) -> Option<String> {
+    let (bytes, ext) = if is_remote_image(src) {
+        let Some(PreparedImage::Available { typst_path }) = images.resolve(src) else {
+            return None;
+        };
+        let bytes = images.remote_assets().get(typst_path)?.clone();
+        (bytes, extension_of(typst_path))
+    } else {
+        if matches!(images.resolve(src), Some(PreparedImage::Missing)) {
+            return None;
+        }
+        let path = match root_dir {
+            Some(dir) => resolve_image_path(src, dir)?,
+            None => Path::new(src).to_path_buf(),
+        };
+        let bytes = std::fs::read(&path).ok()?;
+        (bytes, extension_of(&path.to_string_lossy()))
+    };
+
+    let mime = match ext.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        _ => "application/octet-stream",
+    };
+    Some(format!("data:{mime};base64,{}", base64_encode(&bytes)))
+}
+
+fn extension_of(path: &str) -> String {
+    Path::new(path)
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default()
+}
+
+/// Build `@font-face` rules for the bundled fonts the theme actually uses.
+///
+/// Only families named by the theme (primary fonts and fallbacks) are
+/// inlined, so the color emoji and math fonts don't bloat every export.
+fn font_faces(t: &ThemeTokens) -> String {
+    let wanted: Vec<String> = [&t.fonts.heading, &t.fonts.body, &t.fonts.mono]
+        .into_iter()
+        .chain(&t.fonts.heading_fallback)
+        .chain(&t.fonts.body_fallback)
+        .chain(&t.fonts.mono_fallback)
+        .filter(|family| !family.is_empty())
+        .map(|family| family.to_lowercase())
+        .collect();
+
+    let mut out = String::new();
+    for data in crate::fonts::load_bundled_fonts() {
+        // `font/otf` and `font/ttf` are the registered media types.
+        let (subtype, format) = if data.starts_with(b"OTTO") {
+            ("otf", "opentype")
+        } else {
+            ("ttf", "truetype")
+        };
+        let bytes = Bytes::new(data);
+        // Collections can't be addressed by index from CSS; skip them.
+        let mut faces = Font::iter(bytes.clone());
+        let Some(font) = faces.next() else { continue };
+        if faces.next().is_some() {
+            continue;
+        }
+
+        let info = font.info();
+        if !wanted.contains(&info.family.to_lowercase()) {
+            continue;
+        }
+        let style = match info.variant.style {
+            FontStyle::Normal => "normal",
+            FontStyle::Italic => "italic",
+            FontStyle::Oblique => "oblique",
+        };
+        let _ = writeln!(
+            out,
+            "@font-face {{ font-family: \"{}\"; font-style: {style}; font-weight: {}; src: url(data:font/{subtype};base64,{}) format(\"{format}\"); }}",
+            escape_css_string(&info.family),
+            info.variant.weight.to_number(),
+            base64_encode(bytes.as_slice()),
+        );
+    }
+    out
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// Stylesheet — theme tokens as CSS custom properties
+// ═══════════════════════════════════════════════════════════════════
+
+/// Generate the stylesheet: a `:root` block of `--sp-*` custom properties
+/// resolved from the theme, followed by rules that consume them.
+///
+/// Fallbacks mirror the preamble's so an empty token looks the same in the
+/// browser as in the PDF.
+#[allow(clippy::too_many_lines)]
+pub fn stylesheet(t: &ThemeTokens) -> String {
+    let mut vars: Vec<(String, String)> = Vec::new();
+    let mut var = |name: &str, value: &str| vars.push((name.to_string(), value.to_string()));
+
+    // Page and text
+    var("page-bg", default_if_empty(&t.page.background, "#ffffff"));
+    var("text", default_if_empty(&t.text.color, "#1a1a2e"));
+    var(
+        "font-body",
+        &font_stack(
+            default_if_empty(&t.fonts.body, "Source Serif 4"),
+            &t.fonts.body_fallback,
+            "serif",
+        ),
+    );
+    var(
+        "font-heading",
+        &font_stack(
+            default_if_empty(&t.fonts.heading, "Inter"),
+            &t.fonts.heading_fallback,
+            "sans-serif",
+        ),
+    );
+    var(
+        "font-mono",
+        &font_stack(
+            default_if_empty(&t.fonts.mono, "JetBrains Mono"),
+            &t.fonts.mono_fallback,
+            "monospace",
+        ),
+    );
+    var("size-body", default_if_empty(&t.font_sizes.body, "11pt"));
+    var("size-code", default_if_empty(&t.font_sizes.code, "10pt"));
+    let line_height = if t.text.line_height > 0.0 {
+        t.text.line_height
+    } else {
+        1.5
+    };
+    var("line-height", &format!("{line_height}"));
+    var(
+        "paragraph-gap",
+        default_if_empty(&t.text.paragraph_gap, "0.85em"),
+    );
+
+    // Headings
+    let heading_color = default_if_empty(
+        &t.headings.color,
+        default_if_empty(&t.text.color, "#1a1a2e"),
+    );
+    var("heading-color", heading_color);
+    let levels = [
+        (&t.headings.h1, &t.font_sizes.h1, "26pt", 700),
+        (&t.headings.h2, &t.font_sizes.h2, "21pt", 600),
+        (&t.headings.h3, &t.font_sizes.h3, "17pt", 600),
+        (&t.headings.h4, &t.font_sizes.h4, "14pt", 500),
+        (&t.headings.h5, &t.font_sizes.h5, "12pt", 500),
+        (&t.headings.h6, &t.font_sizes.h6, "11pt", 600),
+    ];
+    for (index, (level, size, default_size, default_weight)) in levels.iter().enumerate() {
+        let n = index + 1;
+        var(&format!("h{n}-size"), default_if_empty(size, default_size));
+        var(
+            &format!("h{n}-color"),
+            default_if_empty(&level.color, heading_color),
+        );
+        let weight = if level.weight > 0 {
+            level.weight
+        } else {
+            *default_weight
+        };
+        var(&format!("h{n}-weight"), &weight.to_string());
+    }
+
+    // Code
+    var(
+        "code-bg",
+        default_if_empty(&t.code_block.background, "#f4f4f8"),
+    );
+    var(
+        "code-border",
+        default_if_empty(&t.code_block.border_color, "#e2e2e8"),
+    );
+    var(
+        "code-radius",
+        default_if_empty(&t.code_block.border_radius, "6pt"),
+    );
+    var(
+        "code-pad-x",
+        default_if_empty(&t.code_block.padding_horizontal, "14pt"),
+    );
+    var(
+        "code-pad-y",
+        default_if_empty(&t.code_block.padding_vertical, "12pt"),
+    );
+    let code_lh = if t.code_block.line_height > 0.0 {
+        t.code_block.line_height
+    } else {
+        1.45
+    };
+    var("code-line-height", &format!("{code_lh}"));
+    var(
+        "code-accent",
+        default_if_empty(&t.code_block.left_accent_color, "transparent"),
+    );
+    var(
+        "inline-code-bg",
+        default_if_empty(&t.code_inline.background, "#f4f4f8"),
+    );
+    var(
+        "inline-code-border",
+        default_if_empty(&t.code_inline.border_color, "#e2e2e8"),
+    );
+    var(
+        "inline-code-radius",
+        default_if_empty(&t.code_inline.border_radius, "3pt"),
+    );
+
+    // Links, quotes, rules
+    var("link", default_if_empty(&t.links.color, "#4a5dbd"));
+    var(
+        "quote-border",
+        default_if_empty(&t.blockquote.border_color, "#4a5dbd"),
+    );
+    var(
+        "quote-border-width",
+        default_if_empty(&t.blockquote.border_width, "2.5pt"),
+    );
+    var(
+        "quote-text",
+        default_if_empty(&t.blockquote.text_color, "#555570"),
+    );
+    var(
+        "quote-padding",
+        default_if_empty(&t.blockquote.left_padding, "14pt"),
+    );
+    var("hr", default_if_empty(&t.horizontal_rule.color, "#e2e2e8"));
+    var(
+        "hr-thickness",
+        default_if_empty(&t.horizontal_rule.thickness, "0.5pt"),
+    );
+
+    // Tables
+    var(
+        "table-header-bg",
+        default_if_empty(&t.table.header_background, "#f4f4f8"),
+    );
+    var(
+        "table-header-text",
+        default_if_empty(&t.table.header_text_color, heading_color),
+    );
+    var(
+        "table-header-border",
+        default_if_empty(&t.table.header_border_color, "#c8c8d4"),
+    );
+    var(
+        "table-header-border-width",
+        default_if_empty(&t.table.header_border_width, "1.5pt"),
+    );
+    var(
+        "table-row-border",
+        default_if_empty(&t.table.row_border_color, "#e2e2e8"),
+    );
+    var(
+        "table-row-border-width",
+        default_if_empty(&t.table.row_border_width, "0.5pt"),
+    );
+    var(
+        "table-stripe",
+        default_if_empty(&t.table.stripe_background, "transparent"),
+    );
+    var(
+        "table-padding",
+        default_if_empty(&t.table.cell_padding, "6pt 10pt"),
+    );
+    var(
+        "table-font-size",
+        default_if_empty(&t.table.font_size, "inherit"),
+    );
+
+    // Captions, lists, footnotes, highlight, math
+    var(
+        "caption",
+        default_if_empty(
+            &t.images.caption_color,
+            default_if_empty(&t.text.color, "#1a1a2e"),
+        ),
+    );
+    var(
+        "caption-size",
+        default_if_empty(&t.images.caption_size, "10pt"),
+    );
+    var(
+        "bullet",
+        default_if_empty(
+            &t.list.bullet_color,
+            default_if_empty(&t.text.color, "#1a1a2e"),
+        ),
+    );
+    var(
+        "footnote-number",
+        default_if_empty(&t.footnotes.number_color, "#4a5dbd"),
+    );
+    var(
+        "footnote-size",
+        default_if_empty(&t.footnotes.text_size, "9pt"),
+    );
+    var(
+        "footnote-separator",
+        default_if_empty(&t.footnotes.separator_color, "#e2e2e8"),
+    );
+    var("highlight", default_if_empty(&t.highlight.fill, "#fff3a3"));
+    var(
+        "highlight-text",
+        default_if_empty(&t.highlight.text_color, "inherit"),
+    );
+    var(
+        "math",
+        default_if_empty(&t.math.color, default_if_empty(&t.text.color, "#1a1a2e")),
+    );
+    var(
+        "term",
+        default_if_empty(
+            &t.description_list.term_color,
+            default_if_empty(&t.text.color, "#1a1a2e"),
+        ),
+    );
+
+    // Alerts
+    var(
+        "alert-border-width",
+        default_if_empty(&t.alerts.border_width, "3pt"),
+    );
+    for (kind, color, fallback) in [
+        ("note", &t.alerts.note_color, "#4a5dbd"),
+        ("tip", &t.alerts.tip_color, "#2e8b57"),
+        ("important", &t.alerts.important_color, "#7c3aed"),
+        ("warning", &t.alerts.warning_color, "#d97706"),
+        ("caution", &t.alerts.caution_color, "#dc2626"),
+    ] {
+        var(&format!("alert-{kind}"), default_if_empty(color, fallback));
+    }
+
+    // Syntax — every token from TOKEN_SCOPE_MAP
+    var(
+        "syntax-bg",
+        default_if_empty(
+            &t.syntax.background,
+            default_if_empty(&t.code_block.background, "#f4f4f8"),
+        ),
+    );
+    let syntax = resolve_syntax_tokens(&t.syntax, &t.colors);
+    for style in &syntax {
+        var(
+            &format!("syntax-{}", style.name),
+            default_if_empty(&style.foreground, "inherit"),
+        );
+    }
+
+    let mut css = String::with_capacity(8192);
+    css.push_str(":root {\n");
+    for (name, value) in &vars {
+        let _ = writeln!(css, "  --sp-{name}: {value};");
+    }
+    css.push_str("}\n");
+    css.push_str(BASE_RULES);
+
+    // Per-level heading rules
+    for n in 1..=6 {
+        let _ = writeln!(
+            css,
+            ".silkprint h{n} {{ font-size: var(--sp-h{n}-size); color: var(--sp-h{n}-color); font-weight: var(--sp-h{n}-weight); }}"
+        );
+    }
+    if t.headings.h1.border == Some(true) {
+        css.push_str(".silkprint h1 { border-bottom: var(--sp-hr-thickness) solid var(--sp-hr); padding-bottom: 0.2em; }\n");
+    }
+    if t.headings.h6.uppercase == Some(true) {
+        css.push_str(".silkprint h6 { text-transform: uppercase; }\n");
+    }
+
+    // Token-dependent toggles
+    if t.text.justification == "justify" {
+        css.push_str(".silkprint p { text-align: justify; }\n");
+    }
+    if t.links.underline {
+        css.push_str(".silkprint a { text-decoration: underline; }\n");
+    }
+    if t.blockquote.italic {
+        css.push_str(".silkprint blockquote { font-style: italic; }\n");
+    }
+    if t.images.caption_italic {
+        css.push_str(".silkprint figcaption { font-style: italic; }\n");
+    }
+    if t.code_block.left_accent {
+        css.push_str(".silkprint pre.code { border-left: 3pt solid var(--sp-code-accent); }\n");
+    }
+    if t.table.vertical_lines {
+        css.push_str(".silkprint th, .silkprint td { border-left: var(--sp-table-row-border-width) solid var(--sp-table-row-border); border-right: var(--sp-table-row-border-width) solid var(--sp-table-row-border); }\n");
+    }
+    for kind in ["note", "tip", "important", "warning", "caution"] {
+        let _ = writeln!(
+            css,
+            ".silkprint .alert-{kind} {{ border-left-color: var(--sp-alert-{kind}); }} .silkprint .alert-{kind} .alert-title {{ color: var(--sp-alert-{kind}); }}"
+        );
+    }
+    for style in &syntax {
+        if style.name == "text" {
+            continue;
+        }
+        let _ = write!(
+            css,
+            ".tok-{name} {{ color: var(--sp-syntax-{name});",
+            name = style.name
+        );
+        if style.bold {
+            css.push_str(" font-weight: bold;");
+        }
+        if style.italic {
+            css.push_str(" font-style: italic;");
+        }
+        css.push_str(" }\n");
+    }
+    css
+}
+
+/// Static rules shared by every theme; all colours come from custom properties.
+const BASE_RULES: &str = r"html { background: var(--sp-page-bg); }
+body { margin: 0; background: var(--sp-page-bg); color: var(--sp-text); }
+.silkprint { max-width: 46rem; margin: 0 auto; padding: 3rem 1.5rem; font-family: var(--sp-font-body); font-size: var(--sp-size-body); line-height: var(--sp-line-height); }
+.silkprint p { margin: 0 0 var(--sp-paragraph-gap); }
+.silkprint h1, .silkprint h2, .silkprint h3, .silkprint h4, .silkprint h5, .silkprint h6 { font-family: var(--sp-font-heading); line-height: 1.2; margin: 1.4em 0 0.5em; }
+.silkprint a { color: var(--sp-link); text-decoration: none; }
+.silkprint code, .silkprint pre { font-family: var(--sp-font-mono); font-size: var(--sp-size-code); font-variant-ligatures: none; }
+.silkprint :not(pre) > code { background: var(--sp-inline-code-bg); border: 0.5pt solid var(--sp-inline-code-border); border-radius: var(--sp-inline-code-radius); padding: 1.5pt 3pt; }
+.silkprint pre.code { background: var(--sp-syntax-bg); color: var(--sp-syntax-text); border: 0.5pt solid var(--sp-code-border); border-radius: var(--sp-code-radius); padding: var(--sp-code-pad-y) var(--sp-code-pad-x); line-height: var(--sp-code-line-height); overflow-x: auto; }
+.silkprint blockquote { margin: 1em 0; padding: 8pt 8pt 8pt var(--sp-quote-padding); border-left: var(--sp-quote-border-width) solid var(--sp-quote-border); color: var(--sp-quote-text); }
+.silkprint hr { border: 0; border-top: var(--sp-hr-thickness) solid var(--sp-hr); margin: 1.5em 0; }
+.silkprint ul, .silkprint ol { padding-left: 1.5em; }
+.silkprint li::marker { color: var(--sp-bullet); }
+.silkprint li.task { list-style: none; }
+.silkprint table { border-collapse: collapse; margin: 1em 0; font-size: var(--sp-table-font-size); }
+.silkprint th, .silkprint td { padding: var(--sp-table-padding); border-bottom: var(--sp-table-row-border-width) solid var(--sp-table-row-border); }
+.silkprint th { background: var(--sp-table-header-bg); color: var(--sp-table-header-text); font-family: var(--sp-font-heading); font-weight: 600; border-bottom: var(--sp-table-header-border-width) solid var(--sp-table-header-border); }
+.silkprint tbody tr:nth-child(even) { background: var(--sp-table-stripe); }
+.silkprint figure { margin: 1.5em 0; text-align: center; }
+.silkprint figure img { max-width: 100%; }
+.silkprint figcaption { color: var(--sp-caption); font-size: var(--sp-caption-size); margin-top: 0.5em; }
+.silkprint img.inline { height: 1.1em; vertical-align: middle; }
+.silkprint .image-missing { color: #787878; font-size: 0.85em; }
+.silkprint .image-missing.block { display: block; text-align: center; border: 0.5pt solid #b4b4b4; border-radius: 4pt; padding: 12pt; }
+.silkprint mark { background: var(--sp-highlight); color: var(--sp-highlight-text); }
+.silkprint .math { color: var(--sp-math); font-family: var(--sp-font-mono); }
+.silkprint .math.display { text-align: center; margin: 1em 0; }
+.silkprint .alert { border-left: var(--sp-alert-border-width) solid; border-radius: 0 4pt 4pt 0; padding: 12pt; margin: 1em 0; }
+.silkprint .alert-title { font-weight: bold; margin-bottom: 0.4em; }
+.silkprint dt { color: var(--sp-term); font-family: var(--sp-font-heading); font-weight: 600; }
+.silkprint dd { margin: 0 0 0.6em 20pt; }
+.silkprint .footnote-ref a, .silkprint .footnote-backref { color: var(--sp-footnote-number); }
+.silkprint .footnotes { border-top: 0.5pt solid var(--sp-footnote-separator); margin-top: 2em; font-size: var(--sp-footnote-size); }
+.silkprint .title-block { text-align: center; margin-bottom: 3em; }
+.silkprint .title-block .title { font-family: var(--sp-font-heading); font-size: var(--sp-h1-size); font-weight: 700; color: var(--sp-h1-color); margin: 0; }
+.silkprint .title-block .subtitle { font-size: 1.4em; }
+.silkprint .mermaid svg { max-width: 100%; height: auto; }
+";
+
+/// Return `value` if non-empty, otherwise `fallback`.
+fn or<'a>(value: &'a str, fallback: &'a str) -> &'a str {
+    if value.is_empty() { fallback } else { value }
+}
+
+fn font_stack(primary: &str, fallbacks: &[String], generic: &str) -> String {
+    let mut families = vec![format!("\"{}\"", escape_css_string(primary))];
+    families.extend(
+        fallbacks
+            .iter()
+            .filter(|family| !family.is_empty() && family.as_str() != primary)
+            .map(|family| format!("\"{}\"", escape_css_string(family))),
+    );
+    families.push(generic.to_string());
+    families.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::image::ImageMode;
+    use crate::render::markdown::parse;
+
+    fn test_theme() -> ResolvedTheme {
+        ResolvedTheme {
+            tokens: ThemeTokens::default(),
+            tmtheme_xml: String::new(),
+            fonts: Vec::new(),
+        }
+    }
+
+    fn export(markdown: &str) -> String {
+        let arena = comrak::Arena::new();
+        let root = parse(&arena, markdown);
+        let mut warnings = WarningCollector::new();
+        let images = PreparedImages::default();
+        emit_body(root, &test_theme(), &images, None, &mut warnings)
+    }
+
+    #[test]
+    fn stylesheet_exposes_theme_tokens_as_custom_properties() {
+        let mut tokens = ThemeTokens::default();
+        tokens.headings.color = "#123456".to_string();
+        tokens.links.color = "#abcdef".to_string();
+        tokens.syntax.keyword.color = "#ff00aa".to_string();
+        tokens.syntax.keyword.bold = Some(true);
+
+        let css = stylesheet(&tokens);
+        assert!(css.contains("--sp-heading-color: #123456;"));
+        assert!(css.contains("--sp-link: #abcdef;"));
+        assert!(css.contains("--sp-syntax-keyword: #ff00aa;"));
+        assert!(
+            css.contains(".tok-keyword { color: var(--sp-syntax-keyword); font-weight: bold; }")
+        );
+    }
+
+    #[test]
+    fn emits_semantic_html() {
+        let html = export("# Title\n\nSome *em* and `code`.\n\n> quoted\n");
+        assert!(html.contains("<h1 id=\"title\">Title</h1>"));
+        assert!(html.contains("<em>em</em>"));
+        assert!(html.contains("<code>code</code>"));
+        assert!(html.contains("<blockquote>"));
+    }
+
+    #[test]
+    fn headings_carry_slug_and_label_ids() {
+        let html = export("# Intro\n\n## Intro\n\n## Method {#sec:method}\n");
+        assert!(html.contains("<h1 id=\"intro\">Intro</h1>"), "got: {html}");
+        assert!(
+            html.contains("<h2 id=\"intro-1\">Intro</h2>"),
+            "got: {html}"
+        );
+        assert!(
+            html.contains("<h2 id=\"sec:method\">Method</h2>"),
+            "got: {html}"
+        );
+    }
+
+    #[test]
+    fn escapes_text_content() {
+        let html = export("a <b & c\n");
+        assert!(html.contains("a &lt;b &amp; c"));
+    }
+
+    #[test]
+    fn tables_split_header_and_body() {
+        let html = export("| A | B |\n|:--|--:|\n| 1 | 2 |\n");
+        assert!(html.contains("<thead>"));
+        assert!(html.contains("<th style=\"text-align: left\">A</th>"));
+        assert!(html.contains("<tbody>"));
+        assert!(html.contains("<td style=\"text-align: right\">2</td>"));
+    }
+
+    #[test]
+    fn footnotes_are_numbered_by_first_reference() {
+        let html = export("One[^b] two[^a].\n\n[^a]: Alpha.\n[^b]: Beta.\n");
+        assert!(html.contains("href=\"#fn-b\" id=\"fnref-b\">1</a>"));
+        assert!(html.contains("href=\"#fn-a\" id=\"fnref-a\">2</a>"));
+        assert!(html.contains("<section class=\"footnotes\">"));
+    }
+
+    #[test]
+    fn alerts_carry_their_kind() {
+        let html = export("> [!WARNING]\n> Careful.\n");
+        assert!(html.contains("class=\"alert alert-warning\""));
+    }
+
+    #[cfg(feature = "terminal")]
+    #[test]
+    fn code_blocks_are_highlighted_with_token_classes() {
+        let html = export("```rust\nfn main() {}\n```\n");
+        assert!(html.contains("data-lang=\"rust\""));
+        assert!(html.contains("<span class=\"tok-keyword\">fn</span>"));
+    }
+
+    #[test]
+    fn local_images_become_data_uris() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+        let arena = comrak::Arena::new();
+        let root = parse(&arena, "![Flow](img/tui-flow.svg)\n");
+        let mut warnings = WarningCollector::new();
+        let images = PreparedImages::prepare(root, ImageMode::Compile, Some(&dir), &mut warnings);
+        let html = emit_body(root, &test_theme(), &images, Some(&dir), &mut warnings);
+        assert!(html.contains("<img src=\"data:image/svg+xml;base64,"));
+        assert!(html.contains("<figcaption>Flow</figcaption>"));
+    }
+}
@@ -4,7 +4,7 @@ use std::path::{Path, PathBuf};
 use comrak::nodes::{AstNode, NodeValue};
 use scraper::{Html, Selector};
 
-use crate::warnings::{SilkprintWarning, WarningCollector};
+use crate::warnings::{SilkprintWarning, SourceLocation, WarningCollector};
 
 /// Virtual path prefix for downloaded remote images served through the Typst world.
 pub const REMOTE_IMAGE_VPATH_PREFIX: &str = "/__remote_image_";
@@ -40,9 +40,11 @@ impl PreparedImages {
 
         for node in root.descendants() {
             let data = node.data.borrow();
+            let location = SourceLocation::from_sourcepos(data.sourcepos);
             match &data.value {
                 NodeValue::Image(link) => prepared.prepare_source(
                     &link.url,
+                    location,
                     mode,
                     root_dir,
                     warnings,
@@ -50,14 +52,20 @@ impl PreparedImages {
                 ),
                 NodeValue::HtmlBlock(block) => prepared.prepare_html(
                     &block.literal,
+                    location.as_ref(),
+                    mode,
+                    root_dir,
+                    warnings,
+                    &mut next_remote_index,
+                ),
+                NodeValue::HtmlInline(html) if html.contains("<img") => prepared.prepare_html(
+                    html,
+                    location.as_ref(),
                     mode,
                     root_dir,
                     warnings,
                     &mut next_remote_index,
                 ),
-                NodeValue::HtmlInline(html) if html.contains("<img") => {
-                    prepared.prepare_html(html, mode, root_dir, warnings, &mut next_remote_index);
-                }
                 _ => {}
             }
         }
@@ -73,22 +81,78 @@ impl PreparedImages {
         &self.remote_assets
     }
 
+    /// Local image files referenced by the document, resolved against
+    /// `root_dir` — including missing ones, so a watcher notices them appear.
+    pub fn local_files(&self, root_dir: &Path) -> Vec<PathBuf> {
+        let mut files: Vec<_> = self
+            .images
+            .keys()
+            .filter(|src| !is_remote_image(src))
+            .map(|src| root_dir.join(src))
+            .collect();
+        files.sort();
+        files
+    }
+
+    /// Re-root images prepared against `doc_dir` onto `root_dir`.
+    ///
+    /// Book mode compiles every chapter in one Typst world rooted at the book
+    /// directory: relative paths become root-relative (`/chapter-dir/img.png`)
+    /// and remote assets get a `namespace` so chapters don't collide.
+    pub fn rebase(&mut self, doc_dir: &Path, root_dir: &Path, namespace: &str) {
+        let prefix = doc_dir.strip_prefix(root_dir).ok();
+        let mut renamed = HashMap::new();
+
+        for image in self.images.values_mut() {
+            let PreparedImage::Available { typst_path } = image else {
+                continue;
+            };
+            if let Some(index) = typst_path.strip_prefix(REMOTE_IMAGE_VPATH_PREFIX) {
+                let vpath = format!("{REMOTE_IMAGE_VPATH_PREFIX}{namespace}{index}");
+                if let Some(bytes) = self.remote_assets.remove(typst_path.as_str()) {
+                    renamed.insert(vpath.clone(), bytes);
+                }
+                *typst_path = vpath;
+            } else if let Some(prefix) = prefix
+                && Path::new(typst_path.as_str()).is_relative()
+            {
+                let joined = prefix.join(typst_path.as_str());
+                let parts: Vec<_> = joined
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect();
+                *typst_path = format!("/{}", parts.join("/"));
+            }
+        }
+
+        self.remote_assets.extend(renamed);
+    }
+
     fn prepare_html(
         &mut self,
         html: &str,
+        location: Option<&SourceLocation>,
         mode: ImageMode,
         root_dir: Option<&Path>,
         warnings: &mut WarningCollector,
         next_remote_index: &mut usize,
     ) {
         for src in collect_html_image_sources(html) {
-            self.prepare_source(&src, mode, root_dir, warnings, next_remote_index);
+            self.prepare_source(
+                &src,
+                location.cloned(),
+                mode,
+                root_dir,
+                warnings,
+                next_remote_index,
+            );
         }
     }
 
     fn prepare_source(
         &mut self,
         src: &str,
+        location: Option<SourceLocation>,
         mode: ImageMode,
         root_dir: Option<&Path>,
         warnings: &mut WarningCollector,
@@ -111,6 +175,7 @@ impl PreparedImages {
             } else {
                 warnings.push(SilkprintWarning::ImageNotFound {
                     path: src.to_string(),
+                    location,
                 });
                 PreparedImage::Missing
             }
@@ -119,6 +184,7 @@ impl PreparedImages {
             {
                 warnings.push(SilkprintWarning::ImageNotFound {
                     path: src.to_string(),
+                    location,
                 });
                 PreparedImage::Missing
             }
@@ -129,6 +195,7 @@ impl PreparedImages {
                 if path.is_absolute() && !path.exists() {
                     warnings.push(SilkprintWarning::ImageNotFound {
                         path: src.to_string(),
+                        location,
                     });
                     PreparedImage::Missing
                 } else {
@@ -369,4 +436,41 @@ mod tests {
             SilkprintWarning::RemoteImageSkipped { .. }
         ));
     }
+
+    #[test]
+    fn rebase_roots_relative_paths_and_namespaces_remote_assets() {
+        let mut images = PreparedImages::default();
+        images.images.insert(
+            "chart.svg".to_string(),
+            PreparedImage::Available {
+                typst_path: "chart.svg".to_string(),
+            },
+        );
+        images.images.insert(
+            "https://example.com/a.png".to_string(),
+            PreparedImage::Available {
+                typst_path: format!("{REMOTE_IMAGE_VPATH_PREFIX}0.png"),
+            },
+        );
+        images
+            .remote_assets
+            .insert(format!("{REMOTE_IMAGE_VPATH_PREFIX}0.png"), vec![1, 2, 3]);
+
+        images.rebase(Path::new("/book/guide"), Path::new("/book"), "c1_");
+
+        assert_eq!(
+            images.resolve("chart.svg"),
+            Some(&PreparedImage::Available {
+                typst_path: "/guide/chart.svg".to_string()
+            })
+        );
+        let remote = format!("{REMOTE_IMAGE_VPATH_PREFIX}c1_0.png");
+        assert_eq!(
+            images.resolve("https://example.com/a.png"),
+            Some(&PreparedImage::Available {
+                typst_path: remote.clone()
+            })
+        );
+        assert_eq!(images.remote_assets().get(&remote), Some(&vec![1, 2, 3]));
+    }
 }
@@ -0,0 +1,893 @@
+//! Includes — other Markdown files and code snippets spliced into a document
+//! before it is parsed.
+//!
+//! A line holding only `{{#include path}}` is replaced by that file. Outside
+//! code fences the file is read as Markdown: its front matter is dropped and
+//! its ATX (`#`) headings are pushed below the section the directive sits in,
+//! so `# Setup` included under `## Runbook` becomes `### Setup`. Inside a
+//! code fence the file is inserted verbatim. Write `\{{#include ...}}` to
+//! show a directive literally.
+//!
+//! A code fence can also take its whole body from a file:
+//!
+//! ````markdown
+//! ```rust file=src/main.rs lines=10-40
+//! ```
+//! ````
+//!
+//! Both forms accept `lines=START-END` (1-based, inclusive, either end open)
+//! and `region=NAME`, which selects the lines between `#region NAME` and
+//! `#endregion` (or mdBook's `ANCHOR: NAME` and `ANCHOR_END: NAME`) markers
+//! in any comment syntax. Directives also accept `shift=N` to set the heading
+//! shift explicitly.
+//!
+//! Paths are relative to the including file and must stay inside the project
+//! root: the nearest directory above the document holding `.git` or
+//! `.silkprint`, or the document's own directory. Links and images in an
+//! included file resolve relative to the including document.
+
+use std::borrow::Cow;
+use std::path::{Component, Path, PathBuf};
+
+use crate::warnings::{LineOrigin, SilkprintWarning, SourceLocation, WarningCollector};
+
+use super::frontmatter;
+
+/// Directories marking the root of a project, which includes cannot escape.
+const PROJECT_MARKERS: &[&str] = &[".git", ".silkprint"];
+
+const DIRECTIVE_OPEN: &str = "{{#include";
+const DIRECTIVE_CLOSE: &str = "}}";
+
+/// A Markdown body with its includes spliced in.
+#[derive(Debug, Clone, Default)]
+pub struct Expanded {
+    pub text: String,
+    /// Every file an include pointed at, in order, including ones that were
+    /// missing — so a watcher can pick them up once they appear.
+    pub dependencies: Vec<PathBuf>,
+    /// Where each run of lines in [`text`](Self::text) came from; empty when
+    /// nothing was included. Hand to [`WarningCollector::set_line_origins`].
+    pub origins: Vec<LineOrigin>,
+}
+
+/// Resolve every include in `body`, the Markdown of the file at `path`.
+///
+/// Without a `path` there is nothing to resolve against: directives are
+/// left as they are, with a warning. Failed includes are warned about and
+/// dropped.
+pub fn expand(body: &str, path: Option<&Path>, warnings: &mut WarningCollector) -> Expanded {
+    if !body.contains(DIRECTIVE_OPEN) && !body.contains("file=") {
+        return Expanded {
+            text: body.to_string(),
+            ..Expanded::default()
+        };
+    }
+
+    let dir = path
+        .and_then(Path::parent)
+        .map(|dir| {
+            if dir.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                dir
+            }
+        })
+        .and_then(|dir| dir.canonicalize().ok());
+    let Some(dir) = dir else {
+        let mut expander = Expander::new(PathBuf::new(), warnings);
+        for (idx, line) in body.lines().enumerate() {
+            let is_include = Directive::parse(line).is_some()
+                || Fence::open(line).is_some_and(|fence| fence.file_spec().is_some());
+            if is_include {
+                let site = Site {
+                    file: None,
+                    number: idx + 1,
+                    line,
+                };
+                expander.warn(site, "includes need an input file");
+            }
+        }
+        return Expanded {
+            text: body.to_string(),
+            ..Expanded::default()
+        };
+    };
+
+    let mut expander = Expander::new(project_root(&dir), warnings);
+    if let Some(canonical) = path.and_then(|path| path.canonicalize().ok()) {
+        expander.stack.push(canonical);
+    }
+    let numbered: Vec<_> = body.lines().enumerate().map(|(i, l)| (i + 1, l)).collect();
+    let lines = expander.expand(&numbered, None, &dir);
+
+    let mut text = String::with_capacity(body.len());
+    let mut origins: Vec<LineOrigin> = Vec::new();
+    for (idx, line) in lines.iter().enumerate() {
+        let body_line = idx + 1;
+        let file = line.file.map(|file| expander.files[file].clone());
+        let continues = origins.last().is_some_and(|last| {
+            last.file == file && last.line + (body_line - last.start) == line.number
+        });
+        if !continues {
+            origins.push(LineOrigin {
+                start: body_line,
+                file,
+                line: line.number,
+            });
+        }
+        text.push_str(&line.text);
+        text.push('\n');
+    }
+    if !body.ends_with('\n') {
+        text.pop();
+    }
+
+    // Nothing spliced in: every line maps to itself.
+    if let [only] = origins.as_slice()
+        && only.file.is_none()
+        && only.start == only.line
+    {
+        origins.clear();
+    }
+    Expanded {
+        text,
+        dependencies: expander.dependencies,
+        origins,
+    }
+}
+
+/// Resolve `target` against `dir` the way includes are: relative, and inside
+/// the project root `dir` belongs to. Returns the canonical path.
+pub(crate) fn resolve_in_project(target: &str, dir: &Path) -> Result<PathBuf, &'static str> {
+    if Path::new(target).is_absolute() {
+        return Err("absolute paths are not allowed");
+    }
+    let dir = dir.canonicalize().map_err(|_| "file not found")?;
+    let canonical = dir
+        .join(target)
+        .canonicalize()
+        .map_err(|_| "file not found")?;
+    if canonical.starts_with(project_root(&dir)) {
+        Ok(canonical)
+    } else {
+        Err("path escapes the project root")
+    }
+}
+
+/// The nearest directory at or above `dir` holding a [`PROJECT_MARKERS`]
+/// entry, or `dir` itself.
+fn project_root(dir: &Path) -> PathBuf {
+    dir.ancestors()
+        .find(|ancestor| {
+            PROJECT_MARKERS
+                .iter()
+                .any(|marker| ancestor.join(marker).exists())
+        })
+        .unwrap_or(dir)
+        .to_path_buf()
+}
+
+/// The line a directive or include fence sits on, for warnings.
+#[derive(Clone, Copy)]
+struct Site<'l> {
+    /// Index into [`Expander::files`]; `None` for the document itself.
+    file: Option<usize>,
+    number: usize,
+    line: &'l str,
+}
+
+/// One output line and where it came from.
+struct Line {
+    text: String,
+    /// Index into [`Expander::files`]; `None` for the document itself.
+    file: Option<usize>,
+    /// 1-based line number in that file.
+    number: usize,
+}
+
+struct Expander<'w> {
+    /// Canonical project root every include must stay under.
+    root: PathBuf,
+    /// Canonical paths of the files being expanded, outermost first.
+    stack: Vec<PathBuf>,
+    /// Files lines came from, indexed by [`Line::file`].
+    files: Vec<PathBuf>,
+    dependencies: Vec<PathBuf>,
+    warnings: &'w mut WarningCollector,
+}
+
+impl<'w> Expander<'w> {
+    fn new(root: PathBuf, warnings: &'w mut WarningCollector) -> Self {
+        Self {
+            root,
+            stack: Vec::new(),
+            files: Vec::new(),
+            dependencies: Vec::new(),
+            warnings,
+        }
+    }
+
+    /// Expand `lines` of `file` (numbered in that file), resolving paths
+    /// against `dir`.
+    fn expand(&mut self, lines: &[(usize, &str)], file: Option<usize>, dir: &Path) -> Vec<Line> {
+        let mut out = Vec::with_capacity(lines.len());
+        let keep = |out: &mut Vec<Line>, number: usize, text: &str| {
+            out.push(Line {
+                text: text.to_string(),
+                file,
+                number,
+            });
+        };
+        let mut fence: Option<Fence> = None;
+        // Level of the section we are in, and whether the previous line
+        // could be the text of a setext heading.
+        let mut level = 0;
+        let mut in_paragraph = false;
+        let mut idx = 0;
+
+        while idx < lines.len() {
+            let (number, line) = lines[idx];
+            let site = Site { file, number, line };
+            idx += 1;
+
+            if let Some(open) = &fence {
+                if open.closed_by(line) {
+                    fence = None;
+                } else if let Some(directive) = Directive::parse(line) {
+                    if let Some((target, code)) = self.select_file(&directive.spec, site, dir) {
+                        out.extend(code.into_iter().map(|(number, text)| Line {
+                            text,
+                            file: Some(target),
+                            number,
+                        }));
+                    }
+                    continue;
+                }
+                keep(&mut out, number, &unescape(line));
+                continue;
+            }
+
+            if let Some(open) = Fence::open(line) {
+                in_paragraph = false;
+                let Some(spec) = open.file_spec() else {
+                    fence = Some(open);
+                    keep(&mut out, number, line);
+                    continue;
+                };
+                // The fence's own body is replaced, up to its closing line.
+                let body_end = lines[idx..]
+                    .iter()
+                    .position(|(_, l)| open.closed_by(l))
+                    .map_or(lines.len(), |pos| idx + pos);
+                if let Some((target, code)) = self.select_file(&spec, site, dir) {
+                    let marker = open.marker_for(&code);
+                    keep(&mut out, number, &open.rewritten(&marker));
+                    out.extend(code.into_iter().map(|(number, text)| Line {
+                        text,
+                        file: Some(target),
+                        number,
+                    }));
+                    let closing = lines.get(body_end).map_or(number, |(n, _)| *n);
+                    keep(&mut out, closing, &format!("{}{marker}", open.indent));
+                } else {
+                    for &(n, l) in &lines[idx - 1..lines.len().min(body_end + 1)] {
+                        keep(&mut out, n, l);
+                    }
+                }
+                idx = body_end + 1;
+                continue;
+            }
+
+            if let Some(directive) = Directive::parse(line) {
+                let shift = directive.shift.unwrap_or(level);
+                let included = self.include_markdown(&directive.spec, site, dir);
+                out.extend(shift_headings(included, shift));
+                in_paragraph = false;
+                continue;
+            }
+
+            if let Some(heading) = atx_level(line) {
+                level = heading;
+                in_paragraph = false;
+            } else if in_paragraph && let Some(heading) = setext_level(line) {
+                level = heading;
+                in_paragraph = false;
+            } else {
+                in_paragraph = !line.trim().is_empty();
+            }
+            keep(&mut out, number, &unescape(line));
+        }
+        out
+    }
+
+    /// An included Markdown file, expanded in turn.
+    fn include_markdown(&mut self, spec: &Spec<'_>, site: Site<'_>, dir: &Path) -> Vec<Line> {
+        let Some(target) = self.resolve(spec.path, site, dir) else {
+            return Vec::new();
+        };
+        if let Some(pos) = self.stack.iter().position(|open| *open == target) {
+            let chain = self.stack[pos..]
+                .iter()
+                .chain(std::iter::once(&target))
+                .map(|path| self.display(path))
+                .collect::<Vec<_>>()
+                .join(" → ");
+            self.warn(site, &format!("include cycle: {chain}"));
+            return Vec::new();
+        }
+        let Some(text) = self.read(&target, site) else {
+            return Vec::new();
+        };
+
+        // Front matter belongs to the included file, not the document.
+        let (body, offset) = match frontmatter::extract(&text) {
+            Ok((_, body)) => {
+                let offset = frontmatter::body_line_offset(&text, &body);
+                (Cow::Owned(body), offset)
+            }
+            Err(_) => (Cow::Borrowed(text.as_str()), 0),
+        };
+        let numbered: Vec<_> = body
+            .lines()
+            .enumerate()
+            .map(|(i, l)| (i + 1 + offset, l))
+            .collect();
+        let Some(selected) = self.select(&numbered, spec, site) else {
+            return Vec::new();
+        };
+
+        let index = self.file_index(&target);
+        let target_dir = target.parent().unwrap_or(&self.root).to_path_buf();
+        self.stack.push(target);
+        let lines = self.expand(&selected, Some(index), &target_dir);
+        self.stack.pop();
+        lines
+    }
+
+    /// Read the file `spec` names and pick out the lines it asks for,
+    /// dedented, as code.
+    fn select_file(
+        &mut self,
+        spec: &Spec<'_>,
+        site: Site<'_>,
+        dir: &Path,
+    ) -> Option<(usize, Vec<(usize, String)>)> {
+        let target = self.resolve(spec.path, site, dir)?;
+        let text = self.read(&target, site)?;
+        let numbered: Vec<_> = text.lines().enumerate().map(|(i, l)| (i + 1, l)).collect();
+        let mut code: Vec<_> = self
+            .select(&numbered, spec, site)?
+            .into_iter()
+            .map(|(n, l)| (n, l.to_string()))
+            .collect();
+        dedent(&mut code);
+        Some((self.file_index(&target), code))
+    }
+
+    /// Apply `spec`'s `lines=` or `region=` to `lines`.
+    fn select<'a>(
+        &mut self,
+        lines: &[(usize, &'a str)],
+        spec: &Spec<'_>,
+        site: Site<'_>,
+    ) -> Option<Vec<(usize, &'a str)>> {
+        let selected = match (spec.lines, spec.region) {
+            (Some(range), _) => parse_range(range)
+                .ok_or_else(|| format!("invalid line range '{range}'"))
+                .and_then(|range| select_lines(lines, range)),
+            (None, Some(region)) => select_region(lines, region),
+            (None, None) => Ok(lines.to_vec()),
+        };
+        selected.map_err(|message| self.warn(site, &message)).ok()
+    }
+
+    /// Resolve `target` against `dir`, keeping it inside the project root.
+    fn resolve(&mut self, target: &str, site: Site<'_>, dir: &Path) -> Option<PathBuf> {
+        if Path::new(target).is_absolute() {
+            self.warn(site, "absolute include paths are not allowed");
+            return None;
+        }
+        let joined = dir.join(target);
+        let Ok(canonical) = joined.canonicalize() else {
+            self.dependencies.push(normalize(&joined));
+            self.warn(site, "file not found");
+            return None;
+        };
+        if !canonical.starts_with(&self.root) {
+            self.warn(site, "path escapes the project root");
+            return None;
+        }
+        if !self.dependencies.contains(&canonical) {
+            self.dependencies.push(canonical.clone());
+        }
+        Some(canonical)
+    }
+
+    fn read(&mut self, target: &Path, site: Site<'_>) -> Option<String> {
+        std::fs::read_to_string(target)
+            .map_err(|e| self.warn(site, &e.to_string()))
+            .ok()
+    }
+
+    fn file_index(&mut self, path: &Path) -> usize {
+        if let Some(index) = self.files.iter().position(|known| known == path) {
+            return index;
+        }
+        self.files.push(path.to_path_buf());
+        self.files.len() - 1
+    }
+
+    /// `path` relative to the project root, for messages.
+    fn display(&self, path: &Path) -> String {
+        path.strip_prefix(&self.root)
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    }
+
+    fn warn(&mut self, site: Site<'_>, message: &str) {
+        let target = Directive::parse(site.line)
+            .map(|directive| directive.spec.path)
+            .or_else(|| Fence::open(site.line).and_then(|f| f.file_spec().map(|s| s.path)))
+            .unwrap_or(site.line.trim());
+        self.warnings.push(SilkprintWarning::IncludeFailed {
+            target: target.to_string(),
+            message: message.to_string(),
+            location: Some(SourceLocation {
+                file: site.file.map(|file| self.files[file].clone()),
+                line: site.number,
+                column: 1,
+                end_line: site.number,
+                end_column: site.line.len().max(1),
+            }),
+        });
+    }
+}
+
+/// What to include and which part of it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Spec<'a> {
+    path: &'a str,
+    /// `START-END`, 1-based and inclusive; either end may be left out.
+    lines: Option<&'a str>,
+    region: Option<&'a str>,
+}
+
+/// A `{{#include path key=value...}}` line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Directive<'a> {
+    spec: Spec<'a>,
+    shift: Option<usize>,
+}
+
+impl<'a> Directive<'a> {
+    fn parse(line: &'a str) -> Option<Self> {
+        let inner = line
+            .trim()
+            .strip_prefix(DIRECTIVE_OPEN)?
+            .strip_suffix(DIRECTIVE_CLOSE)?;
+        if !inner.starts_with(char::is_whitespace) {
+            return None;
+        }
+        let mut words = inner.split_whitespace();
+        let mut directive = Self {
+            spec: Spec {
+                path: words.next()?,
+                lines: None,
+                region: None,
+            },
+            shift: None,
+        };
+        for (key, value) in words.filter_map(|word| word.split_once('=')) {
+            match key {
+                "shift" => directive.shift = value.parse().ok(),
+                _ => directive.spec.set(key, value),
+            }
+        }
+        Some(directive)
+    }
+}
+
+impl<'a> Spec<'a> {
+    fn set(&mut self, key: &str, value: &'a str) {
+        match key {
+            "file" => self.path = value,
+            "lines" => self.lines = Some(value),
+            "region" => self.region = Some(value),
+            _ => {}
+        }
+    }
+}
+
+/// `10-40`, `10-`, `-40`, or `10`.
+fn parse_range(value: &str) -> Option<(Option<usize>, Option<usize>)> {
+    let bound = |s: &str| -> Option<Option<usize>> {
+        if s.is_empty() {
+            Some(None)
+        } else {
+            s.parse().ok().filter(|n| *n > 0).map(Some)
+        }
+    };
+    match value.split_once('-') {
+        Some((start, end)) => Some((bound(start)?, bound(end)?)),
+        None => {
+            let line = bound(value)?;
+            line.map(|_| (line, line))
+        }
+    }
+}
+
+fn select_lines<'a>(
+    lines: &[(usize, &'a str)],
+    (start, end): (Option<usize>, Option<usize>),
+) -> Result<Vec<(usize, &'a str)>, String> {
+    let start = start.unwrap_or(1);
+    let end = end.unwrap_or(usize::MAX);
+    if start > end {
+        return Err(format!("empty line range {start}-{end}"));
+    }
+    let last = lines.last().map_or(0, |(n, _)| *n);
+    if start > last {
+        return Err(format!(
+            "line {start} is past the end of the file ({last} lines)"
+        ));
+    }
+    Ok(lines
+        .iter()
+        .filter(|(n, _)| (start..=end).contains(n))
+        .copied()
+        .collect())
+}
+
+/// The lines between `name`'s region markers, with every marker line dropped.
+fn select_region<'a>(
+    lines: &[(usize, &'a str)],
+    name: &str,
+) -> Result<Vec<(usize, &'a str)>, String> {
+    let mut inside = false;
+    let mut found = false;
+    let mut selected = Vec::new();
+    for &(number, line) in lines {
+        match Marker::parse(line) {
+            Some(Marker::Start(marker)) if marker == name => {
+                inside = true;
+                found = true;
+            }
+            Some(Marker::End(marker)) if inside && marker.is_none_or(|m| m == name) => {
+                inside = false;
+            }
+            Some(_) => {}
+            None if inside => selected.push((number, line)),
+            None => {}
+        }
+    }
+    if found {
+        Ok(selected)
+    } else {
+        Err(format!("region '{name}' not found"))
+    }
+}
+
+/// A region marker comment: `// #region name`, `# #endregion`,
+/// `// ANCHOR: name`, `// ANCHOR_END: name`.
+enum Marker<'a> {
+    Start(&'a str),
+    End(Option<&'a str>),
+}
+
+impl<'a> Marker<'a> {
+    fn parse(line: &'a str) -> Option<Self> {
+        let name = |rest: &'a str| {
+            let rest = rest.trim_start_matches(':').trim();
+            let rest = rest.trim_end_matches("-->").trim_end_matches("*/").trim();
+            rest.split_whitespace().next()
+        };
+        if let Some((_, rest)) = line.split_once("#endregion") {
+            return Some(Self::End(name(rest)));
+        }
+        if let Some((_, rest)) = line.split_once("#region") {
+            return name(rest).map(Self::Start);
+        }
+        if let Some((_, rest)) = line.split_once("ANCHOR_END:") {
+            return Some(Self::End(name(rest)));
+        }
+        if let Some((_, rest)) = line.split_once("ANCHOR:") {
+            return name(rest).map(Self::Start);
+        }
+        None
+    }
+}
+
+/// An open code fence.
+struct Fence<'a> {
+    indent: &'a str,
+    marker: char,
+    len: usize,
+    info: &'a str,
+}
+
+impl<'a> Fence<'a> {
+    fn open(line: &'a str) -> Option<Self> {
+        let rest = line.trim_start();
+        let indent = &line[..line.len() - rest.len()];
+        if indent.len() > 3 {
+            return None;
+        }
+        let marker = rest.chars().next().filter(|c| matches!(*c, '`' | '~'))?;
+        let len = rest.chars().take_while(|c| *c == marker).count();
+        let info = rest[len..].trim();
+        (len >= 3 && !(marker == '`' && info.contains('`'))).then_some(Self {
+            indent,
+            marker,
+            len,
+            info,
+        })
+    }
+
+    fn closed_by(&self, line: &str) -> bool {
+        let rest = line.trim_start();
+        let len = rest.chars().take_while(|c| *c == self.marker).count();
+        line.len() - rest.len() <= 3 && len >= self.len && rest[len..].trim().is_empty()
+    }
+
+    /// The include this fence's info string asks for, if any.
+    fn file_spec(&self) -> Option<Spec<'a>> {
+        let mut spec = Spec {
+            path: "",
+            lines: None,
+            region: None,
+        };
+        for (key, value) in self
+            .info
+            .split_whitespace()
+            .filter_map(|word| word.split_once('='))
+        {
+            spec.set(key, value);
+        }
+        (!spec.path.is_empty()).then_some(spec)
+    }
+
+    /// A fence marker long enough that no line of `code` closes it early.
+    fn marker_for(&self, code: &[(usize, String)]) -> String {
+        let longest = code
+            .iter()
+            .map(|(_, line)| {
+                line.trim_start()
+                    .chars()
+                    .take_while(|c| *c == self.marker)
+                    .count()
+            })
+            .max()
+            .unwrap_or(0);
+        self.marker.to_string().repeat(self.len.max(longest + 1))
+    }
+
+    /// The opening line with `marker` and without the include attributes.
+    fn rewritten(&self, marker: &str) -> String {
+        let info: Vec<_> = self
+            .info
+            .split_whitespace()
+            .filter(|word| {
+                !matches!(
+                    word.split_once('=').map(|(key, _)| key),
+                    Some("file" | "lines" | "region")
+                )
+            })
+            .collect();
+        format!("{}{marker}{}", self.indent, info.join(" "))
+    }
+}
+
+/// Drop a leading backslash from an escaped directive.
+fn unescape(line: &str) -> Cow<'_, str> {
+    let rest = line.trim_start();
+    match rest.strip_prefix('\\') {
+        Some(directive) if Directive::parse(directive).is_some() => {
+            format!("{}{directive}", &line[..line.len() - rest.len()]).into()
+        }
+        _ => line.into(),
+    }
+}
+
+/// Remove the indentation every non-blank line shares.
+fn dedent(lines: &mut [(usize, String)]) {
+    let indent = lines
+        .iter()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(_, line)| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    for (_, line) in lines {
+        *line = match line.get(indent..) {
+            Some(rest) => rest.to_string(),
+            None => line.trim_start().to_string(),
+        };
+    }
+}
+
+/// Push every ATX heading outside code fences `shift` levels deeper,
+/// stopping at level 6.
+fn shift_headings(mut lines: Vec<Line>, shift: usize) -> Vec<Line> {
+    if shift == 0 {
+        return lines;
+    }
+    let mut fence: Option<(char, usize)> = None;
+    for line in &mut lines {
+        if let Some((marker, len)) = fence {
+            let open = Fence {
+                indent: "",
+                marker,
+                len,
+                info: "",
+            };
+            if open.closed_by(&line.text) {
+                fence = None;
+            }
+            continue;
+        }
+        if let Some(open) = Fence::open(&line.text) {
+            fence = Some((open.marker, open.len));
+            continue;
+        }
+        if let Some(level) = atx_level(&line.text) {
+            let added = (level + shift).min(6) - level;
+            line.text = format!("{}{}", "#".repeat(added), line.text.trim_start());
+        }
+    }
+    lines
+}
+
+fn atx_level(line: &str) -> Option<usize> {
+    let rest = line.trim_start();
+    if line.len() - rest.len() > 3 {
+        return None;
+    }
+    let level = rest.chars().take_while(|c| *c == '#').count();
+    let after = &rest[level..];
+    ((1..=6).contains(&level) && (after.is_empty() || after.starts_with([' ', '\t'])))
+        .then_some(level)
+}
+
+fn setext_level(line: &str) -> Option<usize> {
+    let rest = line.trim();
+    if rest.is_empty() || line.len() - line.trim_start().len() > 3 {
+        return None;
+    }
+    if rest.chars().all(|c| c == '=') {
+        Some(1)
+    } else if rest.chars().all(|c| c == '-') {
+        Some(2)
+    } else {
+        None
+    }
+}
+
+/// Lexically resolve `.` and `..` components without touching the filesystem.
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !out.pop() {
+                    out.push("..");
+                }
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+
+    fn write(path: &Path, content: &str) {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).expect("should create dirs");
+        }
+        std::fs::write(path, content).expect("should write file");
+    }
+
+    #[test]
+    fn markdown_include_shifts_headings_and_strips_front_matter() {
+        let dir = tempdir().expect("should create tempdir");
+        std::fs::create_dir(dir.path().join(".git")).expect("should mark project");
+        write(
+            &dir.path().join("common/setup.md"),
+            "---\ntitle: Setup\n---\n# Setup\n\nInstall it.\n\n```md\n# not a heading\n```\n",
+        );
+        let doc = dir.path().join("runbooks/deploy.md");
+        write(
+            &doc,
+            "# Deploy\n\n## Before\n\n{{#include ../common/setup.md}}\n\nDone.\n",
+        );
+
+        let mut warnings = WarningCollector::new();
+        let body = std::fs::read_to_string(&doc).expect("should read");
+        let expanded = expand(&body, Some(&doc), &mut warnings);
+
+        assert!(warnings.is_empty(), "{:?}", warnings.warnings());
+        assert!(expanded.text.contains("\n### Setup\n\nInstall it.\n"));
+        assert!(expanded.text.contains("# not a heading"));
+        assert!(!expanded.text.contains("title: Setup"));
+        assert!(expanded.text.ends_with("\nDone.\n"));
+        let setup = dir
+            .path()
+            .join("common/setup.md")
+            .canonicalize()
+            .expect("exists");
+        assert_eq!(expanded.dependencies, vec![setup.clone()]);
+        assert_eq!(
+            expanded.origins[1],
+            LineOrigin {
+                start: 5,
+                file: Some(setup),
+                line: 4,
+            }
+        );
+        assert_eq!(expanded.origins[2].file, None);
+        assert_eq!(expanded.origins[2].line, 6);
+    }
+
+    #[test]
+    fn code_fence_takes_lines_and_regions_from_a_file() {
+        let dir = tempdir().expect("should create tempdir");
+        write(
+            &dir.path().join("src/main.rs"),
+            "use std::io;\n\nfn main() {\n    // #region greet\n    println!(\"hi\");\n    // #endregion\n}\n",
+        );
+        let doc = dir.path().join("doc.md");
+        let body = "```rust file=src/main.rs lines=1-1\n```\n\n```rust file=src/main.rs region=greet\nstale\n```\n";
+
+        let mut warnings = WarningCollector::new();
+        let expanded = expand(body, Some(&doc), &mut warnings);
+
+        assert!(warnings.is_empty(), "{:?}", warnings.warnings());
+        let blocks: Vec<_> = expanded.text.split("\n\n").collect();
+        assert!(
+            blocks[0].starts_with("```rust\nuse std::io;\n```"),
+            "{}",
+            blocks[0]
+        );
+        assert!(
+            blocks[1].starts_with("```rust\nprintln!(\"hi\");\n```"),
+            "{}",
+            blocks[1]
+        );
+        assert!(!expanded.text.contains("stale"));
+    }
+
+    #[test]
+    fn cycles_escapes_and_missing_files_are_warned_about() {
+        let dir = tempdir().expect("should create tempdir");
+        let project = dir.path().join("project");
+        std::fs::create_dir_all(project.join(".silkprint")).expect("should mark project");
+        write(&dir.path().join("secret.md"), "secret\n");
+        write(&project.join("a.md"), "A\n\n{{#include b.md}}\n");
+        write(&project.join("b.md"), "B\n\n{{#include a.md}}\n");
+        let doc = project.join("a.md");
+        let body = "A\n\n{{#include b.md}}\n\n{{#include ../secret.md}}\n\n{{#include gone.md}}\n\n\\{{#include b.md}}\n";
+
+        let mut warnings = WarningCollector::new();
+        let expanded = expand(body, Some(&doc), &mut warnings);
+
+        let messages: Vec<_> = warnings
+            .warnings()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(messages.len(), 3, "{messages:?}");
+        assert!(messages[0].contains("include cycle: a.md → b.md → a.md"));
+        assert!(messages[1].contains("escapes the project root"));
+        assert!(messages[2].contains("not found"));
+        assert!(!expanded.text.contains("secret"));
+        assert!(expanded.text.contains("B\n"));
+        assert!(expanded.text.ends_with("\n{{#include b.md}}\n"));
+        assert!(expanded.dependencies.contains(&project.join("gone.md")));
+    }
+}
@@ -0,0 +1,466 @@
+//! Page layout: the page's columns, directives that change the column count
+//! or turn pages sideways for part of a document, and wide tables.
+//!
+//! The theme's `[page] columns` and `column_gap` — or front matter
+//! `columns:` and `column-gap:` — set the page's columns. In the body,
+//!
+//! ```text
+//! <!-- columns: 1 -->     from here to the next switch
+//! <!-- landscape -->      ... and back with <!-- portrait -->
+//!
+//! ::: columns 2           until the closing `:::`
+//! :::
+//! ::: landscape
+//! :::
+//! ```
+//!
+//! change the layout for a run of top-level blocks; the `:::` lines need
+//! blank lines around them. A run with fewer columns than the page (a
+//! full-width title and abstract over a two-column page) floats to the top
+//! of its page across all columns; any other count is laid out in a
+//! `#columns` block. Those runs are containers, so headings inside them
+//! can't break the page. Landscape runs start and end on a new page.
+//!
+//! Tables with more columns than the theme's `[table] wide_columns` are
+//! wide, and get its `wide` treatment. Putting a top-level wide table on a
+//! landscape page happens here; shrinking and breaking one, or a nested
+//! one, is up to the emitter.
+
+use std::fmt::Write;
+
+use comrak::nodes::{AstNode, NodeValue};
+
+use crate::theme::ResolvedTheme;
+use crate::theme::tokens::TableTokens;
+
+use super::crossref;
+use super::escape::is_typst_length;
+use super::frontmatter::FrontMatter;
+
+/// Default space between columns when neither theme nor front matter set one.
+const DEFAULT_GUTTER: &str = "4%";
+
+/// Column count past which a table is wide when the theme doesn't say.
+const DEFAULT_WIDE_COLUMNS: u16 = 6;
+
+/// Smallest fraction of its size a shrunk table's text goes down to.
+const MIN_SHRINK: f64 = 0.6;
+
+/// The page's column count and gutter, and how wide tables are laid out.
+#[derive(Debug, Clone)]
+pub struct Layout {
+    pub columns: u8,
+    pub gutter: String,
+    pub wide_tables: WideTables,
+}
+
+impl Layout {
+    /// Front matter's columns over the theme's. Zero counts as one, and a
+    /// gap that isn't a Typst length falls back to the default.
+    pub fn resolve(theme: &ResolvedTheme, front_matter: Option<&FrontMatter>) -> Self {
+        let page = &theme.tokens.page;
+        let columns = front_matter
+            .and_then(|fm| fm.columns)
+            .unwrap_or(page.columns)
+            .max(1);
+        let gutter = front_matter
+            .and_then(|fm| fm.column_gap.as_deref())
+            .filter(|gap| !gap.is_empty())
+            .unwrap_or(&page.column_gap);
+        let gutter = if is_typst_length(gutter) {
+            gutter
+        } else {
+            DEFAULT_GUTTER
+        };
+        Self {
+            columns,
+            gutter: gutter.to_string(),
+            wide_tables: WideTables::from_theme(&theme.tokens.table),
+        }
+    }
+
+    /// Typst that opens and closes a run of `count` columns, or `None` when
+    /// the page already has that many.
+    fn run(&self, count: u8) -> Option<(String, String)> {
+        let count = count.max(1);
+        if count == self.columns {
+            return None;
+        }
+        let mut open = String::from("\n");
+        let mut close = String::from("\n");
+        if count < self.columns {
+            open.push_str("#place(top, scope: \"parent\", float: true, clearance: 1.5em)[\n");
+            close.push_str("]\n");
+        }
+        if count > 1 {
+            let _ = writeln!(open, "#columns({count}, gutter: {})[", self.gutter);
+            close.push_str("]\n");
+        }
+        Some((open, close))
+    }
+
+    /// Typst that ends the `from` layout and starts the `to` one. Pages
+    /// can't sit inside a column run, so turning the page closes the run
+    /// and opens it again on the other side.
+    fn transition(&self, from: State, to: State) -> String {
+        let mut raw = String::new();
+        if from == to {
+            return raw;
+        }
+        if let Some((_, close)) = self.run(from.columns) {
+            raw.push_str(&close);
+        }
+        match (from.landscape, to.landscape) {
+            (true, false) => raw.push_str("\n]\n"),
+            (false, true) => raw.push_str("\n#page(flipped: true)[\n"),
+            _ => {}
+        }
+        if let Some((open, _)) = self.run(to.columns) {
+            raw.push_str(&open);
+        }
+        raw
+    }
+}
+
+/// What a theme does with a wide table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WideTable {
+    /// Scale its text down, more the wider it is.
+    Shrink,
+    /// Give it a landscape page of its own.
+    Landscape,
+    /// Let it break across pages, repeating the header row.
+    Break,
+    /// Leave it alone.
+    Off,
+}
+
+/// The theme's `[table] wide_columns` and `wide`.
+#[derive(Debug, Clone)]
+pub struct WideTables {
+    threshold: u16,
+    mode: WideTable,
+    font_size: String,
+}
+
+impl WideTables {
+    pub fn from_theme(tokens: &TableTokens) -> Self {
+        let threshold = match tokens.wide_columns {
+            0 => DEFAULT_WIDE_COLUMNS,
+            n => n,
+        };
+        let mode = match tokens.wide.as_str() {
+            "landscape" => WideTable::Landscape,
+            "break" => WideTable::Break,
+            "none" | "off" => WideTable::Off,
+            _ => WideTable::Shrink,
+        };
+        Self {
+            threshold,
+            mode,
+            font_size: tokens.font_size.clone(),
+        }
+    }
+
+    /// The treatment a table of `columns` columns gets, if it is wide.
+    pub fn treatment(&self, columns: usize) -> Option<WideTable> {
+        (self.mode != WideTable::Off && columns > usize::from(self.threshold)).then_some(self.mode)
+    }
+
+    /// Text size for a shrunk table of `columns` columns: the table size
+    /// scaled by how far it runs past the threshold.
+    pub fn shrunk_size(&self, columns: usize) -> String {
+        let base = if self.font_size.is_empty() {
+            "1em"
+        } else {
+            &self.font_size
+        };
+        let columns = u32::try_from(columns.max(1)).unwrap_or(u32::MAX);
+        let scale = (f64::from(self.threshold) / f64::from(columns)).clamp(MIN_SHRINK, 1.0);
+        format!("{base} * {scale:.2}")
+    }
+}
+
+/// Where the layout stands between two top-level blocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct State {
+    columns: u8,
+    landscape: bool,
+}
+
+enum Change {
+    Columns(u8),
+    Landscape(bool),
+}
+
+impl Change {
+    fn apply(self, state: State) -> State {
+        match self {
+            Change::Columns(columns) => State {
+                columns: columns.max(1),
+                ..state
+            },
+            Change::Landscape(landscape) => State { landscape, ..state },
+        }
+    }
+}
+
+enum Directive {
+    /// `<!-- columns: N -->`, `<!-- landscape -->`, `<!-- portrait -->`
+    Switch(Change),
+    /// `::: columns N`, `::: landscape`
+    Open(Change),
+    /// `:::`
+    Close,
+}
+
+/// Replace layout directives among `root`'s top-level blocks with the
+/// Typst that starts and ends each run, and turn the page for wide tables
+/// when the theme asks for it.
+pub fn lower_layout_directives<'a>(
+    arena: &'a comrak::Arena<'a>,
+    root: &'a AstNode<'a>,
+    layout: &Layout,
+) {
+    let page = State {
+        columns: layout.columns,
+        landscape: false,
+    };
+    let mut current = page;
+    // Layouts to return to as fenced runs close.
+    let mut enclosing = Vec::new();
+    let raw = move |text: String| arena.alloc(NodeValue::Raw(text).into());
+
+    let blocks: Vec<_> = root.children().collect();
+    for node in blocks {
+        if !current.landscape
+            && table_columns(node).and_then(|columns| layout.wide_tables.treatment(columns))
+                == Some(WideTable::Landscape)
+        {
+            // The caption paragraph has to stay next to its table.
+            let last = crossref::table_caption(node).map_or(node, |(caption, _)| caption);
+            let (open, close) = layout.run(current.columns).unwrap_or_default();
+            node.insert_before(raw(format!("{close}\n#page(flipped: true, columns: 1)[\n")));
+            last.insert_after(raw(format!("\n]\n{open}")));
+            continue;
+        }
+
+        let Some(directive) = directive(node) else {
+            continue;
+        };
+        let next = match directive {
+            Directive::Switch(change) => change.apply(current),
+            Directive::Open(change) => {
+                enclosing.push(current);
+                change.apply(current)
+            }
+            Directive::Close => match enclosing.pop() {
+                Some(state) => state,
+                None => continue,
+            },
+        };
+
+        let text = layout.transition(current, next);
+        if !text.is_empty() {
+            node.insert_before(raw(text));
+        }
+        node.detach();
+        current = next;
+    }
+
+    let text = layout.transition(current, page);
+    if !text.is_empty() {
+        root.append(raw(text));
+    }
+}
+
+/// The column count of a table or ```` ```csv ```` block.
+fn table_columns(node: &AstNode<'_>) -> Option<usize> {
+    match &node.data.borrow().value {
+        NodeValue::Table(table) => Some(table.num_columns),
+        NodeValue::CodeBlock(block) if block.info.split([' ', ',', '\t']).next() == Some("csv") => {
+            let rows = super::csv::parse_rows(&block.literal)?;
+            rows.iter().map(Vec::len).max()
+        }
+        _ => None,
+    }
+}
+
+fn directive(node: &AstNode<'_>) -> Option<Directive> {
+    match &node.data.borrow().value {
+        NodeValue::HtmlBlock(block) => {
+            let comment = block
+                .literal
+                .trim()
+                .strip_prefix("<!--")?
+                .strip_suffix("-->")?
+                .trim();
+            match comment {
+                "landscape" => Some(Directive::Switch(Change::Landscape(true))),
+                "portrait" => Some(Directive::Switch(Change::Landscape(false))),
+                _ => {
+                    let count = comment.strip_prefix("columns:")?.trim().parse().ok()?;
+                    Some(Directive::Switch(Change::Columns(count)))
+                }
+            }
+        }
+        NodeValue::Paragraph => {
+            let child = node.first_child()?;
+            if child.next_sibling().is_some() {
+                return None;
+            }
+            let NodeValue::Text(text) = &child.data.borrow().value else {
+                return None;
+            };
+            let rest = text.trim().strip_prefix(":::")?.trim();
+            if rest.is_empty() {
+                return Some(Directive::Close);
+            }
+            if rest == "landscape" {
+                return Some(Directive::Open(Change::Landscape(true)));
+            }
+            let count = rest.strip_prefix("columns")?.trim().parse().ok()?;
+            Some(Directive::Open(Change::Columns(count)))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::markdown;
+
+    fn layout(columns: u8, wide: &str) -> Layout {
+        Layout {
+            columns,
+            gutter: "8mm".to_string(),
+            wide_tables: WideTables::from_theme(&TableTokens {
+                wide_columns: 2,
+                wide: wide.to_string(),
+                ..TableTokens::default()
+            }),
+        }
+    }
+
+    fn lowered(source: &str, layout: &Layout) -> Vec<String> {
+        let arena = comrak::Arena::new();
+        let root = markdown::parse(&arena, source);
+        lower_layout_directives(&arena, root, layout);
+        root.children()
+            .map(|node| match &node.data.borrow().value {
+                NodeValue::Raw(raw) => raw.clone(),
+                NodeValue::Heading(_) => "heading".to_string(),
+                NodeValue::Paragraph => "paragraph".to_string(),
+                NodeValue::Table(_) => "table".to_string(),
+                other => format!("{other:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn full_width_run_floats_over_page_columns() {
+        let blocks = lowered(
+            "<!-- columns: 1 -->\n# Title\n\nAbstract.\n\n<!-- columns: 2 -->\n\nBody.\n",
+            &layout(2, ""),
+        );
+        assert_eq!(
+            blocks,
+            vec![
+                "\n#place(top, scope: \"parent\", float: true, clearance: 1.5em)[\n".to_string(),
+                "heading".to_string(),
+                "paragraph".to_string(),
+                "\n]\n".to_string(),
+                "paragraph".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn fenced_run_returns_to_the_page_columns() {
+        let blocks = lowered(
+            "Intro.\n\n::: columns 3\n\nBody.\n\n:::\n\nOutro.\n\n:::\n",
+            &layout(1, ""),
+        );
+        assert_eq!(
+            blocks,
+            vec![
+                "paragraph".to_string(),
+                "\n#columns(3, gutter: 8mm)[\n".to_string(),
+                "paragraph".to_string(),
+                "\n]\n".to_string(),
+                "paragraph".to_string(),
+                "paragraph".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn landscape_run_closes_and_reopens_column_runs() {
+        let blocks = lowered(
+            "::: columns 2\n\nA.\n\n::: landscape\n\nB.\n\n:::\n\n:::\n",
+            &layout(1, ""),
+        );
+        assert_eq!(
+            blocks,
+            vec![
+                "\n#columns(2, gutter: 8mm)[\n".to_string(),
+                "paragraph".to_string(),
+                "\n]\n\n#page(flipped: true)[\n\n#columns(2, gutter: 8mm)[\n".to_string(),
+                "paragraph".to_string(),
+                "\n]\n\n]\n\n#columns(2, gutter: 8mm)[\n".to_string(),
+                "\n]\n".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn wide_table_gets_a_landscape_page_with_its_caption() {
+        let source =
+            "| a | b | c |\n|---|---|---|\n| 1 | 2 | 3 |\n\nTable: Wide {#tbl:wide}\n\nAfter.\n";
+        let blocks = lowered(source, &layout(1, "landscape"));
+        assert_eq!(
+            blocks,
+            vec![
+                "\n#page(flipped: true, columns: 1)[\n".to_string(),
+                "table".to_string(),
+                "paragraph".to_string(),
+                "\n]\n".to_string(),
+                "paragraph".to_string(),
+            ]
+        );
+
+        let blocks = lowered(source, &layout(1, "shrink"));
+        assert_eq!(blocks, vec!["table", "paragraph", "paragraph"]);
+    }
+
+    #[test]
+    fn shrunk_size_scales_the_table_font() {
+        let wide = WideTables::from_theme(&TableTokens {
+            font_size: "9pt".to_string(),
+            ..TableTokens::default()
+        });
+        assert_eq!(wide.treatment(6), None);
+        assert_eq!(wide.treatment(8), Some(WideTable::Shrink));
+        assert_eq!(wide.shrunk_size(8), "9pt * 0.75");
+        assert_eq!(wide.shrunk_size(40), "9pt * 0.60");
+    }
+
+    #[test]
+    fn resolve_keeps_only_length_gaps() {
+        let theme = crate::theme::load_theme(
+            &crate::ThemeSource::BuiltIn("silk-light".to_string()),
+            &mut crate::warnings::WarningCollector::new(),
+        )
+        .expect("should load theme");
+        let gutter = |gap: &str| {
+            let front_matter = FrontMatter {
+                column_gap: Some(gap.to_string()),
+                ..Default::default()
+            };
+            Layout::resolve(&theme, Some(&front_matter)).gutter
+        };
+        assert_eq!(gutter("6mm"), "6mm");
+        assert_eq!(gutter("6mm)[#panic()]"), DEFAULT_GUTTER);
+    }
+}
@@ -2,7 +2,7 @@ use std::path::{Path, PathBuf};
 
 use comrak::nodes::{AstNode, NodeValue};
 
-use crate::warnings::{SilkprintWarning, WarningCollector};
+use crate::warnings::{SilkprintWarning, SourceLocation, WarningCollector};
 
 pub fn validate_links<'a>(
     root: &'a AstNode<'a>,
@@ -22,30 +22,38 @@ pub fn validate_links<'a>(
         if target.starts_with('#') || target.starts_with("mailto:") {
             continue;
         }
+        let location = SourceLocation::from_sourcepos(node.data.borrow().sourcepos);
         if target.starts_with("http://") || target.starts_with("https://") {
-            validate_remote(&target, warnings);
+            validate_remote(&target, location, warnings);
         } else {
-            validate_local(&target, base_dir, warnings);
+            validate_local(&target, location, base_dir, warnings);
         }
     }
 }
 
-fn validate_remote(url: &str, warnings: &mut WarningCollector) {
+fn validate_remote(url: &str, location: Option<SourceLocation>, warnings: &mut WarningCollector) {
     #[cfg(not(target_arch = "wasm32"))]
     if let Err(message) = crate::render::remote::validate_remote_link(url) {
         warnings.push(SilkprintWarning::LinkValidationFailed {
             target: url.to_string(),
             message,
+            location,
         });
     }
 }
 
-fn validate_local(target: &str, base_dir: Option<&Path>, warnings: &mut WarningCollector) {
+fn validate_local(
+    target: &str,
+    location: Option<SourceLocation>,
+    base_dir: Option<&Path>,
+    warnings: &mut WarningCollector,
+) {
     let path = local_target_path(target, base_dir);
     if !path.exists() {
         warnings.push(SilkprintWarning::LinkValidationFailed {
             target: target.to_string(),
             message: "local target not found".to_string(),
+            location,
         });
     }
 }
@@ -97,6 +105,11 @@ mod tests {
         validate_links(root, Some(&dir.path().join("doc.md")), &mut warnings);
 
         assert_eq!(warnings.warnings().len(), 1);
+        let location = warnings.warnings()[0]
+            .location()
+            .expect("should point at the link");
+        assert_eq!((location.line, location.column), (1, 1));
+        assert_eq!(location.end_column, 21);
     }
 
     #[test]
@@ -2,13 +2,15 @@ use std::collections::HashMap;
 use std::fmt::Write;
 
 use comrak::Options;
-use comrak::nodes::{AstNode, ListType, NodeValue, TableAlignment};
+use comrak::nodes::{AstNode, ListType, NodeCodeBlock, NodeValue, Sourcepos, TableAlignment};
 
 use crate::theme::ResolvedTheme;
-use crate::warnings::{SilkprintWarning, WarningCollector};
+use crate::warnings::{SilkprintWarning, SourceLocation, WarningCollector};
 
+use super::crossref::{self, CrossRefs, Segment};
 use super::escape::{escape_typst_content, escape_typst_string};
 use super::image::{PreparedImage, PreparedImages};
+use super::layout::{WideTable, WideTables};
 
 /// Configure comrak with all extensions enabled per SPEC Section 8.2.
 pub fn comrak_options() -> Options<'static> {
@@ -169,15 +171,52 @@ pub fn parse<'a>(arena: &'a comrak::Arena<'a>, input: &str) -> &'a AstNode<'a> {
 /// Mermaid code blocks are emitted as image references to virtual SVG files.
 /// The collected mermaid sources are returned so the caller can render them
 /// before Typst compilation.
+///
+/// Headings are numbered as the theme says; see [`emit_typst_with_numbering`]
+/// to take front matter into account.
 pub fn emit_typst<'a>(
     root: &'a AstNode<'a>,
-    _theme: &ResolvedTheme,
+    theme: &ResolvedTheme,
+    images: &'a PreparedImages,
+    warnings: &mut WarningCollector,
+) -> (String, Vec<String>) {
+    let numbering = super::preamble::heading_numbering(&theme.tokens, None);
+    emit_typst_with_numbering(root, theme, numbering, images, warnings)
+}
+
+/// Like [`emit_typst`], with the heading numbering pattern in effect, or
+/// `None` when headings are unnumbered. An appendix only switches to
+/// lettering when there is numbering to switch.
+pub fn emit_typst_with_numbering<'a>(
+    root: &'a AstNode<'a>,
+    theme: &ResolvedTheme,
+    numbering: Option<&str>,
     images: &'a PreparedImages,
     warnings: &mut WarningCollector,
 ) -> (String, Vec<String>) {
+    emit_typst_with_mermaid_offset(root, theme, numbering, images, 0, warnings)
+}
+
+/// Like [`emit_typst_with_numbering`], but numbers mermaid diagrams from
+/// `mermaid_offset`.
+///
+/// Book mode emits each chapter separately into one Typst world, so the
+/// virtual SVG paths must keep counting across chapters.
+pub fn emit_typst_with_mermaid_offset<'a>(
+    root: &'a AstNode<'a>,
+    theme: &ResolvedTheme,
+    numbering: Option<&str>,
+    images: &'a PreparedImages,
+    mermaid_offset: usize,
+    warnings: &mut WarningCollector,
+) -> (String, Vec<String>) {
+    // Strip `{#fig:id}` attribute blocks and remember which nodes they label.
+    let crossrefs = crossref::collect(root, warnings);
+    let wide_tables = WideTables::from_theme(&theme.tokens.table);
+
     // First pass: collect footnote definitions by name so we can inline them
     // at the reference site (Typst's #footnote[...] model).
-    let footnotes = collect_footnote_definitions(root, images, warnings);
+    let footnotes = collect_footnote_definitions(root, images, &crossrefs, &wide_tables, warnings);
 
     let mut ctx = EmitContext {
         out: String::with_capacity(8192),
@@ -188,9 +227,12 @@ pub fn emit_typst<'a>(
         in_table_header: false,
         in_tight_list: false,
         images,
+        crossrefs: &crossrefs,
+        wide_tables: &wide_tables,
+        numbering,
         warnings,
         mermaid_sources: Vec::new(),
-        mermaid_counter: 0,
+        mermaid_counter: mermaid_offset,
     };
 
     emit_node(root, &mut ctx);
@@ -212,6 +254,10 @@ struct EmitContext<'w> {
     in_table_header: bool,
     in_tight_list: bool,
     images: &'w PreparedImages,
+    crossrefs: &'w CrossRefs,
+    wide_tables: &'w WideTables,
+    /// Heading numbering pattern in effect, if headings are numbered.
+    numbering: Option<&'w str>,
     warnings: &'w mut WarningCollector,
     mermaid_sources: Vec<String>,
     mermaid_counter: usize,
@@ -232,7 +278,7 @@ impl EmitContext<'_> {
 ///
 /// This enum mirrors `NodeValue` but owns all the data it needs so we can
 /// drop the `Ref` immediately after extraction.
-enum ExtractedNode {
+pub(super) enum ExtractedNode {
     Document,
     FrontMatter,
     Paragraph,
@@ -304,6 +350,7 @@ enum ExtractedNode {
     Alert {
         title: String,
         icon: &'static str,
+        color: &'static str,
     },
     DescriptionList,
     DescriptionItem,
@@ -322,7 +369,7 @@ enum ExtractedNode {
 /// Extract all needed data from a node value, cloning strings so we can
 /// drop the `Ref<Ast>` borrow immediately.
 #[allow(clippy::too_many_lines)]
-fn extract_node(node: &AstNode<'_>) -> ExtractedNode {
+pub(super) fn extract_node(node: &AstNode<'_>) -> ExtractedNode {
     let data = node.data.borrow();
     match &data.value {
         NodeValue::Document => ExtractedNode::Document,
@@ -393,8 +440,8 @@ fn extract_node(node: &AstNode<'_>) -> ExtractedNode {
                 .title
                 .clone()
                 .unwrap_or_else(|| alert_type.default_title().to_string());
-            let (icon, _) = alert_icon_and_color(alert_type);
-            ExtractedNode::Alert { title, icon }
+            let (icon, color) = alert_icon_and_color(alert_type);
+            ExtractedNode::Alert { title, icon, color }
         }
         NodeValue::DescriptionList => ExtractedNode::DescriptionList,
         NodeValue::DescriptionItem(_) => ExtractedNode::DescriptionItem,
@@ -434,13 +481,29 @@ fn emit_node<'a>(node: &'a AstNode<'a>, ctx: &mut EmitContext<'_>) {
 
         // ─── Heading ─────────────────────────────────────────────
         ExtractedNode::Heading { level } => {
+            let attrs = ctx.crossrefs.get(node).cloned().unwrap_or_default();
+            // The appendix divider itself stays unnumbered.
+            let unnumbered = attrs.unnumbered || attrs.appendix;
             ctx.newline();
+            if unnumbered {
+                ctx.push("#[\n#set heading(numbering: none)\n");
+            }
             for _ in 0..level {
                 ctx.push("=");
             }
             ctx.push(" ");
             emit_children(node, ctx);
+            if let Some(id) = &attrs.id {
+                let _ = write!(ctx.out, " <{id}>");
+            }
             ctx.newline();
+            if unnumbered {
+                ctx.push("]\n");
+            }
+            if attrs.appendix && ctx.numbering.is_some() {
+                ctx.push("#counter(heading).update(0)\n");
+                ctx.push("#set heading(numbering: \"A.1\")\n");
+            }
         }
 
         // ─── Thematic break (horizontal rule) ────────────────────
@@ -450,10 +513,7 @@ fn emit_node<'a>(node: &'a AstNode<'a>, ctx: &mut EmitContext<'_>) {
         }
 
         // ─── Text ────────────────────────────────────────────────
-        ExtractedNode::Text(text) => {
-            let escaped = escape_typst_content(&text);
-            ctx.push(&escaped);
-        }
+        ExtractedNode::Text(text) => emit_text(ctx, node, &text),
 
         // ─── Soft break ──────────────────────────────────────────
         // A soft break (a plain newline inside a paragraph) always collapses
@@ -532,13 +592,12 @@ fn emit_node<'a>(node: &'a AstNode<'a>, ctx: &mut EmitContext<'_>) {
 
         // ─── Link / wikilink ────────────────────────────────────────
         ExtractedNode::Link { url } => {
-            let _ = write!(ctx.out, "#link(\"{}\")[", escape_typst_string(&url));
+            emit_link_open(ctx, &url);
             emit_children(node, ctx);
             ctx.push("]");
         }
         ExtractedNode::WikiLink { url } => {
-            let target = wikilink_target(&url);
-            let _ = write!(ctx.out, "#link(\"{}\")[", escape_typst_string(&target));
+            emit_link_open(ctx, &wikilink_target(&url));
             emit_children(node, ctx);
             ctx.push("]");
         }
@@ -557,28 +616,54 @@ fn emit_node<'a>(node: &'a AstNode<'a>, ctx: &mut EmitContext<'_>) {
             };
 
             let standalone = is_standalone_image(node);
+            let wide = standalone && is_wide(ctx.crossrefs, node);
+            if wide {
+                open_wide(ctx);
+            }
 
             let typst_path = match ctx.images.resolve(&url) {
                 Some(PreparedImage::Available { typst_path }) => Some(typst_path.as_str()),
                 None if !super::image::is_remote_image(&url) => Some(url.as_str()),
                 _ => None,
             };
+            // Alt text is carried into tagged PDFs for screen readers.
+            let alt = alt_argument(&alt_text);
 
-            if let Some(typst_path) = typst_path {
+            if let Some((id, caption)) = figure_attributes(ctx.crossrefs, node)
+                && standalone
+            {
+                open_figure(ctx, "image", Some(caption.as_deref().unwrap_or(&label)));
+                if let Some(typst_path) = typst_path {
+                    let _ = write!(
+                        ctx.out,
+                        "#image(\"{}\"{alt})",
+                        escape_typst_string(typst_path)
+                    );
+                } else {
+                    emit_image_placeholder(ctx, &label, true);
+                }
+                close_figure(ctx, &id);
+            } else if let Some(typst_path) = typst_path {
                 let escaped_path = escape_typst_string(typst_path);
                 if standalone {
                     ctx.push("\n#figure(\n");
-                    let _ = writeln!(ctx.out, "  image(\"{escaped_path}\"),");
+                    let _ = writeln!(ctx.out, "  image(\"{escaped_path}\"{alt}),");
                     if !label.is_empty() {
                         let _ = writeln!(ctx.out, "  caption: [{}],", escape_typst_content(&label));
                     }
                     ctx.push(")\n");
                 } else {
-                    let _ = write!(ctx.out, "#box(image(\"{escaped_path}\", height: 1.1em))");
+                    let _ = write!(
+                        ctx.out,
+                        "#box(image(\"{escaped_path}\", height: 1.1em{alt}))"
+                    );
                 }
             } else {
                 emit_image_placeholder(ctx, &label, standalone);
             }
+            if wide {
+                close_wide(ctx);
+            }
         }
 
         // ─── Block quote ─────────────────────────────────────────
@@ -613,15 +698,39 @@ fn emit_node<'a>(node: &'a AstNode<'a>, ctx: &mut EmitContext<'_>) {
         // ─── Code block ──────────────────────────────────────────
         ExtractedNode::CodeBlock { info, literal } => {
             let lang = info.split([' ', ',', '\t']).next().unwrap_or("");
+            let figure = figure_attributes(ctx.crossrefs, node);
 
             if lang == "math" {
                 ctx.newline();
                 let content = literal.trim();
-                let _ = writeln!(ctx.out, "$ {content} $");
-            } else if lang == "csv"
-                && let Some(rows) = super::csv::parse_rows(&literal)
-            {
-                emit_csv_table(ctx, &rows);
+                if let Some((id, _)) = &figure {
+                    emit_numbered_equation(ctx, content, id);
+                } else {
+                    let _ = writeln!(ctx.out, "$ {content} $");
+                }
+                return;
+            }
+
+            let csv_rows = if lang == "csv" {
+                super::csv::parse_rows(&literal)
+            } else {
+                None
+            };
+            let csv_width = csv_rows.as_deref().map_or(0, csv_columns);
+            let treatment = wide_table_treatment(ctx, node, csv_width);
+            open_wide_table(ctx, treatment, csv_width);
+
+            if let Some((_, caption)) = &figure {
+                let kind = match lang {
+                    "csv" => "table",
+                    "mermaid" => "image",
+                    _ => "raw",
+                };
+                open_figure(ctx, kind, caption.as_deref());
+            }
+
+            if let Some(rows) = &csv_rows {
+                emit_csv_table(ctx, rows, treatment == Some(WideTable::Break));
             } else if lang == "mermaid" {
                 // Emit image reference — SVG will be rendered before compilation
                 let idx = ctx.mermaid_counter;
@@ -629,7 +738,13 @@ fn emit_node<'a>(node: &'a AstNode<'a>, ctx: &mut EmitContext<'_>) {
                 ctx.mermaid_sources.push(literal.clone());
                 ctx.newline();
                 let vpath = super::mermaid::MERMAID_VPATH_PREFIX;
-                let _ = writeln!(ctx.out, "#align(center)[#image(\"{vpath}{idx}.svg\")]");
+                // The caption, or else the diagram definition, is the best
+                // text equivalent a screen reader can get.
+                let alt = figure
+                    .as_ref()
+                    .and_then(|(_, caption)| caption.as_deref())
+                    .map_or_else(|| alt_argument(literal.trim()), alt_argument);
+                let _ = writeln!(ctx.out, "#align(center)[#image(\"{vpath}{idx}.svg\"{alt})]");
             } else {
                 // Use enough backticks to avoid collision with content
                 let fence = backtick_fence(&literal);
@@ -646,18 +761,30 @@ fn emit_node<'a>(node: &'a AstNode<'a>, ctx: &mut EmitContext<'_>) {
                 ctx.newline();
                 let _ = writeln!(ctx.out, "{fence}");
             }
+
+            if let Some((id, _)) = &figure {
+                close_figure(ctx, id);
+            }
+            close_wide_table(ctx, treatment);
         }
 
         // ─── HTML block → convert to Typst ──────────────────────
         ExtractedNode::HtmlBlock { literal } => {
-            let typst = super::html::emit_html_block(&literal, ctx.images, ctx.warnings);
+            // HTML warnings point at the block they came from.
+            let location = SourceLocation::from_sourcepos(node.data.borrow().sourcepos);
+            let mut found = WarningCollector::new();
+            let typst = super::html::emit_html_block(&literal, ctx.images, &mut found);
+            ctx.warnings.extend_located(found, location.as_ref());
             ctx.push(&typst);
         }
 
         // ─── HTML inline → entity decode or convert ─────────────
         ExtractedNode::HtmlInline(html_str) => {
             if html_str.starts_with('<') && html_str.len() > 1 {
-                let typst = super::html::emit_html_inline(&html_str, ctx.images, ctx.warnings);
+                let location = SourceLocation::from_sourcepos(node.data.borrow().sourcepos);
+                let mut found = WarningCollector::new();
+                let typst = super::html::emit_html_inline(&html_str, ctx.images, &mut found);
+                ctx.warnings.extend_located(found, location.as_ref());
                 ctx.push(&typst);
             } else {
                 let decoded = decode_html_entity(&html_str);
@@ -671,6 +798,16 @@ fn emit_node<'a>(node: &'a AstNode<'a>, ctx: &mut EmitContext<'_>) {
             num_columns,
         } => {
             ctx.table_alignments.clone_from(&alignments);
+            let wide = is_wide(ctx.crossrefs, node);
+            if wide {
+                open_wide(ctx);
+            }
+            let treatment = wide_table_treatment(ctx, node, num_columns);
+            open_wide_table(ctx, treatment, num_columns);
+            let figure = figure_attributes(ctx.crossrefs, node);
+            if let Some((_, caption)) = &figure {
+                open_figure(ctx, "table", caption.as_deref());
+            }
             ctx.newline();
 
             // Detect empty header rows (GFM requires headers, but they may be blank)
@@ -705,21 +842,33 @@ fn emit_node<'a>(node: &'a AstNode<'a>, ctx: &mut EmitContext<'_>) {
                 );
             }
 
+            let repeat_header = treatment == Some(WideTable::Break);
             for child in node.children() {
+                let is_header = matches!(&child.data.borrow().value, NodeValue::TableRow(true));
                 // Skip the empty header row entirely
-                if empty_header {
-                    let data = child.data.borrow();
-                    if matches!(&data.value, NodeValue::TableRow(true)) {
-                        continue;
-                    }
+                if empty_header && is_header {
+                    continue;
+                }
+                if repeat_header && is_header {
+                    ctx.push("  table.header(\n");
+                    emit_node(child, ctx);
+                    ctx.push("  ),\n");
+                } else {
+                    emit_node(child, ctx);
                 }
-                emit_node(child, ctx);
             }
 
             ctx.push(")\n");
             if empty_header {
                 ctx.push("}\n");
             }
+            if let Some((id, _)) = &figure {
+                close_figure(ctx, id);
+            }
+            close_wide_table(ctx, treatment);
+            if wide {
+                close_wide(ctx);
+            }
             ctx.table_alignments.clear();
         }
 
@@ -746,15 +895,19 @@ fn emit_node<'a>(node: &'a AstNode<'a>, ctx: &mut EmitContext<'_>) {
                 let _ = write!(ctx.out, "#footnote[{}]", content.trim());
             } else {
                 let _ = write!(ctx.out, "#super[{}]", escape_typst_content(&name));
-                ctx.warnings
-                    .push(SilkprintWarning::FootnoteNotFound { name: name.clone() });
+                ctx.warnings.push(SilkprintWarning::FootnoteNotFound {
+                    name: name.clone(),
+                    location: SourceLocation::from_sourcepos(node.data.borrow().sourcepos),
+                });
             }
         }
 
         // ─── Math ────────────────────────────────────────────────
         ExtractedNode::Math { literal, display } => {
             let trimmed = literal.trim();
-            if display {
+            if display && let Some((id, _)) = figure_attributes(ctx.crossrefs, node) {
+                emit_numbered_equation(ctx, trimmed, &id);
+            } else if display {
                 let _ = write!(ctx.out, "$ {trimmed} $");
             } else {
                 let _ = write!(ctx.out, "${trimmed}$");
@@ -762,7 +915,7 @@ fn emit_node<'a>(node: &'a AstNode<'a>, ctx: &mut EmitContext<'_>) {
         }
 
         // ─── Alert (GitHub-style) ────────────────────────────────
-        ExtractedNode::Alert { title, icon } => {
+        ExtractedNode::Alert { title, icon, .. } => {
             ctx.newline();
             ctx.push("#block(\n");
             ctx.push("  stroke: (left: 3pt + rgb(\"#4a5dbd\")),\n");
@@ -809,6 +962,7 @@ fn emit_children<'a>(node: &'a AstNode<'a>, ctx: &mut EmitContext<'_>) {
             && is_opening_html_tag(html_str)
         {
             // Accumulate siblings until tags balance
+            let location = SourceLocation::from_sourcepos(children[i].data.borrow().sourcepos);
             let mut buf = html_str.clone();
             let mut depth: usize = 1;
             i += 1;
@@ -835,7 +989,9 @@ fn emit_children<'a>(node: &'a AstNode<'a>, ctx: &mut EmitContext<'_>) {
                 i += 1;
             }
 
-            let typst = super::html::emit_html_inline(&buf, ctx.images, ctx.warnings);
+            let mut found = WarningCollector::new();
+            let typst = super::html::emit_html_inline(&buf, ctx.images, &mut found);
+            ctx.warnings.extend_located(found, location.as_ref());
             ctx.push(&typst);
             continue;
         }
@@ -1193,6 +1349,8 @@ fn render_fragment(ctx: &mut EmitContext<'_>, render: impl FnOnce(&mut EmitConte
 fn collect_footnote_definitions<'a>(
     root: &'a AstNode<'a>,
     images: &'a PreparedImages,
+    crossrefs: &CrossRefs,
+    wide_tables: &WideTables,
     warnings: &mut WarningCollector,
 ) -> HashMap<String, String> {
     let mut map = HashMap::new();
@@ -1219,6 +1377,9 @@ fn collect_footnote_definitions<'a>(
                 in_table_header: false,
                 in_tight_list: false,
                 images,
+                crossrefs,
+                wide_tables,
+                numbering: None,
                 warnings,
                 mermaid_sources: Vec::new(),
                 mermaid_counter: 0,
@@ -1258,7 +1419,7 @@ fn has_empty_header<'a>(table_node: &'a AstNode<'a>) -> bool {
 }
 
 /// Collect all plain text from a node's descendants into a buffer.
-fn collect_text<'a>(node: &'a AstNode<'a>, buf: &mut String) {
+pub(super) fn collect_text<'a>(node: &'a AstNode<'a>, buf: &mut String) {
     for child in node.descendants() {
         let data = child.data.borrow();
         if let NodeValue::Text(text) = &data.value {
@@ -1267,6 +1428,51 @@ fn collect_text<'a>(node: &'a AstNode<'a>, buf: &mut String) {
     }
 }
 
+/// GitHub-style heading slug: lowercase alphanumerics joined by dashes.
+pub(super) fn heading_slug(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut prev_dash = false;
+    for ch in text.chars() {
+        if ch.is_alphanumeric() {
+            out.extend(ch.to_lowercase());
+            prev_dash = false;
+        } else if matches!(ch, ' ' | '-' | '_') && !prev_dash && !out.is_empty() {
+            out.push('-');
+            prev_dash = true;
+        }
+    }
+    while out.ends_with('-') {
+        out.pop();
+    }
+    out
+}
+
+/// Slug for a heading, de-duplicated GitHub-style against `seen`: `intro`,
+/// `intro-1`, `intro-2`, ... Headings without any slug text become `section`.
+pub(super) fn unique_heading_slug(title: &str, seen: &mut HashMap<String, usize>) -> String {
+    let mut slug = heading_slug(title);
+    if slug.is_empty() {
+        slug = "section".to_string();
+    }
+    let count = seen.entry(slug.clone()).or_insert(0);
+    if *count > 0 {
+        slug = format!("{slug}-{count}");
+    }
+    *count += 1;
+    slug
+}
+
+/// `, alt: "..."` for an `image()` call, or nothing when there's no text.
+/// Whitespace runs (including newlines) collapse to single spaces.
+pub(crate) fn alt_argument(alt: &str) -> String {
+    let alt = alt.split_whitespace().collect::<Vec<_>>().join(" ");
+    if alt.is_empty() {
+        String::new()
+    } else {
+        format!(", alt: \"{}\"", escape_typst_string(&alt))
+    }
+}
+
 fn emit_image_placeholder(ctx: &mut EmitContext<'_>, label: &str, standalone: bool) {
     let escaped = escape_typst_content(label);
 
@@ -1283,8 +1489,142 @@ fn emit_image_placeholder(ctx: &mut EmitContext<'_>, label: &str, standalone: bo
     }
 }
 
-fn emit_csv_table(ctx: &mut EmitContext<'_>, rows: &[Vec<String>]) {
-    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+/// Emit text, turning known `@fig:id`-style references into `#ref(<fig:id>)`.
+fn emit_text<'a>(ctx: &mut EmitContext<'_>, node: &'a AstNode<'a>, text: &str) {
+    if !text.contains('@') {
+        ctx.push(&escape_typst_content(text));
+        return;
+    }
+    let segments = crossref::split_references(text);
+    for (index, segment) in segments.iter().enumerate() {
+        match segment {
+            Segment::Text(run) => ctx.push(&escape_typst_content(run)),
+            Segment::Reference(label) if ctx.crossrefs.contains(label) => {
+                let _ = write!(ctx.out, "#ref(<{label}>)");
+                // A directly following `(`, `[`, or `.` would extend the call.
+                if let Some(Segment::Text(next)) = segments.get(index + 1)
+                    && next.starts_with(['(', '[', '.'])
+                {
+                    ctx.push(";");
+                }
+            }
+            Segment::Reference(label) => {
+                ctx.warnings.push(SilkprintWarning::ReferenceNotFound {
+                    label: (*label).to_string(),
+                    location: SourceLocation::from_sourcepos(node.data.borrow().sourcepos),
+                });
+                ctx.push(&escape_typst_content(&format!("@{label}")));
+            }
+        }
+    }
+}
+
+/// The `(id, caption)` of a node labeled with a `{#id}` attribute block.
+fn figure_attributes(
+    crossrefs: &CrossRefs,
+    node: &AstNode<'_>,
+) -> Option<(String, Option<String>)> {
+    let attrs = crossrefs.get(node)?;
+    Some((attrs.id.clone()?, attrs.caption.clone()))
+}
+
+/// Open a numbered `#figure(...)[` around a labeled node's body.
+///
+/// Unlabeled figures are unnumbered (see the preamble), so only figures that
+/// can be referenced carry a "Figure N" caption prefix.
+fn open_figure(ctx: &mut EmitContext<'_>, kind: &str, caption: Option<&str>) {
+    ctx.newline();
+    let _ = write!(ctx.out, "#figure(kind: {kind}, numbering: \"1\"");
+    if let Some(caption) = caption.filter(|c| !c.is_empty()) {
+        let _ = write!(ctx.out, ", caption: [{}]", escape_typst_content(caption));
+    }
+    ctx.push(")[\n");
+}
+
+fn close_figure(ctx: &mut EmitContext<'_>, id: &str) {
+    let _ = writeln!(ctx.out, "\n] <{id}>");
+}
+
+/// Whether a node carries `{.wide}`.
+fn is_wide(crossrefs: &CrossRefs, node: &AstNode<'_>) -> bool {
+    crossrefs.get(node).is_some_and(|attrs| attrs.wide)
+}
+
+/// Float a `{.wide}` figure or table across all of the page's columns. On a
+/// single-column page this only lets it move to the top or bottom.
+fn open_wide(ctx: &mut EmitContext<'_>) {
+    ctx.newline();
+    ctx.push("#place(auto, scope: \"parent\", float: true, clearance: 1em)[\n");
+}
+
+fn close_wide(ctx: &mut EmitContext<'_>) {
+    ctx.push("\n]\n");
+}
+
+/// How a table of `columns` columns is laid out in place, if it is wide.
+/// Top-level tables bound for a landscape page already have one (see
+/// [`super::layout`]); nested ones are shrunk instead.
+fn wide_table_treatment(
+    ctx: &EmitContext<'_>,
+    node: &AstNode<'_>,
+    columns: usize,
+) -> Option<WideTable> {
+    match ctx.wide_tables.treatment(columns)? {
+        WideTable::Landscape => {
+            let top_level = node
+                .parent()
+                .is_some_and(|parent| matches!(parent.data.borrow().value, NodeValue::Document));
+            (!top_level).then_some(WideTable::Shrink)
+        }
+        treatment => Some(treatment),
+    }
+}
+
+/// Scope the rules a wide table's treatment needs: smaller text, or a
+/// captioned figure that may break across pages.
+fn open_wide_table(ctx: &mut EmitContext<'_>, treatment: Option<WideTable>, columns: usize) {
+    let rule = match treatment {
+        Some(WideTable::Shrink) => format!(
+            "#show table: set text(size: {})",
+            ctx.wide_tables.shrunk_size(columns)
+        ),
+        Some(WideTable::Break) => "#show figure: set block(breakable: true)".to_string(),
+        _ => return,
+    };
+    ctx.newline();
+    let _ = writeln!(ctx.out, "#[\n{rule}");
+}
+
+fn close_wide_table(ctx: &mut EmitContext<'_>, treatment: Option<WideTable>) {
+    if matches!(treatment, Some(WideTable::Shrink | WideTable::Break)) {
+        ctx.push("]\n");
+    }
+}
+
+fn emit_numbered_equation(ctx: &mut EmitContext<'_>, content: &str, id: &str) {
+    let _ = writeln!(
+        ctx.out,
+        "#math.equation(block: true, numbering: \"(1)\", $ {content} $) <{id}>"
+    );
+}
+
+/// Open a `#link(...)[` call, targeting a label for book-internal anchors.
+fn emit_link_open(ctx: &mut EmitContext<'_>, target: &str) {
+    if let Some(label) = target.strip_prefix(super::book::INTERNAL_LINK_PREFIX) {
+        let _ = write!(ctx.out, "#link(label(\"{}\"))[", escape_typst_string(label));
+    } else {
+        let _ = write!(ctx.out, "#link(\"{}\")[", escape_typst_string(target));
+    }
+}
+
+fn csv_columns(rows: &[Vec<String>]) -> usize {
+    rows.iter().map(Vec::len).max().unwrap_or(0)
+}
+
+/// `repeat_header` puts the first row in a `table.header`, repeated on
+/// every page the table breaks onto.
+fn emit_csv_table(ctx: &mut EmitContext<'_>, rows: &[Vec<String>], repeat_header: bool) {
+    let columns = csv_columns(rows);
     if columns == 0 {
         return;
     }
@@ -1297,16 +1637,23 @@ fn emit_csv_table(ctx: &mut EmitContext<'_>, rows: &[Vec<String>]) {
         ctx.out,
         "#table(\n  columns: {columns},\n  align: ({align},),"
     );
-    for row in rows {
+    for (y, row) in rows.iter().enumerate() {
+        let header = repeat_header && y == 0;
+        if header {
+            ctx.push("  table.header(\n");
+        }
         for idx in 0..columns {
             let cell = row.get(idx).map_or("", String::as_str);
             let _ = writeln!(ctx.out, "  [{}],", escape_typst_content(cell));
         }
+        if header {
+            ctx.push("  ),\n");
+        }
     }
     ctx.push(")\n");
 }
 
-fn wikilink_target(target: &str) -> String {
+pub(super) fn wikilink_target(target: &str) -> String {
     let (path, anchor) = target
         .split_once('#')
         .map_or((target, None), |(path, anchor)| (path, Some(anchor)));
@@ -1331,7 +1678,7 @@ fn uri_scheme(value: &str) -> Option<&str> {
     .then_some(scheme)
 }
 
-fn is_standalone_image(node: &AstNode<'_>) -> bool {
+pub(super) fn is_standalone_image(node: &AstNode<'_>) -> bool {
     let Some(parent) = node.parent() else {
         return false;
     };
@@ -1420,7 +1767,7 @@ pub fn check_content<'a>(root: &'a AstNode<'a>, warnings: &mut WarningCollector)
     for node in root.descendants() {
         let data = node.data.borrow();
         if let NodeValue::CodeBlock(code_block) = &data.value {
-            check_code_block_language(&code_block.info, warnings);
+            check_code_block_language(code_block, data.sourcepos, warnings);
         }
     }
 
@@ -1504,16 +1851,29 @@ const KNOWN_LANGUAGES: &[&str] = &[
 ];
 
 /// Warn if a code block specifies an unrecognized language identifier.
-fn check_code_block_language(info: &str, warnings: &mut WarningCollector) {
-    let lang = info.split([' ', ',', '\t']).next().unwrap_or("");
+///
+/// The warning points at the opening fence, where the language is.
+fn check_code_block_language(
+    code_block: &NodeCodeBlock,
+    pos: Sourcepos,
+    warnings: &mut WarningCollector,
+) {
+    let lang = code_block.info.split([' ', ',', '\t']).next().unwrap_or("");
     if lang.is_empty() {
         return;
     }
 
     let lower = lang.to_lowercase();
     if !KNOWN_LANGUAGES.contains(&lower.as_str()) {
+        let fence = code_block.fence_offset + code_block.fence_length + code_block.info.len();
+        let location = SourceLocation::from_sourcepos(pos).map(|mut location| {
+            location.end_line = location.line;
+            location.end_column = location.column + fence.max(1) - 1;
+            location
+        });
         warnings.push(SilkprintWarning::UnknownLanguage {
             lang: lang.to_string(),
+            location,
         });
     }
 }
@@ -1595,11 +1955,21 @@ mod tests {
     #[test]
     fn check_content_warns_unknown_language() {
         let arena = comrak::Arena::new();
-        let root = parse(&arena, "```qwxyz\ncode\n```");
+        let root = parse(&arena, "Intro\n\n```qwxyz\ncode\n```");
         let mut warnings = WarningCollector::new();
+        warnings.set_source(Some(std::path::Path::new("doc.md")), 3);
         let clean = check_content(root, &mut warnings);
         assert!(!clean);
         assert_eq!(warnings.warnings().len(), 1);
+        let location = warnings.warnings()[0]
+            .location()
+            .expect("should point at the fence");
+        assert_eq!(
+            location.file.as_deref(),
+            Some(std::path::Path::new("doc.md"))
+        );
+        assert_eq!((location.line, location.column), (6, 1));
+        assert_eq!((location.end_line, location.end_column), (6, 8));
     }
 
     #[test]
@@ -1647,6 +2017,7 @@ mod tests {
         ResolvedTheme {
             tokens: crate::theme::tokens::ThemeTokens::default(),
             tmtheme_xml: String::new(),
+            fonts: Vec::new(),
         }
     }
 
@@ -1659,6 +2030,15 @@ mod tests {
         emit_typst(root, &theme, &images, &mut warnings).0
     }
 
+    fn emit_numbered(markdown: &str) -> String {
+        let arena = comrak::Arena::new();
+        let root = parse(&arena, markdown);
+        let theme = test_theme();
+        let images = PreparedImages::default();
+        let mut warnings = WarningCollector::new();
+        emit_typst_with_numbering(root, &theme, Some("1.1"), &images, &mut warnings).0
+    }
+
     #[test]
     fn emit_heading() {
         let result = emit("# Hello World");
@@ -1865,4 +2245,156 @@ mod tests {
         let result = emit("line one  \nline two");
         assert!(result.contains("#linebreak()"));
     }
+
+    // ─── Cross-references ───────────────────────────────────────
+
+    #[test]
+    fn emit_heading_labels_opt_outs_and_appendix() {
+        let source =
+            "# Method {#sec:method}\n\nSee @sec:method.\n\n## Aside {-}\n\n# Appendix\n\n# Data\n";
+        let unnumbered = emit(source);
+        assert!(unnumbered.contains("= Appendix\n]\n"), "got: {unnumbered}");
+        assert!(!unnumbered.contains("A.1"), "got: {unnumbered}");
+
+        let result = emit_numbered(source);
+        assert!(result.contains("= Method <sec:method>\n"), "got: {result}");
+        assert!(result.contains("See #ref(<sec:method>);."), "got: {result}");
+        assert!(
+            result.contains("#[\n#set heading(numbering: none)\n== Aside\n]\n"),
+            "got: {result}"
+        );
+        assert!(
+            result.contains(
+                "= Appendix\n]\n#counter(heading).update(0)\n#set heading(numbering: \"A.1\")\n"
+            ),
+            "got: {result}"
+        );
+        assert!(!result.contains('{'), "got: {result}");
+    }
+
+    #[test]
+    fn emit_wide_figures_and_tables_span_columns() {
+        let result = emit(
+            "![Overview](arch.svg){#fig:arch .wide}\n\n| a | b |\n|---|---|\n| 1 | 2 |\n\n{.wide}\n",
+        );
+        assert_eq!(
+            result
+                .matches("#place(auto, scope: \"parent\", float: true, clearance: 1em)[")
+                .count(),
+            2,
+            "got: {result}"
+        );
+        assert!(result.contains("] <fig:arch>\n\n]\n"), "got: {result}");
+        assert!(!result.contains("{.wide}"), "got: {result}");
+    }
+
+    #[test]
+    fn emit_wide_tables_shrink_or_break_with_repeated_headers() {
+        let table = "| a | b | c | d | e | f | g | h |\n|---|---|---|---|---|---|---|---|\n\
+                     | 1 | 2 | 3 | 4 | 5 | 6 | 7 | 8 |\n\nTable: Wide {#tbl:wide}\n";
+        let result = emit(table);
+        assert!(
+            result.contains("#[\n#show table: set text(size: 1em * 0.75)\n"),
+            "got: {result}"
+        );
+        assert!(!result.contains("table.header("), "got: {result}");
+
+        let arena = comrak::Arena::new();
+        let root = parse(&arena, &format!("> {}", table.replace('\n', "\n> ")));
+        let mut theme = test_theme();
+        theme.tokens.table.wide = "break".to_string();
+        let images = PreparedImages::default();
+        let mut warnings = WarningCollector::new();
+        let (result, _) = emit_typst(root, &theme, &images, &mut warnings);
+        assert!(
+            result.contains("#[\n#show figure: set block(breakable: true)\n"),
+            "got: {result}"
+        );
+        assert!(
+            result.contains("  table.header(\n  [a],\n"),
+            "got: {result}"
+        );
+
+        // Nested tables can't have a landscape page, so they shrink.
+        theme.tokens.table.wide = "landscape".to_string();
+        let root = parse(&arena, &format!("> {}", table.replace('\n', "\n> ")));
+        let (result, _) = emit_typst(root, &theme, &images, &mut warnings);
+        assert!(
+            result.contains("#show table: set text(size:"),
+            "got: {result}"
+        );
+    }
+
+    #[test]
+    fn emit_labeled_image_as_numbered_figure() {
+        let result = emit("![System overview](arch.svg){#fig:arch}\n\nSee @fig:arch.");
+        assert!(
+            result.contains("#figure(kind: image, numbering: \"1\", caption: [System overview])["),
+            "got: {result}"
+        );
+        assert!(result.contains("] <fig:arch>"), "got: {result}");
+        assert!(result.contains("See #ref(<fig:arch>);."), "got: {result}");
+        assert!(!result.contains("{#fig"), "got: {result}");
+    }
+
+    #[test]
+    fn emit_labeled_table_code_and_equation() {
+        let result = emit(
+            "| a |\n|---|\n| 1 |\n\nTable: Results {#tbl:res}\n\n\
+             ```rust {#lst:main caption=\"Entry\"}\nfn main() {}\n```\n\n\
+             $$ E = m c^2 $$ {#eq:energy}\n",
+        );
+        assert!(
+            result.contains("#figure(kind: table, numbering: \"1\", caption: [Results])["),
+            "got: {result}"
+        );
+        assert!(result.contains("] <tbl:res>"), "got: {result}");
+        assert!(
+            result.contains("#figure(kind: raw, numbering: \"1\", caption: [Entry])["),
+            "got: {result}"
+        );
+        assert!(result.contains("```rust\nfn main() {}"), "got: {result}");
+        assert!(
+            result.contains(
+                "#math.equation(block: true, numbering: \"(1)\", $ E = m c^2 $) <eq:energy>"
+            ),
+            "got: {result}"
+        );
+    }
+
+    #[test]
+    fn emit_dangling_reference_warns_and_stays_literal() {
+        let arena = comrak::Arena::new();
+        let root = parse(&arena, "See @fig:missing for details.");
+        let images = PreparedImages::default();
+        let mut warnings = WarningCollector::new();
+        let (result, _) = emit_typst(root, &test_theme(), &images, &mut warnings);
+
+        assert!(!result.contains("#ref("), "got: {result}");
+        assert!(result.contains("fig:missing"), "got: {result}");
+        assert!(matches!(
+            warnings.warnings(),
+            [SilkprintWarning::ReferenceNotFound { label, location: Some(location) }]
+                if label == "fig:missing" && location.line == 1 && location.column == 1
+        ));
+    }
+
+    #[test]
+    fn emit_unsupported_html_warns_at_its_line() {
+        let arena = comrak::Arena::new();
+        let root = parse(&arena, "Intro.\n\nSome <marquee>scroll</marquee> text.\n");
+        let images = PreparedImages::default();
+        let mut warnings = WarningCollector::new();
+        emit_typst(root, &test_theme(), &images, &mut warnings);
+
+        assert!(
+            matches!(
+                warnings.warnings(),
+                [SilkprintWarning::UnsupportedHtmlTag { tag, location: Some(location) }]
+                    if tag == "marquee" && location.line == 3
+            ),
+            "got: {:?}",
+            warnings.warnings()
+        );
+    }
 }
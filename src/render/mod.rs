@@ -1,20 +1,30 @@
+pub mod bibliography;
+pub mod book;
+pub mod conformance;
+pub mod crossref;
 pub mod csv;
 pub mod emoji;
 pub mod escape;
 pub mod frontmatter;
 pub mod html;
+pub mod html_export;
 pub mod image;
+pub mod include;
+pub mod layout;
 pub mod linkcheck;
 pub mod markdown;
 pub mod mermaid;
 pub mod origin;
 pub mod preamble;
 pub mod remote;
+pub mod template;
 #[cfg(feature = "terminal")]
 pub mod terminal;
 pub mod typst;
 
-use std::path::Path;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 use crate::RenderOptions;
 use crate::error::SilkprintError;
@@ -28,6 +38,7 @@ use self::frontmatter::FrontMatter;
 /// Front matter has already been extracted by the caller (`lib.rs`) so
 /// the theme can be resolved with front-matter overrides applied.
 pub fn render_pipeline(
+    engine: &typst::Engine,
     body: &str,
     front_matter: Option<&FrontMatter>,
     input_path: Option<&Path>,
@@ -35,47 +46,176 @@ pub fn render_pipeline(
     theme: &ResolvedTheme,
     warnings: &mut WarningCollector,
 ) -> Result<Vec<u8>, SilkprintError> {
+    let document = prepare_document(
+        body,
+        front_matter,
+        input_path,
+        image::ImageMode::Compile,
+        options,
+        theme,
+        warnings,
+    )?;
+    conformance::enforce(options.pdf_standard, warnings)?;
+
+    // 5. Compile to PDF
+    engine.compile_to_pdf(
+        &document.typst_source,
+        theme,
+        &document.root_dir,
+        &document.mermaid_svgs,
+        &document.remote_assets,
+        typst::PdfSettings::from_options(options),
+    )
+}
+
+/// A document lowered to Typst, with everything its compilation reads.
+#[derive(Debug)]
+pub struct PreparedDocument {
+    pub typst_source: String,
+    /// Root of the Typst world: the input file's directory.
+    pub root_dir: PathBuf,
+    pub mermaid_svgs: HashMap<String, Vec<u8>>,
+    pub remote_assets: HashMap<String, Vec<u8>>,
+    /// Local files the output depends on besides the input itself
+    /// (images, bibliographies, citation styles).
+    pub dependencies: Vec<PathBuf>,
+}
+
+/// Run the pipeline up to, but not including, Typst compilation.
+///
+/// With [`image::ImageMode::TypstOnly`] remote images aren't downloaded and
+/// mermaid diagrams aren't rendered — the source is all that's wanted.
+pub fn prepare_document(
+    body: &str,
+    front_matter: Option<&FrontMatter>,
+    input_path: Option<&Path>,
+    mode: image::ImageMode,
+    options: &RenderOptions,
+    theme: &ResolvedTheme,
+    warnings: &mut WarningCollector,
+) -> Result<PreparedDocument, SilkprintError> {
     // 1. Parse markdown to AST
     let arena = comrak::Arena::new();
     let root = markdown::parse(&arena, body);
 
     // 1b. Resolve image assets and run content checks.
     let root_dir = input_path.and_then(Path::parent);
-    let prepared_images =
-        image::PreparedImages::prepare(root, image::ImageMode::Compile, root_dir, warnings);
+    let prepared_images = image::PreparedImages::prepare(root, mode, root_dir, warnings);
     markdown::check_content(root, warnings);
+    conformance::check_document(front_matter, options.pdf_standard, warnings);
+    conformance::check_content(root, options.pdf_standard, warnings);
 
-    // 2. Generate Typst preamble from theme + front matter + options
-    let preamble = preamble::generate(theme, front_matter, options);
+    // 1c. Lower `[@key]` citations against the front matter bibliography.
+    let doc_dir = root_dir.unwrap_or_else(|| Path::new("."));
+    let options = &rooted_watermark(options, doc_dir)?;
+    let references = lower_citations(&arena, root, front_matter, doc_dir, warnings);
+    lower_typst_blocks(
+        &arena,
+        root,
+        options,
+        &layout::Layout::resolve(theme, front_matter),
+    );
+
+    // 2. Generate Typst preamble from theme + front matter + options,
+    //    followed by any custom template
+    let custom = template::Template::load(
+        theme,
+        front_matter,
+        doc_dir,
+        options.allow_raw_typst,
+        warnings,
+    );
+    let preamble = preamble::generate_with_template(
+        theme,
+        front_matter,
+        options,
+        custom.as_ref().map(|t| t.source.as_str()),
+    );
 
     // 3. Emit Typst content from AST (mermaid blocks become image refs)
-    let (content, mermaid_sources) = markdown::emit_typst(root, theme, &prepared_images, warnings);
+    let (content, mermaid_sources) = markdown::emit_typst_with_numbering(
+        root,
+        theme,
+        preamble::heading_numbering(&theme.tokens, front_matter),
+        &prepared_images,
+        warnings,
+    );
 
     // 3b. Render mermaid diagrams to SVGs (native Rust — always available)
-    let mermaid_svgs = if mermaid_sources.is_empty() {
-        std::collections::HashMap::new()
+    let mermaid_svgs = if mermaid_sources.is_empty() || mode == image::ImageMode::TypstOnly {
+        HashMap::new()
     } else {
         tracing::info!(count = mermaid_sources.len(), "rendering mermaid diagrams");
         mermaid::render_all(&mermaid_sources, theme, warnings)
     };
 
-    // 4. Combine preamble + content
-    let typst_source = format!("{preamble}\n\n{content}");
+    // 4. Combine preamble + content + references
+    let mut dependencies = prepared_images.local_files(doc_dir);
+    dependencies.extend(bibliography::Bibliography::declared_files(
+        front_matter,
+        doc_dir,
+    ));
+    dependencies.extend(template::Template::declared_file(
+        theme,
+        front_matter,
+        doc_dir,
+        options.allow_raw_typst,
+    ));
 
-    // 5. Compile to PDF
-    let root_dir = input_path
-        .and_then(Path::parent)
-        .unwrap_or_else(|| Path::new("."));
+    if let Some(image) = options
+        .watermark
+        .as_deref()
+        .filter(|w| preamble::is_image_watermark(w))
+    {
+        dependencies.push(doc_dir.join(image.trim_start_matches('/')));
+    }
 
-    typst::compile_to_pdf(
-        &typst_source,
-        theme,
-        root_dir,
-        &options.font_dirs,
-        &mermaid_svgs,
-        prepared_images.remote_assets(),
-        warnings,
-    )
+    Ok(PreparedDocument {
+        typst_source: format!("{preamble}\n\n{content}{references}"),
+        root_dir: doc_dir.to_path_buf(),
+        mermaid_svgs,
+        remote_assets: prepared_images.remote_assets().clone(),
+        dependencies,
+    })
+}
+
+/// `options` with an absolute image `--watermark` (as the CLI passes it)
+/// made relative to `root_dir`, the root of the Typst world and the only
+/// place images load from.
+fn rooted_watermark<'o>(
+    options: &'o RenderOptions,
+    root_dir: &Path,
+) -> Result<Cow<'o, RenderOptions>, SilkprintError> {
+    let Some(image) = options
+        .watermark
+        .as_deref()
+        .filter(|flag| preamble::is_image_watermark(flag) && Path::new(flag).is_absolute())
+    else {
+        return Ok(Cow::Borrowed(options));
+    };
+    // The CLI canonicalizes images that exist; match either form of the root.
+    let roots = [
+        std::fs::canonicalize(root_dir).ok(),
+        std::path::absolute(root_dir).ok(),
+    ];
+    let Some(relative) = roots
+        .iter()
+        .flatten()
+        .find_map(|root| Path::new(image).strip_prefix(root).ok())
+    else {
+        return Err(SilkprintError::WatermarkOutsideRoot {
+            path: image.to_string(),
+            root: root_dir.display().to_string(),
+        });
+    };
+    let parts: Vec<_> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect();
+    Ok(Cow::Owned(RenderOptions {
+        watermark: Some(format!("/{}", parts.join("/"))),
+        ..options.clone()
+    }))
 }
 
 /// Orchestrates the pipeline up to Typst source generation (no compilation).
@@ -87,39 +227,164 @@ pub fn render_to_typst_source(
     theme: &ResolvedTheme,
     warnings: &mut WarningCollector,
 ) -> Result<String, SilkprintError> {
-    let arena = comrak::Arena::new();
-    let root = markdown::parse(&arena, body);
-    let prepared_images = image::PreparedImages::prepare(
-        root,
+    let document = prepare_document(
+        body,
+        front_matter,
+        input_path,
         image::ImageMode::TypstOnly,
-        input_path.and_then(Path::parent),
+        options,
+        theme,
         warnings,
-    );
-    markdown::check_content(root, warnings);
+    )?;
+    Ok(document.typst_source)
+}
 
-    let preamble = preamble::generate(theme, front_matter, options);
-    let (content, _mermaid_sources) = markdown::emit_typst(root, theme, &prepared_images, warnings);
-    Ok(format!("{preamble}\n\n{content}"))
+/// Lower the blocks that turn into Typst directly: ```` ```typst ```` blocks
+/// (with `--allow-raw-typst`) and column/landscape layout directives.
+pub(crate) fn lower_typst_blocks<'a>(
+    arena: &'a comrak::Arena<'a>,
+    root: &'a comrak::nodes::AstNode<'a>,
+    options: &RenderOptions,
+    page_layout: &layout::Layout,
+) {
+    if options.allow_raw_typst {
+        template::lower_raw_typst(root);
+    }
+    layout::lower_layout_directives(arena, root, page_layout);
 }
 
+/// Lower citations and return the `#bibliography(...)` call to append.
+///
+/// Empty when the document declares no bibliography or cites nothing.
+fn lower_citations<'a>(
+    arena: &'a comrak::Arena<'a>,
+    root: &'a comrak::nodes::AstNode<'a>,
+    front_matter: Option<&FrontMatter>,
+    doc_dir: &Path,
+    warnings: &mut WarningCollector,
+) -> String {
+    let Some(bibliography) = bibliography::Bibliography::load(front_matter, doc_dir, warnings)
+    else {
+        return String::new();
+    };
+    if bibliography.lower_citations(arena, root, warnings) {
+        bibliography.to_typst(doc_dir)
+    } else {
+        String::new()
+    }
+}
+
+/// Orchestrates the standalone HTML export: parse → themed HTML document.
+///
+/// Images are loaded the same way as for PDF output (remote images are
+/// fetched) so they can be inlined as data URIs.
 pub fn render_to_html_source(
     body: &str,
+    front_matter: Option<&FrontMatter>,
     input_path: Option<&Path>,
+    theme: &ResolvedTheme,
     validate_links: bool,
     warnings: &mut WarningCollector,
 ) -> Result<String, SilkprintError> {
     let arena = comrak::Arena::new();
     let root = markdown::parse(&arena, body);
+    let root_dir = input_path.and_then(Path::parent);
+    let prepared_images =
+        image::PreparedImages::prepare(root, image::ImageMode::Compile, root_dir, warnings);
     markdown::check_content(root, warnings);
     if validate_links {
         linkcheck::validate_links(root, input_path, warnings);
     }
-    let mut html = String::new();
-    comrak::format_html(root, &markdown::comrak_options(), &mut html).map_err(|err| {
-        SilkprintError::RenderFailed {
-            details: err.to_string(),
-            hint: "failed to export Markdown as HTML".to_string(),
-        }
-    })?;
-    Ok(html)
+    Ok(html_export::emit_document(
+        root,
+        theme,
+        front_matter,
+        &prepared_images,
+        root_dir,
+        warnings,
+    ))
+}
+
+/// Orchestrates book mode: every chapter → one Typst document → PDF.
+///
+/// The book's front matter drives the preamble, so there is exactly one
+/// title page and one outline spanning all chapters.
+pub fn render_book_pipeline(
+    engine: &typst::Engine,
+    book: &book::Book,
+    options: &RenderOptions,
+    theme: &ResolvedTheme,
+    warnings: &mut WarningCollector,
+) -> Result<Vec<u8>, SilkprintError> {
+    let assembled = book::assemble(book, theme, image::ImageMode::Compile, options, warnings)?;
+    let preamble = book_preamble(book, options, theme, warnings)?;
+    conformance::enforce(options.pdf_standard, warnings)?;
+
+    let mermaid_svgs = if assembled.mermaid_sources.is_empty() {
+        HashMap::new()
+    } else {
+        tracing::info!(
+            count = assembled.mermaid_sources.len(),
+            "rendering mermaid diagrams"
+        );
+        mermaid::render_all(&assembled.mermaid_sources, theme, warnings)
+    };
+
+    let typst_source = format!("{preamble}\n\n{}", assembled.content);
+    engine.compile_to_pdf(
+        &typst_source,
+        theme,
+        &book.root,
+        &mermaid_svgs,
+        &assembled.remote_assets,
+        typst::PdfSettings::from_options(options),
+    )
+}
+
+/// Orchestrates book mode up to Typst source generation (no compilation).
+pub fn render_book_to_typst_source(
+    book: &book::Book,
+    options: &RenderOptions,
+    theme: &ResolvedTheme,
+    warnings: &mut WarningCollector,
+) -> Result<String, SilkprintError> {
+    let assembled = book::assemble(book, theme, image::ImageMode::TypstOnly, options, warnings)?;
+    let preamble = book_preamble(book, options, theme, warnings)?;
+    Ok(format!("{preamble}\n\n{}", assembled.content))
+}
+
+/// The book's preamble, with the manifest's or theme's template, if any.
+fn book_preamble(
+    book: &book::Book,
+    options: &RenderOptions,
+    theme: &ResolvedTheme,
+    warnings: &mut WarningCollector,
+) -> Result<String, SilkprintError> {
+    let manifest_dir = book
+        .intro_origin
+        .as_ref()
+        .and_then(|(manifest, _)| manifest.parent())
+        .unwrap_or(&book.root);
+    let custom = template::Template::load(
+        theme,
+        book.front_matter.as_ref(),
+        manifest_dir,
+        options.allow_raw_typst,
+        warnings,
+    );
+    Ok(preamble::generate_with_template(
+        theme,
+        book.front_matter.as_ref(),
+        &book_options(book, &rooted_watermark(options, &book.root)?),
+        custom.as_ref().map(|t| t.source.as_str()),
+    ))
+}
+
+/// Books get a table of contents unless the CLI or manifest turns it off.
+fn book_options(book: &book::Book, options: &RenderOptions) -> RenderOptions {
+    let manifest_toc = book.front_matter.as_ref().and_then(|fm| fm.toc);
+    RenderOptions {
+        toc: Some(options.toc.or(manifest_toc).unwrap_or(true)),
+        ..options.clone()
+    }
 }
@@ -1,28 +1,44 @@
 use std::fmt::Write;
+use std::path::Path;
 
 use crate::RenderOptions;
-use crate::render::frontmatter::FrontMatter;
-use crate::theme::ResolvedTheme;
+use crate::render::escape::is_typst_length;
+use crate::render::frontmatter::{FrontMatter, RunningOverride};
+use crate::render::layout::Layout;
+use crate::theme::tokens::RunningTokens;
+use crate::theme::{ResolvedTheme, resolve_color_ref};
 
 /// Generate the Typst preamble (set/show rules) from theme + front matter + options.
 ///
 /// Produces `#set` and `#show` rules that configure page layout, typography,
 /// headings, code blocks, links, tables, blockquotes, footnotes, and more.
 /// The preamble is a standalone Typst fragment prepended to the emitted content.
-#[allow(clippy::too_many_lines)]
 pub fn generate(
     theme: &ResolvedTheme,
     front_matter: Option<&FrontMatter>,
     options: &RenderOptions,
+) -> String {
+    generate_with_template(theme, front_matter, options, None)
+}
+
+/// [`generate`], with a custom template's source inlined after the built-in
+/// rules and before the title page and outline, so its rules apply to them.
+#[allow(clippy::too_many_lines)]
+pub fn generate_with_template(
+    theme: &ResolvedTheme,
+    front_matter: Option<&FrontMatter>,
+    options: &RenderOptions,
+    template: Option<&str>,
 ) -> String {
     let mut out = String::with_capacity(4096);
     let t = &theme.tokens;
+    let layout = Layout::resolve(theme, front_matter);
 
     // ─── Document Metadata ───────────────────────────────────────
     emit_document_metadata(&mut out, front_matter);
 
     // ─── Page Setup ──────────────────────────────────────────────
-    emit_page_setup(&mut out, t, options);
+    emit_page_setup(&mut out, t, front_matter, options, &layout);
 
     // ─── Syntax Highlighting Theme ───────────────────────────────
     out.push_str("#set raw(theme: \"/__silkprint_theme.tmTheme\")\n\n");
@@ -37,7 +53,7 @@ pub fn generate(
     emit_paragraph_setup(&mut out, t);
 
     // ─── Headings ────────────────────────────────────────────────
-    emit_heading_rules(&mut out, t);
+    emit_heading_rules(&mut out, t, front_matter);
 
     // ─── Code Blocks ─────────────────────────────────────────────
     emit_code_block_rule(&mut out, t);
@@ -63,10 +79,22 @@ pub fn generate(
     // ─── Footnotes ───────────────────────────────────────────────
     emit_footnote_rule(&mut out, t);
 
+    // ─── Bibliography ────────────────────────────────────────────
+    if front_matter.is_some_and(|fm| fm.bibliography.is_some()) {
+        emit_bibliography_rule(&mut out, t);
+    }
+
+    // ─── Custom Template ──────────────────────────────────────────
+    if let Some(template) = template {
+        out.push_str("// Custom template\n");
+        out.push_str(template.trim_end());
+        out.push_str("\n\n");
+    }
+
     // ─── Title Page ───────────────────────────────────────────────
     let show_title_page = options.title_page.unwrap_or(t.title_page.enabled);
     if show_title_page {
-        emit_title_page(&mut out, front_matter, t);
+        emit_title_page(&mut out, front_matter, t, &layout);
     }
 
     // ─── Table of Contents ────────────────────────────────────────
@@ -83,10 +111,15 @@ pub fn generate(
         let depth = front_matter
             .and_then(|fm| fm.toc_depth)
             .unwrap_or(theme_depth);
-        out.push_str("#outline(\n");
-        let _ = writeln!(out, "  depth: {depth},");
-        out.push_str(")\n");
-        out.push_str("#pagebreak()\n\n");
+        if layout.columns > 1 {
+            // The outline gets its own full-width page.
+            let _ = writeln!(out, "#page(columns: 1)[#outline(depth: {depth})]\n");
+        } else {
+            out.push_str("#outline(\n");
+            let _ = writeln!(out, "  depth: {depth},");
+            out.push_str(")\n");
+            out.push_str("#pagebreak()\n\n");
+        }
     }
 
     out
@@ -118,8 +151,9 @@ fn emit_document_metadata(out: &mut String, front_matter: Option<&FrontMatter>)
 
     let has_title = fm.title.is_some();
     let has_author = fm.author.is_some();
+    let has_classification = fm.classification.is_some();
 
-    if !has_title && !has_author {
+    if !has_title && !has_author && !has_classification {
         return;
     }
 
@@ -130,14 +164,29 @@ fn emit_document_metadata(out: &mut String, front_matter: Option<&FrontMatter>)
     if let Some(author) = &fm.author {
         let _ = writeln!(out, "  author: (\"{}\",),", escape_typst_string(author));
     }
+    if let Some(classification) = &fm.classification {
+        let _ = writeln!(
+            out,
+            "  keywords: (\"{}\",),",
+            escape_typst_string(classification)
+        );
+    }
     out.push_str(")\n\n");
 }
 
 fn emit_page_setup(
     out: &mut String,
     t: &crate::theme::tokens::ThemeTokens,
+    front_matter: Option<&FrontMatter>,
     options: &RenderOptions,
+    layout: &Layout,
 ) {
+    let header = running(&t.header, front_matter.and_then(|fm| fm.header.as_ref()));
+    let footer = running(&t.footer, front_matter.and_then(|fm| fm.footer.as_ref()));
+    if header.is_some() || footer.is_some() {
+        emit_running_helpers(out);
+    }
+
     let paper = options.paper.as_typst_str();
     let margin_top = default_if_empty(&t.page.margin_top, "25mm");
     let margin_bottom = default_if_empty(&t.page.margin_bottom, "30mm");
@@ -155,23 +204,370 @@ fn emit_page_setup(
         let _ = writeln!(out, "  fill: rgb(\"{}\"),", t.page.background);
     }
 
+    if layout.columns > 1 {
+        let _ = writeln!(out, "  columns: {},", layout.columns);
+    }
+
     // Page numbering
+    let fmt = default_if_empty(&t.page_numbers.format, "1");
+    let position = &t.page_numbers.position;
+    let align = if position.contains("left") {
+        "left"
+    } else if position.contains("right") {
+        "right"
+    } else {
+        "center"
+    };
     if t.page_numbers.enabled {
-        let fmt = default_if_empty(&t.page_numbers.format, "1");
         let _ = writeln!(out, "  numbering: \"{fmt}\",");
+        let _ = writeln!(out, "  number-align: {align} + bottom,");
+    }
 
-        let position = &t.page_numbers.position;
-        let align = if position.contains("left") {
-            "left + bottom"
-        } else if position.contains("right") {
-            "right + bottom"
+    // An explicit footer replaces the one Typst numbers pages in, so a
+    // banner-only footer carries the page number itself.
+    let page_number = (t.page_numbers.enabled && footer.is_none()).then(|| {
+        format!(
+            "align({align}, counter(page).display(\"{}\"))",
+            escape_typst_string(fmt)
+        )
+    });
+    let classified = front_matter.is_some_and(|fm| fm.classification.is_some());
+    for (kind, running) in [("header", &header), ("footer", &footer)] {
+        if running.is_some() || classified {
+            let page_number = page_number.as_deref().filter(|_| kind == "footer");
+            emit_running(out, kind, running.as_ref(), page_number, t, front_matter);
+        }
+    }
+
+    emit_watermark(out, t, front_matter, options);
+
+    out.push_str(")\n\n");
+
+    if layout.columns > 1 {
+        let _ = writeln!(out, "#set columns(gutter: {})\n", layout.gutter);
+    }
+}
+
+/// Whether a `--watermark` value names an image rather than text.
+pub fn is_image_watermark(flag: &str) -> bool {
+    Path::new(flag)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            matches!(
+                ext.to_ascii_lowercase().as_str(),
+                "png" | "jpg" | "jpeg" | "gif" | "webp" | "svg"
+            )
+        })
+}
+
+/// Write the `background:` (or `foreground:`) argument of `#set page` for
+/// the front matter's or `--watermark`'s watermark, styled by the theme's
+/// `[watermark]`.
+fn emit_watermark(
+    out: &mut String,
+    t: &crate::theme::tokens::ThemeTokens,
+    front_matter: Option<&FrontMatter>,
+    options: &RenderOptions,
+) {
+    let mut watermark = front_matter
+        .and_then(|fm| fm.watermark.clone())
+        .unwrap_or_default();
+    if let Some(flag) = &options.watermark {
+        if is_image_watermark(flag) {
+            watermark.image = Some(flag.clone());
+            watermark.text = None;
         } else {
-            "center + bottom"
+            watermark.text = Some(flag.clone());
+            watermark.image = None;
+        }
+    }
+
+    let tokens = &t.watermark;
+    let opacity = watermark
+        .opacity
+        .or(tokens.opacity)
+        .unwrap_or(0.12)
+        .clamp(0.0, 1.0);
+    let (mark, rotation) = if let Some(image) = &watermark.image {
+        // Typst images have no opacity; veil the image in the page color.
+        let width = watermark
+            .size
+            .as_deref()
+            .filter(|size| is_typst_length(size))
+            .unwrap_or("50%");
+        let veil = default_if_empty(&t.page.background, "#ffffff");
+        let mark = format!(
+            "box({{ image(\"{}\", width: {width}); place(top + left, \
+             rect(width: 100%, height: 100%, fill: rgb(\"{veil}\").transparentize({:.0}%))) }})",
+            escape_typst_string(image),
+            opacity * 100.0
+        );
+        (mark, watermark.rotation.or(tokens.rotation).unwrap_or(0.0))
+    } else if let Some(text) = &watermark.text {
+        let palette = watermark
+            .color
+            .as_deref()
+            .map(|color| resolve_color_ref(color, &t.colors))
+            .filter(|color| color.starts_with('#'));
+        let color = palette.as_deref().unwrap_or_else(|| {
+            default_if_empty(&tokens.color, default_if_empty(&t.text.color, "#808080"))
+        });
+        let size = watermark
+            .size
+            .as_deref()
+            .or(Some(tokens.size.as_str()))
+            .filter(|size| is_typst_length(size))
+            .unwrap_or("72pt");
+        let font_raw = default_if_empty(&tokens.font, &t.fonts.heading);
+        let font = resolve_font_name(default_if_empty(font_raw, "Inter"), t);
+        let mark = format!(
+            "text(font: \"{font}\", size: {size}, weight: 700, \
+             fill: rgb(\"{color}\").transparentize({:.0}%), \"{}\")",
+            (1.0 - opacity) * 100.0,
+            escape_typst_string(text)
+        );
+        (
+            mark,
+            watermark.rotation.or(tokens.rotation).unwrap_or(-45.0),
+        )
+    } else {
+        return;
+    };
+
+    // The veil over an image watermark would wash out the body too, so
+    // only text goes in the foreground.
+    let layer = match watermark.layer.as_deref() {
+        Some("foreground") if watermark.image.is_none() => "foreground",
+        _ => "background",
+    };
+    let _ = writeln!(
+        out,
+        "  {layer}: place(center + horizon, rotate({rotation}deg, {mark})),"
+    );
+}
+
+/// The theme's header or footer with the front matter's overrides applied,
+/// if it is enabled and has a slot to show.
+fn running(tokens: &RunningTokens, over: Option<&RunningOverride>) -> Option<RunningTokens> {
+    let mut running = tokens.clone();
+    if let Some(over) = over {
+        running.enabled = over.enabled.unwrap_or(running.enabled);
+        for (slot, value) in [
+            (&mut running.left, &over.left),
+            (&mut running.center, &over.center),
+            (&mut running.right, &over.right),
+        ] {
+            if let Some(value) = value {
+                slot.clone_from(value);
+            }
+        }
+        running.title_page = over.title_page.unwrap_or(running.title_page);
+        running.skip_chapter_pages = over
+            .skip_chapter_pages
+            .unwrap_or(running.skip_chapter_pages);
+    }
+    let has_slot = [&running.left, &running.center, &running.right]
+        .iter()
+        .any(|slot| !slot.is_empty());
+    (running.enabled && has_slot).then_some(running)
+}
+
+/// Functions the running header and footer call from their `context`.
+fn emit_running_helpers(out: &mut String) {
+    out.push_str("// Running header/footer: the heading in force on the current page\n");
+    out.push_str("#let silkprint-running-heading(level) = {\n");
+    out.push_str("  let here-page = here().page()\n");
+    out.push_str("  let on-page = query(heading.where(level: level))\n");
+    out.push_str("    .filter(h => h.location().page() == here-page)\n");
+    out.push_str("  if on-page.len() > 0 {\n");
+    out.push_str("    on-page.first().body\n");
+    out.push_str("  } else {\n");
+    out.push_str("    let before = query(heading.where(level: level).before(here()))\n");
+    out.push_str("    if before.len() > 0 { before.last().body }\n");
+    out.push_str("  }\n");
+    out.push_str("}\n\n");
+}
+
+/// Write the `header:` or `footer:` argument of `#set page`: the running
+/// slots (or, in a footer without slots, the page number) and the
+/// classification banner, which sits outermost — above the header and
+/// below the footer — on every page.
+fn emit_running(
+    out: &mut String,
+    kind: &str,
+    running: Option<&RunningTokens>,
+    page_number: Option<&str>,
+    t: &crate::theme::tokens::ThemeTokens,
+    front_matter: Option<&FrontMatter>,
+) {
+    let banner = front_matter
+        .and_then(|fm| fm.classification.as_deref())
+        .map(|marking| classification_banner(marking, t));
+
+    let _ = writeln!(out, "  {kind}: context {{");
+    if kind == "header"
+        && let Some(banner) = &banner
+    {
+        let _ = writeln!(out, "    {banner}");
+    }
+    if let Some(running) = running {
+        emit_running_slots(out, kind, running, t, front_matter);
+    } else if let Some(page_number) = page_number {
+        let _ = writeln!(out, "    {page_number}");
+    }
+    if kind == "footer"
+        && let Some(banner) = &banner
+    {
+        let _ = writeln!(out, "    {banner}");
+    }
+    out.push_str("  },\n");
+}
+
+/// A full-width block with the classification marking, in the theme's
+/// `[classification]` colors.
+fn classification_banner(marking: &str, t: &crate::theme::tokens::ThemeTokens) -> String {
+    let c = &t.classification;
+    let background = default_if_empty(
+        &c.background,
+        default_if_empty(&t.alerts.caution_color, "#b91c1c"),
+    );
+    let color = default_if_empty(&c.color, "#ffffff");
+    let font_raw = default_if_empty(&c.font, &t.fonts.heading);
+    let font = resolve_font_name(default_if_empty(font_raw, "Inter"), t);
+    let size = match default_if_empty(&c.size, "x-small") {
+        "small" | "smaller" => default_if_empty(&t.font_sizes.small, "9pt"),
+        "x-small" => "8pt",
+        "large" | "larger" => "13pt",
+        other => other,
+    };
+    format!(
+        "block(width: 100%, fill: rgb(\"{background}\"), inset: 3pt, align(center, \
+         text(font: \"{font}\", size: {size}, weight: 700, tracking: 0.08em, \
+         fill: rgb(\"{color}\"), \"{}\")))",
+        escape_typst_string(marking)
+    )
+}
+
+/// The running slots of a header or footer in a grid, skipped on the pages
+/// the theme or front matter hides them on.
+fn emit_running_slots(
+    out: &mut String,
+    kind: &str,
+    running: &RunningTokens,
+    t: &crate::theme::tokens::ThemeTokens,
+    front_matter: Option<&FrontMatter>,
+) {
+    let font_raw = default_if_empty(&running.font, &t.fonts.body);
+    let font = resolve_font_name(default_if_empty(font_raw, "Source Serif 4"), t);
+    let size = match default_if_empty(&running.size, "small") {
+        "small" | "smaller" => default_if_empty(&t.font_sizes.small, "9pt"),
+        "x-small" => "8pt",
+        "large" | "larger" => "13pt",
+        other => other,
+    };
+    let color = default_if_empty(
+        &running.color,
+        default_if_empty(&t.page_numbers.color, &t.text.color),
+    );
+    let page_format = default_if_empty(&t.page_numbers.format, "1");
+
+    let mut hidden = Vec::new();
+    if !running.title_page {
+        hidden.push("query(<silkprint-title-page>).any(m => m.location().page() == here-page)");
+    }
+    if running.skip_chapter_pages {
+        hidden.push("query(heading.where(level: 1)).any(h => h.location().page() == here-page)");
+    }
+    let shown = if hidden.is_empty() {
+        "true".to_string()
+    } else {
+        format!("not ({})", hidden.join(" or "))
+    };
+
+    out.push_str("    let here-page = here().page()\n");
+    let _ = writeln!(out, "    if {shown} {{");
+    if color.is_empty() {
+        let _ = writeln!(out, "      set text(font: \"{font}\", size: {size})");
+    } else {
+        let _ = writeln!(
+            out,
+            "      set text(font: \"{font}\", size: {size}, fill: rgb(\"{color}\"))"
+        );
+    }
+    out.push_str("      grid(\n");
+    out.push_str("        columns: (1fr, auto, 1fr),\n");
+    out.push_str("        align: (left, center, right),\n");
+    if running.rule {
+        let rule_color = default_if_empty(&running.rule_color, "#e2e2e8");
+        let side = if kind == "header" { "bottom" } else { "top" };
+        let _ = writeln!(
+            out,
+            "        stroke: ({side}: 0.5pt + rgb(\"{rule_color}\")),"
+        );
+        let _ = writeln!(out, "        inset: ({side}: 4pt),");
+    }
+    for slot in [&running.left, &running.center, &running.right] {
+        let _ = writeln!(
+            out,
+            "        {},",
+            running_slot(slot, front_matter, page_format)
+        );
+    }
+    out.push_str("      )\n");
+    out.push_str("    }\n");
+}
+
+/// A slot template as a Typst expression joining its literal text and
+/// placeholders, or `none` when empty. Unknown placeholders are kept as
+/// text.
+fn running_slot(template: &str, front_matter: Option<&FrontMatter>, page_format: &str) -> String {
+    let literal = |s: &str| format!("\"{}\"", escape_typst_string(s));
+    let field = |value: Option<&str>| value.map_or_else(|| "none".to_string(), literal);
+    let page_format = escape_typst_string(page_format);
+
+    let mut pieces = Vec::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        let Some(len) = rest[open..].find('}') else {
+            break;
+        };
+        let close = open + len;
+        let expr = match &rest[open + 1..close] {
+            "title" => Some(field(front_matter.and_then(|fm| fm.title.as_deref()))),
+            "author" => Some(field(front_matter.and_then(|fm| fm.author.as_deref()))),
+            "date" => Some(front_matter.and_then(|fm| fm.date.as_ref()).map_or_else(
+                || "datetime.today().display()".to_string(),
+                |d| literal(&d.0),
+            )),
+            "chapter" => Some("silkprint-running-heading(1)".to_string()),
+            "section" => Some("silkprint-running-heading(2)".to_string()),
+            "page" => Some(format!("counter(page).display(\"{page_format}\")")),
+            "total" => Some(format!(
+                "numbering(\"{page_format}\", ..counter(page).final())"
+            )),
+            _ => None,
         };
-        let _ = writeln!(out, "  number-align: {align},");
+        match expr {
+            Some(expr) => {
+                if open > 0 {
+                    pieces.push(literal(&rest[..open]));
+                }
+                pieces.push(expr);
+            }
+            None => pieces.push(literal(&rest[..=close])),
+        }
+        rest = &rest[close + 1..];
+    }
+    if !rest.is_empty() {
+        pieces.push(literal(rest));
     }
 
-    out.push_str(")\n\n");
+    if pieces.is_empty() {
+        "none".to_string()
+    } else {
+        format!("({},).join()", pieces.join(", "))
+    }
 }
 
 fn emit_text_setup(
@@ -258,11 +654,45 @@ struct HeadingLevel<'a> {
     tokens: &'a crate::theme::tokens::HeadingLevelTokens,
 }
 
+/// The heading numbering pattern in effect: front matter's over the
+/// theme's, or `None` when `none` (or nothing) turns numbering off.
+pub(crate) fn heading_numbering<'a>(
+    t: &'a crate::theme::tokens::ThemeTokens,
+    front_matter: Option<&'a FrontMatter>,
+) -> Option<&'a str> {
+    let numbering = front_matter
+        .and_then(|fm| fm.numbering.as_deref())
+        .unwrap_or(&t.headings.numbering)
+        .trim();
+    (!matches!(numbering, "" | "none" | "false")).then_some(numbering)
+}
+
 #[allow(clippy::too_many_lines)]
-fn emit_heading_rules(out: &mut String, t: &crate::theme::tokens::ThemeTokens) {
+fn emit_heading_rules(
+    out: &mut String,
+    t: &crate::theme::tokens::ThemeTokens,
+    front_matter: Option<&FrontMatter>,
+) {
     let heading_font = default_if_empty(&t.fonts.heading, "Inter");
     let heading_color = default_if_empty(&t.headings.color, &t.text.color);
 
+    if let Some(numbering) = heading_numbering(t, front_matter) {
+        let _ = writeln!(
+            out,
+            "#set heading(numbering: \"{}\")",
+            escape_typst_string(numbering)
+        );
+    }
+    // References to unnumbered headings show their title instead of failing.
+    out.push_str("#show ref: it => {\n");
+    out.push_str("  let el = it.element\n");
+    out.push_str("  if el != none and el.func() == heading and el.numbering == none {\n");
+    out.push_str("    link(el.location(), el.body)\n");
+    out.push_str("  } else {\n");
+    out.push_str("    it\n");
+    out.push_str("  }\n");
+    out.push_str("}\n\n");
+
     let levels = [
         HeadingLevel {
             level: 1,
@@ -392,6 +822,13 @@ fn emit_heading_rules(out: &mut String, t: &crate::theme::tokens::ThemeTokens) {
         // Inline code in headings: strip box styling, inherit heading color/size
         out.push_str("    #show raw.where(block: false): r => r\n");
 
+        // Heading number, set off from the title
+        let number_color = default_if_empty(&t.headings.numbering_color, level_color);
+        let _ = writeln!(
+            out,
+            "    #if it.numbering != none [#text(fill: rgb(\"{number_color}\"))[#context counter(heading).display(it.numbering)]#h(0.5em)]"
+        );
+
         // H6 uppercase treatment
         if hl.tokens.uppercase == Some(true) {
             out.push_str("    #upper(it.body)\n");
@@ -557,8 +994,10 @@ fn emit_image_rules(out: &mut String, t: &crate::theme::tokens::ThemeTokens) {
     // Figure alignment
     let _ = writeln!(out, "#show figure: set align({alignment})");
 
-    // Strip "Figure N:" prefix — markdown alt text is a caption, not an academic label
-    out.push_str("#show figure.caption: it => it.body\n");
+    // Plain markdown figures are unnumbered: alt text is a caption, not an
+    // academic label. Figures labeled with `{#fig:id}` opt back in to "Figure N".
+    out.push_str("#set figure(numbering: none)\n");
+    out.push_str("#show figure.caption: it => if it.numbering == none { it.body } else { it }\n");
 
     // Figure caption styling
     let style = if t.images.caption_italic {
@@ -737,10 +1176,20 @@ fn emit_footnote_rule(out: &mut String, t: &crate::theme::tokens::ThemeTokens) {
     out.push_str("}\n");
 }
 
+fn emit_bibliography_rule(out: &mut String, t: &crate::theme::tokens::ThemeTokens) {
+    let text_size = default_if_empty(&t.font_sizes.small, "9pt");
+
+    // The references heading goes through the regular heading rules; entries
+    // are set a step smaller and ragged so hanging indents stay clean.
+    let _ = writeln!(out, "#show bibliography: set text(size: {text_size})");
+    out.push_str("#show bibliography: set par(justify: false)\n");
+}
+
 fn emit_title_page(
     out: &mut String,
     front_matter: Option<&FrontMatter>,
     t: &crate::theme::tokens::ThemeTokens,
+    layout: &Layout,
 ) {
     let Some(fm) = front_matter else { return };
     let has_content = fm.title.is_some() || fm.subtitle.is_some() || fm.author.is_some();
@@ -757,7 +1206,14 @@ fn emit_title_page(
     let date_color = default_if_empty(&t.title_page.date_color, &t.text.color);
     let sep_color = default_if_empty(&t.title_page.separator_color, "#e2e2e8");
 
-    out.push_str("#page[\n");
+    // The title spans the page's columns.
+    out.push_str(if layout.columns > 1 {
+        "#page(columns: 1)[\n"
+    } else {
+        "#page[\n"
+    });
+    // Marks the page so running headers and footers can skip it.
+    out.push_str("  #metadata(\"title-page\") <silkprint-title-page>\n");
     out.push_str("  #v(1fr)\n");
     out.push_str("  #align(center)[\n");
 
@@ -832,6 +1288,7 @@ fn escape_typst_string(s: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::render::frontmatter::Watermark;
     use crate::theme::ResolvedTheme;
     use crate::theme::tokens::ThemeTokens;
 
@@ -839,6 +1296,7 @@ mod tests {
         ResolvedTheme {
             tokens: ThemeTokens::default(),
             tmtheme_xml: String::new(),
+            fonts: Vec::new(),
         }
     }
 
@@ -869,6 +1327,213 @@ mod tests {
         assert!(preamble.contains("\"Nova\""));
     }
 
+    #[test]
+    fn template_follows_theme_rules_and_precedes_the_outline() {
+        let theme = test_theme();
+        let options = RenderOptions {
+            toc: Some(true),
+            ..RenderOptions::default()
+        };
+        let preamble =
+            generate_with_template(&theme, None, &options, Some("#set page(columns: 2)\n"));
+        let template = preamble
+            .find("#set page(columns: 2)")
+            .expect("template inlined");
+        assert!(
+            preamble
+                .find("#set par(")
+                .is_some_and(|rules| rules < template)
+        );
+        assert!(preamble.find("#outline(").is_some_and(|toc| toc > template));
+    }
+
+    #[test]
+    fn running_header_fills_slots_and_skips_the_title_page() {
+        let mut theme = test_theme();
+        theme.tokens.header.enabled = true;
+        theme.tokens.header.right = "{page} of {total}".to_string();
+        let fm = FrontMatter {
+            title: Some("Report \"Q3\"".to_string()),
+            header: Some(RunningOverride {
+                enabled: Some(true),
+                left: Some("{title} — {chapter}".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let preamble = generate(&theme, Some(&fm), &RenderOptions::default());
+        assert!(preamble.contains("#let silkprint-running-heading(level)"));
+        assert!(preamble.contains("  header: context {"));
+        assert!(
+            preamble
+                .contains("(\"Report \\\"Q3\\\"\", \" — \", silkprint-running-heading(1),).join()")
+        );
+        assert!(preamble.contains(
+            "(counter(page).display(\"1\"), \" of \", numbering(\"1\", ..counter(page).final()),).join()"
+        ));
+        assert!(preamble.contains("query(<silkprint-title-page>)"));
+        assert!(!preamble.contains("footer:"));
+
+        let fm = FrontMatter {
+            header: Some(RunningOverride {
+                enabled: Some(false),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let preamble = generate(&theme, Some(&fm), &RenderOptions::default());
+        assert!(!preamble.contains("header:"));
+    }
+
+    #[test]
+    fn watermark_uses_palette_color_and_flag_overrides_text() {
+        let mut theme = test_theme();
+        theme
+            .tokens
+            .colors
+            .insert("danger".to_string(), "#dc2626".to_string());
+        let fm = FrontMatter {
+            watermark: Some(Watermark {
+                text: Some("DRAFT".to_string()),
+                color: Some("danger".to_string()),
+                opacity: Some(0.25),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let preamble = generate(&theme, Some(&fm), &RenderOptions::default());
+        assert!(preamble.contains(
+            "  background: place(center + horizon, rotate(-45deg, text(font: \"Inter\", size: 72pt, \
+             weight: 700, fill: rgb(\"#dc2626\").transparentize(75%), \"DRAFT\"))),"
+        ));
+
+        let options = RenderOptions {
+            watermark: Some("seal.png".to_string()),
+            ..RenderOptions::default()
+        };
+        let preamble = generate(&theme, Some(&fm), &options);
+        assert!(preamble.contains("rotate(0deg, box({ image(\"seal.png\", width: 50%);"));
+        assert!(!preamble.contains("\"DRAFT\""));
+
+        let fm = FrontMatter {
+            watermark: Some(Watermark {
+                text: Some("DRAFT".to_string()),
+                size: Some("1pt, fill: red".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let preamble = generate(&theme, Some(&fm), &RenderOptions::default());
+        assert!(preamble.contains("size: 72pt, weight: 700"));
+        assert!(!preamble.contains("fill: red"));
+
+        let fm = FrontMatter {
+            watermark: Some(Watermark {
+                text: Some("DRAFT".to_string()),
+                layer: Some("foreground".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let preamble = generate(&theme, Some(&fm), &RenderOptions::default());
+        assert!(preamble.contains("  foreground: place(center + horizon, rotate(-45deg, text("));
+        let preamble = generate(&theme, Some(&fm), &options);
+        assert!(preamble.contains("  background: place(center + horizon, rotate(0deg, box({"));
+        assert!(!preamble.contains("foreground:"));
+    }
+
+    #[test]
+    fn classification_banners_every_page_and_sets_keywords() {
+        let theme = test_theme();
+        let fm = FrontMatter {
+            classification: Some("CONFIDENTIAL".to_string()),
+            ..Default::default()
+        };
+        let preamble = generate(&theme, Some(&fm), &RenderOptions::default());
+        assert!(preamble.contains("  keywords: (\"CONFIDENTIAL\",),"));
+        assert!(preamble.contains("  header: context {"));
+        assert!(preamble.contains("  footer: context {"));
+        assert_eq!(
+            preamble
+                .matches("fill: rgb(\"#ffffff\"), \"CONFIDENTIAL\")))")
+                .count(),
+            2
+        );
+        assert!(!preamble.contains("silkprint-running-heading"));
+    }
+
+    #[test]
+    fn classified_footer_keeps_the_page_number() {
+        let mut theme = test_theme();
+        theme.tokens.page_numbers.enabled = true;
+        theme.tokens.page_numbers.format = "1".to_string();
+        theme.tokens.page_numbers.position = "bottom-right".to_string();
+        let fm = FrontMatter {
+            classification: Some("CONFIDENTIAL".to_string()),
+            ..Default::default()
+        };
+        let preamble = generate(&theme, Some(&fm), &RenderOptions::default());
+        let footer = &preamble[preamble.find("  footer: context {").expect("footer")..];
+        assert!(footer.contains("    align(right, counter(page).display(\"1\"))\n"));
+
+        theme.tokens.page_numbers.enabled = false;
+        let preamble = generate(&theme, Some(&fm), &RenderOptions::default());
+        assert!(!preamble.contains("counter(page).display(\"1\")"));
+    }
+
+    #[test]
+    fn numbering_comes_from_front_matter_over_theme() {
+        let mut theme = test_theme();
+        theme.tokens.headings.numbering = "1.1".to_string();
+        let preamble = generate(&theme, None, &RenderOptions::default());
+        assert!(preamble.contains("#set heading(numbering: \"1.1\")"));
+        assert!(preamble.contains("#context counter(heading).display(it.numbering)"));
+
+        let fm = FrontMatter {
+            numbering: Some("I.a".to_string()),
+            ..Default::default()
+        };
+        let preamble = generate(&theme, Some(&fm), &RenderOptions::default());
+        assert!(preamble.contains("#set heading(numbering: \"I.a\")"));
+
+        let fm = FrontMatter {
+            numbering: Some("none".to_string()),
+            ..Default::default()
+        };
+        let preamble = generate(&theme, Some(&fm), &RenderOptions::default());
+        assert!(!preamble.contains("#set heading(numbering:"));
+        assert!(preamble.contains("#show ref: it => {"));
+    }
+
+    #[test]
+    fn page_columns_come_from_front_matter_over_theme() {
+        let mut theme = test_theme();
+        theme.tokens.page.columns = 2;
+        theme.tokens.page.column_gap = "8mm".to_string();
+        let fm = FrontMatter {
+            title: Some("Paper".to_string()),
+            toc: Some(true),
+            ..Default::default()
+        };
+        let options = RenderOptions {
+            title_page: Some(true),
+            ..Default::default()
+        };
+        let preamble = generate(&theme, Some(&fm), &options);
+        assert!(preamble.contains("  columns: 2,\n"), "got: {preamble}");
+        assert!(preamble.contains("#set columns(gutter: 8mm)"));
+        assert!(preamble.contains("#page(columns: 1)[\n"));
+        assert!(preamble.contains("#page(columns: 1)[#outline(depth: 3)]"));
+
+        let fm = FrontMatter {
+            columns: Some(1),
+            ..Default::default()
+        };
+        let preamble = generate(&theme, Some(&fm), &RenderOptions::default());
+        assert!(!preamble.contains("  columns: 2,"), "got: {preamble}");
+        assert!(!preamble.contains("#set columns("));
+    }
+
     #[test]
     fn omits_document_metadata_without_front_matter() {
         let theme = test_theme();
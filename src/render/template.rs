@@ -0,0 +1,223 @@
+//! Custom Typst templates and raw Typst blocks.
+//!
+//! A template is a `.typ` file named by front matter `template:` (relative to
+//! the document, inside its project root) or a theme's `[meta] template`
+//! (relative to the theme file). Its source is inlined after SilkPrint's own
+//! `#set` and `#show` rules, before the title page and outline, so its rules
+//! override the theme's everywhere. Because it is compiled as part of the
+//! main file, paths inside a template resolve relative to the document.
+//!
+//! A template runs arbitrary Typst, so a front matter `template:` is only
+//! honored with `--allow-raw-typst`. A theme's template is trusted like the
+//! rest of the theme: the theme is chosen on the command line, not by the
+//! document.
+//!
+//! With `--allow-raw-typst`, ```` ```typst ```` fenced blocks are emitted
+//! verbatim instead of as code listings. Use ```` ```typ ```` for a Typst
+//! listing in a document that allows raw blocks.
+
+use std::path::{Path, PathBuf};
+
+use comrak::nodes::{AstNode, NodeValue};
+
+use crate::theme::ResolvedTheme;
+use crate::warnings::{SilkprintWarning, WarningCollector};
+
+use super::frontmatter::FrontMatter;
+use super::include;
+
+/// Fence language whose blocks pass through as raw Typst.
+const RAW_TYPST_LANG: &str = "typst";
+
+/// A template's Typst source.
+#[derive(Debug, Clone)]
+pub struct Template {
+    pub source: String,
+}
+
+impl Template {
+    /// The template front matter or, failing that, the theme asks for.
+    ///
+    /// Front matter paths resolve against `doc_dir`. A front matter template
+    /// without `allow_raw_typst`, or one that can't be read, is reported and
+    /// skipped.
+    pub fn load(
+        theme: &ResolvedTheme,
+        front_matter: Option<&FrontMatter>,
+        doc_dir: &Path,
+        allow_raw_typst: bool,
+        warnings: &mut WarningCollector,
+    ) -> Option<Self> {
+        let requested = front_matter.and_then(|fm| fm.template.as_deref());
+        if let Some(target) = requested.filter(|_| !allow_raw_typst) {
+            warnings.push(SilkprintWarning::TemplateNotAllowed {
+                path: target.to_string(),
+            });
+        }
+        let path = match requested.filter(|_| allow_raw_typst) {
+            Some(target) => match include::resolve_in_project(target, doc_dir) {
+                Ok(path) => path,
+                Err(message) => {
+                    warnings.push(SilkprintWarning::TemplateNotFound {
+                        path: target.to_string(),
+                        message: message.to_string(),
+                    });
+                    return None;
+                }
+            },
+            None if theme.tokens.meta.template.is_empty() => return None,
+            None => PathBuf::from(&theme.tokens.meta.template),
+        };
+
+        match std::fs::read_to_string(&path) {
+            Ok(source) => Some(Self { source }),
+            Err(e) => {
+                warnings.push(SilkprintWarning::TemplateNotFound {
+                    path: path.display().to_string(),
+                    message: e.to_string(),
+                });
+                None
+            }
+        }
+    }
+
+    /// The template file [`Template::load`] would read. A front matter
+    /// template is resolved the same way, so it counts only once it exists
+    /// inside the project.
+    pub fn declared_file(
+        theme: &ResolvedTheme,
+        front_matter: Option<&FrontMatter>,
+        doc_dir: &Path,
+        allow_raw_typst: bool,
+    ) -> Option<PathBuf> {
+        match front_matter
+            .and_then(|fm| fm.template.as_deref())
+            .filter(|_| allow_raw_typst)
+        {
+            Some(target) => include::resolve_in_project(target, doc_dir).ok(),
+            None if theme.tokens.meta.template.is_empty() => None,
+            None => Some(PathBuf::from(&theme.tokens.meta.template)),
+        }
+    }
+}
+
+/// Turn every ```` ```typst ```` block under `root` into raw output, emitted
+/// as is.
+pub fn lower_raw_typst<'a>(root: &'a AstNode<'a>) {
+    for node in root.descendants() {
+        let raw = match &node.data.borrow().value {
+            NodeValue::CodeBlock(block)
+                if block.info.split([' ', ',', '\t']).next() == Some(RAW_TYPST_LANG) =>
+            {
+                format!("\n{}\n", block.literal.trim_end())
+            }
+            _ => continue,
+        };
+        node.data.borrow_mut().value = NodeValue::Raw(raw);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::render::markdown;
+
+    #[test]
+    fn typst_blocks_become_raw_and_other_code_stays() {
+        let arena = comrak::Arena::new();
+        let root = markdown::parse(
+            &arena,
+            "```typst\n#set text(red)\n```\n\n```typ\n#set text(blue)\n```\n",
+        );
+        lower_raw_typst(root);
+
+        let values: Vec<_> = root
+            .children()
+            .map(|node| node.data.borrow().value.clone())
+            .collect();
+        assert!(matches!(&values[0], NodeValue::Raw(raw) if raw == "\n#set text(red)\n"));
+        assert!(matches!(&values[1], NodeValue::CodeBlock(_)));
+    }
+
+    #[test]
+    fn front_matter_template_stays_in_the_project() {
+        let dir = tempdir().expect("should create tempdir");
+        let doc_dir = dir.path().join("doc");
+        std::fs::create_dir(&doc_dir).expect("should create dir");
+        std::fs::write(doc_dir.join("layout.typ"), "#set page(columns: 2)\n")
+            .expect("should write template");
+        std::fs::write(dir.path().join("outside.typ"), "secret").expect("should write file");
+        let theme = crate::theme::load_theme(
+            &crate::ThemeSource::BuiltIn("silk-light".to_string()),
+            &mut WarningCollector::new(),
+        )
+        .expect("should load theme");
+
+        let mut warnings = WarningCollector::new();
+        let front_matter = FrontMatter {
+            template: Some("layout.typ".to_string()),
+            ..Default::default()
+        };
+        let template = Template::load(&theme, Some(&front_matter), &doc_dir, true, &mut warnings)
+            .expect("should load template");
+        assert_eq!(template.source, "#set page(columns: 2)\n");
+        assert_eq!(
+            Template::declared_file(&theme, Some(&front_matter), &doc_dir, true),
+            Some(
+                doc_dir
+                    .join("layout.typ")
+                    .canonicalize()
+                    .expect("should resolve")
+            )
+        );
+
+        let front_matter = FrontMatter {
+            template: Some("../outside.typ".to_string()),
+            ..Default::default()
+        };
+        assert!(
+            Template::load(&theme, Some(&front_matter), &doc_dir, true, &mut warnings).is_none()
+        );
+        assert_eq!(warnings.warnings().len(), 1);
+        assert_eq!(warnings.warnings()[0].name(), "template-not-found");
+        assert!(Template::declared_file(&theme, Some(&front_matter), &doc_dir, true).is_none());
+    }
+
+    #[test]
+    fn front_matter_template_needs_raw_typst() {
+        let dir = tempdir().expect("should create tempdir");
+        std::fs::write(dir.path().join("layout.typ"), "#set page(columns: 2)\n")
+            .expect("should write template");
+        let theme = crate::theme::load_theme(
+            &crate::ThemeSource::BuiltIn("silk-light".to_string()),
+            &mut WarningCollector::new(),
+        )
+        .expect("should load theme");
+        let front_matter = FrontMatter {
+            template: Some("layout.typ".to_string()),
+            ..Default::default()
+        };
+
+        let mut warnings = WarningCollector::new();
+        assert!(
+            Template::load(
+                &theme,
+                Some(&front_matter),
+                dir.path(),
+                false,
+                &mut warnings
+            )
+            .is_none()
+        );
+        assert_eq!(warnings.warnings().len(), 1);
+        assert_eq!(warnings.warnings()[0].name(), "template-not-allowed");
+        assert!(
+            warnings.warnings()[0]
+                .to_string()
+                .contains("--allow-raw-typst")
+        );
+        assert!(Template::declared_file(&theme, Some(&front_matter), dir.path(), false).is_none());
+    }
+}
@@ -724,6 +724,7 @@ mod tests {
         let theme = ResolvedTheme {
             tokens: crate::theme::tokens::ThemeTokens::default(),
             tmtheme_xml: String::new(),
+            fonts: Vec::new(),
         };
         let r = Renderer {
             resolver: ContentStyleResolver::new(&theme),
@@ -748,6 +749,7 @@ mod tests {
         let theme = ResolvedTheme {
             tokens: crate::theme::tokens::ThemeTokens::default(),
             tmtheme_xml: String::new(),
+            fonts: Vec::new(),
         };
         let r = Renderer {
             resolver: ContentStyleResolver::new(&theme),
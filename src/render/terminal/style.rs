@@ -238,6 +238,7 @@ mod tests {
         ResolvedTheme {
             tokens,
             tmtheme_xml: String::new(),
+            fonts: Vec::new(),
         }
     }
 
@@ -2,10 +2,12 @@
 
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
 use image::DynamicImage;
 
 use crate::RenderOptions;
+use crate::render::typst::Engine;
 use crate::theme::ResolvedTheme;
 
 use super::super::model::Rgb;
@@ -33,20 +35,28 @@ fn math_svg(source: &str, theme: &ResolvedTheme, font_dirs: &[PathBuf], bg: Rgb)
 
 fn compile_svg(typst_source: &str, theme: &ResolvedTheme, font_dirs: &[PathBuf]) -> Option<String> {
     let empty = HashMap::new();
-    let world = crate::render::typst::build_world(
-        typst_source,
-        theme,
-        Path::new("."),
-        font_dirs,
-        &empty,
-        &empty,
-    )
-    .ok()?;
+    let world = engine(font_dirs)?.world(typst_source, theme, Path::new("."), &empty, &empty);
     let document = crate::render::typst::compile_paged(&world).ok()?;
     let page = document.pages.first()?;
     Some(typst_svg::svg(page))
 }
 
+/// The engine for the reader's font dirs, kept so each equation doesn't
+/// reload every font.
+fn engine(font_dirs: &[PathBuf]) -> Option<Engine> {
+    static ENGINE: Mutex<Option<(Vec<PathBuf>, Engine)>> = Mutex::new(None);
+
+    let mut cached = ENGINE.lock().ok()?;
+    if let Some((dirs, engine)) = cached.as_ref()
+        && dirs.as_slice() == font_dirs
+    {
+        return Some(engine.clone());
+    }
+    let engine = Engine::new(font_dirs).ok()?;
+    *cached = Some((font_dirs.to_vec(), engine.clone()));
+    Some(engine)
+}
+
 fn math_typst_source(
     source: &str,
     theme: &ResolvedTheme,
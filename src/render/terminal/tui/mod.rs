@@ -39,6 +39,7 @@ use ratatui_image::picker::Picker;
 use unicode_width::UnicodeWidthChar;
 
 use crate::ThemeSource;
+use crate::render::escape::base64_encode;
 use crate::render::origin::{DocumentOrigin, is_markdown_url, same_remote_origin};
 use crate::theme::ResolvedTheme;
 use crate::warnings::WarningCollector;
@@ -544,10 +545,7 @@ impl App {
         origin: Option<DocumentOrigin>,
         settings: super::config::ReaderSettings,
     ) -> Self {
-        let theme_names: Vec<String> = crate::theme::builtin::list_themes()
-            .into_iter()
-            .map(|t| t.name.to_string())
-            .collect();
+        let theme_names = crate::theme::registry::ThemeRegistry::discover().names();
         let theme_idx = theme_names
             .iter()
             .position(|n| n == theme_name)
@@ -2842,33 +2840,6 @@ fn copy_osc52(text: &str) -> io::Result<()> {
     stdout.flush()
 }
 
-fn base64_encode(bytes: &[u8]) -> String {
-    const TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
-    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
-    for chunk in bytes.chunks(3) {
-        let b0 = chunk[0];
-        let b1 = *chunk.get(1).unwrap_or(&0);
-        let b2 = *chunk.get(2).unwrap_or(&0);
-        out.push(char::from(TABLE[usize::from(b0 >> 2)]));
-        out.push(char::from(
-            TABLE[usize::from(((b0 & 0b0000_0011) << 4) | (b1 >> 4))],
-        ));
-        if chunk.len() > 1 {
-            out.push(char::from(
-                TABLE[usize::from(((b1 & 0b0000_1111) << 2) | (b2 >> 6))],
-            ));
-        } else {
-            out.push('=');
-        }
-        if chunk.len() > 2 {
-            out.push(char::from(TABLE[usize::from(b2 & 0b0011_1111)]));
-        } else {
-            out.push('=');
-        }
-    }
-    out
-}
-
 fn bookmarks_from_config(config: &BTreeMap<String, String>) -> Vec<Bookmark> {
     config
         .iter()
@@ -3432,6 +3403,7 @@ fn load_theme_or_default(name: &str) -> ResolvedTheme {
         crate::theme::load_theme(&fallback, &mut wc).unwrap_or_else(|_| ResolvedTheme {
             tokens: crate::theme::tokens::ThemeTokens::default(),
             tmtheme_xml: String::new(),
+            fonts: Vec::new(),
         })
     })
 }
@@ -8,10 +8,10 @@
 
 use std::collections::HashMap;
 
-use comrak::nodes::{AstNode, ListType, NodeValue, TableAlignment};
+use comrak::nodes::{AstNode, ListType, NodeValue, Sourcepos, TableAlignment};
 
 use crate::render::origin::DocumentOrigin;
-use crate::warnings::{SilkprintWarning, WarningCollector};
+use crate::warnings::{SilkprintWarning, SourceLocation, WarningCollector};
 
 use super::highlight::highlight_block;
 use super::model::{
@@ -317,6 +317,7 @@ impl<'a> Walker<'a, '_> {
             if let NodeValue::HtmlInline(html) = &nodes[i].data.borrow().value
                 && is_opening_html_tag(html)
             {
+                let location = SourceLocation::from_sourcepos(nodes[i].data.borrow().sourcepos);
                 let mut buf = html.clone();
                 let mut depth = 1_usize;
                 i += 1;
@@ -337,6 +338,7 @@ impl<'a> Walker<'a, '_> {
                 }
                 out.extend(inline_html_spans(
                     &buf,
+                    location,
                     &mut self.doc.links,
                     self.origin,
                     self.warnings,
@@ -425,7 +427,7 @@ impl<'a> Walker<'a, '_> {
                 link,
             }),
             NodeValue::FootnoteReference(f) => {
-                let n = self.footnote_number(&f.name);
+                let n = self.footnote_number(&f.name, node.data.borrow().sourcepos);
                 out.push(Span {
                     text: format!("[{n}]"),
                     role: Role::Muted,
@@ -450,8 +452,10 @@ impl<'a> Walker<'a, '_> {
                         });
                     }
                 } else {
-                    self.warnings
-                        .push(SilkprintWarning::UnsupportedHtmlTag { tag: tag_name(&h) });
+                    self.warnings.push(SilkprintWarning::UnsupportedHtmlTag {
+                        tag: tag_name(&h),
+                        location: SourceLocation::from_sourcepos(node.data.borrow().sourcepos),
+                    });
                 }
             }
             // Transparent / block-ish containers: descend.
@@ -474,13 +478,14 @@ impl<'a> Walker<'a, '_> {
 
     // ─── Footnotes ───────────────────────────────────────────────
 
-    fn footnote_number(&mut self, name: &str) -> usize {
+    fn footnote_number(&mut self, name: &str, sourcepos: Sourcepos) -> usize {
         if let Some(pos) = self.footnote_order.iter().position(|n| n == name) {
             return pos + 1;
         }
         if !self.footnotes.contains_key(name) {
             self.warnings.push(SilkprintWarning::FootnoteNotFound {
                 name: name.to_string(),
+                location: SourceLocation::from_sourcepos(sourcepos),
             });
         }
         self.footnote_order.push(name.to_string());
@@ -692,6 +697,7 @@ fn inline_html_text(html: &str) -> Option<String> {
 
 fn inline_html_spans(
     html: &str,
+    location: Option<SourceLocation>,
     links: &mut Vec<LinkTarget>,
     origin: Option<&DocumentOrigin>,
     warnings: &mut WarningCollector,
@@ -703,6 +709,7 @@ fn inline_html_spans(
     if spans.is_empty() && warnings.warnings().len() == before {
         warnings.push(SilkprintWarning::UnsupportedHtmlTag {
             tag: tag_name(html),
+            location,
         });
     }
     spans
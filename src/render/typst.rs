@@ -4,19 +4,22 @@
 //! giving full control over font loading, file resolution, and compilation
 //! without depending on a third-party wrapper.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
 
-use typst::foundations::{Bytes, Datetime};
+use typst::foundations::{Bytes, Datetime, Smart};
 use typst::layout::PagedDocument;
 use typst::syntax::{FileId, Source};
 use typst::text::{Font, FontBook};
 use typst::utils::LazyHash;
 use typst::{Library, LibraryExt, World};
+use typst_pdf::PdfStandards;
 
 use crate::error::SilkprintError;
 use crate::theme::ResolvedTheme;
 use crate::warnings::WarningCollector;
+use crate::{PdfStandard, RenderOptions};
 
 /// The virtual path where the tmTheme XML is served to Typst.
 ///
@@ -29,9 +32,9 @@ const TMTHEME_VPATH: &str = "/__silkprint_theme.tmTheme";
 /// Provides the compiler with everything it needs: standard library, fonts,
 /// source files, and file resolution rooted at the input document's directory.
 pub(crate) struct SilkWorld {
-    library: LazyHash<Library>,
-    book: LazyHash<FontBook>,
-    fonts: Vec<Font>,
+    resources: Arc<Resources>,
+    /// The engine's fonts, or those plus the theme's own font files.
+    fonts: Arc<FontSet>,
     main_source: Source,
     main_id: FileId,
     #[cfg(not(target_arch = "wasm32"))]
@@ -41,40 +44,31 @@ pub(crate) struct SilkWorld {
     mermaid_svgs: HashMap<String, Vec<u8>>,
     /// Virtual remote image files keyed by path (e.g., `/__remote_image_0.png`).
     remote_images: HashMap<String, Vec<u8>>,
+    /// Fixed Unix time for `datetime.today()`; the system clock when `None`.
+    now: Option<i64>,
+    /// Hashes of the files served to the compiler, by path, so the PDF's
+    /// document identifier changes with the assets and not only the source.
+    loaded: Mutex<BTreeMap<String, u128>>,
 }
 
 impl SilkWorld {
-    /// Construct a new world from Typst source, font data, theme, and root directory.
+    /// Construct a new world from Typst source, shared resources, theme, and root directory.
     fn new(
         typst_source: &str,
         theme: &ResolvedTheme,
         #[cfg(not(target_arch = "wasm32"))] root_dir: &Path,
         #[cfg(target_arch = "wasm32")] _root_dir: &Path,
-        font_data: Vec<Vec<u8>>,
+        resources: Arc<Resources>,
         mermaid_svgs: HashMap<String, Vec<u8>>,
         remote_images: HashMap<String, Vec<u8>>,
     ) -> Self {
         // Build the main source — detached (no package, virtual path "main.typ")
         let main_source = Source::detached(typst_source);
         let main_id = main_source.id();
-
-        // Build the font book and font collection from raw font bytes
-        let mut book = FontBook::new();
-        let mut fonts = Vec::new();
-
-        for data in font_data {
-            let bytes = Bytes::new(data);
-            for font in Font::iter(bytes) {
-                book.push(font.info().clone());
-                fonts.push(font);
-            }
-        }
-
-        tracing::debug!(font_count = fonts.len(), "loaded fonts into SilkWorld");
+        let fonts = resources.fonts_for(theme);
 
         Self {
-            library: LazyHash::new(Library::default()),
-            book: LazyHash::new(book),
+            resources,
             fonts,
             main_source,
             main_id,
@@ -83,34 +77,41 @@ impl SilkWorld {
             tmtheme_data: theme.tmtheme_xml.as_bytes().to_vec(),
             mermaid_svgs,
             remote_images,
+            now: None,
+            loaded: Mutex::new(BTreeMap::new()),
         }
     }
-}
-
-impl World for SilkWorld {
-    fn library(&self) -> &LazyHash<Library> {
-        &self.library
-    }
-
-    fn book(&self) -> &LazyHash<FontBook> {
-        &self.book
-    }
-
-    fn main(&self) -> FileId {
-        self.main_id
-    }
 
-    fn source(&self, id: FileId) -> typst::diag::FileResult<Source> {
-        if id == self.main_id {
-            Ok(self.main_source.clone())
-        } else {
-            Err(typst::diag::FileError::NotFound(
-                id.vpath().as_rooted_path().to_path_buf(),
-            ))
+    /// Swap in a new revision of the document, keeping fonts and the library.
+    ///
+    /// The main source keeps its file id and is edited in place, so Typst's
+    /// memoized parsing and layout carry over to the next compilation.
+    fn update(
+        &mut self,
+        typst_source: &str,
+        theme: &ResolvedTheme,
+        #[cfg(not(target_arch = "wasm32"))] root_dir: &Path,
+        #[cfg(target_arch = "wasm32")] _root_dir: &Path,
+        mermaid_svgs: HashMap<String, Vec<u8>>,
+        remote_images: HashMap<String, Vec<u8>>,
+    ) {
+        self.main_source.replace(typst_source);
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.root = root_dir.to_path_buf();
         }
+        self.tmtheme_data = theme.tmtheme_xml.as_bytes().to_vec();
+        self.fonts = self.resources.fonts_for(theme);
+        self.mermaid_svgs = mermaid_svgs;
+        self.remote_images = remote_images;
+        self.loaded
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
     }
 
-    fn file(&self, id: FileId) -> typst::diag::FileResult<Bytes> {
+    /// The bytes behind `id`: a virtual asset, or a file under the root.
+    fn read_file(&self, id: FileId) -> typst::diag::FileResult<Bytes> {
         let vpath = id.vpath();
         let path_str = vpath.as_rooted_path().to_string_lossy();
 
@@ -157,36 +158,88 @@ impl World for SilkWorld {
             vpath.as_rooted_path().to_path_buf(),
         ))
     }
+}
+
+impl World for SilkWorld {
+    fn library(&self) -> &LazyHash<Library> {
+        &self.resources.library
+    }
+
+    fn book(&self) -> &LazyHash<FontBook> {
+        &self.fonts.book
+    }
+
+    fn main(&self) -> FileId {
+        self.main_id
+    }
+
+    fn source(&self, id: FileId) -> typst::diag::FileResult<Source> {
+        if id == self.main_id {
+            Ok(self.main_source.clone())
+        } else {
+            Err(typst::diag::FileError::NotFound(
+                id.vpath().as_rooted_path().to_path_buf(),
+            ))
+        }
+    }
+
+    fn file(&self, id: FileId) -> typst::diag::FileResult<Bytes> {
+        let data = self.read_file(id)?;
+        self.loaded
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(
+                id.vpath().as_rooted_path().to_string_lossy().into_owned(),
+                typst::utils::hash128(data.as_slice()),
+            );
+        Ok(data)
+    }
 
     fn font(&self, index: usize) -> Option<Font> {
-        self.fonts.get(index).cloned()
+        self.fonts.fonts.get(index).cloned()
     }
 
     fn today(&self, offset: Option<i64>) -> Option<Datetime> {
-        // SystemTime::now() panics on wasm32-unknown-unknown — return None gracefully.
-        #[cfg(target_arch = "wasm32")]
-        {
-            let _ = offset;
-            None
-        }
+        let secs = self.now.or_else(system_time)?;
+        let offset_secs = offset.unwrap_or(0) * 3600;
+        let (year, month, day, hour, minute, second) =
+            unix_to_ymd_hms(secs.checked_add(offset_secs)?);
+        Datetime::from_ymd_hms(year, month, day, hour, minute, second)
+    }
+}
 
-        #[cfg(not(target_arch = "wasm32"))]
-        {
-            let now = std::time::SystemTime::now();
-            let secs = now.duration_since(std::time::UNIX_EPOCH).ok()?.as_secs();
-            let offset_secs = offset.unwrap_or(0) * 3600;
-            let adjusted = i64::try_from(secs).ok()?.checked_add(offset_secs)?;
-            let (year, month, day, hour, minute, second) = unix_to_ymd_hms(adjusted);
-            Datetime::from_ymd_hms(year, month, day, hour, minute, second)
-        }
+/// Seconds since the Unix epoch from the system clock.
+///
+/// Returns `None` on WASM, where `SystemTime::now()` panics.
+fn system_time() -> Option<i64> {
+    #[cfg(target_arch = "wasm32")]
+    {
+        None
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let now = std::time::SystemTime::now();
+        let secs = now.duration_since(std::time::UNIX_EPOCH).ok()?.as_secs();
+        i64::try_from(secs).ok()
     }
 }
 
+/// The time to stamp into a document: the fixed time if one was given, then
+/// `SOURCE_DATE_EPOCH` (see <https://reproducible-builds.org/specs/source-date-epoch/>),
+/// and otherwise `None` for the system clock.
+fn pinned_time(settings: PdfSettings) -> Option<i64> {
+    settings.fixed_time.or_else(|| {
+        std::env::var("SOURCE_DATE_EPOCH")
+            .ok()
+            .and_then(|value| value.trim().parse().ok())
+    })
+}
+
 /// Convert a Unix timestamp (seconds since epoch) to (year, month, day, hour, minute, second).
 ///
 /// Civil-time algorithm from Howard Hinnant's date library — handles all valid
 /// Unix timestamps without external dependencies.
-#[cfg(not(target_arch = "wasm32"))]
 #[allow(
     clippy::cast_possible_truncation,
     clippy::cast_sign_loss,
@@ -217,34 +270,6 @@ fn unix_to_ymd_hms(secs: i64) -> (i32, u8, u8, u8, u8, u8) {
     (year as i32, m as u8, d as u8, hour, minute, second)
 }
 
-#[allow(clippy::implicit_hasher)]
-#[cfg_attr(not(target_arch = "wasm32"), allow(clippy::unnecessary_wraps))]
-pub(crate) fn build_world(
-    typst_source: &str,
-    theme: &ResolvedTheme,
-    root_dir: &Path,
-    font_dirs: &[PathBuf],
-    mermaid_svgs: &HashMap<String, Vec<u8>>,
-    remote_images: &HashMap<String, Vec<u8>>,
-) -> Result<SilkWorld, SilkprintError> {
-    let font_data = load_font_data(font_dirs);
-    #[cfg(target_arch = "wasm32")]
-    if font_data.is_empty() {
-        return Err(SilkprintError::RenderFailed {
-            details: "no fonts registered for the WASM renderer".to_string(),
-            hint: "Call register_font(...) for the active theme fonts before rendering. Built-in themes expect Inter, Source Serif 4, and JetBrains Mono.".to_string(),
-        });
-    }
-    Ok(SilkWorld::new(
-        typst_source,
-        theme,
-        root_dir,
-        font_data,
-        mermaid_svgs.clone(),
-        remote_images.clone(),
-    ))
-}
-
 pub(crate) fn compile_paged(world: &SilkWorld) -> Result<PagedDocument, SilkprintError> {
     let result = typst::compile::<PagedDocument>(world);
     for diag in &result.warnings {
@@ -290,10 +315,8 @@ fn load_font_data(font_dirs: &[PathBuf]) -> Vec<Vec<u8>> {
         if let Ok(entries) = std::fs::read_dir(dir) {
             for entry in entries.flatten() {
                 let path = entry.path();
-                if path.extension().is_some_and(|ext| {
-                    let e = ext.to_ascii_lowercase();
-                    e == "ttf" || e == "otf" || e == "ttc" || e == "otc"
-                }) && let Ok(data) = std::fs::read(&path)
+                if crate::fonts::is_font_file(&path)
+                    && let Ok(data) = std::fs::read(&path)
                 {
                     tracing::debug!(path = %path.display(), "loaded user font");
                     font_data.push(data);
@@ -310,6 +333,155 @@ fn load_font_data(font_dirs: &[PathBuf]) -> Vec<Vec<u8>> {
     font_data
 }
 
+/// A font book and the fonts it indexes.
+struct FontSet {
+    book: LazyHash<FontBook>,
+    fonts: Vec<Font>,
+}
+
+impl FontSet {
+    fn new(fonts: Vec<Font>) -> Self {
+        let mut book = FontBook::new();
+        for font in &fonts {
+            book.push(font.info().clone());
+        }
+        Self {
+            book: LazyHash::new(book),
+            fonts,
+        }
+    }
+}
+
+/// Theme font sets kept before the cache is cleared.
+const THEMED_FONT_SETS_MAX: usize = 16;
+
+/// Fonts and the standard library, parsed once and shared by every world.
+struct Resources {
+    library: LazyHash<Library>,
+    fonts: Arc<FontSet>,
+    /// Font sets for themes that ship font files, keyed by a hash of those
+    /// files. Reusing the same set keeps its `FontBook` hash stable, so
+    /// Typst's caches survive recompiles.
+    themed: Mutex<HashMap<u128, Arc<FontSet>>>,
+}
+
+impl Resources {
+    /// The font set for a document in `theme`.
+    ///
+    /// Theme font files go first: Typst picks the earliest of equally good
+    /// matches, so a theme's own files win over bundled fonts of the same
+    /// family.
+    fn fonts_for(&self, theme: &ResolvedTheme) -> Arc<FontSet> {
+        if theme.fonts.is_empty() {
+            return Arc::clone(&self.fonts);
+        }
+        let files: Vec<&[u8]> = theme.fonts.iter().map(Bytes::as_slice).collect();
+        let key = typst::utils::hash128(&files);
+
+        let mut themed = self.themed.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(set) = themed.get(&key) {
+            return Arc::clone(set);
+        }
+        let fonts = theme
+            .fonts
+            .iter()
+            .flat_map(|data| Font::iter(data.clone()))
+            .chain(self.fonts.fonts.iter().cloned())
+            .collect();
+        let set = Arc::new(FontSet::new(fonts));
+        if themed.len() >= THEMED_FONT_SETS_MAX {
+            themed.clear();
+        }
+        themed.insert(key, Arc::clone(&set));
+        set
+    }
+}
+
+/// A reusable Typst compiler: the parsed font book and standard library.
+///
+/// Loading fonts (decompressing the bundled set and scanning `--font-dir`s)
+/// dominates render time for small documents, so it happens once per engine.
+/// Cloning is cheap and clones share the same fonts, so one engine can serve
+/// many documents, including from several threads.
+#[derive(Clone)]
+pub struct Engine {
+    resources: Arc<Resources>,
+}
+
+impl Engine {
+    /// Load the bundled fonts plus any fonts in `font_dirs`.
+    pub fn new(font_dirs: &[PathBuf]) -> Result<Self, SilkprintError> {
+        let font_data = load_font_data(font_dirs);
+        #[cfg(target_arch = "wasm32")]
+        if font_data.is_empty() {
+            return Err(SilkprintError::RenderFailed {
+                details: "no fonts registered for the WASM renderer".to_string(),
+                hint: "Call register_font(...) for the active theme fonts before rendering. Built-in themes expect Inter, Source Serif 4, and JetBrains Mono.".to_string(),
+            });
+        }
+
+        // Build the font book and font collection from raw font bytes
+        let fonts: Vec<Font> = font_data
+            .into_iter()
+            .flat_map(|data| Font::iter(Bytes::new(data)))
+            .collect();
+
+        tracing::debug!(font_count = fonts.len(), "loaded fonts into engine");
+
+        Ok(Self {
+            resources: Arc::new(Resources {
+                library: LazyHash::new(Library::default()),
+                fonts: Arc::new(FontSet::new(fonts)),
+                themed: Mutex::new(HashMap::new()),
+            }),
+        })
+    }
+
+    /// Build a world for one document on top of the shared resources.
+    pub(crate) fn world(
+        &self,
+        typst_source: &str,
+        theme: &ResolvedTheme,
+        root_dir: &Path,
+        mermaid_svgs: &HashMap<String, Vec<u8>>,
+        remote_images: &HashMap<String, Vec<u8>>,
+    ) -> SilkWorld {
+        SilkWorld::new(
+            typst_source,
+            theme,
+            root_dir,
+            Arc::clone(&self.resources),
+            mermaid_svgs.clone(),
+            remote_images.clone(),
+        )
+    }
+
+    /// Compile Typst source to PDF bytes.
+    ///
+    /// Typst memoizes layout across calls, so documents sharing styles and
+    /// content with earlier ones compile faster; the cache is trimmed after
+    /// each compilation to keep long-running sessions bounded.
+    #[allow(clippy::implicit_hasher)]
+    pub fn compile_to_pdf(
+        &self,
+        typst_source: &str,
+        theme: &ResolvedTheme,
+        root_dir: &Path,
+        mermaid_svgs: &HashMap<String, Vec<u8>>,
+        remote_images: &HashMap<String, Vec<u8>>,
+        settings: PdfSettings,
+    ) -> Result<Vec<u8>, SilkprintError> {
+        let mut world = self.world(typst_source, theme, root_dir, mermaid_svgs, remote_images);
+        world.now = pinned_time(settings);
+        let result = export_pdf(&world, settings);
+        comemo::evict(CACHE_MAX_AGE);
+        result
+    }
+}
+
+/// Compilations a memoized Typst result may go unused before it is evicted.
+const CACHE_MAX_AGE: usize = 10;
+
 #[allow(clippy::implicit_hasher)]
 /// Compile Typst source to PDF bytes.
 ///
@@ -318,6 +490,8 @@ fn load_font_data(font_dirs: &[PathBuf]) -> Vec<Vec<u8>> {
 /// 2. Constructs a `SilkWorld` with all resources
 /// 3. Compiles the Typst source to a paged document
 /// 4. Exports the document to PDF bytes
+///
+/// Use an [`Engine`] to skip step 1 when compiling more than one document.
 pub fn compile_to_pdf(
     typst_source: &str,
     theme: &ResolvedTheme,
@@ -327,22 +501,123 @@ pub fn compile_to_pdf(
     remote_images: &HashMap<String, Vec<u8>>,
     _warnings: &mut WarningCollector,
 ) -> Result<Vec<u8>, SilkprintError> {
-    let world = build_world(
+    Engine::new(font_dirs)?.compile_to_pdf(
         typst_source,
         theme,
         root_dir,
-        font_dirs,
         mermaid_svgs,
         remote_images,
-    )?;
-    let document = compile_paged(&world)?;
+        PdfSettings::default(),
+    )
+}
+
+/// A long-lived Typst world for recompiling one document as it changes.
+///
+/// Only the source and virtual assets are swapped between revisions; the main
+/// source keeps its file id, so Typst's memoized work carries over.
+pub struct Compiler {
+    engine: Engine,
+    world: Option<SilkWorld>,
+}
+
+impl Compiler {
+    pub fn new(engine: Engine) -> Self {
+        Self {
+            engine,
+            world: None,
+        }
+    }
+
+    /// Compile a new revision of the document to PDF bytes.
+    #[allow(clippy::implicit_hasher)]
+    pub fn compile_to_pdf(
+        &mut self,
+        typst_source: &str,
+        theme: &ResolvedTheme,
+        root_dir: &Path,
+        mermaid_svgs: &HashMap<String, Vec<u8>>,
+        remote_images: &HashMap<String, Vec<u8>>,
+        settings: PdfSettings,
+    ) -> Result<Vec<u8>, SilkprintError> {
+        let mut world = match self.world.take() {
+            Some(mut world) => {
+                world.update(
+                    typst_source,
+                    theme,
+                    root_dir,
+                    mermaid_svgs.clone(),
+                    remote_images.clone(),
+                );
+                world
+            }
+            None => self
+                .engine
+                .world(typst_source, theme, root_dir, mermaid_svgs, remote_images),
+        };
+        world.now = pinned_time(settings);
+        let result = export_pdf(&world, settings);
+        self.world = Some(world);
+        comemo::evict(CACHE_MAX_AGE);
+        result
+    }
+}
+
+/// Settings applied when writing the PDF: the conformance standard, and the
+/// clock behind both the metadata timestamp and `datetime.today()`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PdfSettings {
+    pub standard: Option<PdfStandard>,
+    /// Unix seconds to use instead of the system clock. `SOURCE_DATE_EPOCH`
+    /// is consulted when this is `None`.
+    pub fixed_time: Option<i64>,
+}
+
+impl PdfSettings {
+    pub fn from_options(options: &RenderOptions) -> Self {
+        Self {
+            standard: options.pdf_standard,
+            fixed_time: options.fixed_time,
+        }
+    }
+}
+
+/// typst-pdf's standards set for `standard`. Typst tags every PDF; PDF/UA-1
+/// additionally has it validate the tag structure.
+fn pdf_standards(standard: Option<PdfStandard>) -> Result<PdfStandards, SilkprintError> {
+    let standards: &[typst_pdf::PdfStandard] = match standard {
+        None => &[],
+        Some(PdfStandard::A2b) => &[typst_pdf::PdfStandard::A_2b],
+        Some(PdfStandard::A3b) => &[typst_pdf::PdfStandard::A_3b],
+        Some(PdfStandard::Ua1) => &[typst_pdf::PdfStandard::Ua_1],
+    };
+    PdfStandards::new(standards).map_err(|message| SilkprintError::RenderFailed {
+        details: message.to_string(),
+        hint: "choose a different --pdf-standard".to_string(),
+    })
+}
+
+/// Compile a world's main source and export the document to PDF bytes.
+///
+/// The document identifier is a hash of the Typst source and of every file
+/// the compiler loaded (images, bibliographies, diagrams), so identical
+/// input yields identical bytes once the clock is pinned, and changed assets
+/// change the identifier. Fonts are not part of it.
+fn export_pdf(world: &SilkWorld, settings: PdfSettings) -> Result<Vec<u8>, SilkprintError> {
+    let document = compile_paged(world)?;
 
-    // Build PDF options — only set timestamp, everything else default.
     // Title/author come from #set document() in the Typst source, NOT PdfOptions.
-    let timestamp = build_utc_timestamp();
+    let timestamp = build_utc_timestamp(world.now);
+    let loaded = world.loaded.lock().unwrap_or_else(PoisonError::into_inner);
+    let ident = format!(
+        "{:032x}",
+        typst::utils::hash128(&(world.main_source.text(), &*loaded))
+    );
+    drop(loaded);
 
     let pdf_options = typst_pdf::PdfOptions {
+        ident: Smart::Custom(&ident),
         timestamp,
+        standards: pdf_standards(settings.standard)?,
         ..Default::default()
     };
 
@@ -362,22 +637,12 @@ pub fn compile_to_pdf(
     Ok(pdf_bytes)
 }
 
-/// Build a UTC timestamp for PDF metadata from the current system time.
+/// Build a UTC timestamp for PDF metadata from `now`, or the system time.
 ///
-/// Returns `None` on WASM where `SystemTime::now()` is unavailable.
-fn build_utc_timestamp() -> Option<typst_pdf::Timestamp> {
-    #[cfg(target_arch = "wasm32")]
-    {
-        None
-    }
-
-    #[cfg(not(target_arch = "wasm32"))]
-    {
-        let now = std::time::SystemTime::now();
-        let secs = now.duration_since(std::time::UNIX_EPOCH).ok()?.as_secs();
-        let secs_i64 = i64::try_from(secs).ok()?;
-        let (year, month, day, hour, minute, second) = unix_to_ymd_hms(secs_i64);
-        let dt = Datetime::from_ymd_hms(year, month, day, hour, minute, second)?;
-        Some(typst_pdf::Timestamp::new_utc(dt))
-    }
+/// Returns `None` on WASM when no time is fixed.
+fn build_utc_timestamp(now: Option<i64>) -> Option<typst_pdf::Timestamp> {
+    let secs = now.or_else(system_time)?;
+    let (year, month, day, hour, minute, second) = unix_to_ymd_hms(secs);
+    let dt = Datetime::from_ymd_hms(year, month, day, hour, minute, second)?;
+    Some(typst_pdf::Timestamp::new_utc(dt))
 }
@@ -0,0 +1,407 @@
+//! Theme authoring — the checks, scaffold, and specimen behind `silkprint theme`.
+//!
+//! [`check_theme`] goes further than [`load_theme`](super::load_theme): it
+//! also reports keys the engine would silently ignore and color references
+//! that never resolve, each pointing at the offending line of the TOML.
+
+use crate::ThemeSource;
+use crate::error::SilkprintError;
+use crate::warnings::{SilkprintWarning, WarningCollector};
+
+use super::contrast::ContrastLevel;
+use super::registry::{self, ThemeRegistry};
+use super::tokens::{HeadingLevelTokens, SyntaxStyleTokens, ThemeTokens};
+use super::{
+    ThemeAdjustments, ThemeText, color_fields, find_suggestions, load_toml_source,
+    resolve_color_ref,
+};
+
+/// A Markdown document that exercises every [`ThemeTokens`] section, for
+/// `silkprint theme preview`.
+pub const SPECIMEN: &str = include_str!("specimen.md");
+
+/// Everything [`check_theme`] found wrong with a theme.
+#[derive(Debug)]
+pub struct ThemeCheck {
+    /// Unknown keys and unresolved color references, as `ThemeInvalid`.
+    pub errors: Vec<SilkprintError>,
+    /// Contrast and font warnings raised while loading the theme.
+    pub warnings: Vec<SilkprintWarning>,
+}
+
+/// Lint a theme: TOML syntax, inheritance, unknown keys, unresolved color
+/// references, and WCAG contrast.
+///
+/// Syntax and inheritance problems stop the check and are returned as the
+/// error; everything else is collected into the [`ThemeCheck`]. With
+/// `fix_contrast`, failing colors come back as `ContrastAdjusted` warnings
+/// carrying the values to copy into the theme.
+pub fn check_theme(
+    source: &ThemeSource,
+    registry: &ThemeRegistry,
+    fix_contrast: Option<ContrastLevel>,
+) -> Result<ThemeCheck, SilkprintError> {
+    let text = load_toml_source(source, registry)?;
+    let mut collector = WarningCollector::new();
+    let adjustments = ThemeAdjustments {
+        fix_contrast,
+        ..ThemeAdjustments::default()
+    };
+    let theme = super::load_theme_with(source, registry, adjustments, &mut collector)?;
+
+    let mut errors = Vec::new();
+    let table = text.toml.parse::<toml::Table>().unwrap_or_default();
+    let mut path = Vec::new();
+    let mut unknown = Vec::new();
+    unknown_keys(&table, &schema(), &mut path, &mut unknown);
+    for key in unknown {
+        let message = format!("unknown key `{}`", key.join("."));
+        errors.push(invalid(&text, &key, message));
+    }
+
+    let mut tokens = theme.tokens;
+    let colors = tokens.colors.clone();
+    let mut palette: Vec<(&String, &String)> = colors.iter().collect();
+    palette.sort();
+    let mut unresolved: Vec<(String, String)> = palette
+        .into_iter()
+        .filter(|(_, value)| !value.starts_with('#'))
+        .map(|(key, value)| (format!("colors.{key}"), value.clone()))
+        .collect();
+    for (name, field) in color_fields(&mut tokens) {
+        let value = resolve_color_ref(field, &colors);
+        if !value.is_empty() && !value.starts_with('#') {
+            unresolved.push((name.to_string(), value));
+        }
+    }
+    for (name, value) in unresolved {
+        let key: Vec<String> = name.split('.').map(str::to_string).collect();
+        let message = format!("`{value}` is neither a hex color nor a [colors] key");
+        errors.push(invalid(&text, &key, message));
+    }
+
+    Ok(ThemeCheck {
+        errors,
+        warnings: collector.into_warnings(),
+    })
+}
+
+/// A new theme called `name` that extends `parent`, with the most common
+/// overrides stubbed out as comments.
+pub fn scaffold(
+    name: &str,
+    parent: &str,
+    registry: &ThemeRegistry,
+) -> Result<String, SilkprintError> {
+    if !registry::is_plain_name(name) {
+        return Err(SilkprintError::InvalidThemeName {
+            name: name.to_string(),
+        });
+    }
+    let entry = registry
+        .list()
+        .into_iter()
+        .find(|entry| entry.name == parent)
+        .ok_or_else(|| SilkprintError::ThemeNotFound {
+            name: parent.to_string(),
+            suggestions: find_suggestions(parent, &registry.names()),
+        })?;
+
+    let quote = |value: &str| toml::Value::String(value.to_string()).to_string();
+    Ok(SCAFFOLD
+        .replace("{title}", name)
+        .replace("{name}", &quote(name))
+        .replace("{variant}", &quote(&entry.variant))
+        .replace("{parent_name}", parent)
+        .replace("{parent}", &quote(parent)))
+}
+
+const SCAFFOLD: &str = r##"# {title} — a SilkPrint theme.
+#
+# Anything not set here is inherited from `{parent_name}`. Uncomment what you
+# want to change, then validate and preview the result:
+#
+#   silkprint theme check <this file>
+#   silkprint theme preview <this file>
+
+[meta]
+name = {name}
+version = "1"
+variant = {variant}
+description = ""
+extends = {parent}
+
+[colors]
+# Palette entries. Color fields in any section may name one of these keys.
+# accent = "#7c3aed"
+
+# [page]
+# background = "#ffffff"
+
+# [text]
+# color = "#1a1a2e"
+
+# [headings]
+# color = "accent"
+
+# [links]
+# color = "accent"
+
+# [code_block]
+# background = "#f5f3ff"
+
+# [syntax.keyword]
+# color = "accent"
+# bold = true
+"##;
+
+/// A `ThemeInvalid` error labelling the line that sets `key` in `text`, or
+/// the start of the file when an ancestor theme set it.
+fn invalid(text: &ThemeText, key: &[String], message: String) -> SilkprintError {
+    SilkprintError::ThemeInvalid {
+        src: miette::NamedSource::new(&text.name, text.toml.clone()),
+        span: key_span(&text.toml, key).unwrap_or((0, 0)).into(),
+        message,
+    }
+}
+
+/// Every key the theme engine reads, as a TOML table.
+///
+/// Derived from the token structs, so new fields are picked up
+/// automatically. Serialization drops `None` options, unset font sources
+/// and an unset contrast level, so those are filled in first. An empty
+/// table, like `[colors]`, accepts any key.
+fn schema() -> toml::Table {
+    let level = HeadingLevelTokens {
+        line_height: Some(0.0),
+        border: Some(false),
+        page_break_before: Some(false),
+        uppercase: Some(false),
+        letter_spacing: Some(String::new()),
+        ..HeadingLevelTokens::default()
+    };
+    let style = SyntaxStyleTokens {
+        bold: Some(false),
+        italic: Some(false),
+        ..SyntaxStyleTokens::default()
+    };
+
+    let mut tokens = ThemeTokens::default();
+    tokens.meta.enforce_contrast = "-".to_string();
+    tokens.meta.template = "-".to_string();
+    tokens.watermark.opacity = Some(0.0);
+    tokens.watermark.rotation = Some(0.0);
+    let fonts = &mut tokens.fonts;
+    for source in [
+        &mut fonts.heading_source,
+        &mut fonts.body_source,
+        &mut fonts.mono_source,
+    ] {
+        *source = "-".to_string();
+    }
+    let headings = &mut tokens.headings;
+    for slot in [
+        &mut headings.h1,
+        &mut headings.h2,
+        &mut headings.h3,
+        &mut headings.h4,
+        &mut headings.h5,
+        &mut headings.h6,
+    ] {
+        slot.clone_from(&level);
+    }
+    let syntax = &mut tokens.syntax;
+    for slot in [
+        &mut syntax.text,
+        &mut syntax.keyword,
+        &mut syntax.string,
+        &mut syntax.number,
+        &mut syntax.function,
+        &mut syntax.type_,
+        &mut syntax.comment,
+        &mut syntax.constant,
+        &mut syntax.boolean,
+        &mut syntax.operator,
+        &mut syntax.property,
+        &mut syntax.tag,
+        &mut syntax.attribute,
+        &mut syntax.variable,
+        &mut syntax.builtin,
+        &mut syntax.punctuation,
+        &mut syntax.escape,
+    ] {
+        slot.clone_from(&style);
+    }
+
+    match toml::Value::try_from(&tokens) {
+        Ok(toml::Value::Table(table)) => table,
+        _ => toml::Table::new(),
+    }
+}
+
+/// Collect the dotted paths of keys in `table` that `schema` doesn't know.
+fn unknown_keys(
+    table: &toml::Table,
+    schema: &toml::Table,
+    path: &mut Vec<String>,
+    unknown: &mut Vec<Vec<String>>,
+) {
+    for (key, value) in table {
+        path.push(key.clone());
+        match (schema.get(key), value) {
+            (None, _) => unknown.push(path.clone()),
+            (Some(toml::Value::Table(known)), toml::Value::Table(inner)) if !known.is_empty() => {
+                unknown_keys(inner, known, path, unknown);
+            }
+            _ => {}
+        }
+        path.pop();
+    }
+}
+
+/// Byte span `(offset, length)` of the line that sets `key`: a `[table]`
+/// header or a `key = value` pair, with dotted keys expanded.
+///
+/// Keys inside inline tables (`keyword = { colr = "..." }`) fall back to the
+/// line of the closest enclosing key.
+fn key_span(toml_source: &str, key: &[String]) -> Option<(usize, usize)> {
+    let mut table: Vec<String> = Vec::new();
+    let mut enclosing: Option<(usize, (usize, usize))> = None;
+    let mut offset = 0;
+    for line in toml_source.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        let full: Vec<String> = if let Some(header) = trimmed.strip_prefix('[') {
+            table = dotted(header.split(']').next().unwrap_or_default());
+            table.clone()
+        } else if let Some((name, _)) = trimmed.split_once('=') {
+            table.iter().cloned().chain(dotted(name)).collect()
+        } else {
+            continue;
+        };
+
+        let shared = full.iter().zip(key).take_while(|(a, b)| a == b).count();
+        let span = (start + line.len() - line.trim_start().len(), trimmed.len());
+        if shared == key.len() {
+            return Some(span);
+        }
+        if shared == full.len() && enclosing.is_none_or(|(depth, _)| shared > depth) {
+            enclosing = Some((shared, span));
+        }
+    }
+    enclosing.map(|(_, span)| span)
+}
+
+/// Split a TOML key like `h1.color` or `"syntax".type` into its parts.
+fn dotted(key: &str) -> Vec<String> {
+    key.split('.')
+        .map(|part| part.trim().trim_matches('"').to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(path: &str) -> Vec<String> {
+        path.split('.').map(str::to_string).collect()
+    }
+
+    #[test]
+    fn reports_unknown_keys_and_unresolved_colors_with_spans() {
+        let toml_source = "[meta]\nextends = \"silk-light\"\n\n[colors]\nbrand = \"#7c3aed\"\n\n\
+                           [headings]\ncolour = \"brand\"\nh1.color = \"brnd\"\n\n\
+                           [syntax.keyword]\nbold = true\n";
+        let source = ThemeSource::Inline(toml_source.to_string());
+
+        let check = check_theme(&source, &ThemeRegistry::default(), None).expect("should load");
+
+        let messages: Vec<(String, (usize, usize))> = check
+            .errors
+            .iter()
+            .map(|error| match error {
+                SilkprintError::ThemeInvalid { span, message, .. } => {
+                    (message.clone(), (span.offset(), span.len()))
+                }
+                other => panic!("expected ThemeInvalid, got {other:?}"),
+            })
+            .collect();
+        let colour = toml_source.find("colour").expect("key present");
+        let h1 = toml_source.find("h1.color").expect("key present");
+        assert_eq!(
+            messages,
+            vec![
+                ("unknown key `headings.colour`".to_string(), (colour, 16)),
+                (
+                    "`brnd` is neither a hex color nor a [colors] key".to_string(),
+                    (h1, 17)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn builtin_themes_pass_the_check() {
+        let registry = ThemeRegistry::default();
+        for entry in registry.list() {
+            let source = ThemeSource::BuiltIn(entry.name.clone());
+            let check = check_theme(&source, &registry, None).expect("should load");
+            assert!(
+                check.errors.is_empty(),
+                "{}: {:?}",
+                entry.name,
+                check.errors
+            );
+        }
+    }
+
+    #[test]
+    fn scaffold_extends_parent_and_checks_clean() {
+        let registry = ThemeRegistry::default();
+        let toml_source = scaffold("house", "silk-dark", &registry).expect("should scaffold");
+        assert!(toml_source.contains("extends = \"silk-dark\""));
+        assert!(toml_source.contains("variant = \"dark\""));
+
+        let source = ThemeSource::Inline(toml_source);
+        let check = check_theme(&source, &registry, None).expect("should load");
+        assert!(check.errors.is_empty(), "{:?}", check.errors);
+
+        assert!(matches!(
+            scaffold("../house", "silk-dark", &registry),
+            Err(SilkprintError::InvalidThemeName { .. })
+        ));
+        assert!(matches!(
+            scaffold("house", "silk-drak", &registry),
+            Err(SilkprintError::ThemeNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn key_span_follows_tables_and_dotted_keys() {
+        let toml_source =
+            "# [headings]\n[headings]\n  h2.color = \"x\"\n[headings.h1]\ncolor = \"y\"\n";
+        let line = toml_source.find("h2.color").expect("present");
+        assert_eq!(
+            key_span(toml_source, &keys("headings.h2.color")),
+            Some((line, 14))
+        );
+        let header = toml_source.find("[headings.h1]").expect("present");
+        assert_eq!(
+            key_span(toml_source, &keys("headings.h1")),
+            Some((header, 13))
+        );
+        assert_eq!(key_span(toml_source, &keys("page.background")), None);
+
+        let inline = "[syntax]\nkeyword = { colr = \"#7c3aed\" }\n";
+        let line = inline.find("keyword").expect("present");
+        assert_eq!(
+            key_span(inline, &keys("syntax.keyword.colr")),
+            Some((line, 30))
+        );
+    }
+}
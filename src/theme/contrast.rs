@@ -1,10 +1,49 @@
+use crate::error::SilkprintError;
 use crate::warnings::SilkprintWarning;
 
+/// WCAG conformance level that `--fix-contrast` and `[meta] enforce_contrast`
+/// repair theme colors to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContrastLevel {
+    /// 4.5:1 for text, 3:1 for large text such as headings.
+    Aa,
+    /// 7:1 for text, 4.5:1 for large text.
+    Aaa,
+}
+
+impl ContrastLevel {
+    pub fn from_str_case_insensitive(s: &str) -> Result<Self, SilkprintError> {
+        match s.to_lowercase().as_str() {
+            "aa" => Ok(Self::Aa),
+            "aaa" => Ok(Self::Aaa),
+            _ => Err(SilkprintError::InvalidContrastLevel {
+                level: s.to_string(),
+            }),
+        }
+    }
+
+    /// The minimum ratio at this level for a pair whose AA minimum is
+    /// `aa_minimum`.
+    pub fn minimum(self, aa_minimum: f64) -> f64 {
+        match self {
+            Self::Aa => aa_minimum,
+            Self::Aaa if aa_minimum < 4.5 => 4.5,
+            Self::Aaa => 7.0,
+        }
+    }
+}
+
 /// Calculate the WCAG 2.1 relative luminance of an sRGB color.
 ///
 /// Input: hex color string (e.g., "#1a1a2e").
 /// Output: luminance value between 0.0 (black) and 1.0 (white).
 pub fn relative_luminance(hex: &str) -> Option<f64> {
+    let [r_lin, g_lin, b_lin] = linear_rgb(hex)?;
+    Some(0.2126 * r_lin + 0.7152 * g_lin + 0.0722 * b_lin)
+}
+
+/// Parse a `#rrggbb` color into linear-light sRGB channels.
+fn linear_rgb(hex: &str) -> Option<[f64; 3]> {
     let hex = hex.trim_start_matches('#');
     if hex.len() != 6 {
         return None;
@@ -23,11 +62,7 @@ pub fn relative_luminance(hex: &str) -> Option<f64> {
         }
     };
 
-    let r_lin = to_linear(r);
-    let g_lin = to_linear(g);
-    let b_lin = to_linear(b);
-
-    Some(0.2126 * r_lin + 0.7152 * g_lin + 0.0722 * b_lin)
+    Some([to_linear(r), to_linear(g), to_linear(b)])
 }
 
 /// Calculate the WCAG 2.1 contrast ratio between two colors.
@@ -59,3 +94,100 @@ pub fn check_contrast(
         None
     }
 }
+
+/// The color nearest `fg_hex` in OKLCH lightness that reaches `minimum`
+/// against `bg_hex`, keeping its hue.
+///
+/// Lightness moves toward black or white, whichever needs the smaller step;
+/// chroma is reduced only as far as needed to stay inside sRGB. Returns
+/// `None` for invalid colors, or when not even black or white reaches
+/// `minimum`.
+pub fn fix_lightness(fg_hex: &str, bg_hex: &str, minimum: f64) -> Option<String> {
+    let [lightness, chroma, hue] = oklch(fg_hex)?;
+    let ratio = |hex: &str| contrast_ratio(hex, bg_hex).unwrap_or(0.0);
+    if ratio(fg_hex) >= minimum {
+        return Some(fg_hex.to_string());
+    }
+    let at = |lightness: f64| oklch_to_hex([lightness, chroma, hue]);
+
+    let mut best: Option<(f64, String)> = None;
+    for target in [0.0, 1.0] {
+        if ratio(&at(target)) < minimum {
+            continue;
+        }
+        // Bisect for the smallest change that still passes.
+        let (mut near, mut far) = (lightness, target);
+        for _ in 0..32 {
+            let mid = (near + far) / 2.0;
+            if ratio(&at(mid)) >= minimum {
+                far = mid;
+            } else {
+                near = mid;
+            }
+        }
+        let step = (far - lightness).abs();
+        if best.as_ref().is_none_or(|(best_step, _)| step < *best_step) {
+            best = Some((step, at(far)));
+        }
+    }
+    best.map(|(_, hex)| hex)
+}
+
+/// OKLCH `[lightness, chroma, hue in radians]` of a `#rrggbb` color.
+pub(crate) fn oklch(hex: &str) -> Option<[f64; 3]> {
+    let [red, green, blue] = linear_rgb(hex)?;
+    let lms = [
+        0.412_221_470_8 * red + 0.536_332_536_3 * green + 0.051_445_992_9 * blue,
+        0.211_903_498_2 * red + 0.680_699_545_1 * green + 0.107_396_956_6 * blue,
+        0.088_302_461_9 * red + 0.281_718_837_6 * green + 0.629_978_700_5 * blue,
+    ]
+    .map(f64::cbrt);
+    let lightness = 0.210_454_255_3 * lms[0] + 0.793_617_785 * lms[1] - 0.004_072_046_8 * lms[2];
+    let green_red = 1.977_998_495_1 * lms[0] - 2.428_592_205 * lms[1] + 0.450_593_709_9 * lms[2];
+    let blue_yellow = 0.025_904_037_1 * lms[0] + 0.782_771_766_2 * lms[1] - 0.808_675_766 * lms[2];
+    Some([
+        lightness,
+        green_red.hypot(blue_yellow),
+        blue_yellow.atan2(green_red),
+    ])
+}
+
+/// `#rrggbb` for an OKLCH color, reducing chroma until it fits in sRGB.
+pub(crate) fn oklch_to_hex([lightness, chroma, hue]: [f64; 3]) -> String {
+    let mut chroma = chroma;
+    loop {
+        let (green_red, blue_yellow) = (chroma * hue.cos(), chroma * hue.sin());
+        let lms = [
+            lightness + 0.396_337_777_4 * green_red + 0.215_803_757_3 * blue_yellow,
+            lightness - 0.105_561_345_8 * green_red - 0.063_854_172_8 * blue_yellow,
+            lightness - 0.089_484_177_5 * green_red - 1.291_485_548 * blue_yellow,
+        ]
+        .map(|c| c * c * c);
+        let rgb = [
+            4.076_741_662_1 * lms[0] - 3.307_711_591_3 * lms[1] + 0.230_969_929_2 * lms[2],
+            -1.268_438_004_6 * lms[0] + 2.609_757_401_1 * lms[1] - 0.341_319_396_5 * lms[2],
+            -0.004_196_086_3 * lms[0] - 0.703_418_614_7 * lms[1] + 1.707_614_701 * lms[2],
+        ];
+        if chroma < 1e-4 || rgb.iter().all(|c| (-1e-4..=1.0 + 1e-4).contains(c)) {
+            let [r, g, b] = rgb.map(encode_channel);
+            return format!("#{r:02x}{g:02x}{b:02x}");
+        }
+        chroma *= 0.95;
+    }
+}
+
+/// Gamma-encode a linear sRGB channel to 0–255.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::as_conversions
+)]
+fn encode_channel(linear: f64) -> u8 {
+    let linear = linear.clamp(0.0, 1.0);
+    let encoded = if linear <= 0.003_130_8 {
+        linear * 12.92
+    } else {
+        1.055 * linear.powf(1.0 / 2.4) - 0.055
+    };
+    (encoded * 255.0).round() as u8
+}
@@ -0,0 +1,251 @@
+//! Export a resolved theme for other renderers — web pages, JSON consumers,
+//! syntect, and opaline-themed terminal tools.
+//!
+//! Every exporter walks the same flattened token tree, so tokens added to
+//! [`ThemeTokens`](super::tokens::ThemeTokens) show up in all of them without
+//! changes here.
+
+use std::fmt::Write;
+
+use super::ResolvedTheme;
+use super::syntax::resolve_syntax_tokens;
+
+/// Every token as `(dotted.path, value)`, sorted by path, with colors
+/// resolved. Empty strings and unset options are left out.
+fn flatten(theme: &ResolvedTheme) -> Vec<(String, serde_json::Value)> {
+    fn walk(prefix: &str, value: serde_json::Value, out: &mut Vec<(String, serde_json::Value)>) {
+        match value {
+            serde_json::Value::Object(map) => {
+                for (key, value) in map {
+                    let path = if prefix.is_empty() {
+                        key
+                    } else {
+                        format!("{prefix}.{key}")
+                    };
+                    walk(&path, value, out);
+                }
+            }
+            serde_json::Value::Null => {}
+            serde_json::Value::String(s) if s.is_empty() => {}
+            value => out.push((prefix.to_string(), value)),
+        }
+    }
+
+    let mut out = Vec::new();
+    if let Ok(value) = serde_json::to_value(&theme.tokens) {
+        walk("", value, &mut out);
+    }
+    out.sort_by(|(a, _), (b, _)| a.cmp(b));
+    out
+}
+
+/// The theme as CSS custom properties on `:root`, one per token:
+/// `page.background` becomes `--sp-page-background`.
+///
+/// Font names are quoted and fallback lists comma-joined, so
+/// `font-family: var(--sp-fonts-body), var(--sp-fonts-body-fallback)` works
+/// as is. `[meta]` goes in the header comment instead.
+pub fn to_css(theme: &ResolvedTheme) -> String {
+    let quote = |s: &str| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""));
+
+    let mut css = String::with_capacity(8192);
+    let meta = &theme.tokens.meta;
+    let _ = writeln!(
+        css,
+        "/* {} — SilkPrint theme tokens */",
+        meta.name.replace("*/", "* /")
+    );
+    css.push_str(":root {\n");
+    for (path, value) in flatten(theme) {
+        if path.starts_with("meta.") {
+            continue;
+        }
+        let value = match value {
+            serde_json::Value::String(s) if path.starts_with("fonts.") => quote(&s),
+            serde_json::Value::String(s) => s,
+            serde_json::Value::Array(items) => items
+                .iter()
+                .map(|item| item.as_str().map_or_else(|| item.to_string(), quote))
+                .collect::<Vec<_>>()
+                .join(", "),
+            value => value.to_string(),
+        };
+        if value.is_empty() {
+            continue;
+        }
+        let name = path.replace(['.', '_'], "-");
+        let _ = writeln!(css, "  --sp-{name}: {value};");
+    }
+    css.push_str("}\n");
+    css
+}
+
+/// Every resolved token as one flat JSON object keyed by dotted path
+/// (`"headings.h1.color": "#1a1a2e"`), pretty-printed.
+pub fn to_json(theme: &ResolvedTheme) -> String {
+    let map: serde_json::Map<String, serde_json::Value> = flatten(theme).into_iter().collect();
+    serde_json::to_string_pretty(&serde_json::Value::Object(map)).unwrap_or_default()
+}
+
+/// The theme's syntax colors as a syntect [`Theme`](syntect::highlighting::Theme),
+/// parsed from the same tmTheme Typst highlights with.
+#[cfg(feature = "terminal")]
+pub fn to_syntect_theme(
+    theme: &ResolvedTheme,
+) -> Result<syntect::highlighting::Theme, crate::error::SilkprintError> {
+    let mut reader = std::io::Cursor::new(theme.tmtheme_xml.as_bytes());
+    syntect::highlighting::ThemeSet::load_from_reader(&mut reader).map_err(|e| {
+        crate::error::SilkprintError::ThemeInvalid {
+            src: miette::NamedSource::new("theme.tmTheme", theme.tmtheme_xml.clone()),
+            span: (0, 0).into(),
+            message: format!("generated tmTheme does not load: {e}"),
+        }
+    })
+}
+
+/// The theme as an opaline theme file (TOML), so TUI chrome built on opaline
+/// can match the documents it shows.
+///
+/// Opaline's semantic tokens (`text.primary`, `bg.panel`, `accent.primary`,
+/// …) are filled from the matching SilkPrint tokens, and the syntax roles
+/// become `code.*` tokens and bold/italic styles.
+pub fn to_opaline(theme: &ResolvedTheme) -> String {
+    let t = &theme.tokens;
+    let or = |value: &str, fallback: &str| {
+        if value.is_empty() {
+            fallback.to_string()
+        } else {
+            value.to_string()
+        }
+    };
+    let quote = |value: &str| toml::Value::String(value.to_string()).to_string();
+
+    let background = or(&t.page.background, "#ffffff");
+    let text = or(&t.text.color, "#1a1a2e");
+    let panel = or(&t.code_block.background, &background);
+    let muted = or(&t.images.caption_color, &text);
+    let accent = or(&t.links.color, &text);
+    let tokens = [
+        ("bg.base", background.clone()),
+        ("bg.panel", panel.clone()),
+        ("bg.code", or(&t.syntax.background, &panel)),
+        ("bg.highlight", or(&t.table.stripe_background, &panel)),
+        ("bg.selection", or(&t.highlight.fill, &panel)),
+        ("text.primary", text.clone()),
+        ("text.secondary", or(&t.blockquote.text_color, &text)),
+        ("text.muted", muted.clone()),
+        ("text.dim", or(&t.page_numbers.color, &muted)),
+        ("accent.primary", accent.clone()),
+        ("accent.secondary", or(&t.headings.color, &accent)),
+        ("accent.tertiary", or(&t.list.bullet_color, &accent)),
+        (
+            "border.focused",
+            or(&t.code_block.left_accent_color, &accent),
+        ),
+        ("border.unfocused", or(&t.table.row_border_color, &muted)),
+        ("success", or(&t.alerts.tip_color, &accent)),
+        ("warning", or(&t.alerts.warning_color, &accent)),
+        ("error", or(&t.alerts.caution_color, &accent)),
+        ("info", or(&t.alerts.note_color, &accent)),
+    ];
+    let syntax = resolve_syntax_tokens(&t.syntax, &t.colors);
+
+    let mut out = String::with_capacity(4096);
+    let _ = writeln!(out, "# {} — exported from SilkPrint.\n", t.meta.name);
+    out.push_str("[meta]\n");
+    let _ = writeln!(out, "name = {}", quote(&t.meta.name));
+    let _ = writeln!(out, "variant = {}", quote(&or(&t.meta.variant, "light")));
+    let _ = writeln!(out, "description = {}", quote(&t.meta.description));
+
+    out.push_str("\n[tokens]\n");
+    for (name, color) in &tokens {
+        let _ = writeln!(out, "{} = {}", quote(name), quote(color));
+    }
+    for style in syntax.iter().filter(|style| !style.foreground.is_empty()) {
+        let name = format!("code.{}", style.name);
+        let _ = writeln!(out, "{} = {}", quote(&name), quote(&style.foreground));
+    }
+
+    out.push_str("\n[styles]\n");
+    for style in syntax.iter().filter(|style| !style.foreground.is_empty()) {
+        let _ = writeln!(
+            out,
+            "{} = {{ fg = {}, bold = {}, italic = {} }}",
+            quote(&format!("code.{}", style.name)),
+            quote(&format!("code.{}", style.name)),
+            style.bold,
+            style.italic,
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ThemeSource;
+    use crate::theme::load_theme_from;
+    use crate::theme::registry::ThemeRegistry;
+    use crate::warnings::WarningCollector;
+
+    fn silk_light() -> ResolvedTheme {
+        let mut warnings = WarningCollector::new();
+        load_theme_from(
+            &ThemeSource::BuiltIn("silk-light".to_string()),
+            &ThemeRegistry::default(),
+            &mut warnings,
+        )
+        .expect("should load")
+    }
+
+    #[test]
+    fn css_has_a_property_per_token() {
+        let theme = silk_light();
+        let css = to_css(&theme);
+        let background = &theme.tokens.page.background;
+        assert!(css.contains(&format!("  --sp-page-background: {background};")));
+        assert!(css.contains("  --sp-headings-h1-color: #"), "{css}");
+        assert!(css.contains("  --sp-syntax-keyword-color: #"));
+        assert!(css.contains(&format!(
+            "  --sp-fonts-body: \"{}\";",
+            theme.tokens.fonts.body
+        )));
+        assert!(!css.contains("--sp-meta-"));
+    }
+
+    #[test]
+    fn json_is_flat_and_resolved() {
+        let theme = silk_light();
+        let json: serde_json::Value = serde_json::from_str(&to_json(&theme)).expect("valid JSON");
+        let map = json.as_object().expect("an object");
+        assert_eq!(map["meta.name"], theme.tokens.meta.name.as_str());
+        assert_eq!(map["text.color"], theme.tokens.text.color.as_str());
+        assert!(map.values().all(|value| !value.is_object()));
+    }
+
+    #[test]
+    fn opaline_export_is_valid_toml_with_chrome_tokens() {
+        let theme = silk_light();
+        let table: toml::Table = to_opaline(&theme).parse().expect("valid TOML");
+        let tokens = table["tokens"].as_table().expect("a [tokens] table");
+        assert_eq!(
+            tokens["text.primary"].as_str(),
+            Some(theme.tokens.text.color.as_str())
+        );
+        for name in [
+            "bg.panel",
+            "accent.primary",
+            "border.focused",
+            "code.keyword",
+        ] {
+            assert!(tokens.contains_key(name), "missing {name}");
+        }
+    }
+
+    #[cfg(feature = "terminal")]
+    #[test]
+    fn syntect_theme_loads_from_the_tmtheme() {
+        let theme = to_syntect_theme(&silk_light()).expect("should load");
+        assert!(theme.scopes.len() > 10);
+    }
+}
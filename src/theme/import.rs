@@ -0,0 +1,938 @@
+//! Import VS Code and `TextMate` color themes as SilkPrint themes.
+//!
+//! Scope rules are matched against [`TOKEN_SCOPE_MAP`] the way a `TextMate`
+//! highlighter picks them — the most specific selector wins, later rules
+//! break ties — so each syntax role gets the color the editor shows. Page,
+//! text, heading, link and code colors are derived from the editor's
+//! background and foreground.
+//!
+//! Every color the WCAG checks look at is moved in OKLCH lightness until it
+//! meets its AA minimum, as `--fix-contrast` does, and each change is
+//! reported as a [`ContrastAdjustment`].
+
+use std::path::Path;
+
+use crate::ThemeSource;
+use crate::error::SilkprintError;
+use crate::warnings::{SilkprintWarning, WarningCollector};
+
+use super::contrast::{contrast_ratio, fix_lightness, oklch, oklch_to_hex, relative_luminance};
+use super::registry::{self, ThemeRegistry};
+use super::syntax::TOKEN_SCOPE_MAP;
+use super::tokens::{SyntaxStyleTokens, ThemeTokens};
+
+/// The theme whose layout (fonts, sizes, spacing, and which palette key each
+/// color field names) imported themes start from.
+const TEMPLATE_THEME: &str = "silk-dark";
+
+/// Editor theme file formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditorFormat {
+    /// `TextMate` / Sublime `.tmTheme` property list.
+    TmTheme,
+    /// VS Code color theme JSON (comments and trailing commas allowed).
+    VsCode,
+}
+
+impl EditorFormat {
+    /// Guess the format from the file extension, then from the content.
+    pub fn detect(path: &Path, text: &str) -> Self {
+        let ext = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase);
+        match ext.as_deref() {
+            Some("tmtheme" | "plist" | "xml") => Self::TmTheme,
+            Some("json" | "jsonc") => Self::VsCode,
+            _ if text.trim_start().starts_with('<') => Self::TmTheme,
+            _ => Self::VsCode,
+        }
+    }
+}
+
+/// A SilkPrint theme converted from an editor theme.
+#[derive(Debug, Clone)]
+pub struct ImportedTheme {
+    /// The editor theme's name, used as `[meta] name`.
+    pub name: String,
+    /// The complete theme TOML.
+    pub toml: String,
+    /// Colors changed to meet a WCAG minimum.
+    pub adjustments: Vec<ContrastAdjustment>,
+    /// Warnings from loading the generated theme.
+    pub warnings: Vec<SilkprintWarning>,
+}
+
+/// A color that was lightened or darkened to meet a contrast minimum.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContrastAdjustment {
+    /// Element name, as in `ContrastRatio` warnings (`links`, `syntax: comment`).
+    pub element: String,
+    pub from: String,
+    pub to: String,
+    /// Contrast of the original color.
+    pub ratio: f64,
+    pub minimum: f64,
+}
+
+/// Import the editor theme at `path`.
+pub fn import_theme_file(path: &Path) -> Result<ImportedTheme, SilkprintError> {
+    let text = std::fs::read_to_string(path).map_err(|e| SilkprintError::InputRead {
+        path: path.display().to_string(),
+        source: e,
+    })?;
+    let fallback_name = path.file_stem().map_or_else(
+        || "Imported".to_string(),
+        |stem| stem.to_string_lossy().into_owned(),
+    );
+    import_theme(&text, EditorFormat::detect(path, &text), &fallback_name).map_err(|message| {
+        SilkprintError::ThemeImport {
+            path: path.display().to_string(),
+            message,
+        }
+    })
+}
+
+/// The file stem to save an imported theme under: `name` when given,
+/// otherwise the theme's name in kebab case.
+pub fn file_stem(name: Option<&str>, imported: &ImportedTheme) -> Result<String, SilkprintError> {
+    let stem = match name {
+        Some(name) => name.to_string(),
+        None => imported
+            .name
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(str::to_ascii_lowercase)
+            .collect::<Vec<_>>()
+            .join("-"),
+    };
+    if registry::is_plain_name(&stem) {
+        Ok(stem)
+    } else {
+        Err(SilkprintError::InvalidThemeName { name: stem })
+    }
+}
+
+/// Import an editor theme from its source text. `fallback_name` names the
+/// theme when the editor theme doesn't.
+pub fn import_theme(
+    text: &str,
+    format: EditorFormat,
+    fallback_name: &str,
+) -> Result<ImportedTheme, String> {
+    let editor = match format {
+        EditorFormat::TmTheme => parse_tmtheme(text)?,
+        EditorFormat::VsCode => parse_vscode(text)?,
+    };
+    convert(&editor, fallback_name)
+}
+
+/// Colors read from an editor theme, before any SilkPrint derivation.
+#[derive(Debug, Default)]
+struct EditorTheme {
+    name: Option<String>,
+    /// `"dark"` or `"light"`, when the theme says.
+    variant: Option<String>,
+    background: Option<String>,
+    foreground: Option<String>,
+    /// VS Code `textLink.foreground`.
+    link: Option<String>,
+    /// VS Code `errorForeground`.
+    error: Option<String>,
+    rules: Vec<ScopeRule>,
+}
+
+/// One scope rule: a comma-separated selector list and its style.
+#[derive(Debug)]
+struct ScopeRule {
+    selectors: Vec<String>,
+    foreground: Option<String>,
+    font_style: Option<String>,
+}
+
+// ── Conversion ─────────────────────────────────────────────────
+
+#[allow(clippy::too_many_lines)]
+fn convert(editor: &EditorTheme, fallback_name: &str) -> Result<ImportedTheme, String> {
+    let name = editor
+        .name
+        .clone()
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| fallback_name.to_string());
+
+    // Variant first: it picks the defaults for a theme without global colors.
+    let bg_hint = editor
+        .background
+        .as_deref()
+        .and_then(|bg| normalize_color(bg, [0, 0, 0]));
+    let dark = match editor.variant.as_deref() {
+        Some(variant) => variant != "light",
+        None => bg_hint.is_none_or(|bg| relative_luminance(&bg).unwrap_or(0.0) < 0.18),
+    };
+    let (bg_default, fg_default) = if dark {
+        ([0x1e, 0x1e, 0x1e], [0xd4, 0xd4, 0xd4])
+    } else {
+        ([0xff, 0xff, 0xff], [0x1e, 0x1e, 0x1e])
+    };
+    let bg = editor
+        .background
+        .as_deref()
+        .and_then(|bg| parse_hex(bg).map(|[r, g, b, _]| [r, g, b]))
+        .unwrap_or(bg_default);
+    let color = |value: Option<&str>| value.and_then(|value| normalize_color(value, bg));
+    let fg = color(editor.foreground.as_deref()).unwrap_or_else(|| to_hex(fg_default));
+    let background = to_hex(bg);
+
+    let roles: Vec<(&str, Option<&ScopeRule>)> = TOKEN_SCOPE_MAP
+        .iter()
+        .map(|(role, scopes)| (*role, best_rule(&editor.rules, scopes)))
+        .collect();
+    let role_color = |role: &str| {
+        roles
+            .iter()
+            .find(|(name, _)| *name == role)
+            .and_then(|(_, rule)| color((*rule)?.foreground.as_deref()))
+    };
+
+    let heading = role_color("keyword").unwrap_or_else(|| fg.clone());
+    let link = color(editor.link.as_deref())
+        .or_else(|| role_color("function"))
+        .unwrap_or_else(|| heading.clone());
+
+    let mut palette: Vec<(&str, String)> = vec![
+        ("background", background.clone()),
+        ("surface", blend(&background, &fg, 5)),
+        ("surface_alt", blend(&background, &fg, 8)),
+        ("border_light", blend(&background, &fg, 15)),
+        ("border_strong", blend(&background, &fg, 30)),
+        ("text_primary", fg.clone()),
+        ("text_secondary", blend(&fg, &background, 20)),
+        (
+            "text_muted",
+            role_color("comment").unwrap_or_else(|| blend(&fg, &background, 40)),
+        ),
+        ("heading", heading),
+        ("accent_blue", link),
+        (
+            "accent_green",
+            role_color("string").unwrap_or_else(|| fg.clone()),
+        ),
+        (
+            "accent_amber",
+            role_color("number").unwrap_or_else(|| fg.clone()),
+        ),
+        (
+            "accent_red",
+            color(editor.error.as_deref())
+                .or_else(|| role_color("tag"))
+                .or_else(|| role_color("variable"))
+                .unwrap_or_else(|| fg.clone()),
+        ),
+    ];
+
+    let mut tokens = template()?;
+    tokens.meta.name.clone_from(&name);
+    tokens.meta.version = "1".to_string();
+    tokens.meta.variant = if dark { "dark" } else { "light" }.to_string();
+    tokens.meta.description = format!("Imported from the {name} editor theme");
+    tokens.meta.print_safe = false;
+    tokens.meta.extends = String::new();
+    tokens.headings.color = "heading".to_string();
+    tokens.syntax.background = "surface".to_string();
+
+    let syntax = &mut tokens.syntax;
+    let slots = [
+        ("text", &mut syntax.text),
+        ("keyword", &mut syntax.keyword),
+        ("string", &mut syntax.string),
+        ("number", &mut syntax.number),
+        ("function", &mut syntax.function),
+        ("type", &mut syntax.type_),
+        ("comment", &mut syntax.comment),
+        ("constant", &mut syntax.constant),
+        ("boolean", &mut syntax.boolean),
+        ("operator", &mut syntax.operator),
+        ("property", &mut syntax.property),
+        ("tag", &mut syntax.tag),
+        ("attribute", &mut syntax.attribute),
+        ("variable", &mut syntax.variable),
+        ("builtin", &mut syntax.builtin),
+        ("punctuation", &mut syntax.punctuation),
+        ("escape", &mut syntax.escape),
+    ];
+    for (role, slot) in slots {
+        let rule = roles
+            .iter()
+            .find(|(name, _)| *name == role)
+            .and_then(|(_, rule)| *rule);
+        *slot = match rule.and_then(|rule| color(rule.foreground.as_deref())) {
+            Some(hex) => {
+                let font_style = rule.and_then(|rule| rule.font_style.as_deref());
+                SyntaxStyleTokens {
+                    color: hex,
+                    bold: font_style.map(|style| style.contains("bold")),
+                    italic: font_style.map(|style| style.contains("italic")),
+                }
+            }
+            None => SyntaxStyleTokens {
+                color: "text_primary".to_string(),
+                ..SyntaxStyleTokens::default()
+            },
+        };
+    }
+
+    let adjustments = fix_contrast(&mut tokens, &mut palette);
+    let toml = render_toml(&tokens, &palette)?;
+
+    let mut collector = WarningCollector::new();
+    super::load_theme_from(
+        &ThemeSource::Inline(toml.clone()),
+        &ThemeRegistry::default(),
+        &mut collector,
+    )
+    .map_err(|e| format!("generated theme does not load: {e}"))?;
+
+    Ok(ImportedTheme {
+        name,
+        toml,
+        adjustments,
+        warnings: collector.into_warnings(),
+    })
+}
+
+/// The template theme's tokens, with color references left unresolved.
+fn template() -> Result<ThemeTokens, String> {
+    let toml_source = super::builtin::get_builtin_theme(TEMPLATE_THEME)
+        .ok_or_else(|| format!("built-in theme '{TEMPLATE_THEME}' is missing"))?;
+    toml::from_str(toml_source).map_err(|e| e.to_string())
+}
+
+/// Serialize `tokens` with `[meta]` and the palette first, in order.
+fn render_toml(tokens: &ThemeTokens, palette: &[(&str, String)]) -> Result<String, String> {
+    let meta = toml::to_string(&tokens.meta).map_err(|e| e.to_string())?;
+    let mut rest = match toml::Value::try_from(tokens).map_err(|e| e.to_string())? {
+        toml::Value::Table(table) => table,
+        _ => toml::Table::new(),
+    };
+    rest.remove("meta");
+    rest.remove("colors");
+    let rest = toml::to_string(&rest).map_err(|e| e.to_string())?;
+
+    let mut out = format!(
+        "# {} — imported editor theme.\n\n[meta]\n{meta}\n[colors]\n",
+        tokens.meta.name
+    );
+    for (key, value) in palette {
+        out.push_str(&format!("{key} = \"{value}\"\n"));
+    }
+    out.push('\n');
+    out.push_str(&rest);
+    Ok(out)
+}
+
+/// Repair every color the WCAG checks cover that misses its AA minimum,
+/// with the same OKLCH lightness search as `--fix-contrast`.
+///
+/// A failing field that names a palette color has the palette entry fixed
+/// instead, so the generated theme keeps its named colors.
+fn fix_contrast(
+    tokens: &mut ThemeTokens,
+    palette: &mut [(&str, String)],
+) -> Vec<ContrastAdjustment> {
+    tokens.colors = palette
+        .iter()
+        .map(|(key, value)| ((*key).to_string(), value.clone()))
+        .collect();
+
+    let mut adjustments = Vec::new();
+    for pair in super::contrast_pairs(tokens) {
+        let Some((_, field)) = super::color_fields(tokens)
+            .into_iter()
+            .find(|(name, _)| *name == pair.field)
+        else {
+            continue;
+        };
+        // An earlier pair may already have fixed the same palette entry.
+        let slot = match palette.iter_mut().find(|(key, _)| *key == field.as_str()) {
+            Some((_, value)) => value,
+            None => field,
+        };
+        let Some(ratio) = contrast_ratio(slot, &pair.background) else {
+            continue;
+        };
+        if ratio >= pair.minimum {
+            continue;
+        }
+        let Some(fixed) = fix_lightness(slot, &pair.background, pair.minimum) else {
+            continue;
+        };
+        adjustments.push(ContrastAdjustment {
+            element: pair.element.to_string(),
+            from: std::mem::replace(slot, fixed.clone()),
+            to: fixed,
+            ratio,
+            minimum: pair.minimum,
+        });
+    }
+    adjustments
+}
+
+/// The rule a highlighter would apply to the first of `scopes` any rule
+/// matches: the most specific matching selector, the later rule on a tie.
+fn best_rule<'a>(rules: &'a [ScopeRule], scopes: &[&str]) -> Option<&'a ScopeRule> {
+    scopes.iter().find_map(|scope| {
+        rules
+            .iter()
+            .filter(|rule| rule.foreground.is_some())
+            .filter_map(|rule| {
+                let depth = rule
+                    .selectors
+                    .iter()
+                    .filter_map(|selector| selector_depth(selector, scope))
+                    .max()?;
+                Some((depth, rule))
+            })
+            .reduce(|best, next| if next.0 >= best.0 { next } else { best })
+            .map(|(_, rule)| rule)
+    })
+}
+
+/// How many scope segments `selector` matches in `scope`, if it matches.
+///
+/// Only the last part of a descendant selector (`source.rust keyword`) is
+/// compared; exclusions (`- comment`) are dropped.
+fn selector_depth(selector: &str, scope: &str) -> Option<usize> {
+    let selector = selector.split(" - ").next()?.split_whitespace().last()?;
+    let matches = scope == selector
+        || scope
+            .strip_prefix(selector)
+            .is_some_and(|rest| rest.starts_with('.'));
+    matches.then(|| selector.split('.').count())
+}
+
+// ── Colors ─────────────────────────────────────────────────────
+
+/// Parse `#rgb`, `#rgba`, `#rrggbb` or `#rrggbbaa` into RGBA.
+fn parse_hex(value: &str) -> Option<[u8; 4]> {
+    let hex = value.trim().strip_prefix('#')?;
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |s: &str| u8::from_str_radix(s, 16).ok();
+    let short = |i: usize| channel(&hex[i..=i].repeat(2));
+    match hex.len() {
+        3 => Some([short(0)?, short(1)?, short(2)?, 255]),
+        4 => Some([short(0)?, short(1)?, short(2)?, short(3)?]),
+        6 => Some([
+            channel(&hex[0..2])?,
+            channel(&hex[2..4])?,
+            channel(&hex[4..6])?,
+            255,
+        ]),
+        8 => Some([
+            channel(&hex[0..2])?,
+            channel(&hex[2..4])?,
+            channel(&hex[4..6])?,
+            channel(&hex[6..8])?,
+        ]),
+        _ => None,
+    }
+}
+
+/// An editor color as `#rrggbb`, with any alpha blended over `background`.
+fn normalize_color(value: &str, background: [u8; 3]) -> Option<String> {
+    let [r, g, b, a] = parse_hex(value)?;
+    let blend = |fg: u8, bg: u8| {
+        let mixed =
+            (u16::from(fg) * u16::from(a) + u16::from(bg) * (255 - u16::from(a)) + 127) / 255;
+        u8::try_from(mixed).unwrap_or(u8::MAX)
+    };
+    Some(to_hex([
+        blend(r, background[0]),
+        blend(g, background[1]),
+        blend(b, background[2]),
+    ]))
+}
+
+fn to_hex([r, g, b]: [u8; 3]) -> String {
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+/// `percent`% of the way from `from` to `to`, both `#rrggbb`, in OKLab.
+fn blend(from: &str, to: &str, percent: u8) -> String {
+    let (Some(from_lch), Some(to_lch)) = (oklch(from), oklch(to)) else {
+        return from.to_string();
+    };
+    let lab =
+        |[lightness, chroma, hue]: [f64; 3]| [lightness, chroma * hue.cos(), chroma * hue.sin()];
+    let t = f64::from(percent.min(100)) / 100.0;
+    let [l1, a1, b1] = lab(from_lch);
+    let [l2, a2, b2] = lab(to_lch);
+    let (a, b) = (a1 + (a2 - a1) * t, b1 + (b2 - b1) * t);
+    oklch_to_hex([l1 + (l2 - l1) * t, a.hypot(b), b.atan2(a)])
+}
+
+// ── VS Code JSON ───────────────────────────────────────────────
+
+fn parse_vscode(text: &str) -> Result<EditorTheme, String> {
+    let json: serde_json::Value =
+        serde_json::from_str(&strip_jsonc(text)).map_err(|e| format!("invalid JSON: {e}"))?;
+    let str_at = |value: &serde_json::Value, key: &str| {
+        value.get(key).and_then(|v| v.as_str()).map(str::to_string)
+    };
+    let colors = json.get("colors").cloned().unwrap_or_default();
+
+    let variant = str_at(&json, "type").map(|kind| {
+        if kind.to_ascii_lowercase().contains("light") {
+            "light".to_string()
+        } else {
+            "dark".to_string()
+        }
+    });
+
+    let token_colors = match json.get("tokenColors") {
+        Some(serde_json::Value::Array(entries)) => entries.as_slice(),
+        Some(serde_json::Value::String(file)) => {
+            return Err(format!(
+                "tokenColors points to '{file}'; import that file instead"
+            ));
+        }
+        _ => &[],
+    };
+
+    let mut theme = EditorTheme {
+        name: str_at(&json, "name"),
+        variant,
+        background: str_at(&colors, "editor.background"),
+        foreground: str_at(&colors, "editor.foreground"),
+        link: str_at(&colors, "textLink.foreground"),
+        error: str_at(&colors, "errorForeground"),
+        rules: Vec::new(),
+    };
+
+    for entry in token_colors {
+        let settings = entry.get("settings").cloned().unwrap_or_default();
+        let selectors: Vec<String> = match entry.get("scope") {
+            Some(serde_json::Value::String(scope)) => split_selectors(scope),
+            Some(serde_json::Value::Array(scopes)) => scopes
+                .iter()
+                .filter_map(|scope| scope.as_str())
+                .flat_map(split_selectors)
+                .collect(),
+            _ => {
+                // A rule without a scope carries the global colors, as in
+                // themes converted from tmTheme.
+                theme.background = theme.background.or_else(|| str_at(&settings, "background"));
+                theme.foreground = theme.foreground.or_else(|| str_at(&settings, "foreground"));
+                continue;
+            }
+        };
+        theme.rules.push(ScopeRule {
+            selectors,
+            foreground: str_at(&settings, "foreground"),
+            font_style: str_at(&settings, "fontStyle"),
+        });
+    }
+    Ok(theme)
+}
+
+fn split_selectors(scope: &str) -> Vec<String> {
+    scope
+        .split(',')
+        .map(str::trim)
+        .filter(|selector| !selector.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Remove `//` and `/* */` comments and trailing commas, which VS Code
+/// accepts in theme files but JSON doesn't.
+fn strip_jsonc(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            if c == '\\' {
+                out.extend(chars.next());
+            } else if c == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+        match c {
+            '"' => {
+                in_string = true;
+                out.push(c);
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        out.push('\n');
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut prev = ' ';
+                for next in chars.by_ref() {
+                    if prev == '*' && next == '/' {
+                        break;
+                    }
+                    prev = next;
+                }
+            }
+            '}' | ']' => {
+                let trimmed = out.trim_end().len();
+                if out[..trimmed].ends_with(',') {
+                    out.truncate(trimmed - 1);
+                }
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+// ── tmTheme property lists ─────────────────────────────────────
+
+fn parse_tmtheme(text: &str) -> Result<EditorTheme, String> {
+    let Plist::Dict(root) = PlistReader { rest: text }.document()? else {
+        return Err("expected a <dict> at the top of the property list".to_string());
+    };
+    let mut theme = EditorTheme {
+        name: plist_string(&root, "name"),
+        ..EditorTheme::default()
+    };
+
+    let entries = match plist_get(&root, "settings") {
+        Some(Plist::Array(entries)) => entries.as_slice(),
+        _ => return Err("missing the `settings` array".to_string()),
+    };
+    for entry in entries {
+        let Plist::Dict(entry) = entry else {
+            continue;
+        };
+        let settings: &[(String, Plist)] = match plist_get(entry, "settings") {
+            Some(Plist::Dict(settings)) => settings,
+            _ => &[],
+        };
+        match plist_string(entry, "scope") {
+            Some(scope) => theme.rules.push(ScopeRule {
+                selectors: split_selectors(&scope),
+                foreground: plist_string(settings, "foreground"),
+                font_style: plist_string(settings, "fontStyle"),
+            }),
+            None => {
+                theme.background = theme
+                    .background
+                    .or_else(|| plist_string(settings, "background"));
+                theme.foreground = theme
+                    .foreground
+                    .or_else(|| plist_string(settings, "foreground"));
+            }
+        }
+    }
+    Ok(theme)
+}
+
+/// The subset of Apple's property list format tmTheme files use.
+#[derive(Debug)]
+enum Plist {
+    Dict(Vec<(String, Plist)>),
+    Array(Vec<Plist>),
+    String(String),
+    /// Numbers, booleans, dates and data, which themes don't need.
+    Other,
+}
+
+fn plist_get<'a>(dict: &'a [(String, Plist)], key: &str) -> Option<&'a Plist> {
+    dict.iter().find(|(k, _)| k == key).map(|(_, value)| value)
+}
+
+fn plist_string(dict: &[(String, Plist)], key: &str) -> Option<String> {
+    match plist_get(dict, key)? {
+        Plist::String(value) => Some(value.clone()),
+        _ => None,
+    }
+}
+
+enum Tag<'a> {
+    Open(&'a str),
+    Close(&'a str),
+    Empty(&'a str),
+}
+
+struct PlistReader<'a> {
+    rest: &'a str,
+}
+
+impl<'a> PlistReader<'a> {
+    /// The value inside `<plist>`, or the first value in the document.
+    fn document(&mut self) -> Result<Plist, String> {
+        let before = self.rest;
+        match self.tag()? {
+            Tag::Open("plist") => self.value(),
+            Tag::Open(_) | Tag::Empty(_) => {
+                self.rest = before;
+                self.value()
+            }
+            Tag::Close(name) => Err(format!("unexpected </{name}>")),
+        }
+    }
+
+    fn value(&mut self) -> Result<Plist, String> {
+        match self.tag()? {
+            Tag::Open("dict") => {
+                let mut entries = Vec::new();
+                loop {
+                    match self.tag()? {
+                        Tag::Close("dict") => return Ok(Plist::Dict(entries)),
+                        Tag::Open("key") => {
+                            let key = self.text_until("key")?;
+                            entries.push((key, self.value()?));
+                        }
+                        _ => return Err("expected <key> in <dict>".to_string()),
+                    }
+                }
+            }
+            Tag::Open("array") => {
+                let mut items = Vec::new();
+                loop {
+                    let before = self.rest;
+                    if let Tag::Close("array") = self.tag()? {
+                        return Ok(Plist::Array(items));
+                    }
+                    self.rest = before;
+                    items.push(self.value()?);
+                }
+            }
+            Tag::Open("string") => Ok(Plist::String(self.text_until("string")?)),
+            Tag::Open(name) => {
+                self.text_until(name)?;
+                Ok(Plist::Other)
+            }
+            Tag::Empty("dict") => Ok(Plist::Dict(Vec::new())),
+            Tag::Empty("array") => Ok(Plist::Array(Vec::new())),
+            Tag::Empty("string") => Ok(Plist::String(String::new())),
+            Tag::Empty(_) => Ok(Plist::Other),
+            Tag::Close(name) => Err(format!("unexpected </{name}>")),
+        }
+    }
+
+    /// The next element tag, skipping text, declarations and comments.
+    fn tag(&mut self) -> Result<Tag<'a>, String> {
+        loop {
+            let start = self
+                .rest
+                .find('<')
+                .ok_or_else(|| "unexpected end of property list".to_string())?;
+            self.rest = &self.rest[start..];
+            if let Some(comment) = self.rest.strip_prefix("<!--") {
+                let end = comment
+                    .find("-->")
+                    .ok_or_else(|| "unterminated comment".to_string())?;
+                self.rest = &comment[end + 3..];
+                continue;
+            }
+            let end = self
+                .rest
+                .find('>')
+                .ok_or_else(|| "unterminated tag".to_string())?;
+            let inner = &self.rest[1..end];
+            self.rest = &self.rest[end + 1..];
+            if inner.starts_with(['?', '!']) {
+                continue;
+            }
+            if let Some(name) = inner.strip_prefix('/') {
+                return Ok(Tag::Close(name.trim()));
+            }
+            if let Some(name) = inner.strip_suffix('/') {
+                return Ok(Tag::Empty(
+                    name.split_whitespace().next().unwrap_or_default(),
+                ));
+            }
+            return Ok(Tag::Open(
+                inner.split_whitespace().next().unwrap_or_default(),
+            ));
+        }
+    }
+
+    /// Unescaped text up to `</name>`, consuming the closing tag.
+    fn text_until(&mut self, name: &str) -> Result<String, String> {
+        let close = format!("</{name}>");
+        let end = self
+            .rest
+            .find(&close)
+            .ok_or_else(|| format!("missing {close}"))?;
+        let text = &self.rest[..end];
+        self.rest = &self.rest[end + close.len()..];
+        Ok(text
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&quot;", "\"")
+            .replace("&apos;", "'")
+            .replace("&amp;", "&"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TMTHEME: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+  <key>name</key>
+  <string>Ember &amp; Ash</string>
+  <key>settings</key>
+  <array>
+    <dict>
+      <key>settings</key>
+      <dict>
+        <key>background</key>
+        <string>#1c1b22</string>
+        <key>foreground</key>
+        <string>#e6e1cf</string>
+        <key>caret</key>
+        <string>#ffcc66</string>
+      </dict>
+    </dict>
+    <!-- keywords -->
+    <dict>
+      <key>scope</key>
+      <string>keyword, storage</string>
+      <key>settings</key>
+      <dict>
+        <key>foreground</key>
+        <string>#ff7733</string>
+        <key>fontStyle</key>
+        <string>bold</string>
+      </dict>
+    </dict>
+    <dict>
+      <key>scope</key>
+      <string>keyword.operator</string>
+      <key>settings</key>
+      <dict>
+        <key>foreground</key>
+        <string>#f29668</string>
+      </dict>
+    </dict>
+    <dict>
+      <key>scope</key>
+      <string>comment</string>
+      <key>settings</key>
+      <dict>
+        <key>foreground</key>
+        <string>#3e4b59</string>
+        <key>fontStyle</key>
+        <string>italic</string>
+      </dict>
+    </dict>
+  </array>
+  <key>uuid</key>
+  <string>6a5d6c3e</string>
+  <key>semanticClass</key>
+  <dict/>
+</dict>
+</plist>
+"#;
+
+    #[test]
+    fn tmtheme_scopes_map_to_syntax_roles() {
+        let editor = parse_tmtheme(TMTHEME).expect("should parse");
+        assert_eq!(editor.name.as_deref(), Some("Ember & Ash"));
+        assert_eq!(editor.background.as_deref(), Some("#1c1b22"));
+        assert_eq!(editor.rules.len(), 3);
+
+        let keyword = best_rule(&editor.rules, &["keyword"]).expect("keyword rule");
+        assert_eq!(keyword.foreground.as_deref(), Some("#ff7733"));
+        let operator = best_rule(&editor.rules, &["keyword.operator"]).expect("operator rule");
+        assert_eq!(operator.foreground.as_deref(), Some("#f29668"));
+        assert!(best_rule(&editor.rules, &["string"]).is_none());
+
+        let imported = import_theme(TMTHEME, EditorFormat::TmTheme, "ember").expect("imports");
+        let tokens: ThemeTokens = toml::from_str(&imported.toml).expect("valid TOML");
+        assert_eq!(tokens.meta.name, "Ember & Ash");
+        assert_eq!(tokens.meta.variant, "dark");
+        assert_eq!(tokens.colors["background"], "#1c1b22");
+        assert_eq!(tokens.syntax.keyword.color, "#ff7733");
+        assert_eq!(tokens.syntax.keyword.bold, Some(true));
+        assert_eq!(tokens.syntax.string.color, "text_primary");
+
+        // The dim comment color fails 4.5:1 on the code surface and is lifted.
+        let comment = imported
+            .adjustments
+            .iter()
+            .find(|adjustment| adjustment.element == "syntax: comment")
+            .expect("comment should be adjusted");
+        assert_eq!(comment.from, "#3e4b59");
+        assert_eq!(tokens.syntax.comment.color, comment.to);
+        assert!(
+            !imported
+                .warnings
+                .iter()
+                .any(|w| matches!(w, SilkprintWarning::ContrastRatio { .. })),
+            "{:?}",
+            imported.warnings
+        );
+    }
+
+    #[test]
+    fn vscode_json_with_comments_imports_editor_colors() {
+        let json = r##"{
+            // A light theme
+            "name": "Paper Trail",
+            "type": "light",
+            "colors": {
+                "editor.background": "#fdf6e3",
+                "editor.foreground": "#5c6a72",
+                "textLink.foreground": "#3a94c5cc",
+            },
+            /* rules */
+            "tokenColors": [
+                { "scope": ["string", "string.quoted"], "settings": { "foreground": "#8da101" } },
+                { "scope": "entity.name.function", "settings": { "foreground": "#35a77c", "fontStyle": "" } },
+            ],
+        }"##;
+
+        let imported = import_theme(json, EditorFormat::VsCode, "paper").expect("imports");
+        let tokens: ThemeTokens = toml::from_str(&imported.toml).expect("valid TOML");
+        assert_eq!(tokens.meta.name, "Paper Trail");
+        assert_eq!(tokens.meta.variant, "light");
+        assert_eq!(tokens.colors["background"], "#fdf6e3");
+        assert_eq!(tokens.syntax.function.italic, Some(false));
+        assert!(tokens.colors["accent_blue"].starts_with('#'));
+        assert!(
+            imported.toml.starts_with("# Paper Trail"),
+            "{}",
+            imported.toml
+        );
+    }
+
+    #[test]
+    fn colors_normalize_and_blend() {
+        assert_eq!(
+            normalize_color("#ABC", [0, 0, 0]).as_deref(),
+            Some("#aabbcc")
+        );
+        assert_eq!(
+            normalize_color("#ffffff80", [0, 0, 0]).as_deref(),
+            Some("#808080")
+        );
+        assert_eq!(normalize_color("red", [0, 0, 0]), None);
+        assert_eq!(blend("#000000", "#ffffff", 0), "#000000");
+        assert_eq!(blend("#000000", "#ffffff", 50), "#636363");
+        assert_eq!(blend("#000000", "#ffffff", 100), "#ffffff");
+        assert_eq!(
+            selector_depth("source.rust keyword.control", "keyword.control.flow"),
+            Some(2)
+        );
+        assert_eq!(selector_depth("keyword.con", "keyword.control"), None);
+    }
+}
@@ -4,18 +4,29 @@
 //! TOML source -> parse -> inheritance chain -> merge -> color resolve ->
 //! syntax fallback -> WCAG checks -> tmTheme generation -> `ResolvedTheme`.
 
+pub mod authoring;
 pub mod builtin;
 pub mod contrast;
+pub mod export;
+pub mod import;
+pub mod print;
+pub mod registry;
 pub mod syntax;
 pub mod tmtheme;
 pub mod tokens;
 
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use typst::foundations::Bytes;
 
 use crate::ThemeSource;
 use crate::error::SilkprintError;
-use crate::warnings::WarningCollector;
+use crate::warnings::{SilkprintWarning, WarningCollector};
 
+use self::contrast::ContrastLevel;
+use self::print::PrintSafe;
+use self::registry::{ThemeLocation, ThemeRegistry};
 use self::tokens::{SyntaxTokens, ThemeTokens};
 
 /// Maximum depth for theme inheritance chains.
@@ -27,6 +38,9 @@ const MAX_INHERITANCE_DEPTH: usize = 5;
 pub struct ResolvedTheme {
     pub tokens: ThemeTokens,
     pub tmtheme_xml: String,
+    /// Font files shipped with the theme (`heading_source` etc.), loaded
+    /// ahead of the bundled fonts.
+    pub fonts: Vec<Bytes>,
 }
 
 /// Load and resolve a theme from the given source.
@@ -36,26 +50,74 @@ pub struct ResolvedTheme {
 /// 2. Builds and resolves the inheritance chain
 /// 3. Resolves all color references
 /// 4. Applies syntax fallbacks from base themes
-/// 5. Runs WCAG contrast checks (warnings)
-/// 6. Generates tmTheme XML for Typst
+/// 5. Derives a print-safe variant when asked to
+/// 6. Repairs contrast when asked to, then runs WCAG contrast checks
+/// 7. Generates tmTheme XML for Typst
+/// 8. Loads any font files the theme ships
+///
+/// Theme names resolve through [`ThemeRegistry::discover`].
 pub fn load_theme(
     source: &ThemeSource,
     warnings: &mut WarningCollector,
 ) -> Result<ResolvedTheme, SilkprintError> {
-    let toml_source = load_toml_source(source)?;
-    let tokens = parse_theme_toml(&toml_source, source)?;
+    load_theme_from(source, &ThemeRegistry::discover(), warnings)
+}
+
+/// [`load_theme`], resolving theme names and `extends` through `registry`.
+pub fn load_theme_from(
+    source: &ThemeSource,
+    registry: &ThemeRegistry,
+    warnings: &mut WarningCollector,
+) -> Result<ResolvedTheme, SilkprintError> {
+    load_theme_with(source, registry, ThemeAdjustments::default(), warnings)
+}
+
+/// Changes applied to a theme as it loads, on top of its own tokens.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ThemeAdjustments {
+    /// WCAG level to repair colors to (`--fix-contrast`). Overrides the
+    /// theme's `[meta] enforce_contrast`.
+    pub fix_contrast: Option<ContrastLevel>,
+    /// Light-paper variant to derive (`--print-safe`).
+    pub print_safe: Option<PrintSafe>,
+}
+
+/// [`load_theme_from`], applying `adjustments`.
+pub fn load_theme_with(
+    source: &ThemeSource,
+    registry: &ThemeRegistry,
+    adjustments: ThemeAdjustments,
+    warnings: &mut WarningCollector,
+) -> Result<ResolvedTheme, SilkprintError> {
+    let text = load_toml_source(source, registry)?;
+    let tokens = parse_theme_toml(&text)?;
 
     // Build inheritance chain and merge
-    let merged = resolve_inheritance(tokens, source)?;
+    let merged = resolve_inheritance(tokens, &text, registry)?;
 
     // Resolve colors: two-level resolution within [colors], then all fields
     let resolved = resolve_all_colors(merged);
 
     // Apply syntax fallbacks if no syntax was defined in the chain
-    let resolved = apply_syntax_fallback(resolved)?;
+    let mut resolved = apply_syntax_fallback(resolved)?;
+
+    // Derive the print-safe variant before contrast is judged
+    if let Some(mode) = adjustments.print_safe {
+        print::make_print_safe(&mut resolved, mode);
+    }
 
-    // Run WCAG contrast checks
-    run_contrast_checks(&resolved, warnings);
+    // Repair contrast if asked to, then run WCAG contrast checks
+    let level = match adjustments.fix_contrast {
+        Some(level) => Some(level),
+        None if resolved.meta.enforce_contrast.is_empty() => None,
+        None => Some(ContrastLevel::from_str_case_insensitive(
+            &resolved.meta.enforce_contrast,
+        )?),
+    };
+    if let Some(level) = level {
+        repair_contrast(&mut resolved, level, warnings);
+    }
+    run_contrast_checks(&resolved, level, warnings);
 
     // Generate tmTheme XML
     let syntax_background = resolve_color_ref(&resolved.syntax.background, &resolved.colors);
@@ -68,54 +130,155 @@ pub fn load_theme(
         &syntax_styles,
     );
 
+    let fonts = crate::fonts::load_theme_fonts(&resolved.fonts, warnings);
+
     Ok(ResolvedTheme {
         tokens: resolved,
         tmtheme_xml,
+        fonts,
     })
 }
 
-/// Load raw TOML source string from the theme source.
-fn load_toml_source(source: &ThemeSource) -> Result<String, SilkprintError> {
-    match source {
-        ThemeSource::BuiltIn(name) => builtin::get_builtin_theme(name)
-            .map(String::from)
-            .ok_or_else(|| {
-                let available = builtin::list_themes();
-                let suggestions = find_suggestions(name, &available);
-                SilkprintError::ThemeNotFound {
-                    name: name.clone(),
-                    suggestions,
-                }
-            }),
+/// The theme files `source` reads, following `extends` through `registry`.
+///
+/// Watch mode re-renders when any of these change. Built-in and inline
+/// themes contribute no files, but their custom ancestors do.
+pub fn theme_files(source: &ThemeSource, registry: &ThemeRegistry) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = Vec::new();
+    let mut text = match source {
         ThemeSource::Custom(path) => {
-            std::fs::read_to_string(path).map_err(|e| SilkprintError::InputRead {
-                path: path.display().to_string(),
-                source: e,
-            })
+            files.push(path.clone());
+            std::fs::read_to_string(path).ok()
         }
-        ThemeSource::Inline(toml_str) => Ok(toml_str.clone()),
+        ThemeSource::Inline(toml_str) => Some(toml_str.clone()),
+        ThemeSource::BuiltIn(_) => None,
+    };
+    let mut next = match source {
+        ThemeSource::BuiltIn(name) => Some(name.clone()),
+        _ => text.as_deref().and_then(extends_of),
+    };
+
+    for _ in 0..MAX_INHERITANCE_DEPTH {
+        let Some(name) = next.take() else {
+            break;
+        };
+        let Some(ThemeLocation::File { path, .. }) =
+            registry.lookup_skipping(&name, files.last().map(PathBuf::as_path))
+        else {
+            break;
+        };
+        if files.contains(&path) {
+            break;
+        }
+        text = std::fs::read_to_string(&path).ok();
+        next = text.as_deref().and_then(extends_of);
+        files.push(path);
     }
+    files
 }
 
-/// Parse a TOML string into `ThemeTokens`.
-fn parse_theme_toml(
-    toml_source: &str,
+/// The `[meta] extends` value of a theme's TOML, if any.
+fn extends_of(toml_source: &str) -> Option<String> {
+    let table = toml_source.parse::<toml::Table>().ok()?;
+    let extends = table.get("meta")?.get("extends")?.as_str()?;
+    (!extends.is_empty()).then(|| extends.to_string())
+}
+
+/// A theme's TOML source and where it came from.
+struct ThemeText {
+    toml: String,
+    /// Name for diagnostics: the file path, `builtin:<name>`, or `inline`.
+    /// Unique per theme, so it also identifies links in an `extends` chain.
+    name: String,
+    /// The theme file, for file-based themes.
+    path: Option<PathBuf>,
+    /// Directory that relative font sources resolve against.
+    dir: Option<PathBuf>,
+}
+
+/// Load raw TOML source from the theme source.
+fn load_toml_source(
     source: &ThemeSource,
-) -> Result<ThemeTokens, SilkprintError> {
-    toml::from_str(toml_source).map_err(|e| {
-        let src_name = match source {
-            ThemeSource::BuiltIn(name) => format!("builtin:{name}"),
-            ThemeSource::Custom(path) => path.display().to_string(),
-            ThemeSource::Inline(_) => "inline".to_string(),
-        };
-        SilkprintError::ThemeInvalid {
-            src: miette::NamedSource::new(src_name, toml_source.to_string()),
-            span: (0, toml_source.len().min(1)).into(),
-            message: format!("TOML parse error: {e}"),
-        }
+    registry: &ThemeRegistry,
+) -> Result<ThemeText, SilkprintError> {
+    match source {
+        ThemeSource::BuiltIn(name) => load_named(name, registry.lookup(name), registry),
+        ThemeSource::Custom(path) => read_theme_file(path),
+        ThemeSource::Inline(toml_str) => Ok(ThemeText {
+            toml: toml_str.clone(),
+            name: "inline".to_string(),
+            path: None,
+            dir: None,
+        }),
+    }
+}
+
+/// Load the theme `name` resolved to in the registry.
+fn load_named(
+    name: &str,
+    location: Option<ThemeLocation>,
+    registry: &ThemeRegistry,
+) -> Result<ThemeText, SilkprintError> {
+    match location {
+        Some(ThemeLocation::BuiltIn(toml_str)) => Ok(ThemeText {
+            toml: toml_str.to_string(),
+            name: format!("builtin:{name}"),
+            path: None,
+            dir: None,
+        }),
+        Some(ThemeLocation::File { path, .. }) => read_theme_file(&path),
+        None => Err(SilkprintError::ThemeNotFound {
+            name: name.to_string(),
+            suggestions: find_suggestions(name, &registry.names()),
+        }),
+    }
+}
+
+fn read_theme_file(path: &Path) -> Result<ThemeText, SilkprintError> {
+    let toml = std::fs::read_to_string(path).map_err(|e| SilkprintError::InputRead {
+        path: path.display().to_string(),
+        source: e,
+    })?;
+    Ok(ThemeText {
+        toml,
+        name: path.display().to_string(),
+        path: Some(path.to_path_buf()),
+        dir: Some(path.parent().map_or_else(PathBuf::new, Path::to_path_buf)),
     })
 }
 
+/// Parse a theme's TOML into `ThemeTokens`.
+///
+/// Font sources and the template are rebased onto the theme file's
+/// directory here, before inheritance, so a parent's files still resolve
+/// from a child theme.
+fn parse_theme_toml(text: &ThemeText) -> Result<ThemeTokens, SilkprintError> {
+    let mut tokens: ThemeTokens =
+        toml::from_str(&text.toml).map_err(|e| SilkprintError::ThemeInvalid {
+            src: miette::NamedSource::new(&text.name, text.toml.clone()),
+            span: e
+                .span()
+                .map_or_else(|| (0, text.toml.len().min(1)).into(), Into::into),
+            message: format!("TOML parse error: {}", e.message()),
+        })?;
+
+    if let Some(dir) = &text.dir {
+        let fonts = &mut tokens.fonts;
+        for source in [
+            &mut fonts.heading_source,
+            &mut fonts.body_source,
+            &mut fonts.mono_source,
+            &mut tokens.meta.template,
+        ] {
+            if !source.is_empty() {
+                *source = dir.join(&*source).to_string_lossy().into_owned();
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
 /// Build the inheritance chain and merge themes bottom-up.
 ///
 /// Steps 1-5 from SPEC Section 5.5:
@@ -123,9 +286,15 @@ fn parse_theme_toml(
 /// - Detect cycles
 /// - Cap depth at 5
 /// - Merge from deepest ancestor first
+///
+/// `extends` may name any theme in `registry`, built-in or custom. A theme
+/// file never resolves `extends` to itself, so one that shadows a built-in
+/// can extend it by name; cycles are therefore detected by where each
+/// theme was loaded from rather than by name.
 fn resolve_inheritance(
     tokens: ThemeTokens,
-    source: &ThemeSource,
+    text: &ThemeText,
+    registry: &ThemeRegistry,
 ) -> Result<ThemeTokens, SilkprintError> {
     if tokens.meta.extends.is_empty() {
         return Ok(tokens);
@@ -133,32 +302,19 @@ fn resolve_inheritance(
 
     // Build the inheritance chain
     let mut chain: Vec<ThemeTokens> = vec![tokens];
-    let mut seen_names: HashSet<String> = HashSet::new();
-
-    // Add the root theme name
-    let root_name = chain[0].meta.name.clone();
-    if !root_name.is_empty() {
-        seen_names.insert(root_name);
-    }
+    let mut seen: Vec<String> = vec![text.name.clone()];
+    let mut current = text.path.clone();
 
     loop {
-        let current = chain.last().ok_or_else(|| SilkprintError::ThemeCycle {
+        let child = chain.last().ok_or_else(|| SilkprintError::ThemeCycle {
             chain: "empty chain".to_string(),
         })?;
-        let parent_name = current.meta.extends.clone();
+        let parent_name = child.meta.extends.clone();
 
         if parent_name.is_empty() {
             break;
         }
 
-        // Cycle detection
-        if seen_names.contains(&parent_name) {
-            let names: Vec<String> = chain.iter().map(|t| t.meta.name.clone()).collect();
-            return Err(SilkprintError::ThemeCycle {
-                chain: format!("{} -> {parent_name}", names.join(" -> ")),
-            });
-        }
-
         // Depth check
         if chain.len() >= MAX_INHERITANCE_DEPTH {
             let names: Vec<String> = chain.iter().map(|t| t.meta.name.clone()).collect();
@@ -167,17 +323,21 @@ fn resolve_inheritance(
             });
         }
 
-        // Load the parent theme
-        let parent_source = ThemeSource::BuiltIn(parent_name.clone());
-        let parent_toml =
-            load_toml_source(&parent_source).map_err(|_| SilkprintError::ThemeNotFound {
-                name: parent_name.clone(),
-                suggestions: find_suggestions(&parent_name, &builtin::list_themes()),
-            })?;
-        let parent_tokens = parse_theme_toml(&parent_toml, source)?;
-
-        seen_names.insert(parent_name);
-        chain.push(parent_tokens);
+        // Load the parent theme, skipping the file that names it
+        let location = registry.lookup_skipping(&parent_name, current.as_deref());
+        let parent = load_named(&parent_name, location, registry)?;
+
+        // Cycle detection
+        if seen.contains(&parent.name) {
+            let names: Vec<String> = chain.iter().map(|t| t.meta.name.clone()).collect();
+            return Err(SilkprintError::ThemeCycle {
+                chain: format!("{} -> {parent_name}", names.join(" -> ")),
+            });
+        }
+
+        chain.push(parse_theme_toml(&parent)?);
+        seen.push(parent.name);
+        current = parent.path;
     }
 
     // Merge from bottom up (deepest ancestor first)
@@ -278,7 +438,7 @@ fn resolve_color_table(colors: &HashMap<String, String>) -> HashMap<String, Stri
 
 /// Resolve a single color reference against the colors table.
 /// Returns the hex value if found, or the original string if not.
-fn resolve_color_ref(value: &str, colors: &HashMap<String, String>) -> String {
+pub(crate) fn resolve_color_ref(value: &str, colors: &HashMap<String, String>) -> String {
     if value.is_empty() || value.starts_with('#') {
         return value.to_string();
     }
@@ -292,129 +452,185 @@ fn resolve_color_ref(value: &str, colors: &HashMap<String, String>) -> String {
 ///
 /// This walks every string field that might hold a color reference
 /// and replaces it with the resolved hex value from the `[colors]` table.
-#[allow(clippy::too_many_lines)]
 fn resolve_token_colors(tokens: &mut ThemeTokens) {
     let colors = tokens.colors.clone();
-
-    // Helper closure to resolve a single field
-    let r = |field: &mut String| {
+    for (_, field) in color_fields(tokens) {
         if !field.is_empty()
             && !field.starts_with('#')
             && let Some(hex) = colors.get(field.as_str())
         {
             *field = hex.clone();
         }
-    };
+    }
+}
 
-    // Page
-    r(&mut tokens.page.background);
-
-    // Text
-    r(&mut tokens.text.color);
-
-    // Headings
-    r(&mut tokens.headings.color);
-    r(&mut tokens.headings.h1.color);
-    r(&mut tokens.headings.h2.color);
-    r(&mut tokens.headings.h3.color);
-    r(&mut tokens.headings.h4.color);
-    r(&mut tokens.headings.h5.color);
-    r(&mut tokens.headings.h6.color);
-
-    // Code block
-    r(&mut tokens.code_block.background);
-    r(&mut tokens.code_block.border_color);
-    r(&mut tokens.code_block.left_accent_color);
-    r(&mut tokens.code_block.language_label_color);
-
-    // Code inline
-    r(&mut tokens.code_inline.background);
-    r(&mut tokens.code_inline.border_color);
-
-    // Blockquote
-    r(&mut tokens.blockquote.border_color);
-    r(&mut tokens.blockquote.background);
-    r(&mut tokens.blockquote.text_color);
-
-    // Table
-    r(&mut tokens.table.header_background);
-    r(&mut tokens.table.header_text_color);
-    r(&mut tokens.table.header_border_color);
-    r(&mut tokens.table.row_border_color);
-    r(&mut tokens.table.stripe_background);
-
-    // Horizontal rule
-    r(&mut tokens.horizontal_rule.color);
-
-    // Links
-    r(&mut tokens.links.color);
-
-    // Images
-    r(&mut tokens.images.caption_color);
-
-    // List
-    r(&mut tokens.list.bullet_color);
-    r(&mut tokens.list.task_checked_color);
-    r(&mut tokens.list.task_unchecked_color);
-
-    // Footnotes
-    r(&mut tokens.footnotes.separator_color);
-    r(&mut tokens.footnotes.number_color);
-    r(&mut tokens.footnotes.backref_color);
-
-    // Alerts
-    r(&mut tokens.alerts.note_color);
-    r(&mut tokens.alerts.tip_color);
-    r(&mut tokens.alerts.important_color);
-    r(&mut tokens.alerts.warning_color);
-    r(&mut tokens.alerts.caution_color);
-
-    // ToC
-    r(&mut tokens.toc.entry_color);
-    r(&mut tokens.toc.page_number_color);
-
-    // Page numbers
-    r(&mut tokens.page_numbers.color);
-
-    // Title page
-    r(&mut tokens.title_page.title_color);
-    r(&mut tokens.title_page.subtitle_color);
-    r(&mut tokens.title_page.author_color);
-    r(&mut tokens.title_page.date_color);
-    r(&mut tokens.title_page.separator_color);
-
-    // Emphasis
-    r(&mut tokens.emphasis.strikethrough_color);
-
-    // Math
-    r(&mut tokens.math.color);
-
-    // Highlight
-    r(&mut tokens.highlight.fill);
-    r(&mut tokens.highlight.text_color);
-
-    // Description list
-    r(&mut tokens.description_list.term_color);
-
-    // Syntax tokens
-    r(&mut tokens.syntax.background);
-    r(&mut tokens.syntax.text.color);
-    r(&mut tokens.syntax.keyword.color);
-    r(&mut tokens.syntax.string.color);
-    r(&mut tokens.syntax.number.color);
-    r(&mut tokens.syntax.function.color);
-    r(&mut tokens.syntax.type_.color);
-    r(&mut tokens.syntax.comment.color);
-    r(&mut tokens.syntax.constant.color);
-    r(&mut tokens.syntax.boolean.color);
-    r(&mut tokens.syntax.operator.color);
-    r(&mut tokens.syntax.property.color);
-    r(&mut tokens.syntax.tag.color);
-    r(&mut tokens.syntax.attribute.color);
-    r(&mut tokens.syntax.variable.color);
-    r(&mut tokens.syntax.builtin.color);
-    r(&mut tokens.syntax.punctuation.color);
-    r(&mut tokens.syntax.escape.color);
+/// Every color field in the token tree, keyed by its dotted TOML path.
+#[allow(clippy::too_many_lines)]
+pub(crate) fn color_fields(tokens: &mut ThemeTokens) -> Vec<(&'static str, &mut String)> {
+    vec![
+        // Page
+        ("page.background", &mut tokens.page.background),
+        // Text
+        ("text.color", &mut tokens.text.color),
+        // Headings
+        ("headings.color", &mut tokens.headings.color),
+        ("headings.h1.color", &mut tokens.headings.h1.color),
+        ("headings.h2.color", &mut tokens.headings.h2.color),
+        ("headings.h3.color", &mut tokens.headings.h3.color),
+        ("headings.h4.color", &mut tokens.headings.h4.color),
+        ("headings.h5.color", &mut tokens.headings.h5.color),
+        ("headings.h6.color", &mut tokens.headings.h6.color),
+        (
+            "headings.numbering_color",
+            &mut tokens.headings.numbering_color,
+        ),
+        // Code block
+        ("code_block.background", &mut tokens.code_block.background),
+        (
+            "code_block.border_color",
+            &mut tokens.code_block.border_color,
+        ),
+        (
+            "code_block.left_accent_color",
+            &mut tokens.code_block.left_accent_color,
+        ),
+        (
+            "code_block.language_label_color",
+            &mut tokens.code_block.language_label_color,
+        ),
+        // Code inline
+        ("code_inline.background", &mut tokens.code_inline.background),
+        (
+            "code_inline.border_color",
+            &mut tokens.code_inline.border_color,
+        ),
+        // Blockquote
+        (
+            "blockquote.border_color",
+            &mut tokens.blockquote.border_color,
+        ),
+        ("blockquote.background", &mut tokens.blockquote.background),
+        ("blockquote.text_color", &mut tokens.blockquote.text_color),
+        // Table
+        (
+            "table.header_background",
+            &mut tokens.table.header_background,
+        ),
+        (
+            "table.header_text_color",
+            &mut tokens.table.header_text_color,
+        ),
+        (
+            "table.header_border_color",
+            &mut tokens.table.header_border_color,
+        ),
+        ("table.row_border_color", &mut tokens.table.row_border_color),
+        (
+            "table.stripe_background",
+            &mut tokens.table.stripe_background,
+        ),
+        // Horizontal rule
+        ("horizontal_rule.color", &mut tokens.horizontal_rule.color),
+        // Links
+        ("links.color", &mut tokens.links.color),
+        // Images
+        ("images.caption_color", &mut tokens.images.caption_color),
+        // List
+        ("list.bullet_color", &mut tokens.list.bullet_color),
+        (
+            "list.task_checked_color",
+            &mut tokens.list.task_checked_color,
+        ),
+        (
+            "list.task_unchecked_color",
+            &mut tokens.list.task_unchecked_color,
+        ),
+        // Footnotes
+        (
+            "footnotes.separator_color",
+            &mut tokens.footnotes.separator_color,
+        ),
+        ("footnotes.number_color", &mut tokens.footnotes.number_color),
+        (
+            "footnotes.backref_color",
+            &mut tokens.footnotes.backref_color,
+        ),
+        // Alerts
+        ("alerts.note_color", &mut tokens.alerts.note_color),
+        ("alerts.tip_color", &mut tokens.alerts.tip_color),
+        ("alerts.important_color", &mut tokens.alerts.important_color),
+        ("alerts.warning_color", &mut tokens.alerts.warning_color),
+        ("alerts.caution_color", &mut tokens.alerts.caution_color),
+        // ToC
+        ("toc.entry_color", &mut tokens.toc.entry_color),
+        ("toc.page_number_color", &mut tokens.toc.page_number_color),
+        // Page numbers
+        ("page_numbers.color", &mut tokens.page_numbers.color),
+        // Running header and footer
+        ("header.color", &mut tokens.header.color),
+        ("header.rule_color", &mut tokens.header.rule_color),
+        ("footer.color", &mut tokens.footer.color),
+        ("footer.rule_color", &mut tokens.footer.rule_color),
+        // Watermark and classification banner
+        ("watermark.color", &mut tokens.watermark.color),
+        (
+            "classification.background",
+            &mut tokens.classification.background,
+        ),
+        ("classification.color", &mut tokens.classification.color),
+        // Title page
+        ("title_page.title_color", &mut tokens.title_page.title_color),
+        (
+            "title_page.subtitle_color",
+            &mut tokens.title_page.subtitle_color,
+        ),
+        (
+            "title_page.author_color",
+            &mut tokens.title_page.author_color,
+        ),
+        ("title_page.date_color", &mut tokens.title_page.date_color),
+        (
+            "title_page.separator_color",
+            &mut tokens.title_page.separator_color,
+        ),
+        // Emphasis
+        (
+            "emphasis.strikethrough_color",
+            &mut tokens.emphasis.strikethrough_color,
+        ),
+        // Math
+        ("math.color", &mut tokens.math.color),
+        // Highlight
+        ("highlight.fill", &mut tokens.highlight.fill),
+        ("highlight.text_color", &mut tokens.highlight.text_color),
+        // Description list
+        (
+            "description_list.term_color",
+            &mut tokens.description_list.term_color,
+        ),
+        // Syntax tokens
+        ("syntax.background", &mut tokens.syntax.background),
+        ("syntax.text.color", &mut tokens.syntax.text.color),
+        ("syntax.keyword.color", &mut tokens.syntax.keyword.color),
+        ("syntax.string.color", &mut tokens.syntax.string.color),
+        ("syntax.number.color", &mut tokens.syntax.number.color),
+        ("syntax.function.color", &mut tokens.syntax.function.color),
+        ("syntax.type.color", &mut tokens.syntax.type_.color),
+        ("syntax.comment.color", &mut tokens.syntax.comment.color),
+        ("syntax.constant.color", &mut tokens.syntax.constant.color),
+        ("syntax.boolean.color", &mut tokens.syntax.boolean.color),
+        ("syntax.operator.color", &mut tokens.syntax.operator.color),
+        ("syntax.property.color", &mut tokens.syntax.property.color),
+        ("syntax.tag.color", &mut tokens.syntax.tag.color),
+        ("syntax.attribute.color", &mut tokens.syntax.attribute.color),
+        ("syntax.variable.color", &mut tokens.syntax.variable.color),
+        ("syntax.builtin.color", &mut tokens.syntax.builtin.color),
+        (
+            "syntax.punctuation.color",
+            &mut tokens.syntax.punctuation.color,
+        ),
+        ("syntax.escape.color", &mut tokens.syntax.escape.color),
+    ]
 }
 
 /// Apply base syntax fallback if no syntax tokens were defined in the chain.
@@ -454,32 +670,60 @@ fn has_syntax_tokens(syntax: &SyntaxTokens) -> bool {
         || !syntax.comment.color.is_empty()
 }
 
-/// Run WCAG contrast checks and emit warnings.
+/// A foreground/background pair covered by the WCAG checks.
+struct ContrastPair {
+    element: &'static str,
+    /// Dotted path of the foreground field, as in [`color_fields`].
+    field: &'static str,
+    foreground: String,
+    background: String,
+    /// Minimum ratio at level AA.
+    minimum: f64,
+}
+
+/// The pairs the WCAG checks cover, with colors resolved to hex.
 ///
 /// Per SPEC 5.5 step 10: checks ~12 foreground/background pairs.
 #[allow(clippy::too_many_lines)]
-fn run_contrast_checks(tokens: &ThemeTokens, warnings: &mut WarningCollector) {
-    let page_bg = &tokens.page.background;
-    let code_bg = &tokens.code_block.background;
+fn contrast_pairs(tokens: &ThemeTokens) -> Vec<ContrastPair> {
+    let page_bg = tokens.page.background.as_str();
+    let pair = |element: &'static str,
+                field: &'static str,
+                foreground: &str,
+                background: &str,
+                minimum: f64| ContrastPair {
+        element,
+        field,
+        foreground: resolve_color_ref(foreground, &tokens.colors),
+        background: resolve_color_ref(background, &tokens.colors),
+        minimum,
+    };
 
-    // Pairs: (element name, foreground, background, minimum ratio)
-    let checks: &[(&str, &str, &str, f64)] = &[
+    let mut pairs = vec![
         // Body text vs page background (AA: 4.5:1)
-        ("body text", &tokens.text.color, page_bg, 4.5),
+        pair("body text", "text.color", &tokens.text.color, page_bg, 4.5),
         // Heading text vs page background (3:1 large text)
-        ("headings", &tokens.headings.color, page_bg, 3.0),
+        pair(
+            "headings",
+            "headings.color",
+            &tokens.headings.color,
+            page_bg,
+            3.0,
+        ),
         // Link color vs page background (4.5:1)
-        ("links", &tokens.links.color, page_bg, 4.5),
+        pair("links", "links.color", &tokens.links.color, page_bg, 4.5),
         // Blockquote text vs page background (4.5:1)
-        (
+        pair(
             "blockquote text",
+            "blockquote.text_color",
             &tokens.blockquote.text_color,
             page_bg,
             4.5,
         ),
         // Table header text vs header background (4.5:1)
-        (
+        pair(
             "table header",
+            "table.header_text_color",
             if tokens.table.header_text_color.is_empty() {
                 &tokens.headings.color
             } else {
@@ -489,80 +733,217 @@ fn run_contrast_checks(tokens: &ThemeTokens, warnings: &mut WarningCollector) {
             4.5,
         ),
         // Caption/footnote text vs page background (4.5:1)
-        ("caption text", &tokens.images.caption_color, page_bg, 4.5),
-        (
+        pair(
+            "caption text",
+            "images.caption_color",
+            &tokens.images.caption_color,
+            page_bg,
+            4.5,
+        ),
+        pair(
             "footnote numbers",
+            "footnotes.number_color",
             &tokens.footnotes.number_color,
             page_bg,
             4.5,
         ),
         // Page number color vs page background (3:1)
-        ("page numbers", &tokens.page_numbers.color, page_bg, 3.0),
+        pair(
+            "page numbers",
+            "page_numbers.color",
+            &tokens.page_numbers.color,
+            page_bg,
+            3.0,
+        ),
+        pair(
+            "running header",
+            "header.color",
+            &tokens.header.color,
+            page_bg,
+            3.0,
+        ),
+        pair(
+            "running footer",
+            "footer.color",
+            &tokens.footer.color,
+            page_bg,
+            3.0,
+        ),
+        pair(
+            "classification banner",
+            "classification.color",
+            &tokens.classification.color,
+            &tokens.classification.background,
+            4.5,
+        ),
     ];
 
-    for (element, fg, bg, minimum) in checks {
-        if fg.is_empty() || bg.is_empty() {
-            continue;
-        }
-        if let Some(warning) = contrast::check_contrast(element, fg, bg, *minimum) {
-            warnings.push(warning);
-        }
-    }
-
     // Syntax token colors vs syntax background (4.5:1 each)
     // Prefer syntax.background when set, fall back to code_block.background
-    let syntax_bg = if tokens.syntax.background.is_empty() {
-        code_bg
+    let syntax = &tokens.syntax;
+    let syntax_bg = if syntax.background.is_empty() {
+        &tokens.code_block.background
     } else {
-        &tokens.syntax.background
+        &syntax.background
     };
-    let syntax_checks: &[(&str, &str)] = &[
-        ("syntax: text", &tokens.syntax.text.color),
-        ("syntax: keyword", &tokens.syntax.keyword.color),
-        ("syntax: string", &tokens.syntax.string.color),
-        ("syntax: number", &tokens.syntax.number.color),
-        ("syntax: function", &tokens.syntax.function.color),
-        ("syntax: type", &tokens.syntax.type_.color),
-        ("syntax: comment", &tokens.syntax.comment.color),
-        ("syntax: constant", &tokens.syntax.constant.color),
-        ("syntax: boolean", &tokens.syntax.boolean.color),
-        ("syntax: operator", &tokens.syntax.operator.color),
-        ("syntax: property", &tokens.syntax.property.color),
-        ("syntax: tag", &tokens.syntax.tag.color),
-        ("syntax: attribute", &tokens.syntax.attribute.color),
-        ("syntax: variable", &tokens.syntax.variable.color),
-        ("syntax: builtin", &tokens.syntax.builtin.color),
-        ("syntax: punctuation", &tokens.syntax.punctuation.color),
-        ("syntax: escape", &tokens.syntax.escape.color),
+    let syntax_pairs = [
+        ("syntax: text", "syntax.text.color", &syntax.text.color),
+        (
+            "syntax: keyword",
+            "syntax.keyword.color",
+            &syntax.keyword.color,
+        ),
+        (
+            "syntax: string",
+            "syntax.string.color",
+            &syntax.string.color,
+        ),
+        (
+            "syntax: number",
+            "syntax.number.color",
+            &syntax.number.color,
+        ),
+        (
+            "syntax: function",
+            "syntax.function.color",
+            &syntax.function.color,
+        ),
+        ("syntax: type", "syntax.type.color", &syntax.type_.color),
+        (
+            "syntax: comment",
+            "syntax.comment.color",
+            &syntax.comment.color,
+        ),
+        (
+            "syntax: constant",
+            "syntax.constant.color",
+            &syntax.constant.color,
+        ),
+        (
+            "syntax: boolean",
+            "syntax.boolean.color",
+            &syntax.boolean.color,
+        ),
+        (
+            "syntax: operator",
+            "syntax.operator.color",
+            &syntax.operator.color,
+        ),
+        (
+            "syntax: property",
+            "syntax.property.color",
+            &syntax.property.color,
+        ),
+        ("syntax: tag", "syntax.tag.color", &syntax.tag.color),
+        (
+            "syntax: attribute",
+            "syntax.attribute.color",
+            &syntax.attribute.color,
+        ),
+        (
+            "syntax: variable",
+            "syntax.variable.color",
+            &syntax.variable.color,
+        ),
+        (
+            "syntax: builtin",
+            "syntax.builtin.color",
+            &syntax.builtin.color,
+        ),
+        (
+            "syntax: punctuation",
+            "syntax.punctuation.color",
+            &syntax.punctuation.color,
+        ),
+        (
+            "syntax: escape",
+            "syntax.escape.color",
+            &syntax.escape.color,
+        ),
     ];
+    pairs.extend(
+        syntax_pairs
+            .into_iter()
+            .map(|(element, field, fg)| pair(element, field, fg, syntax_bg, 4.5)),
+    );
+
+    pairs.retain(|pair| !pair.foreground.is_empty() && !pair.background.is_empty());
+    pairs
+}
+
+/// Run WCAG contrast checks at `level` (AA when `None`) and emit warnings.
+fn run_contrast_checks(
+    tokens: &ThemeTokens,
+    level: Option<ContrastLevel>,
+    warnings: &mut WarningCollector,
+) {
+    let level = level.unwrap_or(ContrastLevel::Aa);
+    for pair in contrast_pairs(tokens) {
+        let minimum = level.minimum(pair.minimum);
+        if let Some(warning) =
+            contrast::check_contrast(pair.element, &pair.foreground, &pair.background, minimum)
+        {
+            warnings.push(warning);
+        }
+    }
+}
 
-    for (element, fg) in syntax_checks {
-        if fg.is_empty() || syntax_bg.is_empty() {
+/// Move every foreground that misses `level` in OKLCH lightness until it
+/// passes, reporting each new value as a `ContrastAdjusted` warning.
+fn repair_contrast(
+    tokens: &mut ThemeTokens,
+    level: ContrastLevel,
+    warnings: &mut WarningCollector,
+) {
+    let mut fixes = Vec::new();
+    for pair in contrast_pairs(tokens) {
+        let minimum = level.minimum(pair.minimum);
+        let Some(ratio) = contrast::contrast_ratio(&pair.foreground, &pair.background) else {
+            continue;
+        };
+        if ratio >= minimum {
             continue;
         }
-        if let Some(warning) = contrast::check_contrast(element, fg, syntax_bg, 4.5) {
-            warnings.push(warning);
+        // Unreachable pairs are left for the contrast check to report.
+        if let Some(fixed) = contrast::fix_lightness(&pair.foreground, &pair.background, minimum) {
+            fixes.push((pair, fixed, minimum));
+        }
+    }
+
+    for (pair, fixed, minimum) in fixes {
+        if let Some((_, field)) = color_fields(tokens)
+            .into_iter()
+            .find(|(name, _)| *name == pair.field)
+        {
+            field.clone_from(&fixed);
         }
+        warnings.push(SilkprintWarning::ContrastAdjusted {
+            element: pair.element.to_string(),
+            field: pair.field.to_string(),
+            from: pair.foreground,
+            to: fixed,
+            minimum,
+        });
     }
 }
 
 /// Find similar theme names for error suggestions.
-fn find_suggestions(name: &str, themes: &[builtin::ThemeInfo]) -> String {
+fn find_suggestions(name: &str, themes: &[String]) -> String {
     let name_lower = name.to_lowercase();
     let mut matches: Vec<&str> = themes
         .iter()
         .filter(|t| {
-            let t_lower = t.name.to_lowercase();
+            let t_lower = t.to_lowercase();
             t_lower.contains(&name_lower)
                 || name_lower.contains(&t_lower)
                 || levenshtein_distance(&name_lower, &t_lower) <= 3
         })
-        .map(|t| t.name)
+        .map(String::as_str)
         .collect();
 
     if matches.is_empty() {
         // Fall back to showing first few themes
-        matches = themes.iter().take(5).map(|t| t.name).collect();
+        matches = themes.iter().take(5).map(String::as_str).collect();
     }
 
     matches.join(", ")
@@ -0,0 +1,185 @@
+//! Print-safe variants of any theme, for `--print-safe`.
+//!
+//! The page becomes white and every other color is remapped in OKLCH. On
+//! dark themes lightness is mirrored, so text that stood out against the
+//! dark page stands out the same way on paper and the brightest accents
+//! become the darkest inks; hue and chroma are kept, so syntax roles stay
+//! distinct. Background fills are pulled close to white either way.
+//!
+//! The greyscale variant drops chroma altogether and tells syntax roles
+//! apart by weight, slant and grey level instead of hue.
+
+use crate::error::SilkprintError;
+
+use super::color_fields;
+use super::contrast::{oklch, oklch_to_hex, relative_luminance};
+use super::tokens::{SyntaxStyleTokens, ThemeTokens};
+
+/// Which print-safe variant to derive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrintSafe {
+    /// White paper, theme hues kept.
+    Color,
+    /// White paper, no color at all.
+    Greyscale,
+}
+
+impl PrintSafe {
+    pub fn from_str_case_insensitive(s: &str) -> Result<Self, SilkprintError> {
+        match s.to_lowercase().as_str() {
+            "color" | "colour" => Ok(Self::Color),
+            "greyscale" | "grayscale" => Ok(Self::Greyscale),
+            _ => Err(SilkprintError::InvalidPrintSafe {
+                mode: s.to_string(),
+            }),
+        }
+    }
+}
+
+/// Greyscale syntax styles: `(role, bold, italic, OKLCH lightness)`.
+///
+/// Structure words are bold, prose-like tokens (strings, comments) italic,
+/// and literals and comments sit at lighter greys than the code text.
+const GREYSCALE_SYNTAX: &[(&str, bool, bool, f64)] = &[
+    ("text", false, false, 0.2),
+    ("keyword", true, false, 0.15),
+    ("string", false, true, 0.35),
+    ("number", false, false, 0.4),
+    ("function", true, false, 0.25),
+    ("type", true, true, 0.25),
+    ("comment", false, true, 0.55),
+    ("constant", false, false, 0.4),
+    ("boolean", true, false, 0.4),
+    ("operator", false, false, 0.3),
+    ("property", false, false, 0.3),
+    ("tag", true, false, 0.15),
+    ("attribute", false, true, 0.3),
+    ("variable", false, false, 0.2),
+    ("builtin", true, true, 0.15),
+    ("punctuation", false, false, 0.4),
+    ("escape", true, false, 0.35),
+];
+
+/// Turn `tokens`, with colors already resolved to hex, into the `mode`
+/// print-safe variant.
+pub fn make_print_safe(tokens: &mut ThemeTokens, mode: PrintSafe) {
+    let dark = relative_luminance(&tokens.page.background).is_some_and(|lum| lum < 0.18);
+    let greyscale = mode == PrintSafe::Greyscale;
+
+    for (name, field) in color_fields(tokens) {
+        let Some([lightness, chroma, hue]) = oklch(field) else {
+            continue;
+        };
+        let fill = name.ends_with("background") || name == "highlight.fill";
+        let (lightness, chroma) = match (fill, dark) {
+            // Keep fills a faint step off the page, in the same order.
+            (true, true) => (1.0 - 0.25 * lightness, chroma * 0.5),
+            (true, false) => (1.0 - 0.5 * (1.0 - lightness), chroma),
+            (false, true) => (1.0 - lightness, chroma),
+            (false, false) => (lightness, chroma),
+        };
+        let chroma = if greyscale { 0.0 } else { chroma };
+        *field = oklch_to_hex([lightness, chroma, hue]);
+    }
+    tokens.page.background = "#ffffff".to_string();
+
+    if greyscale {
+        let syntax = &mut tokens.syntax;
+        let slots = [
+            &mut syntax.text,
+            &mut syntax.keyword,
+            &mut syntax.string,
+            &mut syntax.number,
+            &mut syntax.function,
+            &mut syntax.type_,
+            &mut syntax.comment,
+            &mut syntax.constant,
+            &mut syntax.boolean,
+            &mut syntax.operator,
+            &mut syntax.property,
+            &mut syntax.tag,
+            &mut syntax.attribute,
+            &mut syntax.variable,
+            &mut syntax.builtin,
+            &mut syntax.punctuation,
+            &mut syntax.escape,
+        ];
+        for (slot, (_, bold, italic, lightness)) in slots.into_iter().zip(GREYSCALE_SYNTAX) {
+            *slot = SyntaxStyleTokens {
+                color: oklch_to_hex([*lightness, 0.0, 0.0]),
+                bold: Some(*bold),
+                italic: Some(*italic),
+            };
+        }
+    }
+
+    tokens.meta.variant = "light".to_string();
+    tokens.meta.print_safe = true;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ThemeSource;
+    use crate::theme::registry::ThemeRegistry;
+    use crate::theme::{ThemeAdjustments, load_theme_with};
+    use crate::warnings::WarningCollector;
+
+    fn load(name: &str, mode: Option<PrintSafe>) -> ThemeTokens {
+        let adjustments = ThemeAdjustments {
+            print_safe: mode,
+            ..ThemeAdjustments::default()
+        };
+        let mut warnings = WarningCollector::new();
+        load_theme_with(
+            &ThemeSource::BuiltIn(name.to_string()),
+            &ThemeRegistry::default(),
+            adjustments,
+            &mut warnings,
+        )
+        .expect("should load")
+        .tokens
+    }
+
+    fn lum(hex: &str) -> f64 {
+        relative_luminance(hex).expect("hex color")
+    }
+
+    #[test]
+    fn dark_theme_becomes_light_with_order_and_hue_kept() {
+        let original = load("silk-dark", None);
+        let print = load("silk-dark", Some(PrintSafe::Color));
+
+        assert_eq!(print.page.background, "#ffffff");
+        assert_eq!(print.meta.variant, "light");
+        assert!(lum(&print.text.color) < 0.1, "{}", print.text.color);
+        assert!(lum(&print.code_block.background) > 0.8);
+
+        // Brighter than the page before means darker than the page now.
+        let (text, muted) = (&original.text.color, &original.images.caption_color);
+        assert!(lum(text) > lum(muted));
+        assert!(lum(&print.text.color) < lum(&print.images.caption_color));
+
+        let hue = |hex: &str| oklch(hex).expect("hex color")[2];
+        let keyword = (&original.syntax.keyword.color, &print.syntax.keyword.color);
+        assert!((hue(keyword.0) - hue(keyword.1)).abs() < 0.1, "{keyword:?}");
+    }
+
+    #[test]
+    fn greyscale_separates_syntax_roles_by_style() {
+        let print = load("silk-light", Some(PrintSafe::Greyscale));
+
+        for (_, field) in color_fields(&mut print.clone()) {
+            if let Some([_, chroma, _]) = oklch(field) {
+                assert!(chroma < 0.01, "{field} still has color");
+            }
+        }
+        let syntax = &print.syntax;
+        assert_eq!(syntax.keyword.bold, Some(true));
+        assert_eq!(syntax.comment.italic, Some(true));
+        assert_ne!(
+            (syntax.keyword.bold, syntax.keyword.italic),
+            (syntax.string.bold, syntax.string.italic)
+        );
+    }
+}
@@ -0,0 +1,370 @@
+//! Theme registry — built-in themes merged with user and project theme directories.
+//!
+//! A theme name resolves through three layers, highest priority first:
+//!
+//! 1. `./.silkprint/themes/<name>.toml` (project-local)
+//! 2. `~/.config/silkprint/themes/<name>.toml` (user-global; honours `XDG_CONFIG_HOME`)
+//! 3. The built-in themes
+//!
+//! A custom theme's name is its file stem, so a project can shadow a built-in
+//! by dropping in a file of the same name. Files starting with `_` or `.` are
+//! ignored, like the built-in `_base-syntax-*` fallbacks.
+
+use std::path::{Path, PathBuf};
+
+use super::builtin;
+
+/// Project-local theme directory, relative to the working directory.
+pub const PROJECT_THEME_DIR: &str = ".silkprint/themes";
+
+/// Which layer of the registry a theme came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThemeOrigin {
+    BuiltIn,
+    User,
+    Project,
+}
+
+impl ThemeOrigin {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::BuiltIn => "built-in",
+            Self::User => "user",
+            Self::Project => "project",
+        }
+    }
+}
+
+/// A theme the registry can resolve by name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ThemeLocation {
+    BuiltIn(&'static str),
+    File { origin: ThemeOrigin, path: PathBuf },
+}
+
+/// Metadata for `--list-themes` and the reader's theme picker.
+#[derive(Debug, Clone)]
+pub struct ThemeEntry {
+    pub name: String,
+    pub variant: String,
+    pub description: String,
+    pub print_safe: bool,
+    pub family: String,
+    pub origin: ThemeOrigin,
+}
+
+impl From<builtin::ThemeInfo> for ThemeEntry {
+    fn from(info: builtin::ThemeInfo) -> Self {
+        Self {
+            name: info.name.to_string(),
+            variant: info.variant.to_string(),
+            description: info.description.to_string(),
+            print_safe: info.print_safe,
+            family: info.family.to_string(),
+            origin: ThemeOrigin::BuiltIn,
+        }
+    }
+}
+
+/// Built-in themes plus any custom theme directories.
+#[derive(Debug, Clone, Default)]
+pub struct ThemeRegistry {
+    /// Custom theme directories, highest priority first.
+    dirs: Vec<(ThemeOrigin, PathBuf)>,
+}
+
+impl ThemeRegistry {
+    /// The registry for this process: the user config directory and
+    /// `./.silkprint/themes`. Built-ins only on WASM, which has no filesystem.
+    pub fn discover() -> Self {
+        #[cfg(target_arch = "wasm32")]
+        {
+            Self::default()
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            Self::with_dirs(user_theme_dir(), Some(PathBuf::from(PROJECT_THEME_DIR)))
+        }
+    }
+
+    /// A registry over explicit user and project theme directories.
+    pub fn with_dirs(user: Option<PathBuf>, project: Option<PathBuf>) -> Self {
+        let dirs = project
+            .map(|dir| (ThemeOrigin::Project, dir))
+            .into_iter()
+            .chain(user.map(|dir| (ThemeOrigin::User, dir)))
+            .collect();
+        Self { dirs }
+    }
+
+    /// Find the highest-priority theme called `name`.
+    pub fn lookup(&self, name: &str) -> Option<ThemeLocation> {
+        self.lookup_skipping(name, None)
+    }
+
+    /// [`lookup`](Self::lookup), passing over the theme file at `skip`.
+    ///
+    /// A theme resolving its own `extends` skips itself, so a project
+    /// `nord.toml` with `extends = "nord"` reaches the next layer down.
+    pub fn lookup_skipping(&self, name: &str, skip: Option<&Path>) -> Option<ThemeLocation> {
+        if is_plain_name(name) {
+            for (origin, dir) in &self.dirs {
+                let path = dir.join(format!("{name}.toml"));
+                if path.is_file() && skip != Some(path.as_path()) {
+                    return Some(ThemeLocation::File {
+                        origin: *origin,
+                        path,
+                    });
+                }
+            }
+        }
+        builtin::get_builtin_theme(name).map(ThemeLocation::BuiltIn)
+    }
+
+    /// Every theme, built-ins first, with custom themes replacing built-ins
+    /// of the same name and project themes replacing user themes.
+    pub fn list(&self) -> Vec<ThemeEntry> {
+        let mut entries: Vec<ThemeEntry> = builtin::list_themes()
+            .into_iter()
+            .map(ThemeEntry::from)
+            .collect();
+
+        // Lowest priority first, so later layers overwrite earlier ones.
+        for (origin, dir) in self.dirs.iter().rev() {
+            for path in toml_files(dir) {
+                let Some(entry) = custom_entry(&path, *origin, &entries) else {
+                    continue;
+                };
+                match entries.iter_mut().find(|e| e.name == entry.name) {
+                    Some(existing) => *existing = entry,
+                    None => entries.push(entry),
+                }
+            }
+        }
+
+        entries
+    }
+
+    /// Names of every theme, for typo suggestions.
+    pub fn names(&self) -> Vec<String> {
+        self.list().into_iter().map(|entry| entry.name).collect()
+    }
+}
+
+/// `$XDG_CONFIG_HOME/silkprint/themes`, or `~/.config/silkprint/themes`.
+#[cfg(not(target_arch = "wasm32"))]
+fn user_theme_dir() -> Option<PathBuf> {
+    let config = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+    Some(config.join("silkprint").join("themes"))
+}
+
+/// Whether `name` can be used as a file stem without escaping its directory.
+pub(crate) fn is_plain_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(['.', '_'])
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// The `.toml` files in `dir`, sorted by name.
+fn toml_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file()
+                && path.extension().is_some_and(|ext| ext == "toml")
+                && path
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .is_some_and(is_plain_name)
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// Read the `[meta]` summary of a custom theme. Fields it leaves out are
+/// taken from the theme it `extends`, when that is already listed.
+fn custom_entry(path: &Path, origin: ThemeOrigin, known: &[ThemeEntry]) -> Option<ThemeEntry> {
+    let name = path.file_stem()?.to_str()?.to_string();
+    let table = match std::fs::read_to_string(path)
+        .ok()
+        .and_then(|text| text.parse::<toml::Table>().ok())
+    {
+        Some(table) => table,
+        None => {
+            tracing::warn!(path = %path.display(), "skipping unreadable theme file");
+            return None;
+        }
+    };
+    let meta = table.get("meta").and_then(toml::Value::as_table);
+    let field = |key: &str| meta.and_then(|m| m.get(key));
+    let parent = field("extends")
+        .and_then(toml::Value::as_str)
+        .and_then(|parent| known.iter().find(|entry| entry.name == parent));
+
+    let variant = field("variant")
+        .and_then(toml::Value::as_str)
+        .map(str::to_string)
+        .or_else(|| parent.map(|p| p.variant.clone()))
+        .unwrap_or_else(|| "light".to_string());
+    let print_safe = field("print_safe")
+        .and_then(toml::Value::as_bool)
+        .or_else(|| parent.map(|p| p.print_safe))
+        .unwrap_or(false);
+    let description = field("description")
+        .and_then(toml::Value::as_str)
+        .filter(|d| !d.is_empty())
+        .map_or_else(|| path.display().to_string(), str::to_string);
+
+    Some(ThemeEntry {
+        name,
+        variant,
+        description,
+        print_safe,
+        family: origin.label().to_string(),
+        origin,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::ThemeSource;
+    use crate::error::SilkprintError;
+    use crate::theme::{load_theme_from, theme_files};
+    use crate::warnings::WarningCollector;
+
+    #[test]
+    fn project_themes_shadow_user_themes_and_builtins() {
+        let dir = tempdir().expect("should create temp dir");
+        let user = dir.path().join("user");
+        let project = dir.path().join("project");
+        std::fs::create_dir_all(&user).expect("should create user dir");
+        std::fs::create_dir_all(&project).expect("should create project dir");
+        std::fs::write(user.join("house.toml"), "[meta]\nvariant = \"dark\"\n")
+            .expect("should write theme");
+        std::fs::write(project.join("house.toml"), "[meta]\nextends = \"nord\"\n")
+            .expect("should write theme");
+        std::fs::write(project.join("nord.toml"), "[meta]\nextends = \"dracula\"\n")
+            .expect("should write theme");
+        std::fs::write(project.join("_partial.toml"), "").expect("should write theme");
+
+        let registry = ThemeRegistry::with_dirs(Some(user), Some(project.clone()));
+
+        assert_eq!(
+            registry.lookup("house"),
+            Some(ThemeLocation::File {
+                origin: ThemeOrigin::Project,
+                path: project.join("house.toml"),
+            })
+        );
+        assert!(matches!(
+            registry.lookup("nord"),
+            Some(ThemeLocation::File {
+                origin: ThemeOrigin::Project,
+                ..
+            })
+        ));
+        assert!(matches!(
+            registry.lookup("dracula"),
+            Some(ThemeLocation::BuiltIn(_))
+        ));
+        assert_eq!(registry.lookup("_partial"), None);
+        assert_eq!(registry.lookup("../project/house"), None);
+
+        let entries = registry.list();
+        let house = entries
+            .iter()
+            .find(|entry| entry.name == "house")
+            .expect("should list the custom theme");
+        assert_eq!(house.origin, ThemeOrigin::Project);
+        assert_eq!(house.variant, "dark", "should inherit nord's variant");
+        assert_eq!(
+            entries.iter().filter(|entry| entry.name == "nord").count(),
+            1
+        );
+        assert_eq!(entries.len(), builtin::list_themes().len() + 1);
+    }
+
+    #[test]
+    fn shadowing_theme_extends_the_layer_below() {
+        let dir = tempdir().expect("should create temp dir");
+        let user = dir.path().join("user");
+        let project = dir.path().join("project");
+        std::fs::create_dir_all(&user).expect("should create user dir");
+        std::fs::create_dir_all(&project).expect("should create project dir");
+        std::fs::write(
+            project.join("nord.toml"),
+            "[meta]\nname = \"nord\"\nextends = \"nord\"\n\n[headings]\ncolor = \"#7c3aed\"\n",
+        )
+        .expect("should write theme");
+        std::fs::write(
+            user.join("nord.toml"),
+            "[meta]\nname = \"nord\"\nextends = \"nord\"\n\n[links]\ncolor = \"#123456\"\n",
+        )
+        .expect("should write theme");
+        let registry = ThemeRegistry::with_dirs(Some(user.clone()), Some(project.clone()));
+        let source = ThemeSource::BuiltIn("nord".to_string());
+
+        let mut warnings = WarningCollector::new();
+        let theme = load_theme_from(&source, &registry, &mut warnings)
+            .expect("should resolve through to the built-in");
+        let builtin = load_theme_from(&source, &ThemeRegistry::default(), &mut warnings)
+            .expect("should load the built-in");
+        assert_eq!(theme.tokens.headings.color, "#7c3aed");
+        assert_eq!(theme.tokens.links.color, "#123456");
+        assert_eq!(theme.tokens.page.background, builtin.tokens.page.background);
+
+        assert_eq!(
+            theme_files(&source, &registry),
+            vec![project.join("nord.toml"), user.join("nord.toml")]
+        );
+    }
+
+    #[test]
+    fn custom_themes_extend_each_other() {
+        let dir = tempdir().expect("should create temp dir");
+        std::fs::write(
+            dir.path().join("base.toml"),
+            "[meta]\nname = \"Base\"\nextends = \"silk-light\"\n\n[colors]\nbrand = \"#7c3aed\"\n",
+        )
+        .expect("should write theme");
+        std::fs::write(
+            dir.path().join("report.toml"),
+            "[meta]\nname = \"Report\"\nextends = \"base\"\n\n[headings]\ncolor = \"brand\"\n",
+        )
+        .expect("should write theme");
+        let registry = ThemeRegistry::with_dirs(None, Some(dir.path().to_path_buf()));
+        let source = ThemeSource::BuiltIn("report".to_string());
+
+        let mut warnings = WarningCollector::new();
+        let theme =
+            load_theme_from(&source, &registry, &mut warnings).expect("should resolve the chain");
+        assert_eq!(theme.tokens.headings.color, "#7c3aed");
+
+        assert_eq!(
+            theme_files(&source, &registry),
+            vec![dir.path().join("report.toml"), dir.path().join("base.toml")]
+        );
+
+        let missing = ThemeSource::BuiltIn("reprot".to_string());
+        match load_theme_from(&missing, &registry, &mut warnings) {
+            Err(SilkprintError::ThemeNotFound { suggestions, .. }) => {
+                assert!(suggestions.contains("report"), "{suggestions}");
+            }
+            other => panic!("expected ThemeNotFound, got {other:?}"),
+        }
+    }
+}
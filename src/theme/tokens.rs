@@ -24,6 +24,10 @@ pub struct ThemeTokens {
     pub alerts: AlertTokens,
     pub toc: TocTokens,
     pub page_numbers: PageNumberTokens,
+    pub header: RunningTokens,
+    pub footer: RunningTokens,
+    pub watermark: WatermarkTokens,
+    pub classification: ClassificationTokens,
     pub title_page: TitlePageTokens,
     pub emphasis: EmphasisTokens,
     pub math: MathTokens,
@@ -41,6 +45,14 @@ pub struct MetaTokens {
     pub description: String,
     pub print_safe: bool,
     pub extends: String,
+    /// WCAG level (`"AA"` or `"AAA"`) to repair failing colors to. Left out
+    /// of serialization when unset, like the font sources.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub enforce_contrast: String,
+    /// Typst template (`.typ`) applied after the theme's own rules, relative
+    /// to the theme file.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub template: String,
 }
 
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
@@ -58,6 +70,15 @@ pub struct FontTokens {
     pub heading_fallback: Vec<String>,
     pub body_fallback: Vec<String>,
     pub mono_fallback: Vec<String>,
+    /// Font file or directory shipped with the theme, relative to the theme
+    /// file. Left out of serialization when unset so an empty value in a
+    /// child theme doesn't mask the parent's during `extends` merging.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub heading_source: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub body_source: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub mono_source: String,
 }
 
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
@@ -107,6 +128,11 @@ pub struct HeadingTokens {
     pub font: String,
     pub line_height: f64,
     pub letter_spacing: String,
+    /// Typst numbering pattern like `"1.1"` or `"I.a"`; empty for none.
+    /// Front matter `numbering:` overrides it.
+    pub numbering: String,
+    /// Color of heading numbers; falls back to the heading's color.
+    pub numbering_color: String,
     pub h1: HeadingLevelTokens,
     pub h2: HeadingLevelTokens,
     pub h3: HeadingLevelTokens,
@@ -186,6 +212,12 @@ pub struct TableTokens {
     pub stripe_background: String,
     pub vertical_lines: bool,
     pub cell_padding: String,
+    /// Tables with more columns than this are wide [default: 6].
+    pub wide_columns: u16,
+    /// What wide tables do: `shrink` (text scaled down from `font_size`),
+    /// `landscape` (a page of their own, turned sideways), `break` (across
+    /// pages, repeating the header row), or `none`.
+    pub wide: String,
 }
 
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
@@ -276,6 +308,54 @@ pub struct PageNumberTokens {
     pub first_page: bool,
 }
 
+/// A running header or footer: `left`, `center` and `right` slot templates
+/// (`{title}`, `{author}`, `{date}`, `{chapter}`, `{section}`, `{page}`,
+/// `{total}`). A footer takes the place of the plain page number, so put
+/// `{page}` in one of its slots.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RunningTokens {
+    pub enabled: bool,
+    pub left: String,
+    pub center: String,
+    pub right: String,
+    pub font: String,
+    pub size: String,
+    pub color: String,
+    /// Draw a rule between the header or footer and the body.
+    pub rule: bool,
+    pub rule_color: String,
+    /// Show on the title page too.
+    pub title_page: bool,
+    /// Hide on pages where a level-1 heading starts.
+    pub skip_chapter_pages: bool,
+}
+
+/// Defaults for a watermark. The text or image itself comes from front
+/// matter `watermark:` or `--watermark`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct WatermarkTokens {
+    pub color: String,
+    /// 0.0 (invisible) to 1.0 (solid).
+    pub opacity: Option<f64>,
+    /// Degrees; positive turns clockwise.
+    pub rotation: Option<f64>,
+    pub font: String,
+    pub size: String,
+}
+
+/// The banner front matter `classification:` puts at the top and bottom
+/// of every page.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ClassificationTokens {
+    pub background: String,
+    pub color: String,
+    pub font: String,
+    pub size: String,
+}
+
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
 #[serde(default)]
 pub struct TitlePageTokens {
@@ -1,13 +1,20 @@
 use std::fmt;
+use std::path::{Path, PathBuf};
+
+use comrak::nodes::Sourcepos;
+
+use crate::error::SilkprintError;
 
 /// Non-fatal warnings collected during rendering.
 ///
 /// Displayed after completion in default/verbose modes.
-/// Suppressed by `--quiet`. Never cause non-zero exit codes.
+/// Suppressed by `--quiet`. Only cause a non-zero exit when denied by a
+/// [`WarningPolicy`] (`--deny-warnings`, `--deny <name>`).
 #[derive(Debug, Clone)]
 pub enum SilkprintWarning {
     ImageNotFound {
         path: String,
+        location: Option<SourceLocation>,
     },
     FontNotAvailable {
         name: String,
@@ -15,6 +22,7 @@ pub enum SilkprintWarning {
     },
     UnknownLanguage {
         lang: String,
+        location: Option<SourceLocation>,
     },
     UnrecognizedFrontMatter {
         field: String,
@@ -24,6 +32,14 @@ pub enum SilkprintWarning {
         ratio: f64,
         minimum: f64,
     },
+    ContrastAdjusted {
+        element: String,
+        /// Dotted theme key that was changed (`links.color`).
+        field: String,
+        from: String,
+        to: String,
+        minimum: f64,
+    },
     RemoteImageSkipped {
         url: String,
     },
@@ -37,20 +53,113 @@ pub enum SilkprintWarning {
     },
     UnsupportedHtmlTag {
         tag: String,
+        location: Option<SourceLocation>,
     },
     FootnoteNotFound {
         name: String,
+        location: Option<SourceLocation>,
     },
     LinkValidationFailed {
         target: String,
         message: String,
+        location: Option<SourceLocation>,
+    },
+    ReferenceNotFound {
+        label: String,
+        location: Option<SourceLocation>,
+    },
+    DuplicateLabel {
+        label: String,
+        location: Option<SourceLocation>,
+    },
+    BibliographyNotFound {
+        path: String,
+    },
+    UnknownCitation {
+        key: String,
+        location: Option<SourceLocation>,
+    },
+    PdfStandardViolation {
+        standard: String,
+        message: String,
+        location: Option<SourceLocation>,
+    },
+    IncludeFailed {
+        target: String,
+        message: String,
+        location: Option<SourceLocation>,
+    },
+    TemplateNotFound {
+        path: String,
+        message: String,
+    },
+    TemplateNotAllowed {
+        path: String,
     },
 }
 
+/// Where in its Markdown source a warning points.
+///
+/// Lines and columns are 1-based; columns count bytes, as in comrak's
+/// `sourcepos`. The end position is inclusive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+    /// The file the position refers to, when known.
+    pub file: Option<PathBuf>,
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+impl SourceLocation {
+    /// The location of a parsed node, or `None` for synthetic nodes that
+    /// don't come from the source.
+    pub fn from_sourcepos(pos: Sourcepos) -> Option<Self> {
+        (pos.start.line > 0).then(|| Self {
+            file: None,
+            line: pos.start.line,
+            column: pos.start.column.max(1),
+            end_line: pos.end.line.max(pos.start.line),
+            end_column: pos.end.column,
+        })
+    }
+
+    /// Byte offset and length of this location within `text`, the full
+    /// contents of [`file`](Self::file). `None` if it doesn't fit.
+    pub fn byte_span(&self, text: &str) -> Option<(usize, usize)> {
+        let start = line_start(text, self.line)? + self.column - 1;
+        let end = (line_start(text, self.end_line)? + self.end_column).min(text.len());
+        let valid = start <= end && text.is_char_boundary(start) && text.is_char_boundary(end);
+        valid.then_some((start, end - start))
+    }
+}
+
+/// Where a run of lines in a parsed body came from, once includes have
+/// spliced other files into it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineOrigin {
+    /// First body line (1-based) of the run.
+    pub start: usize,
+    /// The included file the run came from; `None` for the document itself.
+    pub file: Option<PathBuf>,
+    /// Line in that file the run starts at.
+    pub line: usize,
+}
+
+/// Byte offset at which 1-based `line` starts.
+fn line_start(text: &str, line: usize) -> Option<usize> {
+    match line {
+        0 => None,
+        1 => Some(0),
+        _ => text.match_indices('\n').nth(line - 2).map(|(i, _)| i + 1),
+    }
+}
+
 impl fmt::Display for SilkprintWarning {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::ImageNotFound { path } => {
+            Self::ImageNotFound { path, .. } => {
                 write!(f, "image '{path}' not found, skipping")
             }
             Self::FontNotAvailable { name, fallback } => {
@@ -59,7 +168,7 @@ impl fmt::Display for SilkprintWarning {
                     "font '{name}' not available, falling back to '{fallback}'"
                 )
             }
-            Self::UnknownLanguage { lang } => {
+            Self::UnknownLanguage { lang, .. } => {
                 write!(
                     f,
                     "code block language '{lang}' not recognized for highlighting"
@@ -78,6 +187,18 @@ impl fmt::Display for SilkprintWarning {
                     "{element}: contrast ratio {ratio:.2}:1 below minimum {minimum:.1}:1"
                 )
             }
+            Self::ContrastAdjusted {
+                element,
+                field,
+                from,
+                to,
+                minimum,
+            } => {
+                write!(
+                    f,
+                    "{element}: adjusted {field} from {from} to {to} to reach {minimum:.1}:1"
+                )
+            }
             Self::RemoteImageSkipped { url } => {
                 write!(
                     f,
@@ -90,23 +211,224 @@ impl fmt::Display for SilkprintWarning {
             Self::MermaidRenderFailed { index, message } => {
                 write!(f, "mermaid diagram {index} failed to render: {message}")
             }
-            Self::UnsupportedHtmlTag { tag } => {
+            Self::UnsupportedHtmlTag { tag, .. } => {
                 write!(f, "unsupported HTML tag <{tag}>, content may be lost")
             }
-            Self::FootnoteNotFound { name } => {
+            Self::FootnoteNotFound { name, .. } => {
                 write!(f, "footnote '{name}' referenced but not defined")
             }
-            Self::LinkValidationFailed { target, message } => {
+            Self::LinkValidationFailed {
+                target, message, ..
+            } => {
                 write!(f, "link validation failed for '{target}': {message}")
             }
+            Self::ReferenceNotFound { label, .. } => {
+                write!(f, "reference '@{label}' does not match any labeled element")
+            }
+            Self::DuplicateLabel { label, .. } => {
+                write!(
+                    f,
+                    "label '{label}' is defined more than once, keeping the first"
+                )
+            }
+            Self::BibliographyNotFound { path } => {
+                write!(f, "bibliography '{path}' not found, citations left as text")
+            }
+            Self::UnknownCitation { key, .. } => {
+                write!(f, "citation key '@{key}' not found in the bibliography")
+            }
+            Self::PdfStandardViolation {
+                standard, message, ..
+            } => {
+                write!(f, "{standard}: {message}")
+            }
+            Self::IncludeFailed {
+                target, message, ..
+            } => {
+                write!(f, "include '{target}' skipped: {message}")
+            }
+            Self::TemplateNotFound { path, message } => {
+                write!(f, "template '{path}' skipped: {message}")
+            }
+            Self::TemplateNotAllowed { path } => {
+                write!(
+                    f,
+                    "template '{path}' skipped: front matter templates need --allow-raw-typst"
+                )
+            }
         }
     }
 }
 
+/// Every warning's `--deny` name and stable diagnostic code, in the same
+/// `silkprint::area::kind` scheme as [`SilkprintError`]'s codes.
+pub const WARNING_CODES: &[(&str, &str)] = &[
+    ("image-not-found", "silkprint::image::not_found"),
+    ("font-not-available", "silkprint::font::not_available"),
+    ("unknown-language", "silkprint::code::unknown_language"),
+    (
+        "unrecognized-front-matter",
+        "silkprint::frontmatter::unrecognized",
+    ),
+    ("contrast-ratio", "silkprint::theme::contrast"),
+    ("contrast-adjusted", "silkprint::theme::contrast_adjusted"),
+    ("remote-image-skipped", "silkprint::image::remote_skipped"),
+    (
+        "remote-image-fetch-failed",
+        "silkprint::image::remote_fetch_failed",
+    ),
+    ("mermaid-render-failed", "silkprint::mermaid::render_failed"),
+    ("unsupported-html-tag", "silkprint::html::unsupported_tag"),
+    ("footnote-not-found", "silkprint::footnote::not_found"),
+    (
+        "link-validation-failed",
+        "silkprint::link::validation_failed",
+    ),
+    ("reference-not-found", "silkprint::crossref::not_found"),
+    ("duplicate-label", "silkprint::crossref::duplicate_label"),
+    (
+        "bibliography-not-found",
+        "silkprint::bibliography::not_found",
+    ),
+    (
+        "unknown-citation",
+        "silkprint::bibliography::unknown_citation",
+    ),
+    (
+        "pdf-standard-violation",
+        "silkprint::pdf::standard_violation",
+    ),
+    ("include-failed", "silkprint::include::failed"),
+    ("template-not-found", "silkprint::template::not_found"),
+    ("template-not-allowed", "silkprint::template::not_allowed"),
+];
+
+impl SilkprintWarning {
+    /// Short kebab-case name, as accepted by `--deny` (`image-not-found`).
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::ImageNotFound { .. } => "image-not-found",
+            Self::FontNotAvailable { .. } => "font-not-available",
+            Self::UnknownLanguage { .. } => "unknown-language",
+            Self::UnrecognizedFrontMatter { .. } => "unrecognized-front-matter",
+            Self::ContrastRatio { .. } => "contrast-ratio",
+            Self::ContrastAdjusted { .. } => "contrast-adjusted",
+            Self::RemoteImageSkipped { .. } => "remote-image-skipped",
+            Self::RemoteImageFetchFailed { .. } => "remote-image-fetch-failed",
+            Self::MermaidRenderFailed { .. } => "mermaid-render-failed",
+            Self::UnsupportedHtmlTag { .. } => "unsupported-html-tag",
+            Self::FootnoteNotFound { .. } => "footnote-not-found",
+            Self::LinkValidationFailed { .. } => "link-validation-failed",
+            Self::ReferenceNotFound { .. } => "reference-not-found",
+            Self::DuplicateLabel { .. } => "duplicate-label",
+            Self::BibliographyNotFound { .. } => "bibliography-not-found",
+            Self::UnknownCitation { .. } => "unknown-citation",
+            Self::PdfStandardViolation { .. } => "pdf-standard-violation",
+            Self::IncludeFailed { .. } => "include-failed",
+            Self::TemplateNotFound { .. } => "template-not-found",
+            Self::TemplateNotAllowed { .. } => "template-not-allowed",
+        }
+    }
+
+    /// Where in the source this warning points, for Markdown-level problems.
+    pub fn location(&self) -> Option<&SourceLocation> {
+        match self {
+            Self::ImageNotFound { location, .. }
+            | Self::UnknownLanguage { location, .. }
+            | Self::UnsupportedHtmlTag { location, .. }
+            | Self::FootnoteNotFound { location, .. }
+            | Self::LinkValidationFailed { location, .. }
+            | Self::ReferenceNotFound { location, .. }
+            | Self::DuplicateLabel { location, .. }
+            | Self::UnknownCitation { location, .. }
+            | Self::PdfStandardViolation { location, .. }
+            | Self::IncludeFailed { location, .. } => location.as_ref(),
+            _ => None,
+        }
+    }
+
+    fn location_mut(&mut self) -> Option<&mut SourceLocation> {
+        self.location_slot().and_then(Option::as_mut)
+    }
+
+    /// The `location` field of warnings that carry one.
+    fn location_slot(&mut self) -> Option<&mut Option<SourceLocation>> {
+        match self {
+            Self::ImageNotFound { location, .. }
+            | Self::UnknownLanguage { location, .. }
+            | Self::UnsupportedHtmlTag { location, .. }
+            | Self::FootnoteNotFound { location, .. }
+            | Self::LinkValidationFailed { location, .. }
+            | Self::ReferenceNotFound { location, .. }
+            | Self::DuplicateLabel { location, .. }
+            | Self::UnknownCitation { location, .. }
+            | Self::PdfStandardViolation { location, .. }
+            | Self::IncludeFailed { location, .. } => Some(location),
+            _ => None,
+        }
+    }
+
+    /// Stable diagnostic code (`silkprint::image::not_found`).
+    pub fn code(&self) -> &'static str {
+        let name = self.name();
+        WARNING_CODES
+            .iter()
+            .find(|(known, _)| *known == name)
+            .map_or("silkprint::warning", |(_, code)| code)
+    }
+}
+
+/// Which warnings fail the build: all of them, or only the named ones.
+#[derive(Debug, Clone, Default)]
+pub struct WarningPolicy {
+    deny_all: bool,
+    deny: Vec<String>,
+}
+
+impl WarningPolicy {
+    /// Build a policy from `--deny-warnings` and `--deny <name>` values.
+    ///
+    /// Names may be short (`image-not-found`) or full codes
+    /// (`silkprint::image::not_found`).
+    pub fn new(deny_all: bool, deny: &[String]) -> Result<Self, SilkprintError> {
+        for name in deny {
+            let known = WARNING_CODES
+                .iter()
+                .any(|(short, code)| name == short || name == code);
+            if !known {
+                return Err(SilkprintError::UnknownWarning {
+                    name: name.clone(),
+                    names: WARNING_CODES
+                        .iter()
+                        .map(|(short, _)| *short)
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                });
+            }
+        }
+        Ok(Self {
+            deny_all,
+            deny: deny.to_vec(),
+        })
+    }
+
+    /// Whether `warning` should fail the build.
+    pub fn is_denied(&self, warning: &SilkprintWarning) -> bool {
+        self.deny_all
+            || self
+                .deny
+                .iter()
+                .any(|name| name == warning.name() || name == warning.code())
+    }
+}
+
 /// Collects warnings during the rendering pipeline.
 #[derive(Debug, Default)]
 pub struct WarningCollector {
     warnings: Vec<SilkprintWarning>,
+    source_file: Option<PathBuf>,
+    line_offset: usize,
+    line_origins: Vec<LineOrigin>,
 }
 
 impl WarningCollector {
@@ -114,10 +436,68 @@ impl WarningCollector {
         Self::default()
     }
 
-    pub fn push(&mut self, warning: SilkprintWarning) {
+    /// Attribute located warnings pushed from now on to `file`, whose
+    /// Markdown body starts after `line_offset` lines of front matter.
+    ///
+    /// Parsing sees only the body, so comrak's line numbers are shifted back
+    /// into file coordinates here.
+    pub fn set_source(&mut self, file: Option<&Path>, line_offset: usize) {
+        self.source_file = file.map(Path::to_path_buf);
+        self.line_offset = line_offset;
+        self.line_origins.clear();
+    }
+
+    /// Map body lines back to the files includes spliced in, until the next
+    /// [`set_source`](Self::set_source).
+    pub fn set_line_origins(&mut self, origins: Vec<LineOrigin>) {
+        self.line_origins = origins;
+    }
+
+    /// Push a warning. Located warnings without a file are taken to be in
+    /// body coordinates and moved into file coordinates; ones that already
+    /// name a file are kept as they are.
+    pub fn push(&mut self, mut warning: SilkprintWarning) {
+        if let Some(location) = warning.location_mut()
+            && location.file.is_none()
+        {
+            let origin = self
+                .line_origins
+                .iter()
+                .rev()
+                .find(|origin| origin.start <= location.line);
+            let (file, line) = match origin {
+                Some(LineOrigin {
+                    start,
+                    file: Some(file),
+                    line,
+                }) => (Some(file), line + location.line - start),
+                Some(LineOrigin { start, line, .. }) => (
+                    self.source_file.as_ref(),
+                    line + location.line - start + self.line_offset,
+                ),
+                None => (self.source_file.as_ref(), location.line + self.line_offset),
+            };
+            location.end_line = line + location.end_line.saturating_sub(location.line);
+            location.line = line;
+            location.file = file.cloned();
+        }
         self.warnings.push(warning);
     }
 
+    /// Push every warning from `found`, pointing the ones that could carry
+    /// a location but don't at `location` — for helpers that see only a
+    /// fragment of a node, like embedded HTML.
+    pub fn extend_located(&mut self, found: Self, location: Option<&SourceLocation>) {
+        for mut warning in found.warnings {
+            if let Some(slot) = warning.location_slot()
+                && slot.is_none()
+            {
+                *slot = location.cloned();
+            }
+            self.push(warning);
+        }
+    }
+
     pub fn warnings(&self) -> &[SilkprintWarning] {
         &self.warnings
     }
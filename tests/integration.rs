@@ -62,6 +62,32 @@ fn test_conflicting_flags() {
         .failure();
 }
 
+#[test]
+fn test_watch_rejects_stdout_output() {
+    silkprint()
+        .arg("tests/fixtures/basic.md")
+        .arg("--watch")
+        .arg("-o")
+        .arg("-")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Conflicting"));
+}
+
+#[test]
+fn test_batch_rejects_single_output_flags() {
+    for flag in ["-o", "--dump-typst", "--watch"] {
+        let mut cmd = silkprint();
+        cmd.arg("tests/fixtures/*.md").arg(flag);
+        if flag == "-o" {
+            cmd.arg("out.pdf");
+        }
+        cmd.assert()
+            .failure()
+            .stderr(predicate::str::contains("Conflicting"));
+    }
+}
+
 #[test]
 fn test_nonexistent_theme() {
     silkprint()
@@ -114,6 +140,30 @@ fn test_dump_typst() {
         .stdout(predicate::str::contains("#set").or(predicate::str::contains("page")));
 }
 
+#[test]
+fn test_print_safe_dump_typst() {
+    silkprint()
+        .args([
+            "--theme",
+            "silkcircuit-neon",
+            "--print-safe=greyscale",
+            "--dump-typst",
+        ])
+        .arg("tests/fixtures/basic.md")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("fill: rgb(\"#ffffff\")"));
+    silkprint()
+        .args([
+            "--print-safe=sepia",
+            "--dump-typst",
+            "tests/fixtures/basic.md",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("print-safe"));
+}
+
 #[test]
 fn test_dump_typst_resolves_relative_image_paths() {
     let dir = tempdir().expect("should create temp dir");
@@ -133,7 +183,244 @@ fn test_dump_typst_resolves_relative_image_paths() {
         .arg(&markdown_path)
         .assert()
         .success()
-        .stdout(predicate::str::contains("image(\"badge.svg\")"));
+        .stdout(predicate::str::contains(
+            "image(\"badge.svg\", alt: \"Badge\")",
+        ));
+}
+
+#[test]
+fn test_dump_typst_resolves_includes() {
+    let dir = tempdir().expect("should create temp dir");
+    std::fs::create_dir_all(dir.path().join("common")).expect("should create dir");
+    std::fs::create_dir_all(dir.path().join("docs")).expect("should create dir");
+    std::fs::write(
+        dir.path().join("common/setup.md"),
+        "# Setup\n\nInstall it.\n",
+    )
+    .expect("should write include");
+    std::fs::write(
+        dir.path().join("common/run.sh"),
+        "#!/bin/sh\n# ANCHOR: run\nmake deploy\n# ANCHOR_END: run\n",
+    )
+    .expect("should write snippet");
+    let markdown_path = dir.path().join("docs/runbook.md");
+    std::fs::write(
+        &markdown_path,
+        "# Runbook\n\n{{#include ../common/setup.md}}\n\n```sh file=../common/run.sh region=run\n```\n\n{{#include missing.md}}\n",
+    )
+    .expect("should write markdown fixture");
+
+    // Without a project marker, the document's directory is the root.
+    silkprint()
+        .arg("--dump-typst")
+        .arg(&markdown_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Install it").not())
+        .stderr(predicate::str::contains("project root"));
+
+    std::fs::create_dir(dir.path().join(".git")).expect("should mark project root");
+    silkprint()
+        .arg("--dump-typst")
+        .arg(&markdown_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("== Setup"))
+        .stdout(predicate::str::contains("Install it"))
+        .stdout(predicate::str::contains("make deploy"))
+        .stdout(predicate::str::contains("ANCHOR").not())
+        .stderr(predicate::str::contains("missing.md"));
+}
+
+#[test]
+fn test_dump_typst_template_and_raw_typst() {
+    let dir = tempdir().expect("should create temp dir");
+    std::fs::write(dir.path().join("layout.typ"), "#set page(columns: 2)\n")
+        .expect("should write template");
+    let markdown_path = dir.path().join("doc.md");
+    std::fs::write(
+        &markdown_path,
+        "---\ntemplate: layout.typ\n---\n\n# Doc\n\n```typst\n#colbreak()\n```\n",
+    )
+    .expect("should write markdown fixture");
+
+    silkprint()
+        .arg("--dump-typst")
+        .arg(&markdown_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("#set page(columns: 2)").not())
+        .stdout(predicate::str::contains("```typst\n#colbreak()"))
+        .stderr(predicate::str::contains("--allow-raw-typst"));
+
+    silkprint()
+        .args(["--dump-typst", "--allow-raw-typst"])
+        .arg(&markdown_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("#set page(columns: 2)"))
+        .stdout(predicate::str::contains("\n#colbreak()\n"))
+        .stdout(predicate::str::contains("```typst").not());
+}
+
+#[test]
+fn test_dump_typst_running_header_and_footer() {
+    let dir = tempdir().expect("should create temp dir");
+    let markdown_path = dir.path().join("doc.md");
+    std::fs::write(
+        &markdown_path,
+        "---\ntitle: Report\nheader:\n  left: \"{title}\"\n  right: \"{chapter}\"\n\
+         footer:\n  center: \"{page} / {total}\"\n---\n\n# One\n\nText.\n\n# Two\n\nMore.\n",
+    )
+    .expect("should write markdown fixture");
+
+    silkprint()
+        .arg("--dump-typst")
+        .arg(&markdown_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("  header: context {"))
+        .stdout(predicate::str::contains("silkprint-running-heading(1)"))
+        .stdout(predicate::str::contains("  footer: context {"))
+        .stdout(predicate::str::contains("counter(page).display("));
+}
+
+#[test]
+fn test_dump_typst_numbered_headings_with_appendix() {
+    let dir = tempdir().expect("should create temp dir");
+    let markdown_path = dir.path().join("doc.md");
+    std::fs::write(
+        &markdown_path,
+        "---\nnumbering: true\ntoc: true\n---\n\n# Method {#sec:method}\n\n\
+         ## Aside {-}\n\nSee @sec:method and @sec:aside.\n\n## Detail {#sec:aside .unnumbered}\n\n\
+         # Appendix\n\n# Data\n\nText.\n",
+    )
+    .expect("should write markdown fixture");
+
+    silkprint()
+        .arg("--dump-typst")
+        .arg(&markdown_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("#set heading(numbering: \"1.1\")"))
+        .stdout(predicate::str::contains(
+            "#counter(heading).update(0)\n#set heading(numbering: \"A.1\")\n",
+        ))
+        .stdout(predicate::str::contains("== Detail <sec:aside>"));
+}
+
+#[test]
+fn test_dump_typst_two_columns_with_full_width_abstract() {
+    let dir = tempdir().expect("should create temp dir");
+    let markdown_path = dir.path().join("doc.md");
+    std::fs::write(
+        &markdown_path,
+        "---\ntitle: Paper\ncolumns: 2\n---\n\n<!-- columns: 1 -->\n\n**Abstract.** Short.\n\n\
+         <!-- columns: 2 -->\n\n# Body\n\nText.\n\n| a | b |\n|---|---|\n| 1 | 2 |\n\n{.wide}\n\n\
+         ::: columns 3\n\nThree.\n\n:::\n",
+    )
+    .expect("should write markdown fixture");
+
+    silkprint()
+        .arg("--dump-typst")
+        .arg(&markdown_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("  columns: 2,"))
+        .stdout(predicate::str::contains("#set columns(gutter: "))
+        .stdout(predicate::str::contains(
+            "#place(top, scope: \"parent\", float: true, clearance: 1.5em)[",
+        ))
+        .stdout(predicate::str::contains("#columns(3, gutter: "))
+        .stdout(predicate::str::contains(
+            "#place(auto, scope: \"parent\", float: true, clearance: 1em)[",
+        ));
+}
+
+#[test]
+fn test_dump_typst_landscape_sections_and_wide_tables() {
+    let dir = tempdir().expect("should create temp dir");
+    let theme_path = dir.path().join("wide.toml");
+    std::fs::write(
+        &theme_path,
+        "[meta]\nname = \"Wide\"\nextends = \"silk-light\"\n\n[table]\nwide = \"landscape\"\nwide_columns = 4\n",
+    )
+    .expect("should write theme");
+    let markdown_path = dir.path().join("doc.md");
+    std::fs::write(
+        &markdown_path,
+        "# Data\n\n| a | b | c | d | e |\n|---|---|---|---|---|\n| 1 | 2 | 3 | 4 | 5 |\n\n\
+         Table: Wide {#tbl:wide}\n\nSee @tbl:wide.\n\n```csv\na,b,c,d,e,f\n1,2,3,4,5,6\n```\n\n\
+         ::: landscape\n\n## Sideways\n\nText.\n\n:::\n\n<!-- landscape -->\n\nMore.\n\n\
+         <!-- portrait -->\n\nDone.\n",
+    )
+    .expect("should write markdown fixture");
+
+    silkprint()
+        .arg("--theme")
+        .arg(&theme_path)
+        .arg("--dump-typst")
+        .arg(&markdown_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "#page(flipped: true, columns: 1)[",
+        ))
+        .stdout(predicate::str::contains("\n#page(flipped: true)[\n"))
+        .stdout(predicate::str::contains("== Sideways"));
+}
+
+#[test]
+fn test_dump_typst_watermark_and_classification() {
+    let dir = tempdir().expect("should create temp dir");
+    let markdown_path = dir.path().join("doc.md");
+    std::fs::write(
+        &markdown_path,
+        "---\ntitle: Plan\nclassification: INTERNAL\n---\n\n# Plan\n\nText.\n",
+    )
+    .expect("should write markdown fixture");
+
+    silkprint()
+        .args(["--watermark", "DRAFT"])
+        .arg("--dump-typst")
+        .arg(&markdown_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("  keywords: (\"INTERNAL\",),"))
+        .stdout(predicate::str::contains(
+            "  background: place(center + horizon, rotate(-45deg, text(",
+        ))
+        .stdout(predicate::str::contains("\"DRAFT\")))"))
+        .stdout(predicate::str::contains("\"INTERNAL\")))"));
+}
+
+#[test]
+fn test_watermark_image_resolves_against_cwd() {
+    let dir = tempdir().expect("should create temp dir");
+    let docs = dir.path().join("docs");
+    std::fs::create_dir_all(docs.join("assets")).expect("should create dirs");
+    std::fs::write(docs.join("a.md"), "# Plan\n").expect("should write doc");
+    std::fs::write(docs.join("assets/seal.png"), b"png").expect("should write image");
+    std::fs::write(dir.path().join("outside.png"), b"png").expect("should write image");
+
+    silkprint()
+        .current_dir(dir.path())
+        .args([
+            "--dump-typst",
+            "--watermark",
+            "docs/assets/seal.png",
+            "docs/a.md",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("image(\"/assets/seal.png\""));
+
+    silkprint()
+        .current_dir(dir.path())
+        .args(["--dump-typst", "--watermark", "outside.png", "docs/a.md"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("outside the document root"));
 }
 
 // ── Render modes ─────────────────────────────────────────────────
@@ -187,3 +474,445 @@ fn test_theme_flag() {
         .assert()
         .success();
 }
+
+#[test]
+fn test_project_and_user_themes() {
+    let dir = tempdir().expect("should create temp dir");
+    let project_themes = dir.path().join("project/.silkprint/themes");
+    let user_themes = dir.path().join("config/silkprint/themes");
+    std::fs::create_dir_all(&project_themes).expect("should create project theme dir");
+    std::fs::create_dir_all(&user_themes).expect("should create user theme dir");
+    std::fs::write(
+        user_themes.join("house.toml"),
+        "[meta]\nname = \"House\"\nextends = \"silk-light\"\ndescription = \"Company style\"\n",
+    )
+    .expect("should write theme");
+    std::fs::write(
+        project_themes.join("handbook.toml"),
+        "[meta]\nname = \"Handbook\"\nextends = \"house\"\n",
+    )
+    .expect("should write theme");
+    std::fs::write(dir.path().join("project/doc.md"), "# Doc\n").expect("should write doc");
+
+    let run = || {
+        let mut cmd = silkprint();
+        cmd.current_dir(dir.path().join("project"))
+            .env("XDG_CONFIG_HOME", dir.path().join("config"));
+        cmd
+    };
+
+    run()
+        .arg("--list-themes")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("house").and(predicate::str::contains("[user]")))
+        .stdout(predicate::str::contains("handbook").and(predicate::str::contains("[project]")));
+    run()
+        .arg("--theme")
+        .arg("handbook")
+        .arg("--check")
+        .arg("doc.md")
+        .assert()
+        .success();
+    run()
+        .arg("--theme")
+        .arg("handbok")
+        .arg("--check")
+        .arg("doc.md")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("handbook"));
+}
+
+// ── Batch mode ───────────────────────────────────────────────────
+
+#[test]
+fn test_batch_mirrors_tree_and_reports_failures() {
+    let dir = tempdir().expect("should create temp dir");
+    let docs = dir.path().join("docs");
+    std::fs::create_dir_all(docs.join("ops")).expect("should create dirs");
+    std::fs::write(docs.join("index.md"), "# Index\n").expect("should write doc");
+    std::fs::write(docs.join("ops/restore.md"), "# Restore\n").expect("should write doc");
+    let out = dir.path().join("build");
+
+    silkprint()
+        .arg("pdf")
+        .arg(format!("{}/**/*.md", docs.display()))
+        .arg("--out-dir")
+        .arg(&out)
+        .arg("--jobs")
+        .arg("2")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("2 rendered"));
+
+    assert!(out.join("index.pdf").is_file(), "index.pdf should exist");
+    assert!(
+        out.join("ops/restore.pdf").is_file(),
+        "ops/restore.pdf should mirror the source tree"
+    );
+
+    std::fs::write(
+        docs.join("broken.md"),
+        "---\ntheme: no-such-theme\n---\n\n# Broken\n",
+    )
+    .expect("should write doc");
+    silkprint()
+        .arg("pdf")
+        .arg(format!("{}/*.md", docs.display()))
+        .arg("--out-dir")
+        .arg(&out)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("1 failed"));
+    assert!(out.join("index.pdf").is_file());
+}
+
+#[test]
+fn test_json_messages_and_denied_warnings() {
+    let dir = tempdir().expect("should create temp dir");
+    let input = dir.path().join("doc.md");
+    std::fs::write(
+        &input,
+        "---\ntitle: Doc\n---\n\n# Doc\n\n![gone](missing.png)\n",
+    )
+    .expect("should write doc");
+
+    silkprint()
+        .arg("--check")
+        .arg("--message-format")
+        .arg("json")
+        .arg(&input)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            r#""severity":"warning","code":"silkprint::image::not_found""#,
+        ))
+        .stdout(predicate::str::contains(
+            r#""span":{"offset":27,"length":20,"line":7,"column":1}"#,
+        ));
+
+    silkprint()
+        .arg("--check")
+        .arg("--message-format")
+        .arg("json")
+        .arg("--deny")
+        .arg("image-not-found")
+        .arg(&input)
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            r#""severity":"error","code":"silkprint::image::not_found""#,
+        ))
+        .stdout(predicate::str::contains(
+            r#""code":"silkprint::warning::denied""#,
+        ));
+}
+
+#[test]
+fn test_pdf_standards() {
+    let dir = tempdir().expect("should create temp dir");
+    let input = dir.path().join("doc.md");
+    std::fs::write(&input, "# Doc\n\nArchived text.\n").expect("should write doc");
+    let output = dir.path().join("doc.pdf");
+
+    silkprint()
+        .arg("pdf")
+        .arg(&input)
+        .arg("-o")
+        .arg(&output)
+        .arg("--pdf-standard")
+        .arg("a-2b")
+        .assert()
+        .success();
+    assert!(output.is_file(), "PDF/A output should be written");
+
+    std::fs::write(&input, "# Doc\n\n![](chart.png)\n").expect("should write doc");
+    silkprint()
+        .arg("pdf")
+        .arg(&input)
+        .arg("-o")
+        .arg(dir.path().join("ua.pdf"))
+        .arg("--pdf-standard")
+        .arg("ua-1")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("PDF/UA-1"))
+        .stderr(predicate::str::contains("no alt text"));
+}
+
+// ── Book mode ────────────────────────────────────────────────────
+
+#[test]
+fn test_book_renders_summary_to_single_pdf() {
+    let dir = tempdir().expect("should create temp dir");
+    std::fs::write(
+        dir.path().join("SUMMARY.md"),
+        "# Summary\n\n- [Intro](intro.md)\n- [Usage](usage.md)\n",
+    )
+    .expect("should write summary");
+    std::fs::write(
+        dir.path().join("intro.md"),
+        "# Intro\n\nContinue with [usage](usage.md#flags).\n",
+    )
+    .expect("should write chapter");
+    std::fs::write(
+        dir.path().join("usage.md"),
+        "# Usage\n\n## Flags\n\nAll of them.\n",
+    )
+    .expect("should write chapter");
+
+    silkprint()
+        .arg("book")
+        .arg(dir.path())
+        .arg("--dump-typst")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("#outline(").and(predicate::str::contains(
+                "#link(label(\"chapter-2-flags\"))",
+            )),
+        );
+
+    let output = silkprint()
+        .arg("book")
+        .arg(dir.path())
+        .arg("-o")
+        .arg("-")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    assert!(output.starts_with(b"%PDF"), "book output should be a PDF");
+}
+
+#[test]
+fn test_citations_render_with_bibliography() {
+    let dir = tempdir().expect("should create temp dir");
+    std::fs::write(
+        dir.path().join("refs.bib"),
+        "@article{knuth84,\n  author = {Knuth, Donald E.},\n  title = {Literate Programming},\n  journal = {The Computer Journal},\n  year = {1984},\n}\n",
+    )
+    .expect("should write bibliography");
+    let markdown_path = dir.path().join("paper.md");
+    std::fs::write(
+        &markdown_path,
+        "---\nbibliography: refs.bib\ncsl: ieee\n---\n\n# Paper\n\nAs argued [@knuth84, p. 97], and [@missing].\n",
+    )
+    .expect("should write markdown fixture");
+
+    silkprint()
+        .arg("--dump-typst")
+        .arg(&markdown_path)
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("#cite(label(\"knuth84\"), supplement: [p. 97])").and(
+                predicate::str::contains("#bibliography(\"/refs.bib\", style: \"ieee\")"),
+            ),
+        )
+        .stderr(predicate::str::contains("missing"));
+
+    let output = silkprint()
+        .arg(&markdown_path)
+        .arg("-o")
+        .arg("-")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    assert!(output.starts_with(b"%PDF"), "cited document should render");
+}
+
+// ── Theme authoring ──────────────────────────────────────────────
+
+#[test]
+fn test_theme_new_check_and_preview() {
+    let dir = tempdir().expect("should create temp dir");
+    let run = || {
+        let mut cmd = silkprint();
+        cmd.current_dir(dir.path());
+        cmd
+    };
+
+    run()
+        .args(["theme", "new", "house", "--extends", "silk-dark"])
+        .assert()
+        .success();
+    let theme_path = dir.path().join(".silkprint/themes/house.toml");
+    let scaffold = std::fs::read_to_string(&theme_path).expect("should write the scaffold");
+    assert!(scaffold.contains("extends = \"silk-dark\""), "{scaffold}");
+    run()
+        .args(["theme", "new", "house"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("house.toml"));
+
+    run().args(["theme", "check", "house"]).assert().success();
+
+    std::fs::write(
+        &theme_path,
+        format!("{scaffold}\n[headings]\ncolour = \"accent\"\n\n[links]\ncolor = \"acent\"\n"),
+    )
+    .expect("should write theme");
+    run()
+        .args(["theme", "check", ".silkprint/themes/house.toml"])
+        .assert()
+        .failure()
+        .stderr(
+            predicate::str::contains("unknown key `headings.colour`")
+                .and(predicate::str::contains("`acent` is neither"))
+                .and(predicate::str::contains("2 problem(s)")),
+        );
+
+    run()
+        .args(["theme", "preview", "silk-light"])
+        .assert()
+        .success();
+    let pdf = std::fs::read(dir.path().join("silk-light-preview.pdf"))
+        .expect("should write the preview PDF");
+    assert!(pdf.starts_with(b"%PDF"));
+}
+
+#[test]
+fn test_theme_import_vscode() {
+    let dir = tempdir().expect("should create temp dir");
+    std::fs::write(
+        dir.path().join("dusk.json"),
+        r##"{
+            // Exported from VS Code
+            "name": "Dusk Harbor",
+            "type": "dark",
+            "colors": { "editor.background": "#14161b", "editor.foreground": "#c8ccd4" },
+            "tokenColors": [
+                { "scope": "keyword", "settings": { "foreground": "#c792ea" } },
+                { "scope": "comment", "settings": { "foreground": "#2c313a" } },
+            ],
+        }"##,
+    )
+    .expect("should write editor theme");
+    let run = || {
+        let mut cmd = silkprint();
+        cmd.current_dir(dir.path());
+        cmd
+    };
+
+    run()
+        .args(["theme", "import", "dusk.json"])
+        .assert()
+        .success()
+        .stderr(
+            predicate::str::contains("syntax: comment").and(predicate::str::contains("#2c313a")),
+        );
+    let theme = std::fs::read_to_string(dir.path().join(".silkprint/themes/dusk-harbor.toml"))
+        .expect("should write the theme");
+    assert!(theme.contains("name = \"Dusk Harbor\""), "{theme}");
+
+    run()
+        .args(["theme", "check", "dusk-harbor", "--deny", "contrast-ratio"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_theme_export_formats() {
+    silkprint()
+        .args(["theme", "export", "silk-light"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains(":root {")
+                .and(predicate::str::contains("--sp-page-background: #")),
+        );
+    silkprint()
+        .args(["theme", "export", "silk-light", "--format", "json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"syntax.keyword.color\": \"#"));
+    silkprint()
+        .args([
+            "theme",
+            "export",
+            "silk-dark",
+            "--format",
+            "tmtheme",
+            "--print-safe",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("<plist").and(predicate::str::contains("#ffffff")));
+    silkprint()
+        .args(["theme", "export", "silk-dark", "--format", "opaline"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[tokens]"));
+}
+
+#[test]
+fn test_fix_contrast() {
+    let dir = tempdir().expect("should create temp dir");
+    let theme =
+        "[meta]\nname = \"Faint\"\nextends = \"silk-light\"\n\n[links]\ncolor = \"#a8c4ff\"\n";
+    std::fs::write(dir.path().join("faint.toml"), theme).expect("should write theme");
+    std::fs::write(
+        dir.path().join("doc.md"),
+        "# Doc\n\nSee [the site](https://example.com).\n",
+    )
+    .expect("should write doc");
+    let run = || {
+        let mut cmd = silkprint();
+        cmd.current_dir(dir.path());
+        cmd
+    };
+
+    run()
+        .args([
+            "--theme",
+            "faint.toml",
+            "--check",
+            "doc.md",
+            "--deny",
+            "contrast-ratio",
+        ])
+        .assert()
+        .failure();
+    run()
+        .args([
+            "--theme",
+            "faint.toml",
+            "--check",
+            "doc.md",
+            "--fix-contrast",
+        ])
+        .args(["--deny", "contrast-ratio"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "adjusted links.color from #a8c4ff",
+        ));
+    run()
+        .args(["theme", "check", "faint.toml", "--fix-contrast=aaa"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("links.color").and(predicate::str::contains("7.0:1")));
+
+    std::fs::write(
+        dir.path().join("faint.toml"),
+        theme.replace("extends", "enforce_contrast = \"AA\"\nextends"),
+    )
+    .expect("should write theme");
+    run()
+        .args([
+            "--theme",
+            "faint.toml",
+            "--check",
+            "doc.md",
+            "--deny",
+            "contrast-ratio",
+        ])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("adjusted links.color"));
+}
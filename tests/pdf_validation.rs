@@ -64,6 +64,78 @@ fn test_pdf_metadata() {
     );
 }
 
+#[test]
+fn test_pdf_reproducible() {
+    let path = "tests/fixtures/full-features.md";
+    let input = std::fs::read_to_string(path).expect("fixture should exist");
+    let options = RenderOptions {
+        fixed_time: Some(1_700_000_000),
+        ..Default::default()
+    };
+    let render_once = || {
+        render(&input, Some(Path::new(path)), &options)
+            .expect("render should produce PDF bytes")
+            .0
+    };
+
+    let first = render_once();
+    let second = render_once();
+    assert!(
+        first == second,
+        "renders with a fixed time should be byte-identical"
+    );
+
+    // 1_700_000_000 is 2023-11-14T22:13:20Z.
+    let doc = Document::load_mem(&first).expect("PDF should parse");
+    let info = doc
+        .trailer
+        .get(b"Info")
+        .and_then(lopdf::Object::as_reference)
+        .and_then(|id| doc.get_dictionary(id))
+        .expect("PDF should have an Info dictionary");
+    let created = info
+        .get(b"CreationDate")
+        .and_then(lopdf::Object::as_str)
+        .expect("Info should have a CreationDate");
+    assert!(
+        String::from_utf8_lossy(created).starts_with("D:20231114221320"),
+        "CreationDate should use the fixed time, got {}",
+        String::from_utf8_lossy(created)
+    );
+}
+
+#[test]
+fn test_pdf_id_tracks_assets() {
+    let dir = tempfile::tempdir().expect("should create temp dir");
+    let path = dir.path().join("doc.md");
+    let input = "# Logo\n\n![Logo](logo.svg)\n";
+    let options = RenderOptions {
+        fixed_time: Some(1_700_000_000),
+        ..Default::default()
+    };
+    let render_with = |fill: &str| {
+        let svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"10\" height=\"10\">\
+             <rect width=\"10\" height=\"10\" fill=\"{fill}\"/></svg>"
+        );
+        std::fs::write(dir.path().join("logo.svg"), svg).expect("should write image");
+        let bytes = render(input, Some(&path), &options)
+            .expect("render should produce PDF bytes")
+            .0;
+        let doc = Document::load_mem(&bytes).expect("PDF should parse");
+        format!(
+            "{:?}",
+            doc.trailer.get(b"ID").expect("PDF should have an ID")
+        )
+    };
+
+    assert_ne!(
+        render_with("red"),
+        render_with("blue"),
+        "the same Markdown with a different image should get a different ID"
+    );
+}
+
 // ── Fonts ────────────────────────────────────────────────────────
 
 #[test]